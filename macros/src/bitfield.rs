@@ -1,25 +1,48 @@
 use drone_macros_core::compile_error;
 use if_chain::if_chain;
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-use quote::{format_ident, quote};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
 use syn::{
-    parenthesized,
+    bracketed, parenthesized,
     parse::{Parse, ParseStream, Result},
-    parse_macro_input, Data, DeriveInput, Fields, Ident, LitInt, LitStr, PathArguments, Token,
-    Type,
+    parse_macro_input,
+    spanned::Spanned,
+    Data, DeriveInput, Expr, ExprLit, Fields, Ident, Lit, LitInt, LitStr, PathArguments, Token,
+    Type, TypeArray, TypePath, Visibility,
 };
 
+const OPTIONS: &[&str] = &[
+    "atomic",
+    "builder",
+    "checked",
+    "debug",
+    "display",
+    "eq",
+    "hash",
+    "mask",
+    "serde",
+    "serde_struct",
+];
+
 #[derive(Default)]
 struct Input {
+    options: Vec<Opt>,
     fields: Vec<Field>,
 }
 
+struct Opt {
+    ident: Ident,
+    value: Option<LitInt>,
+}
+
 struct Field {
     ident: Ident,
+    len: Option<LitInt>,
     mode: Mode,
     offset: LitInt,
     width: Option<LitInt>,
+    ty: Option<Type>,
     doc: Option<LitStr>,
 }
 
@@ -33,19 +56,53 @@ impl Parse for Input {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let content;
         parenthesized!(content in input);
+        let mut options = Vec::new();
         let mut fields = Vec::new();
         let mut last_comma = true;
         while last_comma && !content.is_empty() {
-            fields.push(content.parse()?);
+            if content.peek2(syn::token::Paren) || content.peek2(syn::token::Bracket) {
+                fields.push(content.parse()?);
+            } else {
+                options.push(content.parse()?);
+            }
             last_comma = content.parse::<Option<Token![,]>>()?.is_some();
         }
-        Ok(Self { fields: fields.into_iter().collect() })
+        Ok(Self { options, fields })
+    }
+}
+
+impl Parse for Opt {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        if !OPTIONS.contains(&ident.to_string().as_str()) {
+            return Err(syn::Error::new(ident.span(), "unknown option"));
+        }
+        Ok(Self { ident, value })
+    }
+}
+
+impl Input {
+    fn option(&self, name: &str) -> Option<&Opt> {
+        self.options.iter().find(|opt| opt.ident == name)
     }
 }
 
 impl Parse for Field {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let ident = input.parse()?;
+        let len = if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            Some(content.parse()?)
+        } else {
+            None
+        };
         let content;
         parenthesized!(content in input);
         let mode = content.parse()?;
@@ -57,13 +114,19 @@ impl Parse for Field {
         } else {
             None
         };
+        let ty = if width.is_some() && content.peek(Token![,]) && !content.peek2(LitStr) {
+            content.parse::<Token![,]>()?;
+            Some(content.parse()?)
+        } else {
+            None
+        };
         let doc = if content.peek(Token![,]) && content.peek2(LitStr) {
             content.parse::<Token![,]>()?;
             Some(content.parse()?)
         } else {
             None
         };
-        Ok(Self { ident, mode, offset, width, doc })
+        Ok(Self { ident, len, mode, offset, width, ty, doc })
     }
 }
 
@@ -98,9 +161,123 @@ impl Mode {
     }
 }
 
+enum Backing {
+    Int(TypePath),
+    Bytes(Expr),
+}
+
+impl Backing {
+    fn int_width(&self) -> Option<u32> {
+        if let Self::Int(bits) = self {
+            ["u8", "u16", "u32", "u64", "u128"]
+                .iter()
+                .position(|ident| bits.qself.is_none() && bits.path.is_ident(ident))
+                .map(|i| 8 << i)
+        } else {
+            None
+        }
+    }
+
+    fn bits_ty(&self, width: &LitInt) -> TokenStream2 {
+        match self {
+            Self::Int(bits) => quote!(#bits),
+            Self::Bytes(_) => {
+                let width = width.base10_parse::<usize>().unwrap_or(128);
+                let ty = match width {
+                    0..=8 => "u8",
+                    9..=16 => "u16",
+                    17..=32 => "u32",
+                    33..=64 => "u64",
+                    _ => "u128",
+                };
+                let ty = format_ident!("{}", ty);
+                quote!(#ty)
+            }
+        }
+    }
+
+    fn pos(&self, offset: &LitInt, width: &LitInt, len: Option<&LitInt>) -> TokenStream2 {
+        let ty = match self {
+            Self::Int(bits) => quote!(#bits),
+            Self::Bytes(_) => quote!(usize),
+        };
+        if let Some(len) = len {
+            quote!({
+                assert!(index < #len);
+                (#offset + index * #width) as #ty
+            })
+        } else {
+            quote!(#offset as #ty)
+        }
+    }
+
+    fn width(&self, width: &LitInt) -> TokenStream2 {
+        match self {
+            Self::Int(bits) => quote!(#width as #bits),
+            Self::Bytes(_) => quote!(#width),
+        }
+    }
+
+    fn read_bit(&self, pos: &TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote! {
+                unsafe { ::drone_core::bitfield::Bitfield::read_bit(self, #pos) }
+            },
+            Self::Bytes(_) => quote! {
+                ::drone_core::bitfield::bytes::read_bit(&self.0, #pos)
+            },
+        }
+    }
+
+    fn modify_bit(&self, op: &Ident, pos: &TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Int(_) => quote! {
+                unsafe { ::drone_core::bitfield::Bitfield::#op(self, #pos) };
+            },
+            Self::Bytes(_) => quote! {
+                ::drone_core::bitfield::bytes::#op(&mut self.0, #pos);
+            },
+        }
+    }
+
+    fn read_bits(&self, pos: &TokenStream2, width: &LitInt) -> TokenStream2 {
+        let width_expr = self.width(width);
+        match self {
+            Self::Int(_) => quote! {
+                unsafe { ::drone_core::bitfield::Bitfield::read_bits(self, #pos, #width_expr) }
+            },
+            Self::Bytes(_) => {
+                let ty = self.bits_ty(width);
+                quote! {
+                    ::drone_core::bitfield::bytes::read_bits(&self.0, #pos, #width_expr) as #ty
+                }
+            }
+        }
+    }
+
+    fn write_bits(&self, pos: &TokenStream2, width: &LitInt, value: &TokenStream2) -> TokenStream2 {
+        let width_expr = self.width(width);
+        match self {
+            Self::Int(_) => quote! {
+                unsafe {
+                    ::drone_core::bitfield::Bitfield::write_bits(self, #pos, #width_expr, #value);
+                }
+            },
+            Self::Bytes(_) => quote! {
+                ::drone_core::bitfield::bytes::write_bits(
+                    &mut self.0,
+                    #pos,
+                    #width_expr,
+                    #value as u128,
+                );
+            },
+        }
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
-    let DeriveInput { attrs, ident, data, .. } = parse_macro_input!(input);
+    let DeriveInput { attrs, vis, ident, data, .. } = parse_macro_input!(input);
     let bitfield = attrs.into_iter().find(|attr| {
         if_chain! {
             if attr.path.leading_colon.is_none();
@@ -110,143 +287,1022 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
             then { x.ident == "bitfield" } else { false }
         }
     });
-    let Input { fields } = match bitfield {
+    let input = match bitfield {
         Some(attr) => {
             let input = attr.tokens.into();
-            parse_macro_input!(input)
+            parse_macro_input!(input as Input)
         }
         None => Input::default(),
     };
-    let bits = if_chain! {
+    let backing = if_chain! {
         if let Data::Struct(x) = data;
         if let Fields::Unnamed(x) = x.fields;
         if x.unnamed.len() <= 1;
         if let Some(x) = x.unnamed.into_iter().next();
-        if let Type::Path(x) = x.ty;
         then {
-            x
+            match x.ty {
+                Type::Path(x) => Backing::Int(x),
+                Type::Array(TypeArray { elem, len, .. }) if is_u8(&elem) => Backing::Bytes(len),
+                _ => {
+                    compile_error!("Bitfield can be derived only from an integer or a byte array");
+                }
+            }
         } else {
             compile_error!("Bitfield can be derived only from a tuple struct with one field");
         }
     };
 
-    let field_tokens = fields
-        .into_iter()
+    if let Err(err) = check_layout(&input, &backing) {
+        return err.to_compile_error().into();
+    }
+
+    let field_tokens = input
+        .fields
+        .iter()
         .flat_map(|field| {
             let mut fields = Vec::new();
-            let Field {
-                ident,
-                mode,
-                offset,
-                width,
-                doc,
-            } = field;
-            let width = width.unwrap_or_else(|| LitInt::new("1", Span::call_site()));
+            let Field { ident, len, mode, offset, width, ty, doc } = field;
+            let width = width.clone().unwrap_or_else(|| LitInt::new("1", Span::call_site()));
             let mut attrs = vec![quote!(#[inline])];
             if let Some(doc) = doc {
                 attrs.push(quote!(#[doc = #doc]));
             }
             let attrs = &attrs;
-            if width.base10_digits() == "1" {
+            let index = if len.is_some() { quote!(index: usize,) } else { quote!() };
+            let pos = backing.pos(offset, &width, len.as_ref());
+            let bits = backing.bits_ty(&width);
+            let check_option = input.option("checked").is_some();
+            let mask = if len.is_none() && backing.int_width().is_some() {
+                let width = width.base10_parse::<u32>().unwrap_or(128);
+                let mask = if width < 128 { (1_u128 << width) - 1 } else { u128::max_value() };
+                Some(Literal::u128_suffixed(mask))
+            } else {
+                None
+            };
+            if let Some(ty) = ty.as_ref().filter(|ty| is_signed(ty)) {
+                let shift = 128 - width.base10_parse::<u32>().unwrap_or(128).min(128);
+                let fits = if shift == 0 {
+                    quote!(true)
+                } else {
+                    quote!({
+                        let value = value as i128;
+                        value >= -(1 << (127 - #shift)) && value < 1 << (127 - #shift)
+                    })
+                };
                 if mode.is_read() {
-                    let read_bit = format_ident!("{}", ident);
-                    fields.push(quote! {
-                        #(#attrs)*
-                        pub fn #read_bit(&self) -> bool {
-                            unsafe {
-                                ::drone_core::bitfield::Bitfield::read_bit(self, #offset as #bits)
+                    let read_signed = format_ident!("{}", ident);
+                    if let Some(mask) = &mask {
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub const fn #read_signed(&self) -> #ty {
+                                (((self.0 >> #offset & #mask as #bits) as u128 as i128) << #shift
+                                    >> #shift) as #ty
                             }
-                        }
-                    });
+                        });
+                    } else {
+                        let read_bits = backing.read_bits(&pos, &width);
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub fn #read_signed(&self, #index) -> #ty {
+                                let bits = #read_bits as u128 as i128;
+                                (bits << #shift >> #shift) as #ty
+                            }
+                        });
+                    }
                 }
                 if mode.is_write() {
-                    let set_bit = format_ident!("set_{}", ident);
-                    let clear_bit = format_ident!("clear_{}", ident);
-                    let toggle_bit = format_ident!("toggle_{}", ident);
+                    if let Some(mask) = &mask {
+                        let with_signed = format_ident!("with_{}", ident);
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub const fn #with_signed(self, value: #ty) -> Self {
+                                Self(
+                                    self.0 & !((#mask as #bits) << #offset)
+                                        | (value as i128 as u128 as #bits & #mask as #bits)
+                                            << #offset,
+                                )
+                            }
+                        });
+                    }
+                    let write_signed = format_ident!("write_{}", ident);
+                    let try_write_signed = format_ident!("try_write_{}", ident);
+                    let value = quote!(value as i128 as u128 as #bits);
+                    let write = backing.write_bits(&pos, &width, &value);
+                    let check = if check_option {
+                        quote!(debug_assert!(#fits, "value out of field range");)
+                    } else {
+                        quote!()
+                    };
                     fields.push(quote! {
                         #(#attrs)*
-                        pub fn #set_bit(&mut self) -> &mut Self {
-                            unsafe {
-                                ::drone_core::bitfield::Bitfield::set_bit(self, #offset as #bits);
-                            }
+                        pub fn #write_signed(&mut self, #index value: #ty) -> &mut Self {
+                            #check
+                            #write
                             self
                         }
                     });
                     fields.push(quote! {
                         #(#attrs)*
-                        pub fn #clear_bit(&mut self) -> &mut Self {
-                            unsafe {
-                                ::drone_core::bitfield::Bitfield::clear_bit(self, #offset as #bits);
+                        pub fn #try_write_signed(
+                            &mut self,
+                            #index
+                            value: #ty,
+                        ) -> ::core::result::Result<&mut Self, ::drone_core::bitfield::OutOfRange> {
+                            if #fits {
+                                #write
+                                Ok(self)
+                            } else {
+                                Err(::drone_core::bitfield::OutOfRange)
                             }
-                            self
                         }
                     });
+                }
+            } else if let Some(ty) = ty {
+                if mode.is_read() {
+                    let read_enum = format_ident!("{}", ident);
+                    let read_bits = backing.read_bits(&pos, &width);
                     fields.push(quote! {
                         #(#attrs)*
-                        pub fn #toggle_bit(&mut self) -> &mut Self {
-                            unsafe {
-                                ::drone_core::bitfield::Bitfield::toggle_bit(self, #offset as #bits);
-                            }
+                        pub fn #read_enum(
+                            &self,
+                            #index
+                        ) -> ::core::result::Result<#ty, ::drone_core::bitfield::InvalidValue> {
+                            let bits = #read_bits as u8;
+                            <#ty as ::drone_core::bitfield::BitfieldEnum>::from_bits(bits)
+                                .ok_or(::drone_core::bitfield::InvalidValue(bits))
+                        }
+                    });
+                }
+                if mode.is_write() {
+                    let write_enum = format_ident!("write_{}", ident);
+                    let value = quote! {
+                        ::drone_core::bitfield::BitfieldEnum::into_bits(value) as #bits
+                    };
+                    let write_bits = backing.write_bits(&pos, &width, &value);
+                    fields.push(quote! {
+                        #(#attrs)*
+                        pub fn #write_enum(&mut self, #index value: #ty) -> &mut Self {
+                            #write_bits
                             self
                         }
                     });
                 }
+            } else if width.base10_digits() == "1" {
+                if mode.is_read() {
+                    let read_bit = format_ident!("{}", ident);
+                    if mask.is_some() {
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub const fn #read_bit(&self) -> bool {
+                                self.0 >> #offset & 1 != 0
+                            }
+                        });
+                    } else {
+                        let read = backing.read_bit(&pos);
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub fn #read_bit(&self, #index) -> bool {
+                                #read
+                            }
+                        });
+                    }
+                }
+                if mode.is_write() {
+                    if mask.is_some() {
+                        let with_bit = format_ident!("with_{}", ident);
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub const fn #with_bit(self, value: bool) -> Self {
+                                Self(self.0 & !(1 << #offset) | (value as #bits) << #offset)
+                            }
+                        });
+                    }
+                    for op in &["set", "clear", "toggle"] {
+                        let method = format_ident!("{}_{}", op, ident);
+                        let modify = backing.modify_bit(&format_ident!("{}_bit", op), &pos);
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub fn #method(&mut self, #index) -> &mut Self {
+                                #modify
+                                self
+                            }
+                        });
+                    }
+                }
             } else {
                 if mode.is_read() {
                     let read_bits = format_ident!("{}", ident);
-                    fields.push(quote! {
-                        #(#attrs)*
-                        pub fn #read_bits(&self) -> #bits {
-                            unsafe {
-                                ::drone_core::bitfield::Bitfield::read_bits(
-                                    self,
-                                    #offset as #bits,
-                                    #width as #bits,
-                                )
+                    if let Some(mask) = &mask {
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub const fn #read_bits(&self) -> #bits {
+                                self.0 >> #offset & #mask as #bits
                             }
-                        }
-                    });
+                        });
+                    } else {
+                        let read = backing.read_bits(&pos, &width);
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub fn #read_bits(&self, #index) -> #bits {
+                                #read
+                            }
+                        });
+                    }
                 }
                 if mode.is_write() {
+                    if let Some(mask) = &mask {
+                        let with_bits = format_ident!("with_{}", ident);
+                        fields.push(quote! {
+                            #(#attrs)*
+                            pub const fn #with_bits(self, bits: #bits) -> Self {
+                                Self(
+                                    self.0 & !((#mask as #bits) << #offset)
+                                        | (bits & #mask as #bits) << #offset,
+                                )
+                            }
+                        });
+                    }
                     let write_bits = format_ident!("write_{}", ident);
+                    let try_write_bits = format_ident!("try_write_{}", ident);
+                    let write = backing.write_bits(&pos, &width, &quote!(bits));
+                    let fits = if width.base10_parse::<u32>().map_or(false, |w| w < 128) {
+                        quote!((bits as u128) >> #width == 0)
+                    } else {
+                        quote!(true)
+                    };
+                    let check = if check_option {
+                        quote!(debug_assert!(#fits, "value out of field range");)
+                    } else {
+                        quote!()
+                    };
                     fields.push(quote! {
                         #(#attrs)*
-                        pub fn #write_bits(&mut self, bits: #bits) -> &mut Self {
-                            unsafe {
-                                ::drone_core::bitfield::Bitfield::write_bits(
-                                    self,
-                                    #offset as #bits,
-                                    #width as #bits,
-                                    bits,
-                                );
-                            }
+                        pub fn #write_bits(&mut self, #index bits: #bits) -> &mut Self {
+                            #check
+                            #write
                             self
                         }
                     });
+                    let try_write = backing.write_bits(&pos, &width, &quote!(bits));
+                    fields.push(quote! {
+                        #(#attrs)*
+                        pub fn #try_write_bits(
+                            &mut self,
+                            #index
+                            bits: #bits,
+                        ) -> ::core::result::Result<&mut Self, ::drone_core::bitfield::OutOfRange> {
+                            if #fits {
+                                #try_write
+                                Ok(self)
+                            } else {
+                                Err(::drone_core::bitfield::OutOfRange)
+                            }
+                        }
+                    });
                 }
             }
             fields
         })
         .collect::<Vec<_>>();
 
+    let enum_checks = enum_checks(&input);
+    let fmt_tokens = fmt_tokens(&input, &ident);
+    let bytes_tokens = bytes_tokens(&backing);
+    let builder_tokens = builder_tokens(&input, &backing, &vis, &ident);
+    let cmp_tokens = match cmp_tokens(&input, &backing, &ident) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let atomic_tokens = match atomic_tokens(&input, &backing, &ident) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let serde_tokens = match serde_tokens(&input, &backing, &ident) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let bitfield_tokens = match &backing {
+        Backing::Int(bits) => quote! {
+            impl ::drone_core::bitfield::Bitfield for #ident {
+                type Bits = #bits;
+
+                #[inline]
+                fn bits(&self) -> #bits {
+                    self.0
+                }
+
+                #[inline]
+                fn bits_mut(&mut self) -> &mut #bits {
+                    &mut self.0
+                }
+            }
+        },
+        Backing::Bytes(_) => quote!(),
+    };
+
     let expanded = quote! {
-        impl ::drone_core::bitfield::Bitfield for #ident {
-            type Bits = #bits;
+        #bitfield_tokens
 
-            #[inline]
-            fn bits(&self) -> #bits {
-                self.0
+        impl #ident {
+            #bytes_tokens
+            #(#field_tokens)*
+        }
+
+        #enum_checks
+
+        #fmt_tokens
+
+        #builder_tokens
+
+        #cmp_tokens
+
+        #atomic_tokens
+
+        #serde_tokens
+    };
+    expanded.into()
+}
+
+fn cmp_tokens(input: &Input, backing: &Backing, ident: &Ident) -> Result<TokenStream2> {
+    let eq = input.option("eq").is_some();
+    let hash = input.option("hash").is_some();
+    if !eq && !hash {
+        return Ok(quote!());
+    }
+    let mask = if let Some(value) = input.option("mask").and_then(|opt| opt.value.as_ref()) {
+        value.base10_parse::<u128>()?.to_le_bytes().to_vec()
+    } else {
+        let mut mask = Vec::new();
+        for Field { len, offset, width, .. } in &input.fields {
+            let offset = offset.base10_parse::<usize>()?;
+            let width = width.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>)?;
+            let count = len.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>)?;
+            for bit in offset..offset + width * count {
+                if mask.len() <= bit / 8 {
+                    mask.resize(bit / 8 + 1, 0);
+                }
+                mask[bit / 8] |= 1_u8 << (bit % 8);
+            }
+        }
+        mask
+    };
+    let (ty, masked) = match backing {
+        Backing::Int(bits) => {
+            let mut int_mask = [0; 16];
+            for (byte, &mask) in int_mask.iter_mut().zip(&mask) {
+                *byte = mask;
+            }
+            let mask = Literal::u128_suffixed(u128::from_le_bytes(int_mask));
+            (quote!(#bits), quote!(value.0 & #mask as #bits))
+        }
+        Backing::Bytes(len) => {
+            let mask = mask.iter().map(|&byte| Literal::u8_suffixed(byte));
+            (
+                quote!([u8; #len]),
+                quote!({
+                    const MASK: &[u8] = &[#(#mask),*];
+                    let mut bytes = value.0;
+                    for (i, byte) in bytes.iter_mut().enumerate() {
+                        *byte &= MASK.get(i).copied().unwrap_or(0);
+                    }
+                    bytes
+                }),
+            )
+        }
+    };
+    let masked_fn = quote! {
+        #[inline]
+        fn masked(value: &#ident) -> #ty {
+            #masked
+        }
+    };
+    let mut tokens = Vec::new();
+    if eq {
+        tokens.push(quote! {
+            impl ::core::cmp::PartialEq for #ident {
+                #[inline]
+                fn eq(&self, other: &Self) -> bool {
+                    #masked_fn
+                    masked(self) == masked(other)
+                }
             }
 
+            impl ::core::cmp::Eq for #ident {}
+        });
+    }
+    if hash {
+        tokens.push(quote! {
+            impl ::core::hash::Hash for #ident {
+                #[inline]
+                fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                    #masked_fn
+                    ::core::hash::Hash::hash(&masked(self), state);
+                }
+            }
+        });
+    }
+    Ok(quote!(#(#tokens)*))
+}
+
+fn atomic_tokens(input: &Input, backing: &Backing, ident: &Ident) -> Result<TokenStream2> {
+    let opt = match input.option("atomic") {
+        Some(opt) => opt,
+        None => return Ok(quote!()),
+    };
+    let atomic = match backing.int_width() {
+        Some(width) if width <= 32 => format_ident!("AtomicU{}", width),
+        _ => {
+            return Err(syn::Error::new(
+                opt.ident.span(),
+                "`atomic` option requires a `u8`, `u16`, or `u32` backing",
+            ));
+        }
+    };
+    let mut methods = Vec::new();
+    for Field { ident, len, mode, width, ty, doc, .. } in &input.fields {
+        if !mode.is_write() {
+            continue;
+        }
+        let doc = doc.iter();
+        let (index, index_arg) = if len.is_some() {
+            (quote!(index: usize,), quote!(index))
+        } else {
+            (quote!(), quote!())
+        };
+        let is_bit = ty.is_none() && width.as_ref().map_or(true, |w| w.base10_digits() == "1");
+        if is_bit {
+            for op in &["set", "clear", "toggle"] {
+                let fetch = format_ident!("fetch_{}_{}", op, ident);
+                let method = format_ident!("{}_{}", op, ident);
+                let doc = doc.clone();
+                methods.push(quote! {
+                    #(#[doc = #doc])*
+                    #[inline]
+                    pub fn #fetch(
+                        atomic: &::core::sync::atomic::#atomic,
+                        #index
+                        order: ::core::sync::atomic::Ordering,
+                    ) -> Self {
+                        Self::fetch_modify(atomic, order, |next| {
+                            next.#method(#index_arg);
+                        })
+                    }
+                });
+            }
+        } else {
+            let fetch = format_ident!("fetch_write_{}", ident);
+            let write = format_ident!("write_{}", ident);
+            let value_ty = match ty {
+                Some(ty) => quote!(#ty),
+                None => backing.bits_ty(width.as_ref().unwrap()),
+            };
+            let index_arg = if len.is_some() { quote!(index,) } else { quote!() };
+            methods.push(quote! {
+                #(#[doc = #doc])*
+                #[inline]
+                pub fn #fetch(
+                    atomic: &::core::sync::atomic::#atomic,
+                    #index
+                    value: #value_ty,
+                    order: ::core::sync::atomic::Ordering,
+                ) -> Self {
+                    Self::fetch_modify(atomic, order, |next| {
+                        next.#write(#index_arg value);
+                    })
+                }
+            });
+        }
+    }
+    Ok(quote! {
+        impl #ident {
+            /// Atomically modifies the value stored in `atomic` with `f`,
+            /// retrying on contention. Returns the previous value.
+            ///
+            /// `f` can be called multiple times.
             #[inline]
-            fn bits_mut(&mut self) -> &mut #bits {
-                &mut self.0
+            pub fn fetch_modify<F: FnMut(&mut Self)>(
+                atomic: &::core::sync::atomic::#atomic,
+                order: ::core::sync::atomic::Ordering,
+                mut f: F,
+            ) -> Self {
+                use ::core::sync::atomic::Ordering;
+                let failure = match order {
+                    Ordering::Release => Ordering::Relaxed,
+                    Ordering::AcqRel => Ordering::Acquire,
+                    order => order,
+                };
+                let mut prev = atomic.load(Ordering::Relaxed);
+                loop {
+                    let mut next = Self(prev);
+                    f(&mut next);
+                    match atomic.compare_exchange_weak(prev, next.0, order, failure) {
+                        Ok(prev) => break Self(prev),
+                        Err(current) => prev = current,
+                    }
+                }
+            }
+
+            #(#methods)*
+        }
+    })
+}
+
+#[allow(clippy::too_many_lines)]
+fn serde_tokens(input: &Input, backing: &Backing, ident: &Ident) -> Result<TokenStream2> {
+    let raw = input.option("serde");
+    let named = input.option("serde_struct");
+    if let (Some(_), Some(opt)) = (raw, named) {
+        return Err(syn::Error::new(
+            opt.ident.span(),
+            "`serde` and `serde_struct` options are mutually exclusive",
+        ));
+    }
+    let serde = quote!(::drone_core::bitfield::serde::serde);
+    if raw.is_some() {
+        let ty = match backing {
+            Backing::Int(bits) => quote!(#bits),
+            Backing::Bytes(len) => quote!([u8; #len]),
+        };
+        return Ok(quote! {
+            impl #serde::Serialize for #ident {
+                #[inline]
+                fn serialize<S: #serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    #serde::Serialize::serialize(&self.0, serializer)
+                }
+            }
+
+            impl<'de> #serde::Deserialize<'de> for #ident {
+                #[inline]
+                fn deserialize<D: #serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<Self, D::Error> {
+                    <#ty as #serde::Deserialize<'de>>::deserialize(deserializer).map(Self)
+                }
+            }
+        });
+    }
+    if named.is_none() {
+        return Ok(quote!());
+    }
+    let zero = match backing {
+        Backing::Int(_) => quote!(0),
+        Backing::Bytes(len) => quote!([0; #len]),
+    };
+    let mut names = Vec::new();
+    let mut reads = Vec::new();
+    let mut writes = Vec::new();
+    let mut serialize_fields = Vec::new();
+    let mut seq_fields = Vec::new();
+    let mut map_fields = Vec::new();
+    for Field { ident, len, mode, offset, width, ty, .. } in &input.fields {
+        if !mode.is_read() {
+            continue;
+        }
+        let field = names.len();
+        let name = LitStr::new(&ident.to_string(), Span::call_site());
+        let width = width.clone().unwrap_or_else(|| LitInt::new("1", Span::call_site()));
+        let pos = backing.pos(offset, &width, len.as_ref());
+        let bits = backing.bits_ty(&width);
+        let read = backing.read_bits(&pos, &width);
+        let write = backing.write_bits(&pos, &width, &quote!(bits as #bits));
+        reads.push(quote!(#field => (#read) as u128,));
+        writes.push(quote!(#field => { #write }));
+        let (value_ty, to_value, from_value) = match ty {
+            Some(ty) if is_signed(ty) => {
+                let shift = 128 - width.base10_parse::<u32>().unwrap_or(128).min(128);
+                (
+                    quote!(#ty),
+                    quote!(((raw as i128) << #shift >> #shift) as #ty),
+                    quote!(value as i128 as u128),
+                )
+            }
+            Some(_) => (quote!(u8), quote!(raw as u8), quote!(u128::from(value))),
+            None if width.base10_digits() == "1" => {
+                (quote!(bool), quote!(raw != 0), quote!(u128::from(value)))
+            }
+            None => (bits.clone(), quote!(raw as #bits), quote!(value as u128)),
+        };
+        let read_value = |index: TokenStream2| {
+            quote!({
+                let raw = Fields::read_field(self, #field, #index);
+                #to_value
+            })
+        };
+        let deserialize_field = |next: TokenStream2| {
+            if let Some(len) = len {
+                quote!({
+                    let values: [#value_ty; #len] = #next;
+                    for (index, &value) in values.iter().enumerate() {
+                        Fields::write_field(&mut result, #field, index, #from_value);
+                    }
+                })
+            } else {
+                quote!({
+                    let value: #value_ty = #next;
+                    Fields::write_field(&mut result, #field, 0, #from_value);
+                })
+            }
+        };
+        let value = if let Some(len) = len {
+            let indices = (0..len.base10_parse::<usize>()?).collect::<Vec<_>>();
+            let values = indices.iter().map(|index| read_value(quote!(#index)));
+            quote!([#(#values),*])
+        } else {
+            read_value(quote!(0))
+        };
+        serialize_fields.push(quote! {
+            #serde::ser::SerializeStruct::serialize_field(&mut state, #name, &#value)?;
+        });
+        seq_fields.push(deserialize_field(quote! {
+            seq.next_element()?
+                .ok_or_else(|| #serde::de::Error::invalid_length(#field, &self))?
+        }));
+        map_fields.push(deserialize_field(quote!(map.next_value()?)));
+        names.push(name);
+    }
+    let struct_name = LitStr::new(&ident.to_string(), Span::call_site());
+    let expecting = format!("struct {}", ident);
+    let count = names.len();
+    let map_indices = 0..count;
+    Ok(quote! {
+        const _: () = {
+            const FIELDS: &[&str] = &[#(#names),*];
+
+            trait Fields {
+                fn read_field(&self, field: usize, index: usize) -> u128;
+                fn write_field(&mut self, field: usize, index: usize, bits: u128);
+            }
+
+            impl Fields for #ident {
+                #[allow(unused_variables)]
+                fn read_field(&self, field: usize, index: usize) -> u128 {
+                    match field {
+                        #(#reads)*
+                        _ => unreachable!(),
+                    }
+                }
+
+                #[allow(unused_variables)]
+                fn write_field(&mut self, field: usize, index: usize, bits: u128) {
+                    match field {
+                        #(#writes)*
+                        _ => unreachable!(),
+                    }
+                }
+            }
+
+            impl #serde::Serialize for #ident {
+                fn serialize<S: #serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    let mut state = serializer.serialize_struct(#struct_name, #count)?;
+                    #(#serialize_fields)*
+                    #serde::ser::SerializeStruct::end(state)
+                }
+            }
+
+            struct FieldsVisitor;
+
+            impl<'de> #serde::de::Visitor<'de> for FieldsVisitor {
+                type Value = #ident;
+
+                fn expecting(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.write_str(#expecting)
+                }
+
+                #[allow(unused_mut, unused_variables)]
+                fn visit_seq<A: #serde::de::SeqAccess<'de>>(
+                    self,
+                    mut seq: A,
+                ) -> ::core::result::Result<#ident, A::Error> {
+                    let mut result = #ident(#zero);
+                    #(#seq_fields)*
+                    Ok(result)
+                }
+
+                #[allow(unused_mut)]
+                fn visit_map<A: #serde::de::MapAccess<'de>>(
+                    self,
+                    mut map: A,
+                ) -> ::core::result::Result<#ident, A::Error> {
+                    let mut result = #ident(#zero);
+                    while let Some(field) =
+                        map.next_key_seed(::drone_core::bitfield::serde::FieldSeed(FIELDS))?
+                    {
+                        match field {
+                            #(Some(#map_indices) => #map_fields)*
+                            _ => {
+                                map.next_value::<#serde::de::IgnoredAny>()?;
+                            }
+                        }
+                    }
+                    Ok(result)
+                }
+            }
+
+            impl<'de> #serde::Deserialize<'de> for #ident {
+                fn deserialize<D: #serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<Self, D::Error> {
+                    deserializer.deserialize_struct(#struct_name, FIELDS, FieldsVisitor)
+                }
+            }
+        };
+    })
+}
+
+fn check_layout(input: &Input, backing: &Backing) -> Result<()> {
+    let total = match backing {
+        Backing::Int(_) => backing.int_width().map(|width| width as usize),
+        Backing::Bytes(Expr::Lit(ExprLit { lit: Lit::Int(len), .. })) => {
+            Some(len.base10_parse::<usize>()? * 8)
+        }
+        Backing::Bytes(_) => None,
+    };
+    let mut ranges = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    let mut push_error = |err| match &mut errors {
+        Some(errors) => errors.combine(err),
+        None => errors = Some(err),
+    };
+    for Field { ident, len, offset, width, ty, .. } in &input.fields {
+        let offset = offset.base10_parse::<usize>()?;
+        let width = width.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>)?;
+        if ty.as_ref().map_or(false, |ty| !is_signed(ty)) && width > 8 {
+            push_error(syn::Error::new(
+                ident.span(),
+                format!("enum field `{}` is wider than 8 bits", ident),
+            ));
+        }
+        let count = len.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>)?;
+        let end = offset + width * count;
+        if width == 0 || count == 0 {
+            push_error(syn::Error::new(ident.span(), format!("field `{}` is empty", ident)));
+            continue;
+        }
+        if let Some(total) = total {
+            if end > total {
+                push_error(syn::Error::new(
+                    ident.span(),
+                    format!("field `{}` exceeds the backing width of {} bits", ident, total),
+                ));
+            }
+        }
+        for (other, other_offset, other_end) in &ranges {
+            if offset < *other_end && *other_offset < end {
+                push_error(syn::Error::new(
+                    ident.span(),
+                    format!("field `{}` overlaps with field `{}`", ident, other),
+                ));
             }
         }
+        ranges.push((ident, offset, end));
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+fn builder_tokens(
+    input: &Input,
+    backing: &Backing,
+    vis: &Visibility,
+    ident: &Ident,
+) -> TokenStream2 {
+    if input.option("builder").is_none() {
+        return quote!();
+    }
+    let builder = format_ident!("{}Builder", ident);
+    let zero = match backing {
+        Backing::Int(_) => quote!(0),
+        Backing::Bytes(len) => quote!([0; #len]),
+    };
+    let mut methods = Vec::new();
+    for Field { ident, len, mode, width, ty, doc, .. } in &input.fields {
+        if !mode.is_write() {
+            continue;
+        }
+        let doc = doc.iter();
+        let (index, index_arg) = if len.is_some() {
+            (quote!(index: usize,), quote!(index,))
+        } else {
+            (quote!(), quote!())
+        };
+        let is_bit = ty.is_none() && width.as_ref().map_or(true, |w| w.base10_digits() == "1");
+        if is_bit {
+            let set_bit = format_ident!("set_{}", ident);
+            let clear_bit = format_ident!("clear_{}", ident);
+            methods.push(quote! {
+                #(#[doc = #doc])*
+                #[inline]
+                pub fn #ident(mut self, #index value: bool) -> Self {
+                    if value {
+                        self.0.#set_bit(#index_arg);
+                    } else {
+                        self.0.#clear_bit(#index_arg);
+                    }
+                    self
+                }
+            });
+        } else {
+            let write = format_ident!("write_{}", ident);
+            let value_ty = match ty {
+                Some(ty) => quote!(#ty),
+                None => backing.bits_ty(width.as_ref().unwrap()),
+            };
+            methods.push(quote! {
+                #(#[doc = #doc])*
+                #[inline]
+                pub fn #ident(mut self, #index value: #value_ty) -> Self {
+                    self.0.#write(#index_arg value);
+                    self
+                }
+            });
+        }
+    }
+    let builder_doc = format!("A consuming builder for [`{}`].", ident);
+    quote! {
+        #[doc = #builder_doc]
+        #[must_use]
+        #vis struct #builder(#ident);
 
         impl #ident {
-            #(#field_tokens)*
+            /// Returns a builder starting from the value with all bits cleared.
+            #[inline]
+            pub fn builder() -> #builder {
+                #builder(Self(#zero))
+            }
+
+            /// Returns a builder starting from this value.
+            #[inline]
+            pub fn into_builder(self) -> #builder {
+                #builder(self)
+            }
         }
+
+        impl #builder {
+            #(#methods)*
+
+            /// Returns the built value.
+            #[inline]
+            pub fn build(self) -> #ident {
+                self.0
+            }
+        }
+    }
+}
+
+fn bytes_tokens(backing: &Backing) -> TokenStream2 {
+    let mut tokens = Vec::new();
+    let (ty, conv) = match backing {
+        Backing::Int(bits) => (quote!([u8; ::core::mem::size_of::<#bits>()]), Some(bits)),
+        Backing::Bytes(len) => (quote!([u8; #len]), None),
     };
-    expanded.into()
+    for endian in &["le", "be", "ne"] {
+        let to_bytes = format_ident!("to_{}_bytes", endian);
+        let from_bytes = format_ident!("from_{}_bytes", endian);
+        let doc_to = format!(
+            "Returns the memory representation of this value as a byte array in {} byte order.",
+            endian_name(endian)
+        );
+        let doc_from = format!(
+            "Creates a value from its representation as a byte array in {} byte order.",
+            endian_name(endian)
+        );
+        let (to_body, from_body) = if let Some(bits) = conv {
+            (quote!(self.0.#to_bytes()), quote!(Self(<#bits>::#from_bytes(bytes))))
+        } else {
+            let swap = match *endian {
+                "le" => quote!(false),
+                "be" => quote!(true),
+                _ => quote!(cfg!(target_endian = "big")),
+            };
+            (
+                quote! {
+                    let mut bytes = self.0;
+                    if #swap {
+                        bytes.reverse();
+                    }
+                    bytes
+                },
+                quote! {
+                    let mut bytes = bytes;
+                    if #swap {
+                        bytes.reverse();
+                    }
+                    Self(bytes)
+                },
+            )
+        };
+        let constness = if conv.is_some() { quote!(const) } else { quote!() };
+        tokens.push(quote! {
+            #[doc = #doc_to]
+            #[inline]
+            pub #constness fn #to_bytes(self) -> #ty {
+                #to_body
+            }
+
+            #[doc = #doc_from]
+            #[inline]
+            pub #constness fn #from_bytes(bytes: #ty) -> Self {
+                #from_body
+            }
+        });
+    }
+    quote!(#(#tokens)*)
+}
+
+fn endian_name(endian: &str) -> &'static str {
+    match endian {
+        "le" => "little-endian",
+        "be" => "big-endian",
+        _ => "native",
+    }
+}
+
+/// Checks at compile time that the discriminants of each enum field type fit
+/// the field width.
+fn enum_checks(input: &Input) -> TokenStream2 {
+    let checks = input
+        .fields
+        .iter()
+        .filter_map(|Field { width, ty, .. }| {
+            let ty = ty.as_ref().filter(|ty| !is_signed(ty))?;
+            let width = width.as_ref()?;
+            Some(quote_spanned! { ty.span() =>
+                const _: [(); 0] = [(); (<#ty as ::drone_core::bitfield::BitfieldEnum>::WIDTH
+                    > #width) as usize];
+            })
+        })
+        .collect::<Vec<_>>();
+    quote!(#(#checks)*)
+}
+
+fn is_signed(ty: &Type) -> bool {
+    if let Type::Path(x) = ty {
+        x.qself.is_none()
+            && ["i8", "i16", "i32", "i64", "i128"].iter().any(|ident| x.path.is_ident(ident))
+    } else {
+        false
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    if let Type::Path(x) = ty { x.qself.is_none() && x.path.is_ident("u8") } else { false }
+}
+
+fn fmt_tokens(input: &Input, ident: &Ident) -> TokenStream2 {
+    let mut tokens = Vec::new();
+    let mut debug_fields = Vec::new();
+    let mut display_fields = Vec::new();
+    for Field { ident, len, mode, width, ty, .. } in &input.fields {
+        if !mode.is_read() {
+            continue;
+        }
+        let name = LitStr::new(&ident.to_string(), Span::call_site());
+        let value = if let Some(len) = len {
+            let indices = (0..len.base10_parse::<usize>().unwrap_or(0)).collect::<Vec<_>>();
+            quote!([#(self.#ident(#indices)),*])
+        } else {
+            quote!(self.#ident())
+        };
+        let is_bits = ty.is_none() && width.as_ref().map_or(false, |w| w.base10_digits() != "1");
+        debug_fields.push(quote!(.field(#name, &#value)));
+        let format = if is_bits && len.is_none() {
+            format!("{}={{:#x}}", ident)
+        } else {
+            format!("{}={{:?}}", ident)
+        };
+        display_fields.push(quote!(write!(f, #format, #value)?;));
+    }
+    let separators = display_fields.iter().skip(1).map(|_| quote!(f.write_str(" ")?;));
+    let first = display_fields.iter().take(1);
+    let rest = display_fields.iter().skip(1);
+    let struct_name = LitStr::new(&ident.to_string(), Span::call_site());
+    if input.option("debug").is_some() {
+        tokens.push(quote! {
+            impl ::core::fmt::Debug for #ident {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.debug_struct(#struct_name) #(#debug_fields)* .finish()
+                }
+            }
+        });
+    }
+    if input.option("display").is_some() {
+        tokens.push(quote! {
+            impl ::core::fmt::Display for #ident {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    #(#first)*
+                    #(#separators #rest)*
+                    Ok(())
+                }
+            }
+        });
+    }
+    quote!(#(#tokens)*)
 }
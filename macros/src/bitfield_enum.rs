@@ -0,0 +1,64 @@
+use drone_macros_core::compile_error;
+use if_chain::if_chain;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Meta, NestedMeta};
+
+pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
+    let DeriveInput { attrs, ident, data, .. } = parse_macro_input!(input);
+    let repr_u8 = attrs.iter().any(|attr| {
+        if_chain! {
+            if attr.path.is_ident("repr");
+            if let Ok(Meta::List(list)) = attr.parse_meta();
+            then {
+                list.nested.iter().any(|nested| match nested {
+                    NestedMeta::Meta(Meta::Path(path)) => path.is_ident("u8"),
+                    _ => false,
+                })
+            } else {
+                false
+            }
+        }
+    });
+    if !repr_u8 {
+        compile_error!("BitfieldEnum can be derived only from an enum with `#[repr(u8)]`");
+    }
+    let variants = if let Data::Enum(x) = data {
+        x.variants
+    } else {
+        compile_error!("BitfieldEnum can be derived only from an enum");
+    };
+    let mut arms = Vec::new();
+    let mut discriminants = Vec::new();
+    for variant in variants {
+        if let Fields::Unit = variant.fields {
+            let variant = variant.ident;
+            arms.push(quote! {
+                x if x == #ident::#variant as u8 => Some(#ident::#variant),
+            });
+            discriminants.push(quote!(#ident::#variant as u8));
+        } else {
+            compile_error!("BitfieldEnum variants can't have fields");
+        }
+    }
+
+    let expanded = quote! {
+        impl ::drone_core::bitfield::BitfieldEnum for #ident {
+            const WIDTH: u32 = 8 - (0 #(| #discriminants)*).leading_zeros();
+
+            #[inline]
+            fn from_bits(bits: u8) -> Option<Self> {
+                match bits {
+                    #(#arms)*
+                    _ => None,
+                }
+            }
+
+            #[inline]
+            fn into_bits(self) -> u8 {
+                self as u8
+            }
+        }
+    };
+    expanded.into()
+}
@@ -0,0 +1,248 @@
+use crate::heap::{read_layout, Input as HeapInput, Region as HeapRegion};
+use drone_config::Config;
+use drone_macros_core::compile_error;
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    braced, bracketed,
+    parse::{Parse, ParseStream, Result},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Attribute, Ident, LitInt, Token, Visibility,
+};
+
+/// Ports reserved by `drone_core::log`.
+const RESERVED_PORTS: &[u8] = &[0, 1, 29, 30, 31];
+
+/// Number of log ports, the same as `drone_core::log::PORTS_COUNT`.
+const PORTS_COUNT: u8 = 32;
+
+struct Input {
+    mod_attrs: Vec<Attribute>,
+    mod_vis: Visibility,
+    mod_ident: Ident,
+    heap: Option<TokenStream2>,
+    log: Option<Vec<LogPort>>,
+    thr: Option<Thr>,
+}
+
+struct LogPort {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    port: LitInt,
+}
+
+struct Thr {
+    threads: Vec<Ident>,
+    thr_ident: Ident,
+    tokens: TokenStream2,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mod_attrs = input.call(Attribute::parse_outer)?;
+        let mod_vis = input.parse()?;
+        input.parse::<Token![mod]>()?;
+        let mod_ident = input.parse()?;
+        input.parse::<Token![;]>()?;
+        let mut heap = None;
+        let mut log = None;
+        let mut thr = None;
+        while !input.is_empty() {
+            let ident = input.parse::<Ident>()?;
+            let content;
+            braced!(content in input);
+            if ident == "heap" && heap.is_none() {
+                heap = Some(content.parse()?);
+            } else if ident == "log" && log.is_none() {
+                log = Some(parse_log(&content)?);
+            } else if ident == "thr" && thr.is_none() {
+                thr = Some(content.parse()?);
+            } else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("Unexpected section `{}`", ident),
+                ));
+            }
+        }
+        Ok(Self { mod_attrs, mod_vis, mod_ident, heap, log, thr })
+    }
+}
+
+fn parse_log(input: ParseStream<'_>) -> Result<Vec<LogPort>> {
+    let mut ports = Vec::<LogPort>::new();
+    while !input.is_empty() {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let ident = input.parse::<Ident>()?;
+        input.parse::<Token![=]>()?;
+        let port = input.parse::<LitInt>()?;
+        input.parse::<Token![;]>()?;
+        let number = port.base10_parse::<u8>()?;
+        if number >= PORTS_COUNT {
+            return Err(syn::Error::new(
+                port.span(),
+                format!("port number must be less than {}", PORTS_COUNT),
+            ));
+        }
+        if RESERVED_PORTS.contains(&number) {
+            return Err(syn::Error::new(port.span(), format!("port {} is reserved", number)));
+        }
+        for other in &ports {
+            if other.ident == ident {
+                return Err(syn::Error::new(ident.span(), "duplicate port name"));
+            }
+            if other.port.base10_parse::<u8>()? == number {
+                return Err(syn::Error::new(
+                    port.span(),
+                    format!("port {} is already used by `{}`", number, other.ident),
+                ));
+            }
+        }
+        ports.push(LogPort { attrs, ident, port });
+    }
+    Ok(ports)
+}
+
+impl Parse for Thr {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let keyword = input.parse::<Ident>()?;
+        if keyword != "threads" {
+            return Err(syn::Error::new(keyword.span(), "expected `threads`"));
+        }
+        input.parse::<Token![=]>()?;
+        let content;
+        bracketed!(content in input);
+        let threads = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+        input.parse::<Token![;]>()?;
+        let threads = threads.into_iter().collect::<Vec<_>>();
+        for (i, thread) in threads.iter().enumerate() {
+            if threads[..i].contains(thread) {
+                return Err(syn::Error::new(thread.span(), "duplicate thread name"));
+            }
+        }
+        let fork = input.fork();
+        fork.call(Attribute::parse_outer)?;
+        fork.parse::<Visibility>()?;
+        fork.parse::<Token![struct]>()?;
+        let thr_ident = fork.parse()?;
+        let tokens = input.parse()?;
+        Ok(Self { threads, thr_ident, tokens })
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn proc_macro(input: TokenStream) -> TokenStream {
+    let Input { mod_attrs, mod_vis, mod_ident, heap, log, thr } = parse_macro_input!(input);
+    let config = match Config::read_from_cargo_manifest_dir() {
+        Ok(config) => config,
+        Err(err) => compile_error!("{}: {}", drone_config::CONFIG_NAME, err),
+    };
+    let int = |value: u32| LitInt::new(&value.to_string(), Span::call_site());
+    let flash_origin = int(config.memory.flash.origin);
+    let flash_size = int(config.memory.flash.size);
+    let ram_origin = int(config.memory.ram.origin);
+    let ram_size = int(config.memory.ram.size);
+    let mut heap_size = config.heap.size;
+    let mut regions_tokens = Vec::new();
+    if let Some(heap) = &heap {
+        let HeapInput { layout, regions, .. } = match syn::parse2(heap.clone()) {
+            Ok(input) => input,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        if let Some(layout) = &layout {
+            match read_layout(layout) {
+                Ok((_, size, _)) => heap_size = size,
+                Err(err) => return syn::Error::new(layout.span(), err).to_compile_error().into(),
+            }
+        }
+        for HeapRegion { ident, origin, layout, .. } in regions {
+            let size = match read_layout(&layout) {
+                Ok((_, size, _)) => int(size),
+                Err(err) => return syn::Error::new(layout.span(), err).to_compile_error().into(),
+            };
+            let origin_ident = format_ident!("{}_ORIGIN", ident);
+            let size_ident = format_ident!("{}_SIZE", ident);
+            let origin_doc = format!("Heap region `{}` origin address.", ident);
+            let size_doc = format!("Heap region `{}` size in bytes.", ident);
+            regions_tokens.push(quote! {
+                #[doc = #origin_doc]
+                pub const #origin_ident: usize = #origin;
+                #[doc = #size_doc]
+                pub const #size_ident: usize = #size;
+            });
+        }
+    }
+    let heap_origin = int(config.memory.ram.origin + config.memory.ram.size - heap_size);
+    let heap_size = int(heap_size);
+    let baud_rate_tokens = config.log.and_then(|log| {
+        let baud_rate = log.swo.map(|swo| swo.baud_rate).or(log.dso.map(|dso| dso.baud_rate))?;
+        let baud_rate = int(baud_rate);
+        Some(quote! {
+            /// Log output baud rate.
+            pub const LOG_BAUD_RATE: usize = #baud_rate;
+        })
+    });
+    let ports_tokens = log.unwrap_or_default().into_iter().map(|LogPort { attrs, ident, port }| {
+        let ident = format_ident!("{}_PORT", ident);
+        quote! {
+            #(#attrs)*
+            pub const #ident: u8 = #port;
+        }
+    });
+    let heap_tokens = heap.map(|heap| quote!(::drone_core::heap! { #heap }));
+    let (thr_consts_tokens, thr_tokens) = thr.map_or((None, None), |thr| {
+        let Thr { threads, thr_ident, tokens } = thr;
+        let count = threads.len();
+        let thr_nums = threads.iter().enumerate().map(|(thr_num, thread)| {
+            let ident = format_ident!("{}_THR_NUM", thread);
+            let doc = format!("Thread number of `{}`.", thread);
+            quote! {
+                #[doc = #doc]
+                pub const #ident: usize = #thr_num;
+            }
+        });
+        let thr_consts_tokens = quote! {
+            /// Number of threads.
+            pub const THR_COUNT: usize = #count;
+            #(#thr_nums)*
+        };
+        let thr_ctors = (0..count).map(|thr_num| quote!(#thr_ident::new(#thr_num)));
+        let thr_tokens = quote! {
+            static mut THREADS: [#thr_ident; #count] = [#(#thr_ctors),*];
+
+            ::drone_core::thr! {
+                use THREADS;
+                #tokens
+            }
+        };
+        (Some(thr_consts_tokens), Some(thr_tokens))
+    });
+
+    let expanded = quote! {
+        #(#mod_attrs)*
+        #mod_vis mod #mod_ident {
+            /// Flash memory origin address.
+            pub const FLASH_ORIGIN: usize = #flash_origin;
+            /// Flash memory size in bytes.
+            pub const FLASH_SIZE: usize = #flash_size;
+            /// RAM origin address.
+            pub const RAM_ORIGIN: usize = #ram_origin;
+            /// RAM size in bytes.
+            pub const RAM_SIZE: usize = #ram_size;
+            /// Heap origin address. The heap is placed at the end of the RAM.
+            pub const HEAP_ORIGIN: usize = #heap_origin;
+            /// Heap size in bytes.
+            pub const HEAP_SIZE: usize = #heap_size;
+            #(#regions_tokens)*
+            #baud_rate_tokens
+            #(#ports_tokens)*
+            #thr_consts_tokens
+        }
+
+        #heap_tokens
+
+        #thr_tokens
+    };
+    expanded.into()
+}
@@ -1,17 +1,64 @@
 use drone_config::Config;
 use drone_macros_core::compile_error;
 use proc_macro::TokenStream;
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
+use serde::Deserialize;
+use std::{env, fs, path::PathBuf};
 use syn::{
+    braced,
     parse::{Parse, ParseStream, Result},
-    parse_macro_input, Attribute, Ident, LitInt, Token, Visibility,
+    parse_macro_input,
+    punctuated::Punctuated,
+    Attribute, Ident, LitInt, LitStr, Token, Visibility,
 };
 
-struct Input {
+pub(crate) struct Input {
     heap_attrs: Vec<Attribute>,
     heap_vis: Visibility,
     heap_ident: Ident,
+    pub(crate) layout: Option<LitStr>,
+    attrs: Vec<Ident>,
+    pub(crate) regions: Vec<Region>,
+}
+
+pub(crate) struct Region {
+    attrs: Vec<Attribute>,
+    pub(crate) ident: Ident,
+    pub(crate) origin: LitInt,
+    pub(crate) layout: LitStr,
+    region_attrs: Vec<Ident>,
+}
+
+/// The maximum alignment required for pool blocks, which is the alignment of a
+/// pointer on the 32-bit targets.
+const MAX_BLOCK_ALIGN: u32 = 4;
+
+/// Pools layout from an external file.
+#[derive(Deserialize)]
+struct LayoutFile {
+    heap: Layout,
+}
+
+#[derive(Deserialize)]
+struct Layout {
+    size: Size,
+    pools: Vec<LayoutPool>,
+}
+
+#[derive(Deserialize)]
+struct LayoutPool {
+    block: Size,
+    capacity: u32,
+}
+
+/// A size given either as a number or as a string with an optional `K` or `M`
+/// suffix, the same way as in `Drone.toml`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Size {
+    Int(u32),
+    Str(String),
 }
 
 impl Parse for Input {
@@ -21,43 +68,279 @@ impl Parse for Input {
         input.parse::<Token![struct]>()?;
         let heap_ident = input.parse()?;
         input.parse::<Token![;]>()?;
-        Ok(Self { heap_attrs, heap_vis, heap_ident })
+        let mut layout = None;
+        let mut attrs = Vec::new();
+        let mut regions = Vec::new();
+        while !input.is_empty() {
+            if input.peek(Token![#]) || input.peek(Ident) && input.peek2(Ident) {
+                regions.push(input.parse()?);
+                continue;
+            }
+            let key = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            if key == "layout" && layout.is_none() {
+                layout = Some(input.parse()?);
+            } else if key == "attrs" && attrs.is_empty() {
+                attrs = parse_region_attrs(input)?;
+            } else {
+                return Err(input.error(format!("unexpected key `{}`", key)));
+            }
+            input.parse::<Token![;]>()?;
+        }
+        Ok(Self { heap_attrs, heap_vis, heap_ident, layout, attrs, regions })
+    }
+}
+
+impl Parse for Region {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let keyword = input.parse::<Ident>()?;
+        if keyword != "region" {
+            return Err(syn::Error::new(keyword.span(), "expected `region`"));
+        }
+        let ident = input.parse::<Ident>()?;
+        let content;
+        braced!(content in input);
+        let mut origin = None;
+        let mut layout = None;
+        let mut region_attrs = Vec::new();
+        while !content.is_empty() {
+            let key = content.parse::<Ident>()?;
+            content.parse::<Token![=]>()?;
+            if key == "origin" && origin.is_none() {
+                origin = Some(content.parse()?);
+            } else if key == "layout" && layout.is_none() {
+                layout = Some(content.parse()?);
+            } else if key == "attrs" && region_attrs.is_empty() {
+                region_attrs = parse_region_attrs(&content)?;
+            } else {
+                return Err(content.error(format!("unexpected key `{}`", key)));
+            }
+            content.parse::<Token![;]>()?;
+        }
+        let origin = origin.ok_or_else(|| syn::Error::new(ident.span(), "missing `origin`"))?;
+        let layout = layout.ok_or_else(|| syn::Error::new(ident.span(), "missing `layout`"))?;
+        Ok(Self { attrs, ident, origin, layout, region_attrs })
+    }
+}
+
+fn parse_region_attrs(input: ParseStream<'_>) -> Result<Vec<Ident>> {
+    let attrs = Punctuated::<Ident, Token![,]>::parse_separated_nonempty(input)?;
+    for attr in &attrs {
+        if attr != "dma" && attr != "zero_wait" {
+            return Err(syn::Error::new(
+                attr.span(),
+                "unknown region attribute, expected `dma` or `zero_wait`",
+            ));
+        }
+    }
+    Ok(attrs.into_iter().collect())
+}
+
+fn region_attrs_tokens(attrs: &[Ident]) -> TokenStream2 {
+    let dma = attrs.iter().any(|attr| attr == "dma");
+    let zero_wait = attrs.iter().any(|attr| attr == "zero_wait");
+    quote!(::drone_core::heap::RegionAttrs { dma: #dma, zero_wait: #zero_wait })
+}
+
+impl Size {
+    fn value(&self) -> std::result::Result<u32, String> {
+        match self {
+            Self::Int(value) => Ok(*value),
+            Self::Str(value) => {
+                let (number, multiplier) = if value.ends_with('K') {
+                    (&value[..value.len() - 1], 1024)
+                } else if value.ends_with('M') {
+                    (&value[..value.len() - 1], 1024 * 1024)
+                } else {
+                    (value.as_str(), 1)
+                };
+                number
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid size `{}`", value))?
+                    .checked_mul(multiplier)
+                    .ok_or_else(|| format!("size `{}` is too large", value))
+            }
+        }
+    }
+}
+
+pub(crate) fn read_layout(
+    path: &LitStr,
+) -> std::result::Result<(PathBuf, u32, Vec<(u32, u32)>), String> {
+    let mut full_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?);
+    full_path.push(path.value());
+    let contents = fs::read_to_string(&full_path)
+        .map_err(|err| format!("{}: {}", full_path.display(), err))?;
+    let LayoutFile { heap } =
+        if full_path.extension().map_or(false, |extension| extension == "json") {
+            serde_json::from_str(&contents).map_err(|err| err.to_string())?
+        } else {
+            toml::from_str(&contents).map_err(|err| err.to_string())?
+        };
+    let size = heap.size.value()?;
+    let mut pools = Vec::new();
+    let mut total = 0_u32;
+    for LayoutPool { block, capacity } in &heap.pools {
+        let block = block.value()?;
+        total = block
+            .checked_mul(*capacity)
+            .and_then(|pool_size| total.checked_add(pool_size))
+            .ok_or_else(|| format!("{}: pools size overflows", full_path.display()))?;
+        pools.push((block, *capacity));
+    }
+    if total != size {
+        return Err(format!(
+            "{}: pools take {} bytes, but the heap size is {} bytes",
+            full_path.display(),
+            total,
+            size
+        ));
     }
+    Ok((full_path, size, pools))
 }
 
 #[allow(clippy::too_many_lines)]
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { heap_attrs, heap_vis, heap_ident } = parse_macro_input!(input);
+    let Input { heap_attrs, heap_vis, heap_ident, layout, attrs, regions } =
+        parse_macro_input!(input);
     let config = match Config::read_from_cargo_manifest_dir() {
         Ok(config) => config,
         Err(err) => compile_error!("{}: {}", drone_config::CONFIG_NAME, err),
     };
-    let mut pools = config.heap.pools;
-    pools.sort_by_key(|pool| pool.block);
+    let (heap_size, pools, mut layout_tokens) = if let Some(layout) = &layout {
+        match read_layout(layout) {
+            Ok((path, size, pools)) => {
+                let path = LitStr::new(&path.to_string_lossy(), layout.span());
+                let tokens = quote! {
+                    const _: &str = include_str!(#path);
+                };
+                (size, pools, tokens)
+            }
+            Err(err) => {
+                return syn::Error::new(layout.span(), err).to_compile_error().into();
+            }
+        }
+    } else {
+        let pools = config.heap.pools.iter().map(|pool| (pool.block, pool.capacity)).collect();
+        (config.heap.size, pools, quote!())
+    };
     let mut pools_tokens = Vec::new();
-    let mut pointer = config.memory.ram.origin + config.memory.ram.size - config.heap.size;
-    for pool in &pools {
-        let block = LitInt::new(&pool.block.to_string(), Span::call_site());
-        let capacity = LitInt::new(&pool.capacity.to_string(), Span::call_site());
-        let address = LitInt::new(&pointer.to_string(), Span::call_site());
-        pools_tokens.push(quote! {
-            ::drone_core::heap::Pool::new(#address, #block, #capacity)
+    let mut regions_tokens = Vec::new();
+    let origin = config.memory.ram.origin + config.memory.ram.size - heap_size;
+    let region_attrs = region_attrs_tokens(&attrs);
+    let main_count = pools.len();
+    let mut tag_offsets = Vec::new();
+    let mut blocks_len = 0;
+    regions_tokens.push(quote! {
+        ::drone_core::heap::Region {
+            first_pool: 0,
+            pool_count: #main_count,
+            attrs: #region_attrs,
+        }
+    });
+    if let Err(err) =
+        push_pools(&mut pools_tokens, &mut tag_offsets, &mut blocks_len, origin, pools)
+    {
+        let span = layout.as_ref().map_or_else(Span::call_site, LitStr::span);
+        return syn::Error::new(span, err).to_compile_error().into();
+    }
+    let mut spans = vec![(u64::from(origin), u64::from(origin) + u64::from(heap_size), None)];
+    let mut consts_tokens = Vec::new();
+    for (index, region) in regions.iter().enumerate() {
+        let Region { attrs, ident, origin, layout, region_attrs } = region;
+        let origin = match origin.base10_parse::<u32>() {
+            Ok(origin) => origin,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        let (path, size, pools) = match read_layout(layout) {
+            Ok(layout) => layout,
+            Err(err) => return syn::Error::new(layout.span(), err).to_compile_error().into(),
+        };
+        let (start, end) = (u64::from(origin), u64::from(origin) + u64::from(size));
+        if end > 1 << 32 {
+            return syn::Error::new(ident.span(), "region exceeds the address space")
+                .to_compile_error()
+                .into();
+        }
+        if let Some((_, _, other)) = spans
+            .iter()
+            .find(|&&(other_start, other_end, _)| start < other_end && other_start < end)
+        {
+            let message = match other {
+                Some(other) => format!("region overlaps region `{}`", other),
+                None => "region overlaps the main heap region".to_string(),
+            };
+            return syn::Error::new(ident.span(), message).to_compile_error().into();
+        }
+        spans.push((start, end, Some(ident)));
+        let path = LitStr::new(&path.to_string_lossy(), layout.span());
+        let first_pool = pools_tokens.len();
+        let pool_count = pools.len();
+        let region_attrs = region_attrs_tokens(region_attrs);
+        let index = index + 1;
+        regions_tokens.push(quote! {
+            ::drone_core::heap::Region {
+                first_pool: #first_pool,
+                pool_count: #pool_count,
+                attrs: #region_attrs,
+            }
+        });
+        consts_tokens.push(quote! {
+            #(#attrs)*
+            pub const #ident: usize = #index;
         });
-        pointer += pool.block * pool.capacity;
+        layout_tokens.extend(quote! {
+            const _: &str = include_str!(#path);
+        });
+        if let Err(err) =
+            push_pools(&mut pools_tokens, &mut tag_offsets, &mut blocks_len, origin, pools)
+        {
+            return syn::Error::new(layout.span(), err).to_compile_error().into();
+        }
     }
-    let pools_len = pools.len();
+    let pools_len = pools_tokens.len();
+    let regions_len = regions_tokens.len();
+    let (tags_field, tags_init, tags_fn) = if cfg!(feature = "heaptag") {
+        let blocks_len = blocks_len as usize;
+        (
+            quote!(tags: ::drone_core::heap::TagTable<[u8; #blocks_len]>,),
+            quote!(tags: ::drone_core::heap::TagTable::new([0; #blocks_len]),),
+            quote! {
+                #[inline]
+                fn tags(&self) -> Option<&::drone_core::heap::TagTable<[u8]>> {
+                    Some(&self.tags)
+                }
+
+                #[inline]
+                fn tag_offset(&self, pool_idx: usize) -> usize {
+                    const TAG_OFFSETS: [usize; #pools_len] = [#(#tag_offsets),*];
+                    TAG_OFFSETS[pool_idx]
+                }
+            },
+        )
+    } else {
+        (quote!(), quote!(), quote!())
+    };
 
     let expanded = quote! {
+        #layout_tokens
+
         #(#heap_attrs)*
         #heap_vis struct #heap_ident {
             pools: [::drone_core::heap::Pool; #pools_len],
+            #tags_field
         }
 
         impl #heap_ident {
+            #(#consts_tokens)*
+
             /// Creates a new heap.
             pub const fn new() -> Self {
                 Self {
                     pools: [#(#pools_tokens),*],
+                    #tags_init
                 }
             }
         }
@@ -65,6 +348,16 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
         impl ::drone_core::heap::Allocator for #heap_ident {
             const POOL_COUNT: usize = #pools_len;
 
+            const REGION_COUNT: usize = #regions_len;
+
+            #[inline]
+            fn region(index: usize) -> ::drone_core::heap::Region {
+                const REGIONS: [::drone_core::heap::Region; #regions_len] = [#(#regions_tokens),*];
+                REGIONS[index]
+            }
+
+            #tags_fn
+
             #[inline]
             unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
             where
@@ -135,3 +428,44 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     };
     expanded.into()
 }
+
+fn push_pools(
+    pools_tokens: &mut Vec<TokenStream2>,
+    tag_offsets: &mut Vec<usize>,
+    blocks_len: &mut u32,
+    mut pointer: u32,
+    mut pools: Vec<(u32, u32)>,
+) -> std::result::Result<(), String> {
+    pools.sort_by_key(|&(block, _)| block);
+    for (block, capacity) in pools {
+        let align = block_align(block);
+        if block % align != 0 {
+            return Err(format!(
+                "pool block size {} is not a multiple of {}, blocks would be misaligned",
+                block, align
+            ));
+        }
+        if pointer % align != 0 {
+            return Err(format!(
+                "pool with {}-byte blocks starts at {:#x}, which is not aligned to {} bytes",
+                block, pointer, align
+            ));
+        }
+        tag_offsets.push(*blocks_len as usize);
+        *blocks_len += capacity;
+        let block_lit = LitInt::new(&block.to_string(), Span::call_site());
+        let capacity_lit = LitInt::new(&capacity.to_string(), Span::call_site());
+        let address = LitInt::new(&pointer.to_string(), Span::call_site());
+        pools_tokens.push(quote! {
+            ::drone_core::heap::Pool::new(#address, #block_lit, #capacity_lit)
+        });
+        pointer += block * capacity;
+    }
+    Ok(())
+}
+
+/// Returns the alignment required for blocks of `block` size: the largest
+/// power of two not exceeding the size, up to [`MAX_BLOCK_ALIGN`].
+fn block_align(block: u32) -> u32 {
+    (1 << (31 - block.max(1).leading_zeros())).min(MAX_BLOCK_ALIGN)
+}
@@ -0,0 +1,105 @@
+use drone_macros_core::compile_error;
+use inflector::Inflector;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream, Result},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Attribute, Ident, Token, Type, Visibility,
+};
+
+struct Input {
+    stages: Vec<Stage>,
+}
+
+struct Stage {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    prereqs: Vec<Type>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut stages = Vec::new();
+        while !input.is_empty() {
+            stages.push(input.parse()?);
+        }
+        if stages.is_empty() {
+            return Err(input.error("Expected at least one stage"));
+        }
+        Ok(Self { stages })
+    }
+}
+
+impl Parse for Stage {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
+        let prereqs = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            content.call(Punctuated::<_, Token![,]>::parse_terminated)?.into_iter().collect()
+        } else {
+            Vec::new()
+        };
+        input.parse::<Token![;]>()?;
+        Ok(Self { attrs, vis, ident, prereqs })
+    }
+}
+
+pub fn proc_macro(input: TokenStream) -> TokenStream {
+    let Input { stages } = parse_macro_input!(input);
+    if !stages[0].prereqs.is_empty() {
+        compile_error!("The first stage can't have prerequisites");
+    }
+    let mut tokens = Vec::new();
+    let mut prev: Option<&Ident> = None;
+    for Stage { attrs, vis, ident, prereqs } in &stages {
+        let wrapper = format_ident!("__{}_init_stage", ident.to_string().to_snake_case());
+        let entry = if let Some(prev) = prev {
+            let args =
+                (0..prereqs.len()).map(|i| format_ident!("_prereq{}", i)).collect::<Vec<_>>();
+            quote! {
+                impl #prev {
+                    /// Advances to the next boot stage, consuming the current
+                    /// stage and the prerequisite tokens.
+                    #[inline]
+                    pub fn advance(self, #(#args: #prereqs),*) -> #ident {
+                        #ident { __priv: () }
+                    }
+                }
+            }
+        } else {
+            quote! {
+                unsafe impl ::drone_core::token::Token for #ident {
+                    #[inline]
+                    unsafe fn take() -> Self {
+                        Self { __priv: () }
+                    }
+                }
+            }
+        };
+        tokens.push(quote! {
+            mod #wrapper {
+                use super::*;
+
+                #(#attrs)*
+                pub struct #ident {
+                    __priv: (),
+                }
+
+                #entry
+            }
+
+            #vis use #wrapper::#ident;
+        });
+        prev = Some(ident);
+    }
+    let expanded = quote!(#(#tokens)*);
+    expanded.into()
+}
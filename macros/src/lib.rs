@@ -8,20 +8,26 @@
 extern crate proc_macro;
 
 mod bitfield;
+mod bitfield_enum;
+mod config;
 mod config_override;
 mod heap;
+mod init_tokens;
 mod log_baud_rate;
 mod periph;
+mod periph_instances;
 mod periph_map;
 mod periph_singular;
 mod reg;
 mod reg_assert_taken;
+mod reg_mem;
 mod reg_tokens;
 mod reg_tokens_inner;
 mod simple_token;
 mod simple_tokens;
 mod static_tokens;
 mod thr;
+mod token_group;
 
 use proc_macro::TokenStream;
 
@@ -30,6 +36,16 @@ pub fn derive_bitfield(input: TokenStream) -> TokenStream {
     bitfield::proc_macro_derive(input)
 }
 
+#[proc_macro_derive(BitfieldEnum)]
+pub fn derive_bitfield_enum(input: TokenStream) -> TokenStream {
+    bitfield_enum::proc_macro_derive(input)
+}
+
+#[proc_macro]
+pub fn config(input: TokenStream) -> TokenStream {
+    config::proc_macro(input)
+}
+
 #[proc_macro]
 pub fn config_override(input: TokenStream) -> TokenStream {
     config_override::proc_macro(input)
@@ -40,6 +56,11 @@ pub fn heap(input: TokenStream) -> TokenStream {
     heap::proc_macro(input)
 }
 
+#[proc_macro]
+pub fn init_tokens(input: TokenStream) -> TokenStream {
+    init_tokens::proc_macro(input)
+}
+
 #[proc_macro]
 pub fn log_baud_rate(input: TokenStream) -> TokenStream {
     log_baud_rate::proc_macro(input)
@@ -50,6 +71,11 @@ pub fn periph(input: TokenStream) -> TokenStream {
     periph::proc_macro(input)
 }
 
+#[proc_macro]
+pub fn periph_instances(input: TokenStream) -> TokenStream {
+    periph_instances::proc_macro(input)
+}
+
 #[proc_macro]
 pub fn periph_map(input: TokenStream) -> TokenStream {
     periph_map::proc_macro(input)
@@ -70,6 +96,11 @@ pub fn reg_assert_taken(input: TokenStream) -> TokenStream {
     reg_assert_taken::proc_macro(input)
 }
 
+#[proc_macro]
+pub fn reg_mem(input: TokenStream) -> TokenStream {
+    reg_mem::proc_macro(input)
+}
+
 #[proc_macro]
 pub fn reg_tokens(input: TokenStream) -> TokenStream {
     reg_tokens::proc_macro(input)
@@ -90,6 +121,16 @@ pub fn unsafe_simple_tokens(input: TokenStream) -> TokenStream {
     simple_tokens::proc_macro(input)
 }
 
+#[proc_macro]
+pub fn token_group(input: TokenStream) -> TokenStream {
+    token_group::proc_macro_group(input)
+}
+
+#[proc_macro]
+pub fn unsafe_group_token(input: TokenStream) -> TokenStream {
+    token_group::proc_macro_entry(input)
+}
+
 #[proc_macro]
 pub fn unsafe_static_tokens(input: TokenStream) -> TokenStream {
     static_tokens::proc_macro(input)
@@ -14,6 +14,9 @@ struct Input {
     trait_items: Vec<TraitItem>,
     struct_attrs: Vec<Attribute>,
     struct_ident: Ident,
+    inventory: bool,
+    reg_table: bool,
+    dyn_handle: bool,
     blocks: Vec<Block>,
 }
 
@@ -57,12 +60,43 @@ impl Parse for Input {
         input.parse::<Token![pub]>()?;
         input.parse::<Token![struct]>()?;
         let struct_ident = input.parse()?;
+        let (mut inventory, mut reg_table, mut dyn_handle) = (false, false, false);
+        if input.parse::<Option<Token![:]>>()?.is_some() {
+            loop {
+                let ident = input.parse::<Ident>()?;
+                if ident == "Inventory" {
+                    inventory = true;
+                } else if ident == "RegTable" {
+                    reg_table = true;
+                } else if ident == "Dyn" {
+                    dyn_handle = true;
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "Expected `Inventory`, `RegTable`, or `Dyn`",
+                    ));
+                }
+                if input.parse::<Option<Token![+]>>()?.is_none() {
+                    break;
+                }
+            }
+        }
         input.parse::<Token![;]>()?;
         let mut blocks = Vec::new();
         while !input.is_empty() {
             blocks.push(input.parse()?);
         }
-        Ok(Self { trait_attrs, trait_ident, trait_items, struct_attrs, struct_ident, blocks })
+        Ok(Self {
+            trait_attrs,
+            trait_ident,
+            trait_items,
+            struct_attrs,
+            struct_ident,
+            inventory,
+            reg_table,
+            dyn_handle,
+            blocks,
+        })
     }
 }
 
@@ -130,11 +164,25 @@ impl Parse for Field {
 
 #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { trait_attrs, trait_ident, trait_items, struct_attrs, struct_ident, blocks } =
-        &parse_macro_input!(input);
+    let Input {
+        trait_attrs,
+        trait_ident,
+        trait_items,
+        struct_attrs,
+        struct_ident,
+        inventory,
+        reg_table,
+        dyn_handle,
+        blocks,
+    } = &parse_macro_input!(input);
     let mut tokens = Vec::new();
     let mut periph_bounds = Vec::new();
     let mut periph_fields = Vec::new();
+    let mut dump_tokens = Vec::new();
+    let mut table_tokens = Vec::new();
+    let mut clock_tokens = None;
+    let mut clock_attrs = None;
+    let mut clock_features = None;
     let mut traits_export = Vec::new();
     for Block { ident: block_ident, regs } in blocks {
         let block_snk = block_ident.to_string().to_snake_case();
@@ -217,10 +265,12 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                     let mut u_traits = Vec::new();
                     let mut s_traits = Vec::new();
                     let mut c_traits = Vec::new();
-                    let mut field_option = false;
+                    let (mut field_option, mut field_clock) = (false, false);
                     for ident in traits {
                         if ident == "Option" {
                             field_option = true;
+                        } else if ident == "Clock" {
+                            field_clock = true;
                         } else {
                             u_traits.push(format_ident!("U{}", ident));
                             s_traits.push(format_ident!("S{}", ident));
@@ -232,6 +282,58 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                     features.add_clause(&field_features);
                     let field_attrs = features.attrs();
                     let struct_attrs = field_features.attrs();
+                    if field_clock {
+                        if !reg_shared || field_option {
+                            compile_error!(
+                                "`Clock` can be used only with a non-optional field of a `Shared` \
+                                 register"
+                            );
+                        }
+                        if clock_tokens.is_some() {
+                            compile_error!("`Clock` can be used only once");
+                        }
+                        if size != 32 {
+                            compile_error!("`Clock` can be used only with a 32-bit register");
+                        }
+                        clock_attrs = Some(field_attrs.clone());
+                        clock_features = Some(features.clone());
+                        clock_tokens = Some(quote! {
+                            #field_attrs
+                            impl<T: #trait_ident> ::drone_core::periph::PeriphClock
+                                for #struct_ident<T>
+                            {
+                                #[inline]
+                                fn enable_clock(&self) {
+                                    let (address, mask) = Self::clock_gate();
+                                    unsafe { ::drone_core::periph::modify_clock(address, mask, true) };
+                                }
+
+                                #[inline]
+                                fn disable_clock(&self) {
+                                    let (address, mask) = Self::clock_gate();
+                                    unsafe { ::drone_core::periph::modify_clock(address, mask, false) };
+                                }
+
+                                #[inline]
+                                fn is_clock_enabled(&self) -> bool {
+                                    let (address, mask) = Self::clock_gate();
+                                    unsafe { ::drone_core::reg::load_part::<u32>(address) & mask != 0 }
+                                }
+                            }
+
+                            #field_attrs
+                            impl<T: #trait_ident> #struct_ident<T> {
+                                #[inline]
+                                fn clock_gate() -> (usize, u32) {
+                                    use ::drone_core::reg::{field::RegField, tag::Srt, Reg};
+                                    let address =
+                                        <<T::#s_field as RegField<Srt>>::Reg as Reg<Srt>>::ADDRESS;
+                                    let offset = <T::#s_field as RegField<Srt>>::OFFSET;
+                                    (address, 1 << offset)
+                                }
+                            }
+                        });
+                    }
                     let field_trait_items = quote! {
                         type #u_field: ::drone_core::reg::field::RegField<
                             ::drone_core::reg::tag::Urt,
@@ -458,6 +560,12 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                 let s_traits = &s_traits;
                 let c_traits = &c_traits;
                 traits_export.push((reg_attrs.clone(), reg_trait.clone()));
+                if !reg_option && variant_i == 0 {
+                    table_tokens.push(quote! {
+                        #reg_attrs
+                        <T::#s_reg as ::drone_core::reg::Reg<::drone_core::reg::tag::Srt>>::LAYOUT,
+                    });
+                }
                 if reg_option {
                     traits_export.push((reg_attrs.clone(), reg_trait_opt.clone()));
                     traits_export.push((reg_attrs.clone(), reg_trait_ext.clone()));
@@ -645,6 +753,17 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                             #reg_attrs
                             pub #block_var_snk: T::#s_reg,
                         });
+                        if traits.iter().any(|t| t == "RwReg" || t == "RoReg") {
+                            let name = block_var_snk.to_string().to_uppercase();
+                            dump_tokens.push(quote! {
+                                #reg_attrs
+                                f(
+                                    #name,
+                                    ::drone_core::reg::RReg::load_bits(&self.#block_var_snk)
+                                        as u64,
+                                );
+                            });
+                        }
                     }
                     for (variant_j, variant) in variants.iter().enumerate() {
                         if variant_i == variant_j {
@@ -760,6 +879,9 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
             }
         });
     }
+    if *dyn_handle {
+        traits_export.push((quote!(), format_ident!("{}Dyn", struct_ident)));
+    }
     let traits_export = traits_export
         .into_iter()
         .map(|(attrs, ident)| {
@@ -775,6 +897,149 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
             pub _marker: ::core::marker::PhantomData<T>,
         });
     }
+    let inventory_tokens = if *inventory {
+        let teardown = clock_attrs.as_ref().map(|attrs| {
+            quote! {
+                #attrs
+                ::drone_core::periph::PeriphClock::disable_clock(self);
+            }
+        });
+        Some(quote! {
+            impl<T: #trait_ident> ::drone_core::inventory::Item for #struct_ident<T> {
+                #[inline]
+                fn teardown(
+                    &mut self,
+                    _token: &mut ::drone_core::inventory::GuardToken<Self>,
+                ) {
+                    #teardown
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let table_tokens = if *reg_table {
+        Some(quote! {
+            impl<T: #trait_ident> #struct_ident<T> {
+                /// Layouts of the registers used by the peripheral, including
+                /// shared ones.
+                ///
+                /// Optional registers are skipped.
+                pub const REG_TABLE: &'static [::drone_core::reg::RegLayout] = &[
+                    #(#table_tokens)*
+                ];
+            }
+        })
+    } else {
+        None
+    };
+
+    let dyn_tokens = if *dyn_handle {
+        let dyn_trait = format_ident!("{}Dyn", struct_ident);
+        let dyn_ref = format_ident!("{}Ref", struct_ident);
+        let dyn_trait_doc =
+            format!("An object-safe capability trait implemented by all [`{}`]s.", struct_ident);
+        let dyn_ref_doc = format!("A mutable reference to any [`{}`].", struct_ident);
+        let (table_item, table_impl) = if *reg_table {
+            let table_doc = format!(
+                "Returns [`REG_TABLE`]({}::REG_TABLE) of the peripheral variant.",
+                struct_ident
+            );
+            (
+                Some(quote! {
+                    #[doc = #table_doc]
+                    fn reg_table(&self) -> &'static [::drone_core::reg::RegLayout];
+                }),
+                Some(quote! {
+                    #[inline]
+                    fn reg_table(&self) -> &'static [::drone_core::reg::RegLayout] {
+                        Self::REG_TABLE
+                    }
+                }),
+            )
+        } else {
+            (None, None)
+        };
+        let clock_bounds = clock_features
+            .iter()
+            .map(|features| (features.clone(), quote!(::drone_core::periph::PeriphClock)))
+            .collect::<Vec<_>>();
+        let dyn_traits = clock_bounds
+            .as_slice()
+            .transpose()
+            .into_iter()
+            .map(|(features, bounds)| {
+                let attrs = features.attrs();
+                quote! {
+                    #[doc = #dyn_trait_doc]
+                    #attrs
+                    pub trait #dyn_trait: ::drone_core::periph::PeriphDyn #(+ #bounds)* {
+                        #table_item
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        Some(quote! {
+            impl<T: #trait_ident> ::drone_core::periph::PeriphDyn for #struct_ident<T> {
+                #[inline]
+                fn variant_name(&self) -> &'static str {
+                    ::core::any::type_name::<T>()
+                }
+
+                #[inline]
+                fn dump_dyn(&self, f: &mut dyn FnMut(&'static str, u64)) {
+                    self.dump(f);
+                }
+            }
+
+            #(#dyn_traits)*
+
+            impl<T: #trait_ident> #dyn_trait for #struct_ident<T> {
+                #table_impl
+            }
+
+            #[doc = #dyn_ref_doc]
+            pub struct #dyn_ref<'a>(pub &'a mut dyn #dyn_trait);
+
+            impl<'a, T: #trait_ident> From<&'a mut #struct_ident<T>> for #dyn_ref<'a> {
+                #[inline]
+                fn from(periph: &'a mut #struct_ident<T>) -> Self {
+                    Self(periph)
+                }
+            }
+
+            impl<'a> ::core::ops::Deref for #dyn_ref<'a> {
+                type Target = dyn #dyn_trait + 'a;
+
+                #[inline]
+                fn deref(&self) -> &Self::Target {
+                    &*self.0
+                }
+            }
+
+            impl<'a> ::core::ops::DerefMut for #dyn_ref<'a> {
+                #[inline]
+                fn deref_mut(&mut self) -> &mut Self::Target {
+                    &mut *self.0
+                }
+            }
+
+            impl ::drone_core::periph::PeriphDyn for #dyn_ref<'_> {
+                #[inline]
+                fn variant_name(&self) -> &'static str {
+                    self.0.variant_name()
+                }
+
+                #[inline]
+                fn dump_dyn(&self, f: &mut dyn FnMut(&'static str, u64)) {
+                    self.0.dump_dyn(f);
+                }
+            }
+        })
+    } else {
+        None
+    };
 
     let expanded = quote! {
         #(#tokens)*
@@ -784,6 +1049,25 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
             #(#periph_fields)*
         }
 
+        #clock_tokens
+
+        #inventory_tokens
+
+        #table_tokens
+
+        #dyn_tokens
+
+        impl<T: #trait_ident> #struct_ident<T> {
+            /// Reads all readable registers owned by the peripheral, and passes
+            /// their names and values to `f`.
+            ///
+            /// Optional and shared registers are skipped.
+            #[allow(unused_mut, unused_variables)]
+            pub fn dump<F: FnMut(&'static str, u64)>(&self, mut f: F) {
+                #(#dump_tokens)*
+            }
+        }
+
         #[allow(missing_docs)]
         pub mod traits {
             #(#traits_export)*
@@ -0,0 +1,107 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Group, Literal, TokenStream as TokenStream2, TokenTree};
+use quote::quote;
+use syn::{
+    braced, parenthesized,
+    parse::{Parse, ParseStream, Result},
+    parse_macro_input, Ident, LitInt, Path, Token,
+};
+
+struct Input {
+    macro_path: Path,
+    rows: Vec<TokenStream2>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let macro_path = input.parse()?;
+        input.parse::<Token![!]>()?;
+        input.parse::<Token![;]>()?;
+        let mut rows = Vec::new();
+        while !input.is_empty() {
+            if input.peek(Token![for]) {
+                parse_loop(input, &mut rows)?;
+            } else {
+                rows.push(parse_row(input)?);
+            }
+        }
+        Ok(Self { macro_path, rows })
+    }
+}
+
+fn parse_row(input: ParseStream<'_>) -> Result<TokenStream2> {
+    let content;
+    parenthesized!(content in input);
+    input.parse::<Token![;]>()?;
+    content.parse()
+}
+
+fn parse_loop(input: ParseStream<'_>, rows: &mut Vec<TokenStream2>) -> Result<()> {
+    input.parse::<Token![for]>()?;
+    let var = input.parse::<Ident>()?;
+    input.parse::<Token![in]>()?;
+    let start = input.parse::<LitInt>()?.base10_parse::<u64>()?;
+    let inclusive = if input.peek(Token![..=]) {
+        input.parse::<Token![..=]>()?;
+        true
+    } else {
+        input.parse::<Token![..]>()?;
+        false
+    };
+    let end = input.parse::<LitInt>()?.base10_parse::<u64>()?;
+    let content;
+    braced!(content in input);
+    let mut templates = Vec::new();
+    while !content.is_empty() {
+        templates.push(parse_row(&content)?);
+    }
+    let end = if inclusive { end.saturating_add(1) } else { end };
+    for n in start..end {
+        for template in &templates {
+            rows.push(substitute(template.clone(), &var, n));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces `#var` with the integer literal `n`, and `ident#var` with the
+/// concatenated identifier.
+fn substitute(tokens: TokenStream2, var: &Ident, n: u64) -> TokenStream2 {
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+    let is_var = |i: usize| match (tokens.get(i), tokens.get(i + 1)) {
+        (Some(TokenTree::Punct(punct)), Some(TokenTree::Ident(ident))) => {
+            punct.as_char() == '#' && ident == var
+        }
+        _ => false,
+    };
+    let mut output = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Group(group) => {
+                let mut new = Group::new(group.delimiter(), substitute(group.stream(), var, n));
+                new.set_span(group.span());
+                output.push(TokenTree::Group(new));
+            }
+            TokenTree::Ident(ident) if is_var(i + 1) => {
+                let ident = Ident::new(&format!("{}{}", ident, n), ident.span());
+                output.push(TokenTree::Ident(ident));
+                i += 2;
+            }
+            _ if is_var(i) => {
+                output.push(TokenTree::Literal(Literal::u64_unsuffixed(n)));
+                i += 1;
+            }
+            token => output.push(token.clone()),
+        }
+        i += 1;
+    }
+    output.into_iter().collect()
+}
+
+pub fn proc_macro(input: TokenStream) -> TokenStream {
+    let Input { macro_path, rows } = parse_macro_input!(input);
+    let rows = rows.iter().map(|row| quote!(#macro_path! { #row }));
+    let expanded = quote!(#(#rows)*);
+    expanded.into()
+}
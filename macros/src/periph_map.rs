@@ -339,7 +339,8 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                                     type #c_field_opt = ();
                                 }
                             });
-                            macro_tokens.push((features, quote!(#block_reg_field_snk: ())));
+                            macro_tokens
+                                .push((features, (quote!(#block_reg_field_snk: ()), quote!())));
                         } else {
                             tokens.push(quote! {
                                 #field_attrs
@@ -401,9 +402,18 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                                 }
                             });
                         }
-                        macro_tokens.push((features, quote! {
-                            #block_reg_field_snk: $reg.#block_var_path_snk.#field_path_ident
-                        }));
+                        macro_tokens.push((
+                            features,
+                            (
+                                quote! {
+                                    #block_reg_field_snk: $reg.#block_var_path_snk.#field_path_ident
+                                },
+                                quote! {
+                                    $reg.#block_var_path_snk.#field_path_ident =
+                                        periph.#block_reg_field_snk
+                                },
+                            ),
+                        ));
                     } else {
                         if field_option {
                             tokens.push(quote! {
@@ -480,7 +490,8 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                         }
                     });
                     if !reg_shared && variant_i == 0 {
-                        macro_tokens.push((reg_features.clone(), quote!(#block_var_snk: ())));
+                        macro_tokens
+                            .push((reg_features.clone(), (quote!(#block_var_snk: ()), quote!())));
                     }
                 } else if reg_shared {
                     if fields.iter().any(|field| field.path.is_some()) {
@@ -629,20 +640,27 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                         }
                     });
                     if variant_i == 0 {
-                        let macro_token =
-                            if let Some((from_block_ident, from_var_path_ident)) = variant {
-                                let from_variant = format_ident!(
-                                    "{}_{}",
-                                    from_block_ident.to_string().to_snake_case(),
-                                    from_var_path_ident.to_string().to_snake_case()
-                                );
-                                let into_variant = var_path_snk.as_ref().map(|var_path_snk| {
-                                    format_ident!("into_{}_{}", block_path_snk, var_path_snk)
-                                });
-                                quote!(#block_var_snk: $reg.#from_variant.#into_variant())
-                            } else {
-                                quote!(#block_var_snk: $reg.#block_var_path_snk)
-                            };
+                        let macro_token = if let Some((from_block_ident, from_var_path_ident)) =
+                            variant
+                        {
+                            let from_variant = format_ident!(
+                                "{}_{}",
+                                from_block_ident.to_string().to_snake_case(),
+                                from_var_path_ident.to_string().to_snake_case()
+                            );
+                            let into_variant = var_path_snk.as_ref().map(|var_path_snk| {
+                                format_ident!("into_{}_{}", block_path_snk, var_path_snk)
+                            });
+                            let from_into_variant = format_ident!("into_{}", from_variant);
+                            (quote!(#block_var_snk: $reg.#from_variant.#into_variant()), quote! {
+                                $reg.#from_variant = periph.#block_var_snk.#from_into_variant()
+                            })
+                        } else {
+                            (
+                                quote!(#block_var_snk: $reg.#block_var_path_snk),
+                                quote!($reg.#block_var_path_snk = periph.#block_var_snk),
+                            )
+                        };
                         macro_tokens.push((reg_features.clone(), macro_token));
                     }
                 }
@@ -655,6 +673,8 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     for (features, macro_tokens) in macro_tokens.as_slice().transpose() {
         let attrs = features.attrs();
         let macro_root_path = macro_root_path.iter().collect::<Vec<_>>();
+        let (extract_tokens, release_tokens): (Vec<_>, Vec<_>) = macro_tokens.into_iter().unzip();
+        let release_tokens = release_tokens.into_iter().filter(|tokens| !tokens.is_empty());
         tokens.push(quote! {
             #attrs
             #(#periph_macro_attrs)*
@@ -664,9 +684,16 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                     $crate#(#macro_root_path)*::#periph_struct::<
                         $crate#(#macro_root_path)*::#periph_ty,
                     > {
-                        #(#macro_tokens,)*
+                        #(#extract_tokens,)*
                     }
                 };
+                (release $periph:expr => $reg:ident) => {{
+                    #[allow(unused_variables)]
+                    let periph: $crate#(#macro_root_path)*::#periph_struct::<
+                        $crate#(#macro_root_path)*::#periph_ty,
+                    > = $periph;
+                    #(#release_tokens;)*
+                }};
             }
         });
     }
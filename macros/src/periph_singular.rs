@@ -5,7 +5,7 @@ use quote::{format_ident, quote};
 use syn::{
     braced,
     parse::{Parse, ParseStream, Result},
-    parse_macro_input, Attribute, Ident, Path, Token,
+    parse_macro_input, Attribute, Ident, Path, Token, Type,
 };
 
 const MACRO_PREFIX: &str = "periph_";
@@ -18,9 +18,16 @@ struct Input {
     struct_ident: Ident,
     root_path: Path,
     macro_root_path: Option<Path>,
+    periphs: Vec<Periph>,
     blocks: Vec<Block>,
 }
 
+struct Periph {
+    ident: Ident,
+    ty: Type,
+    path: Path,
+}
+
 struct Block {
     ident: Ident,
     regs: Vec<Reg>,
@@ -70,9 +77,14 @@ impl Parse for Input {
             input.parse::<Token![;]>()?;
             Some(path)
         };
+        let mut periphs = Vec::new();
         let mut blocks = Vec::new();
         while !input.is_empty() {
-            blocks.push(input.parse()?);
+            if input.peek2(Token![:]) {
+                periphs.push(input.parse()?);
+            } else {
+                blocks.push(input.parse()?);
+            }
         }
         Ok(Self {
             macro_attrs,
@@ -81,11 +93,24 @@ impl Parse for Input {
             struct_ident,
             root_path,
             macro_root_path,
+            periphs,
             blocks,
         })
     }
 }
 
+impl Parse for Periph {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let path = input.parse()?;
+        input.parse::<Token![;]>()?;
+        Ok(Self { ident, ty, path })
+    }
+}
+
 impl Parse for Block {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let ident = input.parse()?;
@@ -134,11 +159,22 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
         struct_ident,
         root_path,
         macro_root_path,
+        periphs,
         blocks,
     } = &parse_macro_input!(input);
     let mut tokens = Vec::new();
     let mut periph_tokens = Vec::new();
     let mut macro_tokens = Vec::new();
+    for Periph { ident, ty, path } in periphs {
+        periph_tokens.push(quote! {
+            #[allow(missing_docs)]
+            pub #ident: #ty
+        });
+        macro_tokens.push((
+            CfgCond::default(),
+            (quote!(#ident: #path!($reg)), quote!(#path!(release periph.#ident => $reg))),
+        ));
+    }
     for Block { ident: block_ident, regs } in blocks {
         let block_snk = block_ident.to_string().to_snake_case();
         let block_ident = format_ident!("{}", unkeywordize(&block_snk));
@@ -155,8 +191,13 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                         ::drone_core::reg::tag::Srt,
                     >
                 });
-                macro_tokens
-                    .push((reg_features.clone(), quote!(#block_reg_snk: $reg.#block_reg_snk)));
+                macro_tokens.push((
+                    reg_features.clone(),
+                    (
+                        quote!(#block_reg_snk: $reg.#block_reg_snk),
+                        quote!($reg.#block_reg_snk = periph.#block_reg_snk),
+                    ),
+                ));
             } else {
                 for Field { features: field_features, ident: field_ident } in fields {
                     let field_snk = field_ident.to_string().to_snake_case();
@@ -177,7 +218,10 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                     });
                     macro_tokens.push((
                         features,
-                        quote!(#block_reg_field_snk: $reg.#block_reg_snk.#field_ident),
+                        (
+                            quote!(#block_reg_field_snk: $reg.#block_reg_snk.#field_ident),
+                            quote!($reg.#block_reg_snk.#field_ident = periph.#block_reg_field_snk),
+                        ),
                     ));
                 }
             }
@@ -186,6 +230,7 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     for (features, macro_tokens) in macro_tokens.as_slice().transpose() {
         let attrs = features.attrs();
         let macro_root_path = macro_root_path.iter();
+        let (extract_tokens, release_tokens): (Vec<_>, Vec<_>) = macro_tokens.into_iter().unzip();
         tokens.push(quote! {
             #attrs
             #(#macro_attrs)*
@@ -193,9 +238,14 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
             macro_rules! #macro_ident {
                 ($reg:ident) => {
                     $crate#(#macro_root_path)*::#struct_ident {
-                        #(#macro_tokens,)*
+                        #(#extract_tokens,)*
                     }
                 };
+                (release $periph:expr => $reg:ident) => {{
+                    #[allow(unused_variables)]
+                    let periph = $periph;
+                    #(#release_tokens;)*
+                }};
             }
         });
     }
@@ -5,33 +5,49 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use std::collections::HashSet;
 use syn::{
-    braced,
+    braced, bracketed,
     parse::{Parse, ParseStream, Result},
-    parse_macro_input, Attribute, Ident, LitInt, LitStr, Token, Visibility,
+    parse_macro_input,
+    punctuated::Punctuated,
+    token, Attribute, Ident, LitInt, LitStr, Token, Visibility,
 };
 
 struct Input {
     regs: Vec<Reg>,
 }
 
+#[derive(Clone)]
 struct Reg {
     attrs: Vec<Attribute>,
     vis: Visibility,
     block: Ident,
     ident: Ident,
+    array: Option<(LitInt, LitInt)>,
     address: LitInt,
     size: u8,
     reset: LitInt,
     traits: Vec<Ident>,
     fields: Vec<Field>,
+    sync_only: bool,
+    privileged: bool,
+    access: Vec<u8>,
 }
 
+#[derive(Clone)]
 struct Field {
     attrs: Vec<Attribute>,
     ident: Ident,
     offset: LitInt,
     width: LitInt,
     traits: Vec<Ident>,
+    values: Vec<Value>,
+}
+
+#[derive(Clone)]
+struct Value {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    bits: LitInt,
 }
 
 impl Parse for Input {
@@ -46,14 +62,57 @@ impl Parse for Input {
 
 impl Parse for Reg {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let sync_only = attrs.iter().position(|attr| attr.path.is_ident("sync_only"));
+        let sync_only = sync_only.map(|position| attrs.remove(position)).is_some();
+        let privileged = attrs.iter().position(|attr| attr.path.is_ident("privileged"));
+        let privileged = privileged.map(|position| attrs.remove(position)).is_some();
+        let access = attrs.iter().position(|attr| attr.path.is_ident("access"));
+        let access = access.map(|position| attrs.remove(position));
         let vis = input.parse()?;
         input.parse::<Token![mod]>()?;
         let block = input.parse()?;
         let ident = input.parse()?;
+        let array = if input.peek(token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            let count = content.parse()?;
+            content.parse::<Token![;]>()?;
+            let stride = content.parse()?;
+            Some((count, stride))
+        } else {
+            None
+        };
         input.parse::<Token![;]>()?;
         let address = input.parse()?;
-        let size = input.parse::<LitInt>()?.base10_parse()?;
+        let size_lit = input.parse::<LitInt>()?;
+        let size = size_lit.base10_parse()?;
+        if ![8, 16, 32, 64].contains(&size) {
+            return Err(syn::Error::new(
+                size_lit.span(),
+                "register size must be one of 0x08, 0x10, 0x20, 0x40",
+            ));
+        }
+        let access = match access {
+            Some(attr) => {
+                let widths =
+                    attr.parse_args_with(Punctuated::<LitInt, Token![,]>::parse_terminated)?;
+                let mut access = Vec::new();
+                for width_lit in widths {
+                    let width = width_lit.base10_parse()?;
+                    if ![8, 16, 32].contains(&width) || width >= size {
+                        return Err(syn::Error::new(
+                            width_lit.span(),
+                            "access width must be one of 0x08, 0x10, 0x20, and less than the \
+                             register size",
+                        ));
+                    }
+                    access.push(width);
+                }
+                access
+            }
+            None => Vec::new(),
+        };
         let reset = input.parse()?;
         let mut traits = Vec::new();
         while !input.peek(Token![;]) {
@@ -64,7 +123,21 @@ impl Parse for Reg {
         while input.fork().parse::<Field>().is_ok() {
             fields.push(input.parse()?);
         }
-        Ok(Self { attrs, vis, block, ident, address, size, reset, traits, fields })
+        Ok(Self {
+            attrs,
+            vis,
+            block,
+            ident,
+            array,
+            address,
+            size,
+            reset,
+            traits,
+            fields,
+            sync_only,
+            privileged,
+            access,
+        })
     }
 }
 
@@ -77,10 +150,35 @@ impl Parse for Field {
         let offset = content.parse()?;
         let width = content.parse()?;
         let mut traits = Vec::new();
+        let mut values = Vec::new();
         while !content.is_empty() {
-            traits.push(content.parse()?);
+            if content.peek(token::Brace) {
+                let values_content;
+                let brace = braced!(values_content in content);
+                if width.base10_digits() == "1" {
+                    return Err(syn::Error::new(
+                        brace.span,
+                        "enumerated values are supported only for multiple-bits fields",
+                    ));
+                }
+                values.extend(
+                    values_content.call(Punctuated::<_, Token![,]>::parse_terminated)?.into_iter(),
+                );
+            } else {
+                traits.push(content.parse()?);
+            }
         }
-        Ok(Self { attrs, ident, offset, width, traits })
+        Ok(Self { attrs, ident, offset, width, traits, values })
+    }
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let bits = input.parse()?;
+        Ok(Self { attrs, ident, bits })
     }
 }
 
@@ -89,11 +187,23 @@ impl Reg {
     fn generate(&self) -> TokenStream2 {
         let t = format_ident!("_T");
         let val_ty = format_ident!("u{}", self.size);
+        let tag_bound = if self.sync_only && cfg!(feature = "sync-policy") {
+            quote!(::drone_core::reg::tag::RegAtomic)
+        } else {
+            quote!(::drone_core::reg::tag::RegTag)
+        };
         let mut imports = self.traits.iter().cloned().collect::<HashSet<_>>();
         let mut tokens = Vec::new();
+        let mut hold_tokens = Vec::new();
+        let mut bit_band_tokens = Vec::new();
         let mut struct_tokens = Vec::new();
         let mut ctor_tokens = Vec::new();
-        for Field { attrs, ident, offset, width, traits } in &self.fields {
+        let mut split_struct_tokens = Vec::new();
+        let mut split_ctor_tokens = Vec::new();
+        let mut w1c_mask = 0_u128;
+        let mut ws_mask = 0_u128;
+        let mut layout_tokens = Vec::new();
+        for Field { attrs, ident, offset, width, traits, values } in &self.fields {
             let field_snk = ident.to_string().to_snake_case();
             let mut field_psc = ident.to_string().to_pascal_case();
             if field_psc == "Val" {
@@ -102,6 +212,21 @@ impl Reg {
             let field_psc = format_ident!("{}", field_psc);
             let field_ident = format_ident!("{}", unkeywordize(&field_snk));
             imports.extend(traits.iter().cloned());
+            let name = LitStr::new(&ident.to_string(), Span::call_site());
+            layout_tokens.push(quote! {
+                ::drone_core::reg::FieldLayout { name: #name, offset: #offset, width: #width }
+            });
+            let field_mask = {
+                let offset = offset.base10_parse::<u32>().unwrap_or(0);
+                let width = width.base10_parse::<u32>().unwrap_or(0);
+                ((1_u128 << width) - 1) << offset
+            };
+            if traits.iter().any(|name| name == "W1cRegField") {
+                w1c_mask |= field_mask;
+            }
+            if traits.iter().any(|name| name == "WsRegField") {
+                ws_mask |= field_mask;
+            }
             struct_tokens.push(quote! {
                 #(#attrs)*
                 pub #field_ident: #field_psc<#t>
@@ -109,58 +234,61 @@ impl Reg {
             ctor_tokens.push(quote! {
                 #field_ident: ::drone_core::token::Token::take()
             });
-            tokens.push(quote! {
+            split_struct_tokens.push(quote! {
                 #(#attrs)*
-                #[derive(Clone, Copy)]
-                pub struct #field_psc<#t: ::drone_core::reg::tag::RegTag>(#t);
-
-                unsafe impl<#t> ::drone_core::token::Token for #field_psc<#t>
-                where
-                    #t: ::drone_core::reg::tag::RegTag,
-                {
-                    #[inline]
-                    unsafe fn take() -> Self {
-                        #field_psc(#t::default())
-                    }
-                }
-
-                impl<#t> ::drone_core::reg::field::RegField<#t> for #field_psc<#t>
-                where
-                    #t: ::drone_core::reg::tag::RegTag,
-                {
-                    type Reg = Reg<#t>;
-                    type URegField = #field_psc<::drone_core::reg::tag::Urt>;
-                    type SRegField = #field_psc<::drone_core::reg::tag::Srt>;
-                    type CRegField = #field_psc<::drone_core::reg::tag::Crt>;
-
-                    const OFFSET: usize = #offset;
-                    const WIDTH: usize = #width;
+                pub #field_ident: ::drone_core::reg::field::FieldRef<
+                    'a,
+                    #field_psc<::drone_core::reg::tag::Urt>,
+                >
+            });
+            split_ctor_tokens.push(quote! {
+                #field_ident: ::drone_core::reg::field::FieldRef::new(&mut self.#field_ident)
+            });
+            let kind = if width.base10_digits() == "1" {
+                format_ident!("RegFieldBit")
+            } else {
+                format_ident!("RegFieldBits")
+            };
+            tokens.push(quote! {
+                ::drone_core::reg_field! {
+                    #(#attrs)*
+                    #field_psc, #offset, #width, #kind, #tag_bound, [#(#traits),*]
                 }
             });
-            for ident in traits {
-                tokens.push(quote! {
-                    impl<#t: ::drone_core::reg::tag::RegTag> #ident<#t> for #field_psc<#t> {}
-                });
-            }
             if width.base10_digits() == "1" {
-                tokens.push(quote! {
-                    impl<#t> ::drone_core::reg::field::RegFieldBit<#t> for #field_psc<#t>
-                    where
-                        #t: ::drone_core::reg::tag::RegTag,
-                    {
-                    }
-                });
+                if self.traits.iter().any(|name| name == "RegBitBand") {
+                    let field_bb = format_ident!("{}_bb", field_snk);
+                    bit_band_tokens.push(quote! {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #field_bb(
+                            &self,
+                        ) -> ::drone_core::reg::BitBand<'_, #t, #field_psc<#t>> {
+                            ::drone_core::reg::BitBand::new(&self.#field_ident)
+                        }
+                    });
+                }
                 if traits.iter().any(|name| name == "RRRegField") {
-                    tokens.push(quote! {
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
-                            #(#attrs)*
-                            #[inline]
-                            pub fn #field_ident(&self) -> bool {
-                                ::drone_core::reg::field::RRRegFieldBit::read(
-                                    &self.reg.#field_ident,
-                                    &self.val,
-                                )
-                            }
+                    hold_tokens.push(quote! {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #field_ident(&self) -> bool {
+                            ::drone_core::reg::field::RRRegFieldBit::read(
+                                &self.reg.#field_ident,
+                                &self.val,
+                            )
+                        }
+                    });
+                }
+                if traits.iter().any(|name| name == "RcRegField") {
+                    hold_tokens.push(quote! {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #field_ident(&self) -> ::drone_core::reg::field::ReadCleared<bool> {
+                            ::drone_core::reg::field::RcRegFieldBit::read(
+                                &self.reg.#field_ident,
+                                &self.val,
+                            )
                         }
                     });
                 }
@@ -168,98 +296,394 @@ impl Reg {
                     let set_field = format_ident!("set_{}", field_snk);
                     let clear_field = format_ident!("clear_{}", field_snk);
                     let toggle_field = format_ident!("toggle_{}", field_snk);
+                    hold_tokens.push(quote! {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #set_field(&mut self) -> &mut Self {
+                            ::drone_core::reg::field::WWRegFieldBit::set(
+                                &self.reg.#field_ident,
+                                &mut self.val,
+                            );
+                            self
+                        }
+
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #clear_field(&mut self) -> &mut Self {
+                            ::drone_core::reg::field::WWRegFieldBit::clear(
+                                &self.reg.#field_ident,
+                                &mut self.val,
+                            );
+                            self
+                        }
+
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #toggle_field(&mut self) -> &mut Self {
+                            ::drone_core::reg::field::WWRegFieldBit::toggle(
+                                &self.reg.#field_ident,
+                                &mut self.val,
+                            );
+                            self
+                        }
+                    });
+                }
+            } else {
+                if traits.iter().any(|name| name == "RRRegField") {
+                    hold_tokens.push(quote! {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #field_ident(&self) -> #val_ty {
+                            ::drone_core::reg::field::RRRegFieldBits::read(
+                                &self.reg.#field_ident,
+                                &self.val,
+                            )
+                        }
+                    });
+                }
+                if traits.iter().any(|name| name == "RcRegField") {
+                    hold_tokens.push(quote! {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #field_ident(
+                            &self,
+                        ) -> ::drone_core::reg::field::ReadCleared<#val_ty> {
+                            ::drone_core::reg::field::RcRegFieldBits::read(
+                                &self.reg.#field_ident,
+                                &self.val,
+                            )
+                        }
+                    });
+                }
+                if traits.iter().any(|name| name == "WWRegField") {
+                    let write_field = format_ident!("write_{}", field_snk);
+                    hold_tokens.push(quote! {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #write_field(&mut self, bits: #val_ty) -> &mut Self {
+                            ::drone_core::reg::field::WWRegFieldBits::write(
+                                &self.reg.#field_ident,
+                                &mut self.val,
+                                bits,
+                            );
+                            self
+                        }
+                    });
+                }
+                if !values.is_empty() {
+                    let enum_ident = format_ident!("{}Enum", field_psc);
+                    let value_attrs = values.iter().map(|value| &value.attrs).collect::<Vec<_>>();
+                    let value_idents = values.iter().map(|value| &value.ident).collect::<Vec<_>>();
+                    let value_bits = values.iter().map(|value| &value.bits).collect::<Vec<_>>();
                     tokens.push(quote! {
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
-                            #(#attrs)*
-                            #[inline]
-                            pub fn #set_field(&mut self) -> &mut Self {
-                                ::drone_core::reg::field::WWRegFieldBit::set(
-                                    &self.reg.#field_ident,
-                                    &mut self.val,
-                                );
-                                self
-                            }
+                        #(#attrs)*
+                        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+                        pub enum #enum_ident {
+                            #(
+                                #(#value_attrs)*
+                                #value_idents,
+                            )*
+                        }
+
+                        impl<#t> ::drone_core::reg::field::RegFieldEnum<#t> for #field_psc<#t>
+                        where
+                            #t: ::drone_core::reg::tag::RegTag,
+                        {
+                            type Enum = #enum_ident;
 
-                            #(#attrs)*
                             #[inline]
-                            pub fn #clear_field(&mut self) -> &mut Self {
-                                ::drone_core::reg::field::WWRegFieldBit::clear(
-                                    &self.reg.#field_ident,
-                                    &mut self.val,
-                                );
-                                self
+                            fn enum_from_bits(
+                                bits: #val_ty,
+                            ) -> ::core::option::Option<#enum_ident> {
+                                match bits {
+                                    #(
+                                        #value_bits => ::core::option::Option::Some(
+                                            #enum_ident::#value_idents,
+                                        ),
+                                    )*
+                                    _ => ::core::option::Option::None,
+                                }
                             }
 
-                            #(#attrs)*
                             #[inline]
-                            pub fn #toggle_field(&mut self) -> &mut Self {
-                                ::drone_core::reg::field::WWRegFieldBit::toggle(
-                                    &self.reg.#field_ident,
-                                    &mut self.val,
-                                );
-                                self
+                            fn enum_into_bits(value: #enum_ident) -> #val_ty {
+                                match value {
+                                    #(#enum_ident::#value_idents => #value_bits,)*
+                                }
                             }
                         }
                     });
-                }
-            } else {
-                tokens.push(quote! {
-                    impl<#t> ::drone_core::reg::field::RegFieldBits<#t> for #field_psc<#t>
-                    where
-                        #t: ::drone_core::reg::tag::RegTag,
-                    {
-                    }
-                });
-                if traits.iter().any(|name| name == "RRRegField") {
-                    tokens.push(quote! {
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
+                    if traits.iter().any(|name| name == "RRRegField") {
+                        let read_field_enum = format_ident!("{}_enum", field_snk);
+                        hold_tokens.push(quote! {
                             #(#attrs)*
                             #[inline]
-                            pub fn #field_ident(&self) -> #val_ty {
-                                ::drone_core::reg::field::RRRegFieldBits::read(
+                            pub fn #read_field_enum(
+                                &self,
+                            ) -> ::core::option::Option<#enum_ident> {
+                                ::drone_core::reg::field::RRRegFieldEnum::read_enum(
                                     &self.reg.#field_ident,
                                     &self.val,
                                 )
                             }
-                        }
-                    });
-                }
-                if traits.iter().any(|name| name == "WWRegField") {
-                    let write_field = format_ident!("write_{}", field_snk);
-                    tokens.push(quote! {
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
+                        });
+                    }
+                    if traits.iter().any(|name| name == "WWRegField") {
+                        let write_field_enum = format_ident!("write_{}_enum", field_snk);
+                        hold_tokens.push(quote! {
                             #(#attrs)*
                             #[inline]
-                            pub fn #write_field(&mut self, bits: #val_ty) -> &mut Self {
-                                ::drone_core::reg::field::WWRegFieldBits::write(
+                            pub fn #write_field_enum(&mut self, value: #enum_ident) -> &mut Self {
+                                ::drone_core::reg::field::WWRegFieldEnum::write_enum(
                                     &self.reg.#field_ident,
                                     &mut self.val,
-                                    bits,
+                                    value,
                                 );
                                 self
                             }
-                        }
-                    });
+                        });
+                    }
                 }
             }
         }
+        let bit_band_tokens = if bit_band_tokens.is_empty() {
+            quote!()
+        } else {
+            quote! {
+                impl<#t: #tag_bound> Reg<#t> {
+                    #(#bit_band_tokens)*
+                }
+            }
+        };
+        hold_tokens.push(quote! {
+            /// Returns an iterator over the fields, which values differ between
+            /// `self` and `other`. The old values are taken from `self`, and
+            /// the new values from `other`.
+            #[inline]
+            pub fn diff_fields(&self, other: &Self) -> ::drone_core::reg::FieldChanges {
+                ::drone_core::reg::FieldChanges::new(
+                    <Reg<#t> as ::drone_core::reg::Reg<#t>>::LAYOUT.fields,
+                    self.val.bits() as u64,
+                    other.val.bits() as u64,
+                )
+            }
+        });
         if self.fields.is_empty() {
             struct_tokens.push(quote!(_marker: ::core::marker::PhantomData<#t>));
             ctor_tokens.push(quote!(_marker: ::core::marker::PhantomData));
+        } else if !(self.sync_only && cfg!(feature = "sync-policy")) {
+            let attrs = &self.attrs;
+            tokens.push(quote! {
+                #(#attrs)*
+                pub struct SplitFields<'a> {
+                    #(#split_struct_tokens),*
+                }
+
+                impl Reg<::drone_core::reg::tag::Urt> {
+                    /// Splits the register token into field tokens, which
+                    /// borrow the register token.
+                    #[inline]
+                    pub fn split_fields(&mut self) -> SplitFields<'_> {
+                        SplitFields { #(#split_ctor_tokens),* }
+                    }
+                }
+            });
         }
         for ident in &self.traits {
             tokens.push(quote! {
-                impl<#t: ::drone_core::reg::tag::RegTag> #ident<#t> for Reg<#t> {}
+                impl<#t: #tag_bound> #ident<#t> for Reg<#t> {}
+            });
+        }
+        for &width in &self.access {
+            let part_ty = format_ident!("u{}", width);
+            let count = usize::from(self.size / width);
+            let bytes = usize::from(width / 8);
+            let mut access_tokens = Vec::new();
+            if self.traits.iter().any(|ident| ident == "RReg") {
+                let load_part = format_ident!("load_u{}", width);
+                let doc = LitStr::new(
+                    &format!(
+                        "Reads the `index`-th {}-bit part of the register with a {}-bit access.",
+                        width, width
+                    ),
+                    Span::call_site(),
+                );
+                access_tokens.push(quote! {
+                    #[doc = #doc]
+                    ///
+                    /// # Panics
+                    ///
+                    /// If `index` is out of bounds.
+                    #[inline]
+                    pub fn #load_part(&self, index: usize) -> #part_ty {
+                        assert!(index < #count, "part index out of bounds");
+                        unsafe {
+                            ::drone_core::reg::load_part(
+                                <Self as ::drone_core::reg::Reg<#t>>::ADDRESS + index * #bytes,
+                            )
+                        }
+                    }
+                });
+            }
+            if self.traits.iter().any(|ident| ident == "WReg") {
+                let store_part = format_ident!("store_u{}", width);
+                let doc = LitStr::new(
+                    &format!(
+                        "Writes `value` to the `index`-th {}-bit part of the register with a \
+                         {}-bit access.",
+                        width, width
+                    ),
+                    Span::call_site(),
+                );
+                access_tokens.push(quote! {
+                    #[doc = #doc]
+                    ///
+                    /// # Panics
+                    ///
+                    /// If `index` is out of bounds.
+                    #[inline]
+                    pub fn #store_part(&self, index: usize, value: #part_ty) {
+                        assert!(index < #count, "part index out of bounds");
+                        unsafe {
+                            ::drone_core::reg::store_part(
+                                <Self as ::drone_core::reg::Reg<#t>>::ADDRESS + index * #bytes,
+                                value,
+                            );
+                        }
+                    }
+                });
+            }
+            tokens.push(quote! {
+                impl<#t: #tag_bound> Reg<#t> {
+                    #(#access_tokens)*
+                }
             });
         }
+        let privilege = if self.privileged {
+            quote!(::drone_core::reg::marker::PrivilegedReg)
+        } else {
+            quote!(::drone_core::reg::marker::UnprivilegedReg)
+        };
+        tokens.push(quote! {
+            impl<#t: ::drone_core::reg::tag::RegTag> #privilege<#t> for Reg<#t> {}
+        });
+        let load_raw = if self.traits.iter().any(|ident| ident == "RReg") {
+            quote! {
+                ::core::result::Result::Ok(u64::from(unsafe {
+                    ::drone_core::reg::load_part::<#val_ty>(
+                        <Self as ::drone_core::reg::Reg<#t>>::ADDRESS,
+                    )
+                }))
+            }
+        } else {
+            quote!(::core::result::Result::Err(::drone_core::reg::DynRegError::NotReadable))
+        };
+        let store_raw = if self.traits.iter().any(|ident| ident == "WReg") {
+            quote! {
+                unsafe {
+                    ::drone_core::reg::store_part::<#val_ty>(
+                        <Self as ::drone_core::reg::Reg<#t>>::ADDRESS,
+                        bits as #val_ty,
+                    );
+                }
+                ::core::result::Result::Ok(())
+            }
+        } else {
+            quote! {
+                let _ = bits;
+                ::core::result::Result::Err(::drone_core::reg::DynRegError::NotWritable)
+            }
+        };
+        tokens.push(quote! {
+            impl<#t: #tag_bound> ::drone_core::reg::DynReg for Reg<#t> {
+                #[inline]
+                fn layout(&self) -> ::drone_core::reg::RegLayout {
+                    <Self as ::drone_core::reg::Reg<#t>>::LAYOUT
+                }
+
+                #[inline]
+                fn load_raw(
+                    &self,
+                ) -> ::core::result::Result<u64, ::drone_core::reg::DynRegError> {
+                    #load_raw
+                }
+
+                #[inline]
+                fn store_raw(
+                    &self,
+                    bits: u64,
+                ) -> ::core::result::Result<(), ::drone_core::reg::DynRegError> {
+                    #store_raw
+                }
+            }
+        });
+        // Reading a FIFO register pops an entry, and reading a register with
+        // read-to-clear fields clears them.
+        let read_clears =
+            self.fields.iter().any(|field| field.traits.iter().any(|ident| ident == "RcRegField"));
+        let read_side_effects = read_clears || self.traits.iter().any(|ident| ident == "RegFifo");
+        if self.traits.iter().any(|ident| ident == "RReg") && !read_side_effects {
+            tokens.push(quote! {
+                impl<#t: #tag_bound> ::drone_core::reg::marker::PureReadReg<#t> for Reg<#t> {}
+            });
+        }
+        let summary_view = if self.traits.iter().any(|ident| ident == "RReg") && !read_side_effects
+        {
+            quote! {
+                ::core::option::Option::Some(unsafe {
+                    ::drone_core::reg::RegView::new(<Self as ::drone_core::reg::Reg<#t>>::LAYOUT)
+                })
+            }
+        } else {
+            quote!(::core::option::Option::None)
+        };
+        tokens.push(quote! {
+            impl<#t: #tag_bound> ::drone_core::reg::RegSummary for Reg<#t> {
+                #[inline]
+                fn summary_view() -> ::core::option::Option<::drone_core::reg::RegView> {
+                    #summary_view
+                }
+            }
+        });
         let imports = if imports.is_empty() {
             quote!()
         } else {
             let imports = imports.iter();
             quote!(use super::{#(#imports),*};)
         };
-        let Reg { attrs, vis, address, reset, .. } = self;
+        let w1c_mask = LitInt::new(&format!("0x{:X}", w1c_mask), Span::call_site());
+        let ws_mask = LitInt::new(&format!("0x{:X}", ws_mask), Span::call_site());
+        let hold_must_use = if read_clears {
+            quote!(#[must_use = "read-to-clear fields are lost if the value is discarded"])
+        } else {
+            quote!()
+        };
+        let Reg { attrs, vis, address, size, reset, .. } = self;
         let reg_full = self.reg_full();
+        let reg_name = LitStr::new(&format!("{}_{}", self.block, self.ident), Span::call_site());
+        let hold_fmt = if cfg!(feature = "regfmt") {
+            let ident = LitStr::new(&self.ident.to_string(), Span::call_site());
+            quote! {
+                impl<'a, #t: ::drone_core::reg::tag::RegTag> ::core::fmt::Debug for Hold<'a, #t> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        ::drone_core::reg::fmt_fields(
+                            f,
+                            #ident,
+                            <Reg<#t> as ::drone_core::reg::Reg<#t>>::LAYOUT.fields,
+                            self.val.bits() as u64,
+                        )
+                    }
+                }
+
+                impl<'a, #t: ::drone_core::reg::tag::RegTag> ::core::fmt::Display for Hold<'a, #t> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        ::core::fmt::Debug::fmt(self, f)
+                    }
+                }
+            }
+        } else {
+            quote!()
+        };
 
         quote! {
             #(#attrs)*
@@ -290,8 +714,17 @@ impl Reg {
                     type SReg = Reg<::drone_core::reg::tag::Srt>;
                     type CReg = Reg<::drone_core::reg::tag::Crt>;
 
+                    const LAYOUT: ::drone_core::reg::RegLayout = ::drone_core::reg::RegLayout {
+                        name: #reg_name,
+                        address: #address,
+                        size: #size,
+                        reset: #reset,
+                        fields: &[#(#layout_tokens),*],
+                    };
                     const ADDRESS: usize = #address;
                     const RESET: #val_ty = #reset;
+                    const W1C_MASK: #val_ty = #w1c_mask;
+                    const WS_MASK: #val_ty = #ws_mask;
 
                     #[inline]
                     unsafe fn val_from(bits: #val_ty) -> Val {
@@ -312,6 +745,7 @@ impl Reg {
                 }
 
                 #(#attrs)*
+                #hold_must_use
                 pub struct Hold<'a, #t: ::drone_core::reg::tag::RegTag> {
                     reg: &'a Reg<#t>,
                     val: Val,
@@ -332,11 +766,92 @@ impl Reg {
                     }
                 }
 
+                impl<'a, #t: #tag_bound> Hold<'a, #t> {
+                    #(#hold_tokens)*
+                }
+
+                #bit_band_tokens
+
+                #hold_fmt
+
                 #(#tokens)*
             }
         }
     }
 
+    fn generate_array(&self) -> Result<TokenStream2> {
+        let t = format_ident!("_T");
+        let (count, stride) = self.array.as_ref().unwrap();
+        let count_value = count.base10_parse::<usize>()?;
+        let stride_value = stride.base10_parse::<u64>()?;
+        let address = self.address.base10_parse::<u64>()?;
+        if count_value == 0 {
+            return Err(syn::Error::new(count.span(), "register array must not be empty"));
+        }
+        let mut elem_tokens = Vec::new();
+        let mut elem_fields = Vec::new();
+        let mut elem_mods = Vec::new();
+        for i in 0..count_value {
+            let mut elem = self.clone();
+            elem.array = None;
+            elem.ident = format_ident!("{}{}", self.ident, i);
+            elem.address = LitInt::new(
+                &format!("0x{:X}", address + stride_value * i as u64),
+                self.address.span(),
+            );
+            elem_tokens.push(elem.generate());
+            elem_fields.push(format_ident!("{}", elem.ident.to_string().to_snake_case()));
+            elem_mods.push(elem.reg_full());
+        }
+        let Reg { attrs, vis, address, .. } = self;
+        let reg_full = self.reg_full();
+        let first_field = &elem_fields[0];
+        let first_mod = &elem_mods[0];
+        Ok(quote! {
+            #(#elem_tokens)*
+
+            #(#attrs)*
+            #vis mod #reg_full {
+                /// The number of elements.
+                pub const COUNT: usize = #count;
+
+                /// The address of the first element.
+                pub const ADDRESS: usize = #address;
+
+                /// The distance between adjacent elements in bytes.
+                pub const STRIDE: usize = #stride;
+
+                #(#attrs)*
+                pub struct Array<#t: ::drone_core::reg::tag::RegTag> {
+                    #(pub #elem_fields: super::#elem_mods::Reg<#t>,)*
+                }
+
+                impl<#t: ::drone_core::reg::tag::RegTag> Array<#t> {
+                    /// Returns a handle to the element at `index`, or `None` if
+                    /// `index` is out of bounds.
+                    #[inline]
+                    pub fn get(
+                        &mut self,
+                        index: usize,
+                    ) -> ::core::option::Option<
+                        ::drone_core::reg::ArrayElem<'_, #t, super::#first_mod::Reg<#t>>,
+                    > {
+                        if index < COUNT {
+                            ::core::option::Option::Some(unsafe {
+                                ::drone_core::reg::ArrayElem::new(
+                                    &self.#first_field,
+                                    ADDRESS + index * STRIDE,
+                                )
+                            })
+                        } else {
+                            ::core::option::Option::None
+                        }
+                    }
+                }
+            }
+        })
+    }
+
     fn reg_full(&self) -> Ident {
         format_ident!(
             "{}_{}",
@@ -348,15 +863,25 @@ impl Reg {
 
 pub fn proc_macro(input: TokenStream) -> TokenStream {
     let Input { regs } = parse_macro_input!(input);
+    if let Err(err) = check(&regs) {
+        return err.to_compile_error().into();
+    }
+    if let Some(reg) = regs.iter().find(|reg| reg.array.is_some()) {
+        return match reg.generate_array() {
+            Ok(expanded) => expanded.into(),
+            Err(err) => err.to_compile_error().into(),
+        };
+    }
     let reg_tokens = regs.iter().map(Reg::generate).collect::<Vec<_>>();
+    let t = format_ident!("_T");
     let mut variant_tokens = Vec::new();
     for (i, reg_src) in regs.iter().enumerate() {
+        let mod_src = reg_src.reg_full();
+        let mut into_tokens = Vec::new();
         for (j, reg_dst) in regs.iter().enumerate() {
             if i == j {
                 continue;
             }
-            let t = format_ident!("_T");
-            let mod_src = reg_src.reg_full();
             let mod_dst = reg_dst.reg_full();
             let into_variant = format_ident!(
                 "into_{}_{}",
@@ -370,12 +895,17 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                 ),
                 Span::call_site(),
             );
+            into_tokens.push(quote! {
+                #[doc = #doc]
+                pub fn #into_variant(self) -> #mod_dst::Reg<#t> {
+                    unsafe { ::drone_core::token::Token::take() }
+                }
+            });
+        }
+        if !into_tokens.is_empty() {
             variant_tokens.push(quote! {
                 impl<#t: ::drone_core::reg::tag::RegTag> #mod_src::Reg<#t> {
-                    #[doc = #doc]
-                    pub fn #into_variant(self) -> #mod_dst::Reg<#t> {
-                        unsafe { ::drone_core::token::Token::take() }
-                    }
+                    #(#into_tokens)*
                 }
             });
         }
@@ -386,3 +916,173 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     };
     expanded.into()
 }
+
+fn check(regs: &[Reg]) -> Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    let mut push_error = |err| match &mut errors {
+        Some(errors) => errors.combine(err),
+        None => errors = Some(err),
+    };
+    if regs.len() > 1 {
+        for reg in regs.iter().filter(|reg| reg.array.is_some()) {
+            push_error(syn::Error::new(
+                reg.ident.span(),
+                format!("register array `{} {}` can't have variants", reg.block, reg.ident),
+            ));
+        }
+    }
+    let mut reg_names = HashSet::new();
+    for reg in regs {
+        if reg.traits.iter().any(|ident| ident == "RegBitBand") {
+            check_bit_band(reg, &mut push_error);
+        }
+        if !reg_names.insert(reg.reg_full()) {
+            push_error(syn::Error::new(
+                reg.ident.span(),
+                format!("register `{} {}` is defined more than once", reg.block, reg.ident),
+            ));
+        }
+        let mut field_names = HashSet::new();
+        let mut field_masks = Vec::<(&Ident, u128)>::new();
+        for Field { ident, offset, width, traits, values, .. } in &reg.fields {
+            if !field_names.insert(ident.to_string().to_snake_case()) {
+                push_error(syn::Error::new(
+                    ident.span(),
+                    format!("field `{}` is defined more than once in `{}`", ident, reg.ident),
+                ));
+            }
+            match (offset.base10_parse::<u32>(), width.base10_parse::<u32>()) {
+                (Ok(_), Ok(0)) => {
+                    push_error(syn::Error::new(
+                        width.span(),
+                        format!("field `{}` has zero width", ident),
+                    ));
+                }
+                (Ok(offset_bits), Ok(width_bits))
+                    if offset_bits.saturating_add(width_bits) > reg.size.into() =>
+                {
+                    push_error(syn::Error::new(
+                        offset.span(),
+                        format!(
+                            "field `{}` doesn't fit into the {}-bit register `{}`",
+                            ident, reg.size, reg.ident
+                        ),
+                    ));
+                }
+                (Ok(offset_bits), Ok(width_bits)) => {
+                    let mask = ((1_u128 << width_bits) - 1) << offset_bits;
+                    for (other, other_mask) in &field_masks {
+                        if mask & other_mask != 0 {
+                            push_error(syn::Error::new(
+                                ident.span(),
+                                format!(
+                                    "field `{}` overlaps field `{}` in `{}`",
+                                    ident, other, reg.ident
+                                ),
+                            ));
+                        }
+                    }
+                    field_masks.push((ident, mask));
+                }
+                (Err(err), _) | (_, Err(err)) => push_error(err),
+            }
+            for field_trait in traits {
+                let reg_trait = match field_trait.to_string().as_str() {
+                    "RRRegField" | "RoRRegField" | "RcRegField" => "RReg",
+                    "WWRegField" | "WoWRegField" | "W1cRegField" | "WsRegField" => "WReg",
+                    "WoWoRegField" => "WoReg",
+                    _ => continue,
+                };
+                if !reg.traits.iter().any(|ident| ident == reg_trait) {
+                    push_error(syn::Error::new(
+                        field_trait.span(),
+                        format!(
+                            "field `{}` is `{}`, but register `{}` is not `{}`",
+                            ident, field_trait, reg.ident, reg_trait
+                        ),
+                    ));
+                }
+            }
+            if let Some(field_trait) = traits.iter().find(|name| *name == "RcRegField") {
+                if traits.iter().any(|name| name == "RRRegField" || name == "RoRRegField") {
+                    push_error(syn::Error::new(
+                        field_trait.span(),
+                        format!(
+                            "field `{}` is `RcRegField`, which is used in place of `RRRegField`",
+                            ident
+                        ),
+                    ));
+                }
+            }
+            if let Ok(width_bits) = width.base10_parse::<u32>() {
+                let mut value_bits = HashSet::new();
+                for Value { ident: value_ident, bits, .. } in values {
+                    match bits.base10_parse::<u128>() {
+                        Ok(value) if width_bits < 128 && value >> width_bits != 0 => {
+                            push_error(syn::Error::new(
+                                bits.span(),
+                                format!(
+                                    "value `{}` doesn't fit into the {}-bit field `{}`",
+                                    value_ident, width_bits, ident
+                                ),
+                            ));
+                        }
+                        Ok(value) => {
+                            if !value_bits.insert(value) {
+                                push_error(syn::Error::new(
+                                    bits.span(),
+                                    format!(
+                                        "value `{}` of field `{}` is already taken by another \
+                                         value",
+                                        value_ident, ident
+                                    ),
+                                ));
+                            }
+                        }
+                        Err(err) => push_error(err),
+                    }
+                }
+            }
+        }
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// Checks that the bit-band alias of every bit of `reg` exists, and that
+/// bit-band writes don't clear write-one-to-clear flags.
+fn check_bit_band(reg: &Reg, push_error: &mut impl FnMut(syn::Error)) {
+    const REGIONS: [u64; 2] = [0x2000_0000, 0x4000_0000];
+    const REGION_SIZE: u64 = 0x0010_0000;
+    let (count, stride) = match &reg.array {
+        Some((count, stride)) => (count.base10_parse::<u64>(), stride.base10_parse::<u64>()),
+        None => (Ok(1), Ok(0)),
+    };
+    match (reg.address.base10_parse::<u64>(), count, stride) {
+        (Ok(address), Ok(count), Ok(stride)) => {
+            let end = address + stride * count.saturating_sub(1) + u64::from(reg.size / 8);
+            if !REGIONS.iter().any(|&base| address >= base && end <= base + REGION_SIZE) {
+                push_error(syn::Error::new(
+                    reg.address.span(),
+                    format!(
+                        "register `{} {}` is `RegBitBand`, but it is outside of the bit-band \
+                         regions",
+                        reg.block, reg.ident
+                    ),
+                ));
+            }
+        }
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => push_error(err),
+    }
+    for Field { ident, traits, .. } in &reg.fields {
+        if let Some(field_trait) = traits.iter().find(|name| *name == "W1cRegField") {
+            push_error(syn::Error::new(
+                field_trait.span(),
+                format!(
+                    "field `{}` is `W1cRegField`, but register `{}` is `RegBitBand`, and bit-band \
+                     writes would clear the other flags",
+                    ident, reg.ident
+                ),
+            ));
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use inflector::Inflector;
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    bracketed,
+    parse::{Parse, ParseStream, Result},
+    parse_macro_input, Attribute, Ident, LitInt, LitStr, Token, Type, Visibility,
+};
+
+struct Input {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    elem: Type,
+    len: LitInt,
+    address: LitInt,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let content;
+        bracketed!(content in input);
+        let elem = content.parse()?;
+        content.parse::<Token![;]>()?;
+        let len = content.parse()?;
+        input.parse::<Token![=]>()?;
+        let address = input.parse()?;
+        input.parse::<Option<Token![;]>>()?;
+        Ok(Self { attrs, vis, ident, elem, len, address })
+    }
+}
+
+pub fn proc_macro(input: TokenStream) -> TokenStream {
+    let Input { attrs, vis, ident, elem, len, address } = parse_macro_input!(input);
+    let wrapper = format_ident!("__{}_mmio_region", ident.to_string().to_snake_case());
+    let name = LitStr::new(&ident.to_string(), Span::call_site());
+    let expanded = quote! {
+        mod #wrapper {
+            use super::*;
+
+            #(#attrs)*
+            pub struct #ident {
+                __priv: (),
+            }
+
+            unsafe impl ::drone_core::token::Token for #ident {
+                #[inline]
+                unsafe fn take() -> Self {
+                    Self {
+                        __priv: (),
+                    }
+                }
+            }
+
+            unsafe impl ::drone_core::reg::mem::MmioRegion for #ident {
+                type Elem = #elem;
+
+                const ADDRESS: usize = #address;
+                const LEN: usize = #len;
+            }
+        }
+
+        #vis use #wrapper::#ident;
+
+        ::drone_core::token_record!(#name, #address);
+    };
+    expanded.into()
+}
@@ -2,7 +2,8 @@ use drone_macros_core::unkeywordize;
 use inflector::Inflector;
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
+use std::collections::HashSet;
 use syn::{
     braced,
     parse::{Parse, ParseStream, Result},
@@ -111,7 +112,10 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
         root_path,
         blocks,
     } = parse_macro_input!(input);
-    let mut tokens = Vec::new();
+    if let Err(err) = check(&blocks) {
+        return err.to_compile_error().into();
+    }
+    let mut tokens = vec![check_defined(&root_path, &blocks)];
     let mut prev_macro = prev_macro.map(|prev_macro| quote!(#prev_macro));
     let macro_export = matches!(next_macro_vis, Visibility::Public(_));
     let (conditional_blocks, regular_blocks) =
@@ -169,6 +173,7 @@ fn make_macro(
         let block_snk = block_ident.to_string().to_snake_case();
         let block_name = format_ident!("{}", unkeywordize(&block_snk));
         let mut block_tokens = Vec::new();
+        let mut map_tokens = Vec::new();
         let block_attrs_non_cfg =
             block_attrs.iter().filter(|attr| !is_cfg_attr(attr)).collect::<Vec<_>>();
         for Reg { attrs: reg_attrs, ident: reg_ident, skip } in regs {
@@ -180,6 +185,16 @@ fn make_macro(
                 pub use #root_path::#reg_long as #reg_short;
                 pub use #root_path::#reg_long::Reg as #reg_psc;
             });
+            let reg_cfg_attrs = reg_attrs.iter().filter(|attr| is_cfg_attr(attr));
+            let reg_srt = quote! {
+                <#reg_psc<::drone_core::reg::tag::Srt> as ::drone_core::reg::Reg<
+                    ::drone_core::reg::tag::Srt,
+                >>
+            };
+            map_tokens.push(quote! {
+                #(#reg_cfg_attrs)*
+                #reg_srt::LAYOUT
+            });
             if !skip {
                 let macro_root_path = macro_root_path.iter();
                 defs.push(quote! {
@@ -192,6 +207,9 @@ fn make_macro(
             #(#block_attrs)*
             #block_vis mod #block_name {
                 #(#block_tokens)*
+
+                /// The layouts of the block registers.
+                pub const MAP: &[::drone_core::reg::RegLayout] = &[#(#map_tokens),*];
             }
         });
     }
@@ -201,6 +219,7 @@ fn make_macro(
             #prev_macro! {
                 $(#[$attr])* $vis struct $ty;
                 $(!$undefs;)*
+                $(split { $($subsets)* })?
                 __extend { #(#defs)* $($defs)* }
             }
         }
@@ -210,6 +229,7 @@ fn make_macro(
                 $(#[$attr])* $vis struct $ty;
                 { #(#defs)* $($defs)* }
                 { $($undefs;)* }
+                { $($($subsets)*)? }
             }
         }
     };
@@ -220,25 +240,119 @@ fn make_macro(
             (
                 $(#[$attr:meta])* $vis:vis struct $ty:ident;
                 $(!$undefs:ident;)*
+                $(split { $($subsets:tt)* })?
+                __extend { $($defs:tt)* }
             ) => {
-                #macro_ident! {
-                    $(#[$attr])* $vis struct $ty;
-                    $(!$undefs;)*
-                    __extend {}
-                }
+                #macro_tokens
             };
             (
                 $(#[$attr:meta])* $vis:vis struct $ty:ident;
                 $(!$undefs:ident;)*
-                __extend { $($defs:tt)* }
+                $(split { $($subsets:tt)* })?
             ) => {
-                #macro_tokens
+                #macro_ident! {
+                    $(#[$attr])* $vis struct $ty;
+                    $(!$undefs;)*
+                    $(split { $($subsets)* })?
+                    __extend {}
+                }
             };
         }
     });
     tokens
 }
 
+fn check(blocks: &[Block]) -> Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    let mut push_error = |err| match &mut errors {
+        Some(errors) => errors.combine(err),
+        None => errors = Some(err),
+    };
+    let mut block_names = HashSet::new();
+    for Block { attrs, ident: block_ident, regs, .. } in blocks {
+        if !attrs.iter().any(is_cfg_attr)
+            && !block_names.insert(block_ident.to_string().to_snake_case())
+        {
+            push_error(syn::Error::new(
+                block_ident.span(),
+                format!("block `{}` is listed more than once", block_ident),
+            ));
+        }
+        let mut reg_names = HashSet::new();
+        for Reg { attrs, ident, .. } in regs {
+            if !attrs.iter().any(is_cfg_attr)
+                && !reg_names.insert(ident.to_string().to_snake_case())
+            {
+                push_error(syn::Error::new(
+                    ident.span(),
+                    format!("register `{}` is listed more than once in `{}`", ident, block_ident),
+                ));
+            }
+        }
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// Reports registers, which are not defined by any `reg!` map.
+///
+/// Each register gets a deprecated fallback module, which is shadowed by the
+/// glob import of the root path if the register is defined. Using the fallback
+/// is denied, so the error points at the register with a readable message.
+fn check_defined(root_path: &Path, blocks: &[Block]) -> TokenStream2 {
+    let mut fallbacks = Vec::new();
+    let mut uses = Vec::new();
+    for Block { attrs: block_attrs, ident: block_ident, regs, .. } in blocks {
+        let block_cfg_attrs =
+            block_attrs.iter().filter(|attr| is_cfg_attr(attr)).collect::<Vec<_>>();
+        for Reg { attrs: reg_attrs, ident: reg_ident, .. } in regs {
+            let reg_cfg_attrs =
+                reg_attrs.iter().filter(|attr| is_cfg_attr(attr)).collect::<Vec<_>>();
+            let reg_long = Ident::new(
+                &format!(
+                    "{}_{}",
+                    block_ident.to_string().to_snake_case(),
+                    reg_ident.to_string().to_snake_case()
+                ),
+                reg_ident.span(),
+            );
+            let note = format!(
+                "register `{} {}` is not defined by any `reg!` map",
+                block_ident, reg_ident
+            );
+            fallbacks.push(quote! {
+                #(#block_cfg_attrs)* #(#reg_cfg_attrs)*
+                #[allow(dead_code)]
+                mod #reg_long {
+                    #[deprecated(note = #note)]
+                    pub struct Reg<T>(T);
+                }
+            });
+            let reg_path = quote_spanned!(reg_ident.span() => #reg_long::Reg);
+            uses.push(quote! {
+                #(#block_cfg_attrs)* #(#reg_cfg_attrs)*
+                let _: ::core::option::Option<#reg_path<::drone_core::reg::tag::Srt>> =
+                    ::core::option::Option::None;
+            });
+        }
+    }
+    quote! {
+        const _: () = {
+            #(#fallbacks)*
+
+            const _: () = {
+                #[allow(unused_imports)]
+                use #root_path::*;
+
+                #[deny(deprecated)]
+                #[allow(dead_code)]
+                fn check_defined() {
+                    #(#uses)*
+                }
+            };
+        };
+    }
+}
+
 fn negate_cfg_attrs(cfg_attrs: &[&Attribute]) -> Attribute {
     let cfg_attrs = cfg_attrs.iter().map(|attr| &attr.tokens).collect::<Vec<_>>();
     Attribute {
@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use syn::{
     braced,
     parse::{Parse, ParseStream, Result},
@@ -10,10 +10,13 @@ use syn::{
 
 struct Input {
     attrs: Vec<Attribute>,
+    summary: bool,
+    dyn_reg: bool,
     vis: Visibility,
     ident: Ident,
     defs: Vec<Def>,
     undefs: Vec<Undef>,
+    subsets: Vec<Subset>,
 }
 
 struct Def {
@@ -26,9 +29,20 @@ struct Undef {
     ident: Ident,
 }
 
+struct Subset {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    regs: Vec<Ident>,
+}
+
 impl Parse for Input {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let summary = attrs.iter().position(|attr| attr.path.is_ident("summary"));
+        let summary = summary.map(|position| attrs.remove(position)).is_some();
+        let dyn_reg = attrs.iter().position(|attr| attr.path.is_ident("dyn_reg"));
+        let dyn_reg = dyn_reg.map(|position| attrs.remove(position)).is_some();
         let vis = input.parse()?;
         input.parse::<Token![struct]>()?;
         let ident = input.parse()?;
@@ -45,7 +59,13 @@ impl Parse for Input {
         while !content.is_empty() {
             undefs.push(content.parse()?);
         }
-        Ok(Self { attrs, vis, ident, defs, undefs })
+        let content;
+        braced!(content in input);
+        let mut subsets = Vec::new();
+        while !content.is_empty() {
+            subsets.push(content.parse()?);
+        }
+        Ok(Self { attrs, summary, dyn_reg, vis, ident, defs, undefs, subsets })
     }
 }
 
@@ -67,13 +87,36 @@ impl Parse for Undef {
     }
 }
 
+impl Parse for Subset {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
+        let content;
+        braced!(content in input);
+        let mut regs = Vec::new();
+        while !content.is_empty() {
+            regs.push(content.parse()?);
+            content.parse::<Token![;]>()?;
+        }
+        Ok(Self { attrs, vis, ident, regs })
+    }
+}
+
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { attrs, vis, ident, defs, undefs } = &parse_macro_input!(input);
+    let Input { attrs, summary, dyn_reg, vis, ident, defs, undefs, subsets } =
+        &parse_macro_input!(input);
     let mut def_tokens = BTreeMap::new();
     let mut ctor_tokens = BTreeMap::new();
     let mut assert_tokens = BTreeMap::new();
+    let mut summary_tokens = BTreeMap::new();
+    let mut lookup_tokens = BTreeMap::new();
+    let mut for_each_tokens = BTreeMap::new();
+    let mut def_paths = BTreeMap::new();
     for Def { attrs, ident, path } in defs {
         let string = ident.to_string();
+        def_paths.insert(string.clone(), (attrs, path));
         let lit_str = LitStr::new(&string, Span::call_site());
         def_tokens.insert(string.clone(), quote! {
             #(#attrs)*
@@ -84,8 +127,40 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
             #(#attrs)*
             #ident: ::drone_core::token::Token::take(),
         });
+        let cfg_attrs = attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+        summary_tokens.insert(string.clone(), quote! {
+            #(#cfg_attrs)*
+            {
+                let view = <#path<::drone_core::reg::tag::Srt> as ::drone_core::reg::RegSummary>
+                    ::summary_view();
+                if let ::core::option::Option::Some(view) = view {
+                    view.load().write_to(port);
+                }
+            }
+        });
+        let cfg_attrs = attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+        lookup_tokens.insert(string.clone(), quote! {
+            #(#cfg_attrs)*
+            {
+                if ::drone_core::reg::DynReg::layout(&self.#ident).name == name {
+                    return ::core::option::Option::Some(&self.#ident);
+                }
+            }
+        });
+        let cfg_attrs = attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+        for_each_tokens.insert(string.clone(), quote! {
+            #(#cfg_attrs)*
+            f(&self.#ident);
+        });
+        let path_str = LitStr::new(&quote!(#path).to_string().replace(' ', ""), Span::call_site());
         assert_tokens.insert(string, quote! {
             ::drone_core::reg::assert_taken!(#lit_str);
+            ::drone_core::token_record!(
+                #path_str,
+                <#path<::drone_core::reg::tag::Srt> as ::drone_core::reg::Reg<
+                    ::drone_core::reg::tag::Srt,
+                >>::ADDRESS
+            );
         });
     }
     for Undef { ident } in undefs {
@@ -93,10 +168,70 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
         def_tokens.remove(&ident);
         ctor_tokens.remove(&ident);
         assert_tokens.remove(&ident);
+        summary_tokens.remove(&ident);
+        lookup_tokens.remove(&ident);
+        for_each_tokens.remove(&ident);
+        def_paths.remove(&ident);
     }
+    let split = match split(ident, subsets, &def_paths) {
+        Ok(split) => split,
+        Err(err) => return err.to_compile_error().into(),
+    };
     let def_tokens = def_tokens.values();
     let ctor_tokens = ctor_tokens.values();
     let assert_tokens = assert_tokens.values();
+    let summary_tokens = summary_tokens.values();
+    let summary = if *summary {
+        quote! {
+            impl #ident {
+                /// Writes the address and the value of every readable register
+                /// in the index to `port`, as pairs of `u32` words.
+                ///
+                /// Intended for post-mortem dumps from a panic handler.
+                ///
+                /// # Safety
+                ///
+                /// The registers are read without the register tokens.
+                /// Registers with read side-effects, like read-to-clear flags,
+                /// are skipped.
+                pub unsafe fn summary(port: ::drone_core::log::Port) {
+                    if !port.is_enabled() {
+                        return;
+                    }
+                    #(#summary_tokens)*
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+    let lookup_tokens = lookup_tokens.values();
+    let for_each_tokens = for_each_tokens.values();
+    let dyn_reg = if *dyn_reg {
+        quote! {
+            impl #ident {
+                /// Returns the register named `name`, like `GPIOA_ODR`, or
+                /// `None` if the index has no such register.
+                pub fn dyn_reg(
+                    &self,
+                    name: &str,
+                ) -> ::core::option::Option<&dyn ::drone_core::reg::DynReg> {
+                    #(#lookup_tokens)*
+                    ::core::option::Option::None
+                }
+
+                /// Passes each register in the index to the closure `f`.
+                pub fn for_each_dyn_reg<F: FnMut(&dyn ::drone_core::reg::DynReg)>(
+                    &self,
+                    mut f: F,
+                ) {
+                    #(#for_each_tokens)*
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
     let expanded = quote! {
         #(#attrs)* #vis struct #ident {
             #(#def_tokens)*
@@ -107,7 +242,69 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                 Self { #(#ctor_tokens)* }
             }
         }
+        #summary
+        #dyn_reg
+        #split
         #(#assert_tokens)*
     };
     expanded.into()
 }
+
+fn split(
+    index: &Ident,
+    subsets: &[Subset],
+    def_paths: &BTreeMap<String, (&Vec<Attribute>, &Path)>,
+) -> Result<TokenStream2> {
+    if subsets.is_empty() {
+        return Ok(quote!());
+    }
+    let mut taken = HashSet::new();
+    let mut tokens = Vec::new();
+    let mut idents = Vec::new();
+    let mut ctors = Vec::new();
+    for Subset { attrs, vis, ident, regs } in subsets {
+        let mut def_tokens = Vec::new();
+        let mut ctor_tokens = Vec::new();
+        for reg in regs {
+            let string = reg.to_string();
+            let (reg_attrs, path) = def_paths.get(&string).ok_or_else(|| {
+                syn::Error::new(reg.span(), format!("register `{}` is not in the index", reg))
+            })?;
+            if !taken.insert(string) {
+                return Err(syn::Error::new(
+                    reg.span(),
+                    format!("register `{}` is listed in more than one subset", reg),
+                ));
+            }
+            let cfg_attrs = reg_attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+            def_tokens.push(quote! {
+                #(#reg_attrs)*
+                #[allow(missing_docs)]
+                pub #reg: #path<::drone_core::reg::tag::Srt>,
+            });
+            ctor_tokens.push(quote! {
+                #(#cfg_attrs)*
+                #reg: self.#reg,
+            });
+        }
+        tokens.push(quote! {
+            #(#attrs)* #vis struct #ident {
+                #(#def_tokens)*
+            }
+        });
+        idents.push(ident);
+        ctors.push(quote!(#ident { #(#ctor_tokens)* }));
+    }
+    Ok(quote! {
+        #(#tokens)*
+
+        impl #index {
+            /// Moves the register tokens into the subset structs. The register
+            /// tokens, which are not listed in any subset, are dropped.
+            #[inline]
+            pub fn split(self) -> (#(#idents,)*) {
+                (#(#ctors,)*)
+            }
+        }
+    })
+}
@@ -1,9 +1,10 @@
 use inflector::Inflector;
 use proc_macro::TokenStream;
+use proc_macro2::Span;
 use quote::{format_ident, quote};
 use syn::{
     parse::{Parse, ParseStream, Result},
-    parse_macro_input, Attribute, Ident, Token, Visibility,
+    parse_macro_input, Attribute, Ident, LitStr, Token, Visibility,
 };
 
 struct Input {
@@ -26,6 +27,7 @@ impl Parse for Input {
 pub fn proc_macro(input: TokenStream) -> TokenStream {
     let Input { attrs, vis, ident } = parse_macro_input!(input);
     let wrapper = format_ident!("__{}_simple_token", ident.to_string().to_snake_case());
+    let name = LitStr::new(&ident.to_string(), Span::call_site());
     let expanded = quote! {
         mod #wrapper {
             use super::*;
@@ -46,6 +48,8 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
         }
 
         #vis use #wrapper::#ident;
+
+        ::drone_core::token_record!(#name, 0);
     };
     expanded.into()
 }
@@ -0,0 +1,117 @@
+use inflector::Inflector;
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream, Result},
+    parse_macro_input, Attribute, ExprPath, Ident, LitStr, Path, Token, Type, Visibility,
+};
+
+struct GroupInput {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+}
+
+struct EntryInput {
+    group: Path,
+    token: Type,
+    init: ExprPath,
+}
+
+impl Parse for GroupInput {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
+        input.parse::<Option<Token![;]>>()?;
+        Ok(Self { attrs, vis, ident })
+    }
+}
+
+impl Parse for EntryInput {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let group = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let token = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let init = input.parse()?;
+        input.parse::<Option<Token![;]>>()?;
+        Ok(Self { group, token, init })
+    }
+}
+
+pub fn proc_macro_group(input: TokenStream) -> TokenStream {
+    let GroupInput { attrs, vis, ident } = parse_macro_input!(input);
+    let wrapper = format_ident!("__{}_token_group", ident.to_string().to_snake_case());
+    let name = LitStr::new(&ident.to_string(), Span::call_site());
+    let (start, end) = symbols(&ident);
+    let expanded = quote! {
+        mod #wrapper {
+            use super::*;
+
+            #(#attrs)*
+            pub struct #ident {
+                __priv: (),
+            }
+
+            unsafe impl ::drone_core::token::Token for #ident {
+                #[inline]
+                unsafe fn take() -> Self {
+                    Self {
+                        __priv: (),
+                    }
+                }
+            }
+
+            impl #ident {
+                /// Takes every token registered in this group, and passes
+                /// each of them to its initializer.
+                pub fn init(self) {
+                    extern "C" {
+                        static #start: ::drone_core::token::GroupEntry;
+                        static #end: ::drone_core::token::GroupEntry;
+                    }
+                    unsafe { ::drone_core::token::init_group(&#start, &#end) }
+                }
+            }
+        }
+
+        #vis use #wrapper::#ident;
+
+        ::drone_core::token_record!(#name, 0);
+    };
+    expanded.into()
+}
+
+pub fn proc_macro_entry(input: TokenStream) -> TokenStream {
+    let EntryInput { group, token, init } = parse_macro_input!(input);
+    let group_ident = &group.segments.last().unwrap().ident;
+    let section = LitStr::new(
+        &format!(".tokengroup.{}", group_ident.to_string().to_snake_case()),
+        Span::call_site(),
+    );
+    let expanded = quote! {
+        const _: () = {
+            const _: fn(#group) = ::core::mem::drop;
+
+            unsafe fn take_and_init() {
+                #init(<#token as ::drone_core::token::Token>::take());
+            }
+
+            #[used]
+            #[link_section = #section]
+            static ENTRY: ::drone_core::token::GroupEntry = ::drone_core::token::GroupEntry {
+                name: ::core::stringify!(#token),
+                init: take_and_init,
+            };
+        };
+    };
+    expanded.into()
+}
+
+fn symbols(ident: &Ident) -> (Ident, Ident) {
+    let name = ident.to_string().to_screaming_snake_case();
+    (format_ident!("TOKENGROUP_{}_START", name), format_ident!("TOKENGROUP_{}_END", name))
+}
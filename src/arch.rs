@@ -0,0 +1,275 @@
+//! Architecture hooks.
+//!
+//! Drone core is architecture-agnostic, but some of its parts rely on a few
+//! low-level instructions: memory barriers to order accesses to memory-mapped
+//! registers, and exclusive (load-linked/store-conditional) accesses to
+//! implement lock-free read-modify-write sequences. These instructions are
+//! abstracted by the [`Arch`] trait, which is implemented by the platform
+//! crate and registered with [`set_impl!`]:
+//!
+//! ```no_run
+//! use drone_core::arch::{self, Arch};
+//!
+//! struct RiscV;
+//!
+//! unsafe impl Arch for RiscV {
+//!     fn data_barrier() {
+//!         // fence rw, rw
+//!     }
+//!
+//!     fn sync_barrier() {
+//!         // fence iorw, iorw
+//!     }
+//!
+//!     fn instruction_barrier() {
+//!         // fence.i
+//!     }
+//!
+//!     unsafe fn load_exclusive(address: *const u32) -> u32 {
+//!         // lr.w
+//! #       unimplemented!()
+//!     }
+//!
+//!     unsafe fn store_exclusive(address: *mut u32, value: u32) -> bool {
+//!         // sc.w
+//! #       unimplemented!()
+//!     }
+//!
+//!     fn clear_exclusive() {}
+//! }
+//!
+//! arch::set_impl!(RiscV);
+//! # fn main() {}
+//! ```
+//!
+//! With the `std` feature the hooks are emulated on the host, and the platform
+//! implementation is not needed.
+
+#![cfg_attr(feature = "std", allow(unreachable_code))]
+
+extern "C" {
+    fn drone_data_barrier();
+    fn drone_sync_barrier();
+    fn drone_instruction_barrier();
+    fn drone_load_exclusive(address: *const u32) -> u32;
+    fn drone_store_exclusive(address: *mut u32, value: u32) -> bool;
+    fn drone_clear_exclusive();
+}
+
+/// Architecture-specific low-level operations.
+///
+/// # Safety
+///
+/// The implementation must provide the semantics described for each method.
+/// Exclusive accesses are relied upon for memory safety.
+pub unsafe trait Arch {
+    /// Ensures that all explicit memory accesses before the barrier are
+    /// observed before any explicit memory accesses after the barrier.
+    ///
+    /// `DMB` on ARM, `fence rw, rw` on RISC-V.
+    fn data_barrier();
+
+    /// Ensures that all explicit memory accesses before the barrier are
+    /// complete before any instruction after the barrier is executed.
+    ///
+    /// `DSB` on ARM, `fence iorw, iorw` on RISC-V.
+    fn sync_barrier();
+
+    /// Flushes the instruction pipeline, so that the instructions after the
+    /// barrier observe the effects of the preceding context-altering
+    /// operations.
+    ///
+    /// `ISB` on ARM, `fence.i` on RISC-V.
+    fn instruction_barrier();
+
+    /// Loads a word from `address` and marks the address for an exclusive
+    /// access.
+    ///
+    /// `LDREX` on ARM, `lr.w` on RISC-V.
+    ///
+    /// # Safety
+    ///
+    /// `address` must be valid for reads and aligned.
+    unsafe fn load_exclusive(address: *const u32) -> u32;
+
+    /// Stores `value` to `address` if the exclusive access mark set by the
+    /// last [`load_exclusive`](Arch::load_exclusive) is still held. Returns
+    /// `true` on success.
+    ///
+    /// `STREX` on ARM, `sc.w` on RISC-V.
+    ///
+    /// # Safety
+    ///
+    /// `address` must be valid for writes and aligned.
+    unsafe fn store_exclusive(address: *mut u32, value: u32) -> bool;
+
+    /// Clears the exclusive access mark, if any.
+    ///
+    /// `CLREX` on ARM. On architectures, which drop the reservation
+    /// automatically, this can be a no-op.
+    fn clear_exclusive();
+}
+
+/// Registers the [`Arch`] implementation for the application.
+///
+/// Should be invoked exactly once, usually by the platform crate.
+#[doc(inline)]
+pub use crate::arch_set_impl as set_impl;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! arch_set_impl {
+    ($arch:ty) => {
+        const _: () = {
+            #[no_mangle]
+            extern "C" fn drone_data_barrier() {
+                <$arch as $crate::arch::Arch>::data_barrier();
+            }
+
+            #[no_mangle]
+            extern "C" fn drone_sync_barrier() {
+                <$arch as $crate::arch::Arch>::sync_barrier();
+            }
+
+            #[no_mangle]
+            extern "C" fn drone_instruction_barrier() {
+                <$arch as $crate::arch::Arch>::instruction_barrier();
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn drone_load_exclusive(address: *const u32) -> u32 {
+                <$arch as $crate::arch::Arch>::load_exclusive(address)
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn drone_store_exclusive(address: *mut u32, value: u32) -> bool {
+                <$arch as $crate::arch::Arch>::store_exclusive(address, value)
+            }
+
+            #[no_mangle]
+            extern "C" fn drone_clear_exclusive() {
+                <$arch as $crate::arch::Arch>::clear_exclusive();
+            }
+        };
+    };
+}
+
+/// Data memory barrier. See [`Arch::data_barrier`].
+#[inline]
+pub fn data_barrier() {
+    #[cfg(feature = "std")]
+    return core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+    unsafe { drone_data_barrier() }
+}
+
+/// Data synchronization barrier. See [`Arch::sync_barrier`].
+#[inline]
+pub fn sync_barrier() {
+    #[cfg(feature = "std")]
+    return core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+    unsafe { drone_sync_barrier() }
+}
+
+/// Instruction synchronization barrier. See [`Arch::instruction_barrier`].
+#[inline]
+pub fn instruction_barrier() {
+    #[cfg(feature = "std")]
+    return core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    unsafe { drone_instruction_barrier() }
+}
+
+/// Exclusive load. See [`Arch::load_exclusive`].
+///
+/// # Safety
+///
+/// `address` must be valid for reads and aligned.
+#[inline]
+pub unsafe fn load_exclusive(address: *const u32) -> u32 {
+    #[cfg(feature = "std")]
+    return host::load_exclusive(address);
+    drone_load_exclusive(address)
+}
+
+/// Exclusive store. See [`Arch::store_exclusive`].
+///
+/// # Safety
+///
+/// `address` must be valid for writes and aligned.
+#[inline]
+pub unsafe fn store_exclusive(address: *mut u32, value: u32) -> bool {
+    #[cfg(feature = "std")]
+    return host::store_exclusive(address, value);
+    drone_store_exclusive(address, value)
+}
+
+/// Clears the exclusive access mark. See [`Arch::clear_exclusive`].
+#[inline]
+pub fn clear_exclusive() {
+    #[cfg(feature = "std")]
+    return host::clear_exclusive();
+    unsafe { drone_clear_exclusive() }
+}
+
+/// Atomically replaces the word at `address` with the result of `f` applied
+/// to the current value, using exclusive accesses. Returns the previous value.
+///
+/// `f` can be called multiple times, if the exclusive store fails.
+///
+/// # Safety
+///
+/// `address` must be valid for reads and writes and aligned.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::arch;
+///
+/// let mut word = 0b0101_u32;
+/// let prev = unsafe { arch::modify_exclusive(&mut word, |x| x | 0b1000) };
+/// assert_eq!(prev, 0b0101);
+/// assert_eq!(word, 0b1101);
+/// ```
+#[inline]
+pub unsafe fn modify_exclusive(address: *mut u32, mut f: impl FnMut(u32) -> u32) -> u32 {
+    loop {
+        let value = load_exclusive(address);
+        if store_exclusive(address, f(value)) {
+            return value;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod host {
+    use crate::critical;
+    use core::ptr::{read_volatile, write_volatile};
+    use std::thread::{self, ThreadId};
+
+    static mut MONITOR: Option<(ThreadId, usize)> = None;
+
+    pub(super) unsafe fn load_exclusive(address: *const u32) -> u32 {
+        critical::with(|_| {
+            MONITOR = Some((thread::current().id(), address as usize));
+            read_volatile(address)
+        })
+    }
+
+    pub(super) unsafe fn store_exclusive(address: *mut u32, value: u32) -> bool {
+        critical::with(|_| {
+            if MONITOR != Some((thread::current().id(), address as usize)) {
+                return false;
+            }
+            MONITOR = None;
+            write_volatile(address, value);
+            true
+        })
+    }
+
+    pub(super) fn clear_exclusive() {
+        critical::with(|_| unsafe {
+            if MONITOR.map_or(false, |(owner, _)| owner == thread::current().id()) {
+                MONITOR = None;
+            }
+        });
+    }
+}
@@ -0,0 +1,75 @@
+//! Atomic types used internally by the crate.
+//!
+//! With the `portable-atomic` feature enabled, the types are taken from the
+//! [`portable-atomic`] crate, which emulates missing atomic read-modify-write
+//! operations on targets without native compare-and-swap, such as ARMv6-M.
+//! This covers the [`sync::spsc`](crate::sync::spsc) channels including their
+//! shared state reference counters, the [`sync`](crate::sync) locks, and the
+//! [`heap`](crate::heap) pools. The 0.3 series of the crate is used, which
+//! builds with the pinned toolchain. On a single-core target without
+//! compare-and-swap, it requires the following cfg flag, which makes it
+//! emulate the operations by disabling interrupts:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg portable_atomic_unsafe_assume_single_core"
+//! ```
+//!
+//! Modules outside of this list, e.g. [`future::block_on`], still rely on
+//! `Arc` and need native compare-and-swap.
+//!
+//! Under `cfg(loom)` the atomics and the `UnsafeCell`s of the
+//! [`sync::spsc`](crate::sync::spsc) channels are taken from the [`loom`]
+//! crate, which allows the channel state machines and their accesses to the
+//! stored wakers, values, and errors to be model-checked. The ring buffer
+//! storage is raw memory, and its accesses are not tracked. Only the channels
+//! are switched, because `loom` types can't be used in `static`s. `loom` 0.5
+//! requires Rust 1.51 or newer, so the model checks run on a newer toolchain
+//! than the pinned one.
+//!
+//! [`portable-atomic`]: https://crates.io/crates/portable-atomic
+//! [`future::block_on`]: crate::future::block_on
+//! [`loom`]: https://crates.io/crates/loom
+
+pub(crate) use core::sync::atomic::Ordering;
+#[cfg(not(feature = "portable-atomic"))]
+#[cfg_attr(loom, allow(unused_imports))]
+pub(crate) use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, AtomicU8, AtomicUsize};
+#[cfg(feature = "portable-atomic")]
+#[cfg_attr(loom, allow(unused_imports))]
+pub(crate) use portable_atomic::{AtomicBool, AtomicPtr, AtomicU32, AtomicU8, AtomicUsize};
+
+/// Atomic types for the [`sync::spsc`](crate::sync::spsc) channels.
+#[cfg(not(loom))]
+pub(crate) mod spsc {
+    pub(crate) use super::{AtomicU8, AtomicUsize};
+    pub(crate) use core::sync::atomic::fence;
+
+    /// A [`core::cell::UnsafeCell`] with the access API of `loom`.
+    pub(crate) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+    impl<T> UnsafeCell<T> {
+        #[inline]
+        pub(crate) fn new(data: T) -> Self {
+            Self(core::cell::UnsafeCell::new(data))
+        }
+
+        #[inline]
+        pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+            f(self.0.get())
+        }
+
+        #[inline]
+        pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+            f(self.0.get())
+        }
+    }
+}
+
+/// Atomic types for the [`sync::spsc`](crate::sync::spsc) channels.
+#[cfg(loom)]
+pub(crate) mod spsc {
+    pub(crate) use loom::{
+        cell::UnsafeCell,
+        sync::atomic::{fence, AtomicU8, AtomicUsize},
+    };
+}
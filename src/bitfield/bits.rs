@@ -21,6 +21,9 @@ where
         + Shl<Self, Output = Self>
         + Shr<Self, Output = Self>,
 {
+    /// The value with all bits cleared.
+    const ZERO: Self;
+
     /// Creates a new value with the bits of `bits`.
     fn from_usize(bits: usize) -> Self;
 
@@ -34,6 +37,8 @@ where
 macro_rules! bits {
     ($type:ty) => {
         impl Bits for $type {
+            const ZERO: Self = 0;
+
             #[inline]
             fn from_usize(bits: usize) -> Self {
                 bits as Self
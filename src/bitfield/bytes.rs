@@ -0,0 +1,98 @@
+//! Bit manipulation helpers for byte-array backed bitfields.
+//!
+//! Bit `n` of a byte array is the bit `n % 8` of the byte `n / 8`, i.e. the
+//! array is treated as a little-endian integer.
+
+/// Returns `true` if the bit at `offset` is set.
+///
+/// # Panics
+///
+/// If `offset` exceeds the array size.
+#[inline]
+pub fn read_bit(bytes: &[u8], offset: usize) -> bool {
+    bytes[offset / 8] >> (offset % 8) & 1 != 0
+}
+
+/// Sets the bit at `offset`.
+///
+/// # Panics
+///
+/// If `offset` exceeds the array size.
+#[inline]
+pub fn set_bit(bytes: &mut [u8], offset: usize) {
+    bytes[offset / 8] |= 1 << (offset % 8);
+}
+
+/// Clears the bit at `offset`.
+///
+/// # Panics
+///
+/// If `offset` exceeds the array size.
+#[inline]
+pub fn clear_bit(bytes: &mut [u8], offset: usize) {
+    bytes[offset / 8] &= !(1 << (offset % 8));
+}
+
+/// Toggles the bit at `offset`.
+///
+/// # Panics
+///
+/// If `offset` exceeds the array size.
+#[inline]
+pub fn toggle_bit(bytes: &mut [u8], offset: usize) {
+    bytes[offset / 8] ^= 1 << (offset % 8);
+}
+
+/// Returns `width` number of bits at `offset` position.
+///
+/// # Panics
+///
+/// If `offset + width` exceeds the array size, or `width` exceeds 128.
+pub fn read_bits(bytes: &[u8], offset: usize, width: usize) -> u128 {
+    assert!(width <= 128);
+    let mut bits = 0;
+    for i in 0..width {
+        if read_bit(bytes, offset + i) {
+            bits |= 1 << i;
+        }
+    }
+    bits
+}
+
+/// Writes `width` number of bits at `offset` position from `bits`.
+///
+/// # Panics
+///
+/// If `offset + width` exceeds the array size, or `width` exceeds 128.
+pub fn write_bits(bytes: &mut [u8], offset: usize, width: usize, bits: u128) {
+    assert!(width <= 128);
+    for i in 0..width {
+        if bits >> i & 1 == 0 {
+            clear_bit(bytes, offset + i);
+        } else {
+            set_bit(bytes, offset + i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits() {
+        let mut bytes = [0b1010_0110, 0b0000_0001];
+        assert!(read_bit(&bytes, 1));
+        assert!(!read_bit(&bytes, 3));
+        assert!(read_bit(&bytes, 8));
+        assert_eq!(read_bits(&bytes, 5, 4), 0b1101);
+        write_bits(&mut bytes, 6, 4, 0b0110);
+        assert_eq!(bytes, [0b1010_0110, 0b0000_0001]);
+        write_bits(&mut bytes, 6, 4, 0b1001);
+        assert_eq!(bytes, [0b0110_0110, 0b0000_0010]);
+        toggle_bit(&mut bytes, 0);
+        clear_bit(&mut bytes, 9);
+        set_bit(&mut bytes, 15);
+        assert_eq!(bytes, [0b0110_0111, 0b1000_0000]);
+    }
+}
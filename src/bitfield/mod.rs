@@ -17,7 +17,7 @@
 //!     bar(rw, 5, 1, "1-bit field"),
 //! )]
 //! // The choice of the underlying integer determines the total number of bits.
-//! // Available sizes: `u8`, `u16`, `u32`, `u64`, `u128`.
+//! // Available sizes: `u8`, `u16`, `u32`, `u64`, `u128`, and `[u8; N]`.
 //! struct MyValue(u8);
 //!
 //! //                          * foo bit
@@ -47,8 +47,256 @@
 //! value.write_foo(0b1010);
 //! assert_eq!(value.foo(), 0b1010);
 //!
+//! // Extra bits are silently truncated by `write_foo`. `try_write_foo` returns
+//! // an error instead.
+//! assert!(value.try_write_foo(0b1_0000).is_err());
+//! assert_eq!(value.foo(), 0b1010);
+//!
 //! assert_eq!(value.0, 0b0001_0100);
 //! ```
+//!
+//! A number of consecutive fields of the same width can be declared as an
+//! array. The generated methods take the index of the field as the first
+//! argument, and panic if it is out of range:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(
+//!     // The syntax is `field_name[length](mode, offset[, width[, doc_string]])`.
+//!     // The field with index `i` is located at `offset + i * width`.
+//!     flag[4](rw, 0, 1, "1-bit fields"),
+//!     nibble[2](rw, 8, 4, "4-bit fields"),
+//! )]
+//! struct Flags(u16);
+//!
+//! let mut value = Flags(0);
+//! value.set_flag(2);
+//! value.write_nibble(1, 0b1001);
+//! assert!(value.flag(2));
+//! assert!(!value.flag(3));
+//! assert_eq!(value.nibble(1), 0b1001);
+//! assert_eq!(value.0, 0b1001_0000_0000_0100);
+//! ```
+//!
+//! Options can be passed along with the field definitions. `checked` option
+//! makes `write_*` methods panic in debug builds when the value doesn't fit in
+//! the field.
+//!
+//! `debug` and `display` options implement [`Debug`](core::fmt::Debug) and
+//! [`Display`](core::fmt::Display) respectively, rendering each readable field
+//! with its value:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(debug, display, en(rw, 0), psc(rw, 4, 4))]
+//! struct Ctrl(u8);
+//!
+//! let value = Ctrl(0b1010_0001);
+//! assert_eq!(format!("{:?}", value), "Ctrl { en: true, psc: 10 }");
+//! assert_eq!(format!("{}", value), "en=true psc=0xa");
+//! ```
+//!
+//! For integer-backed bitfields, getters of non-array fields other than enums
+//! are `const fn`. Also for each writable field the macro defines a consuming
+//! `const fn` setter `with_*`. This allows computing values at compile time:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(en(rw, 0), psc(rw, 4, 4))]
+//! struct Ctrl(u8);
+//!
+//! const DEFAULT: Ctrl = Ctrl(0).with_en(true).with_psc(0b1010);
+//! const PSC: u8 = DEFAULT.psc();
+//! assert_eq!(DEFAULT.0, 0b1010_0001);
+//! assert_eq!(PSC, 0b1010);
+//! ```
+//!
+//! Every bitfield type gets `to_le_bytes`, `to_be_bytes`, `to_ne_bytes`,
+//! `from_le_bytes`, `from_be_bytes`, and `from_ne_bytes` methods, which mirror
+//! the methods of the underlying integer. This allows moving packed values
+//! directly to and from DMA buffers and network frames:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(len(rw, 0, 16), kind(rw, 16, 16))]
+//! struct Header(u32);
+//!
+//! let header = Header::from_be_bytes([0x00, 0x01, 0x00, 0x40]);
+//! assert_eq!(header.kind(), 1);
+//! assert_eq!(header.len(), 64);
+//! assert_eq!(header.to_le_bytes(), [0x40, 0x00, 0x01, 0x00]);
+//! ```
+//!
+//! Long descriptors, which don't fit in a single integer, can be backed by a
+//! byte array. Bit `n` of the array is the bit `n % 8` of the byte `n / 8`. In
+//! this case the [`Bitfield`] trait is not implemented, and multiple-bit fields
+//! are read and written as the smallest unsigned integer that fits the field
+//! width:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(crc(rw, 1, 7), size(rw, 8, 22), erase(rw, 46))]
+//! struct Csd([u8; 16]);
+//!
+//! let mut value = Csd([0; 16]);
+//! value.write_size(0x3F_FFFF).set_erase();
+//! assert_eq!(value.size(), 0x3F_FFFF);
+//! assert!(value.erase());
+//! assert_eq!(&value.0[..6], &[0x00, 0xFF, 0xFF, 0x3F, 0x00, 0x40]);
+//! ```
+//!
+//! `eq` and `hash` options implement [`PartialEq`] + [`Eq`] and
+//! [`Hash`](core::hash::Hash) respectively, taking into account only the bits
+//! of the declared fields. Reserved bits are ignored. An explicit mask can be
+//! set with `mask` option:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(eq, hash, foo(rw, 0, 4))]
+//! struct Declared(u8);
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(eq, mask = 0xF0, foo(rw, 0, 4))]
+//! struct Masked(u8);
+//!
+//! assert!(Declared(0x0A) == Declared(0xFA));
+//! assert!(Declared(0x0A) != Declared(0x0B));
+//! assert!(Masked(0x1A) == Masked(0x1B));
+//! assert!(Masked(0x1A) != Masked(0x2A));
+//! ```
+//!
+//! `builder` option defines a consuming builder type, which is convenient for
+//! constructing one-shot configuration words:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(builder, enable(rw, 0), prescaler(rw, 4, 4))]
+//! struct Ctrl(u8);
+//!
+//! let value = Ctrl::builder().enable(true).prescaler(7).build();
+//! assert_eq!(value.0, 0b0111_0001);
+//! let value = value.into_builder().enable(false).build();
+//! assert_eq!(value.0, 0b0111_0000);
+//! ```
+//!
+//! `atomic` option is available for `u8`, `u16`, and `u32` backings. It
+//! generates associated functions operating directly on an atomic storage
+//! word: `fetch_set_*`, `fetch_clear_*`, `fetch_toggle_*` for single-bit
+//! fields, `fetch_write_*` for multiple-bit fields, and the generic
+//! `fetch_modify`. Each one runs a compare-exchange loop and returns the
+//! previous value. This requires the target to support atomic
+//! compare-and-swap:
+//!
+//! ```
+//! use core::sync::atomic::{AtomicU32, Ordering};
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(atomic, enable(rw, 0), mode(rw, 4, 3))]
+//! struct State(u32);
+//!
+//! static STATE: AtomicU32 = AtomicU32::new(0);
+//!
+//! let prev = State::fetch_set_enable(&STATE, Ordering::AcqRel);
+//! assert!(!prev.enable());
+//! State::fetch_write_mode(&STATE, 5, Ordering::AcqRel);
+//! assert_eq!(STATE.load(Ordering::Acquire), 0b0101_0001);
+//! ```
+//!
+//! With the `serde` feature enabled, `serde` and `serde_struct` options
+//! implement `Serialize` and `Deserialize` from the [`serde`] crate. The former
+//! represents the value as the raw integer or byte array. The latter
+//! represents it as a struct with a field for each readable bitfield field:
+//! `bool` for single-bit fields, the declared type for signed fields, the raw
+//! bits for enum fields, and the bits integer for other fields. Array fields
+//! are represented as arrays. Missing fields are deserialized as zero bits, and
+//! unknown fields are ignored:
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(serde, enable(rw, 0))]
+//! struct Raw(u8);
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(serde_struct, enable(rw, 0), prescaler(rw, 4, 4))]
+//! struct Named(u8);
+//! # }
+//! ```
+//!
+//! [`serde`]: https://crates.io/crates/serde
+//!
+//! A multiple-bit field can be typed with a signed integer. The value is
+//! sign-extended on read, and checked to fit in the field on write:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(x(rw, 0, 12, i16, "Signed 12-bit sample"))]
+//! struct Sample(u16);
+//!
+//! let mut value = Sample(0x0FFF);
+//! assert_eq!(value.x(), -1);
+//! value.write_x(-2048);
+//! assert_eq!(value.0, 0x0800);
+//! assert!(value.try_write_x(2048).is_err());
+//! assert!(value.try_write_x(2047).is_ok());
+//! assert_eq!(value.x(), 2047);
+//! ```
+//!
+//! A multiple-bit field can be typed with a `#[repr(u8)]` enum, which derives
+//! [`BitfieldEnum`]:
+//!
+//! ```
+//! use drone_core::bitfield::{Bitfield, BitfieldEnum, InvalidValue};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq, BitfieldEnum)]
+//! #[repr(u8)]
+//! enum Mode {
+//!     Input = 0b00,
+//!     Output = 0b01,
+//!     Alternate = 0b10,
+//! }
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(mode(rw, 2, 2, Mode, "Typed field"))]
+//! struct Port(u8);
+//!
+//! let mut value = Port(0b0000_0100);
+//! // The getter decodes the enum, and the setter takes the enum.
+//! assert_eq!(value.mode(), Ok(Mode::Output));
+//! value.write_mode(Mode::Alternate);
+//! assert_eq!(value.mode(), Ok(Mode::Alternate));
+//! // Undefined values are reported as errors.
+//! let value = Port(0b0000_1100);
+//! assert_eq!(value.mode(), Err(InvalidValue(0b11)));
+//! ```
+//!
+//! An enum-typed field can't be wider than 8 bits, and should be wide enough
+//! to hold every discriminant of the enum. Both are checked at compile time.
+
+pub mod bytes;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde;
 
 mod bits;
 
@@ -58,6 +306,12 @@ mod bits;
 #[doc(inline)]
 pub use drone_core_macros::Bitfield;
 
+/// Defines a new [`BitfieldEnum`].
+///
+/// See [the module level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::BitfieldEnum;
+
 pub use self::bits::Bits;
 
 /// An integer value treated as a sequence of bits, which can be toggled
@@ -143,6 +397,31 @@ pub trait Bitfield: Sized + Send + Sync + Clone + Copy + 'static {
     }
 }
 
+/// A `#[repr(u8)]` enum, which can be used as a type of a [`Bitfield`] field.
+///
+/// See [the module level documentation](self) for details.
+pub trait BitfieldEnum: Sized + Copy {
+    /// The number of bits needed to hold every discriminant. A field typed
+    /// with the enum can't be narrower than this.
+    const WIDTH: u32;
+
+    /// Converts the field bits to the enum variant. Returns `None` if there is
+    /// no variant with the discriminant `bits`.
+    fn from_bits(bits: u8) -> Option<Self>;
+
+    /// Converts the enum variant to the field bits.
+    fn into_bits(self) -> u8;
+}
+
+/// The error returned when a value doesn't fit in a [`Bitfield`] field.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OutOfRange;
+
+/// The error returned when a [`Bitfield`] field contains a value, which
+/// doesn't correspond to any [`BitfieldEnum`] variant.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidValue(pub u8);
+
 fn bit_at<T: Bits>(offset: T) -> T {
     T::from_usize(1) << offset
 }
@@ -150,3 +429,73 @@ fn bit_at<T: Bits>(offset: T) -> T {
 fn bit_mask<T: Bits>(width: T) -> T {
     bit_at(width) - T::from_usize(1)
 }
+
+mod compile_tests {
+    //! ```compile_fail
+    //! use drone_core::bitfield::Bitfield;
+    //! #[derive(Clone, Copy, Bitfield)]
+    //! #[bitfield(foo(rw, 0, 4), bar(rw, 3, 2))]
+    //! struct Overlap(u8);
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::bitfield::Bitfield;
+    //! #[derive(Clone, Copy, Bitfield)]
+    //! #[bitfield(foo(rw, 6, 4))]
+    //! struct OutOfBounds(u8);
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::bitfield::Bitfield;
+    //! #[derive(Clone, Copy, Bitfield)]
+    //! #[bitfield(foo[3](rw, 8, 3))]
+    //! struct OutOfBounds([u8; 2]);
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::bitfield::Bitfield;
+    //! #[derive(Clone, Copy, Bitfield)]
+    //! #[bitfield(atomic, foo(rw, 0, 4))]
+    //! struct NotAtomic(u64);
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```
+    //! use drone_core::bitfield::Bitfield;
+    //! #[derive(Clone, Copy, Bitfield)]
+    //! #[bitfield(foo(rw, 0, 4), bar(rw, 4, 4))]
+    //! struct Adjacent(u8);
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::bitfield::{Bitfield, BitfieldEnum};
+    //! #[derive(Clone, Copy, BitfieldEnum)]
+    //! #[repr(u8)]
+    //! enum Mode {
+    //!     A = 0,
+    //!     B = 1,
+    //! }
+    //! #[derive(Clone, Copy, Bitfield)]
+    //! #[bitfield(mode(rw, 0, 9, Mode))]
+    //! struct TooWide(u16);
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::bitfield::{Bitfield, BitfieldEnum};
+    //! #[derive(Clone, Copy, BitfieldEnum)]
+    //! #[repr(u8)]
+    //! enum Mode {
+    //!     A = 0,
+    //!     B = 4,
+    //! }
+    //! #[derive(Clone, Copy, Bitfield)]
+    //! #[bitfield(mode(rw, 0, 2, Mode))]
+    //! struct TooNarrow(u8);
+    //! fn main() {}
+    //! ```
+}
@@ -0,0 +1,40 @@
+//! Support code for the `serde` and `serde_struct` options of
+//! [`Bitfield`](super::Bitfield).
+
+pub use ::serde;
+
+use ::serde::de::{self, DeserializeSeed, Deserializer, Visitor};
+use core::fmt;
+
+/// Deserializes a struct field identifier into its index in the field list.
+/// Unknown fields are mapped to `None`.
+#[derive(Clone, Copy)]
+pub struct FieldSeed(pub &'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for FieldSeed {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Option<usize>, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldSeed {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("field identifier")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Option<usize>, E> {
+        Ok(Some(value as usize).filter(|&index| index < self.0.len()))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Option<usize>, E> {
+        Ok(self.0.iter().position(|&field| field == value))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Option<usize>, E> {
+        Ok(self.0.iter().position(|&field| field.as_bytes() == value))
+    }
+}
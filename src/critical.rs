@@ -0,0 +1,179 @@
+//! Critical sections.
+//!
+//! A critical section is a region of code, which can't be preempted by any
+//! thread. It is implemented by masking interrupts with the platform hooks
+//! `drone_irq_mask` and `drone_irq_unmask`, which are provided by the platform
+//! crate. With the `std` feature a global re-entrant lock is used instead.
+//!
+//! Drone threads are preemptive, so critical sections should be kept as short
+//! as possible. Lock-free primitives from [`sync`](crate::sync) are preferred
+//! where applicable.
+//!
+//! # Platform Hooks
+//!
+//! Without the `std` feature, an application using critical sections, directly
+//! or through drone-core items like [`future::Wakeup`] or [`log`], must
+//! link with the following two functions:
+//!
+//! ```ignore
+//! /// Masks interrupts and returns the previous mask state.
+//! #[no_mangle]
+//! extern "C" fn drone_irq_mask() -> u8;
+//!
+//! /// Restores the interrupt mask state returned by `drone_irq_mask`.
+//! #[no_mangle]
+//! extern "C" fn drone_irq_unmask(state: u8);
+//! ```
+//!
+//! They are normally exported by the platform crate, e.g. by reading and
+//! setting `PRIMASK` on Cortex-M. `drone_irq_mask` must mask every interrupt,
+//! which can run a Drone thread, and the returned state must be opaque to
+//! drone-core. Calls are nested: `drone_irq_unmask` receives the states in the
+//! reverse order, and must unmask interrupts only if they were unmasked before
+//! the matching `drone_irq_mask` call.
+//!
+//! # `critical-section` Crate
+//!
+//! With the `critical-section` feature enabled, drone-core registers itself as
+//! the implementation of the [`critical-section`] crate. This way third-party
+//! `no_std` libraries using `critical_section::with` mask interrupts in the
+//! same way as the rest of a Drone application.
+//!
+//! The feature depends on `critical-section` 0.2, which builds with the pinned
+//! toolchain. An application must not enable another implementation of the
+//! crate, e.g. its `std` feature, as the implementations would conflict at
+//! link time.
+//!
+//! [`critical-section`]: https://crates.io/crates/critical-section
+//! [`future::Wakeup`]: crate::future::Wakeup
+//! [`log`]: crate::log
+
+#![cfg_attr(feature = "std", allow(unreachable_code))]
+
+use core::marker::PhantomData;
+
+extern "C" {
+    /// Masks interrupts and returns the previous mask state. Provided by the
+    /// platform crate.
+    pub fn drone_irq_mask() -> u8;
+
+    /// Restores the interrupt mask state returned by [`drone_irq_mask`].
+    /// Provided by the platform crate.
+    pub fn drone_irq_unmask(state: u8);
+}
+
+/// A token proving that the current code is running inside a critical
+/// section.
+#[derive(Clone, Copy)]
+pub struct CriticalSection<'cs> {
+    _marker: PhantomData<&'cs ()>,
+}
+
+/// Executes the closure `f` inside a critical section.
+///
+/// Critical sections can be nested.
+///
+/// # Examples
+///
+/// ```
+/// use core::cell::Cell;
+/// use drone_core::critical;
+///
+/// let counter = Cell::new(0);
+/// critical::with(|_cs| counter.set(counter.get() + 1));
+/// assert_eq!(counter.get(), 1);
+/// ```
+///
+/// The critical section is left even if `f` panics.
+#[inline]
+pub fn with<R>(f: impl FnOnce(CriticalSection<'_>) -> R) -> R {
+    let _guard = Guard(unsafe { acquire() });
+    f(CriticalSection { _marker: PhantomData })
+}
+
+/// Leaves the critical section on drop.
+struct Guard(u8);
+
+impl Drop for Guard {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { release(self.0) };
+    }
+}
+
+/// Enters a critical section, and returns the state to restore with
+/// [`release`].
+///
+/// # Safety
+///
+/// Each call must be paired with a call to [`release`] in the reverse nesting
+/// order.
+#[inline]
+pub unsafe fn acquire() -> u8 {
+    #[cfg(feature = "std")]
+    return host::acquire();
+    drone_irq_mask()
+}
+
+/// Leaves a critical section entered with [`acquire`].
+///
+/// # Safety
+///
+/// `state` must be the value returned by the matching [`acquire`] call.
+#[inline]
+pub unsafe fn release(state: u8) {
+    #[cfg(feature = "std")]
+    return host::release(state);
+    drone_irq_unmask(state)
+}
+
+#[cfg(feature = "std")]
+mod host {
+    use core::{
+        cell::Cell,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    static LOCKED: AtomicBool = AtomicBool::new(false);
+
+    std::thread_local! {
+        static OWNER: Cell<bool> = Cell::new(false);
+    }
+
+    pub(super) fn acquire() -> u8 {
+        if OWNER.with(Cell::get) {
+            return 0;
+        }
+        while LOCKED.compare_and_swap(false, true, Ordering::Acquire) {
+            std::thread::yield_now();
+        }
+        OWNER.with(|owner| owner.set(true));
+        1
+    }
+
+    pub(super) fn release(state: u8) {
+        if state != 0 {
+            OWNER.with(|owner| owner.set(false));
+            LOCKED.store(false, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(feature = "critical-section")]
+struct DroneCriticalSection;
+
+#[cfg(feature = "critical-section")]
+unsafe impl critical_section::Impl for DroneCriticalSection {
+    #[inline]
+    unsafe fn acquire() -> u8 {
+        acquire()
+    }
+
+    #[inline]
+    unsafe fn release(token: u8) {
+        release(token)
+    }
+}
+
+#[cfg(feature = "critical-section")]
+critical_section::custom_impl!(DroneCriticalSection);
@@ -0,0 +1,340 @@
+//! Deterministic event trace.
+//!
+//! This module is available with the `evtrace` feature. It records a sequence
+//! of scheduling-relevant events into a compact log, one 32-bit word per
+//! event:
+//!
+//! * thread resumptions, recorded automatically by
+//!   [`thread_resume`](crate::thr::thread_resume);
+//! * timer ticks, recorded automatically when a
+//!   [`Deadline`](crate::future::Deadline) elapses, and by the platform timer
+//!   driver;
+//! * channel operations, recorded automatically by the
+//!   [`sync::spsc`](crate::sync::spsc) channels on each successful send and
+//!   receive, and by the application for its own channels.
+//!
+//! The [`sync::spsc`](crate::sync::spsc) channels get their identifiers
+//! sequentially in the order of creation, starting from zero. Therefore a
+//! program, which creates its channels in the same order, gets the same
+//! identifiers in every run. Identifiers for application channels should be
+//! chosen above the number of created [`sync::spsc`](crate::sync::spsc)
+//! channels.
+//!
+//! On the device the words are written in big-endian order to the
+//! [`EVTRACE_PORT`] log port, if the port is enabled. In the host simulation
+//! the events are collected in memory and can be retrieved with [`take`].
+//!
+//! A log captured from the field can be decoded with [`decode`], and replayed
+//! in the host simulation with [`Replay`], which resumes the threads in the
+//! recorded order and calls the registered handlers for ticks and channel
+//! operations. This makes race-dependent bugs reproducible. Replaying records
+//! the events again, so the replayed trace can be compared with the original
+//! one.
+
+#![cfg_attr(feature = "sim", allow(unreachable_code))]
+
+use crate::{
+    atomic::{AtomicU32, Ordering},
+    log::{Port, EVTRACE_PORT},
+};
+
+const TAG_TRIGGER: u32 = 0x01;
+const TAG_TICK: u32 = 0x02;
+const TAG_SEND: u32 = 0x03;
+const TAG_RECV: u32 = 0x04;
+const PAYLOAD_MASK: u32 = 0x00FF_FFFF;
+
+/// A traced event.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    /// A thread with the given number was resumed.
+    Trigger(u16),
+    /// The given number of timer ticks elapsed since the previous tick event.
+    /// Values more than `0xFF_FFFF` are saturated.
+    Tick(u32),
+    /// A value was sent to the channel with the given identifier.
+    Send(u16),
+    /// A value was received from the channel with the given identifier.
+    Recv(u16),
+}
+
+impl Event {
+    /// Encodes the event into a 32-bit word.
+    pub fn to_bits(self) -> u32 {
+        let (tag, payload) = match self {
+            Self::Trigger(thr_num) => (TAG_TRIGGER, u32::from(thr_num)),
+            Self::Tick(ticks) => (TAG_TICK, ticks.min(PAYLOAD_MASK)),
+            Self::Send(channel) => (TAG_SEND, u32::from(channel)),
+            Self::Recv(channel) => (TAG_RECV, u32::from(channel)),
+        };
+        tag << 24 | payload
+    }
+
+    /// Decodes an event from a 32-bit word. Returns `None` if the word is not
+    /// a valid event.
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        let payload = bits & PAYLOAD_MASK;
+        match bits >> 24 {
+            TAG_TRIGGER if payload <= 0xFFFF => Some(Self::Trigger(payload as u16)),
+            TAG_TICK => Some(Self::Tick(payload)),
+            TAG_SEND if payload <= 0xFFFF => Some(Self::Send(payload as u16)),
+            TAG_RECV if payload <= 0xFFFF => Some(Self::Recv(payload as u16)),
+            _ => None,
+        }
+    }
+}
+
+/// Records `event`.
+#[inline]
+pub fn record(event: Event) {
+    #[cfg(feature = "sim")]
+    return sim::record(event);
+    #[inline(never)]
+    fn write(event: Event) {
+        Port::new(EVTRACE_PORT).write::<u32>(event.to_bits());
+    }
+    if Port::new(EVTRACE_PORT).is_enabled() {
+        write(event);
+    }
+}
+
+/// Records a send operation on the channel `channel`.
+#[inline]
+pub fn send(channel: u16) {
+    record(Event::Send(channel));
+}
+
+/// Records a receive operation on the channel `channel`.
+#[inline]
+pub fn recv(channel: u16) {
+    record(Event::Recv(channel));
+}
+
+/// Records `ticks` elapsed timer ticks.
+#[inline]
+pub fn tick(ticks: u32) {
+    record(Event::Tick(ticks));
+}
+
+/// Allocates an identifier for a new [`sync::spsc`](crate::sync::spsc)
+/// channel.
+pub(crate) fn channel_id() -> u16 {
+    #[cfg(feature = "sim")]
+    return sim::channel_id();
+    static NEXT_CHANNEL: AtomicU32 = AtomicU32::new(0);
+    NEXT_CHANNEL.fetch_add(1, Ordering::Relaxed) as u16
+}
+
+/// Records the timer ticks elapsed since the previous call, given the current
+/// tick `now`. Nothing is recorded if no ticks elapsed.
+pub(crate) fn tick_at(now: u64) {
+    #[cfg(feature = "sim")]
+    return sim::tick_at(now);
+    static LAST_TICK: AtomicU32 = AtomicU32::new(0);
+    let now = now as u32;
+    let ticks = now.wrapping_sub(LAST_TICK.swap(now, Ordering::Relaxed));
+    if ticks != 0 {
+        tick(ticks);
+    }
+}
+
+/// Decodes a raw log captured from [`EVTRACE_PORT`]. Invalid words and a
+/// trailing incomplete word are skipped.
+pub fn decode(log: &[u8]) -> impl Iterator<Item = Event> + '_ {
+    log.chunks_exact(4).filter_map(|word| {
+        Event::from_bits(
+            u32::from(word[0]) << 24
+                | u32::from(word[1]) << 16
+                | u32::from(word[2]) << 8
+                | u32::from(word[3]),
+        )
+    })
+}
+
+#[cfg(feature = "sim")]
+pub use self::sim::{take, Replay};
+
+#[cfg(feature = "sim")]
+mod sim {
+    use super::Event;
+    use crate::thr::{thread_resume, ThrToken};
+    use std::{
+        boxed::Box,
+        cell::{Cell, RefCell},
+        collections::BTreeMap,
+        convert::TryFrom,
+        vec::Vec,
+    };
+
+    std::thread_local! {
+        static EVENTS: RefCell<Vec<Event>> = RefCell::new(Vec::new());
+        static NEXT_CHANNEL: Cell<u16> = Cell::new(0);
+        static LAST_TICK: Cell<u64> = Cell::new(0);
+    }
+
+    pub(super) fn record(event: Event) {
+        EVENTS.with(|events| events.borrow_mut().push(event));
+    }
+
+    pub(super) fn channel_id() -> u16 {
+        NEXT_CHANNEL.with(|next| next.replace(next.get().wrapping_add(1)))
+    }
+
+    pub(super) fn tick_at(now: u64) {
+        let ticks = now.saturating_sub(LAST_TICK.with(|last| last.replace(now)));
+        if ticks != 0 {
+            super::tick(u32::try_from(ticks).unwrap_or(u32::MAX));
+        }
+    }
+
+    /// Returns the events recorded in the current host thread since the last
+    /// call.
+    pub fn take() -> Vec<Event> {
+        EVENTS.with(|events| events.borrow_mut().split_off(0))
+    }
+
+    /// Replays a recorded event trace in the host simulation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(generators)]
+    /// # #![feature(proc_macro_hygiene)]
+    /// # static mut THREADS: [Thr; 1] = [Thr::new(0)];
+    /// # drone_core::thr!(use THREADS; pub struct Thr {} pub struct ThrLocal {});
+    /// # #[derive(Clone, Copy)]
+    /// # struct Thr0;
+    /// # unsafe impl drone_core::token::Token for Thr0 {
+    /// #     unsafe fn take() -> Self { Self }
+    /// # }
+    /// # unsafe impl drone_core::thr::ThrToken for Thr0 {
+    /// #     type Thr = Thr;
+    /// #     const THR_NUM: usize = 0;
+    /// # }
+    /// use drone_core::evtrace::{self, Event, Replay};
+    ///
+    /// # fn main() {
+    /// # use drone_core::token::Token;
+    /// let thr = unsafe { Thr0::take() };
+    /// let trace = [Event::Tick(10), Event::Trigger(0), Event::Send(3)];
+    /// let mut ticks = 0;
+    /// Replay::new().thread(thr).on_tick(|n| ticks += n).run(trace.iter().copied());
+    /// assert_eq!(ticks, 10);
+    /// assert_eq!(evtrace::take(), [Event::Trigger(0)]);
+    /// # }
+    /// ```
+    #[derive(Default)]
+    pub struct Replay<'a> {
+        threads: BTreeMap<u16, unsafe fn()>,
+        on_tick: Option<Box<dyn FnMut(u32) + 'a>>,
+        on_send: Option<Box<dyn FnMut(u16) + 'a>>,
+        on_recv: Option<Box<dyn FnMut(u16) + 'a>>,
+    }
+
+    impl<'a> Replay<'a> {
+        /// Creates an empty replay.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Registers the thread `T` to be resumed on its trigger events.
+        pub fn thread<T: ThrToken>(mut self, _thr: T) -> Self {
+            self.threads.insert(T::THR_NUM as u16, thread_resume::<T>);
+            self
+        }
+
+        /// Sets the handler for tick events, which should advance the
+        /// simulated timer.
+        pub fn on_tick(mut self, f: impl FnMut(u32) + 'a) -> Self {
+            self.on_tick = Some(Box::new(f));
+            self
+        }
+
+        /// Sets the handler for channel send events.
+        pub fn on_send(mut self, f: impl FnMut(u16) + 'a) -> Self {
+            self.on_send = Some(Box::new(f));
+            self
+        }
+
+        /// Sets the handler for channel receive events.
+        pub fn on_recv(mut self, f: impl FnMut(u16) + 'a) -> Self {
+            self.on_recv = Some(Box::new(f));
+            self
+        }
+
+        /// Replays `events` in order.
+        ///
+        /// # Panics
+        ///
+        /// If a trigger event refers to an unregistered thread.
+        pub fn run(mut self, events: impl IntoIterator<Item = Event>) {
+            for event in events {
+                match event {
+                    Event::Trigger(thr_num) => {
+                        let resume = self
+                            .threads
+                            .get(&thr_num)
+                            .unwrap_or_else(|| panic!("thread {} is not registered", thr_num));
+                        unsafe { resume() };
+                    }
+                    Event::Tick(ticks) => {
+                        if let Some(f) = &mut self.on_tick {
+                            f(ticks);
+                        }
+                    }
+                    Event::Send(channel) => {
+                        if let Some(f) = &mut self.on_send {
+                            f(channel);
+                        }
+                    }
+                    Event::Recv(channel) => {
+                        if let Some(f) = &mut self.on_recv {
+                            f(channel);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        let events = [Event::Trigger(7), Event::Tick(0x0100_0000), Event::Send(1), Event::Recv(2)];
+        let log = events.iter().flat_map(|event| event.to_bits().to_be_bytes().to_vec());
+        let log = log.chain(vec![0xFF, 0, 0, 0, 0x01]).collect::<Vec<_>>();
+        assert_eq!(decode(&log).collect::<Vec<_>>(), [
+            Event::Trigger(7),
+            Event::Tick(0xFF_FFFF),
+            Event::Send(1),
+            Event::Recv(2),
+        ]);
+    }
+
+    #[cfg(feature = "sim")]
+    #[test]
+    fn channel_events() {
+        use crate::sync::spsc::{oneshot, ring};
+        let (mut tx, mut rx) = ring::channel::<u8, ()>(4);
+        let (oneshot_tx, mut oneshot_rx) = oneshot::channel::<u8>();
+        assert_eq!(rx.try_next(), Ok(None));
+        tx.send(1).unwrap();
+        assert_eq!(rx.try_next(), Ok(Some(1)));
+        oneshot_tx.send(2).unwrap();
+        assert_eq!(oneshot_rx.try_recv(), Ok(Some(2)));
+        assert_eq!(take(), [Event::Send(0), Event::Recv(0), Event::Send(1), Event::Recv(1)]);
+    }
+
+    #[cfg(feature = "sim")]
+    #[test]
+    fn tick_events() {
+        tick_at(10);
+        tick_at(10);
+        tick_at(15);
+        assert_eq!(take(), [Event::Tick(10), Event::Tick(5)]);
+    }
+}
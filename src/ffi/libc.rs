@@ -4,7 +4,10 @@
 //! applications with C libraries.
 //!
 //! Dynamic memory functions (e.g. `malloc`, `free`) are implemented in terms of
-//! [Drone Heap](crate::heap).
+//! [Drone Heap](crate::heap), so C libraries linked into a Drone application
+//! share the same managed heap. These functions are exported under their C
+//! names only with the `malloc` feature enabled, to not collide with another C
+//! allocator linked into the application.
 
 use crate::ffi::{c_char, c_int};
 use ::alloc::alloc;
@@ -14,6 +17,18 @@ use core::{alloc::Layout, ffi::c_void, ptr};
 #[allow(non_camel_case_types)]
 pub type size_t = usize;
 
+/// Alignment of the blocks returned by [`malloc`](malloc), suitable for any
+/// fundamental C type.
+///
+/// The heap serves these allocations only from pools, which start address and
+/// block size are multiples of the alignment.
+pub const MALLOC_ALIGN: usize = 8;
+
+// Each block is prefixed with a header holding the block size, which is needed
+// to reconstruct the layout on `free` and `realloc`. The header keeps the
+// payload aligned to `MALLOC_ALIGN`.
+const HEADER: usize = MALLOC_ALIGN;
+
 /// Calculates the length of the string `s`, excluding the terminating null byte
 /// (`'\0'`).
 ///
@@ -70,9 +85,12 @@ pub unsafe extern "C" fn strcmp(mut s1: *const c_char, mut s2: *const c_char) ->
 /// # Safety
 ///
 /// This function works with raw pointers.
-#[cfg_attr(not(feature = "std"), no_mangle)]
+#[cfg_attr(all(feature = "malloc", not(feature = "std")), no_mangle)]
 pub unsafe extern "C" fn malloc(size: size_t) -> *mut c_void {
-    alloc::alloc(Layout::from_size_align_unchecked(size, 1)) as *mut c_void
+    match block_layout(size) {
+        Some(layout) => block_init(alloc::alloc(layout), size),
+        None => ptr::null_mut(),
+    }
 }
 
 /// Allocates memory for an array of `nmemb` elements of `size` bytes each and
@@ -83,9 +101,12 @@ pub unsafe extern "C" fn malloc(size: size_t) -> *mut c_void {
 /// # Safety
 ///
 /// This function works with raw pointers.
-#[cfg_attr(not(feature = "std"), no_mangle)]
+#[cfg_attr(all(feature = "malloc", not(feature = "std")), no_mangle)]
 pub unsafe extern "C" fn calloc(nmemb: size_t, size: size_t) -> *mut c_void {
-    alloc::alloc_zeroed(Layout::from_size_align_unchecked(nmemb * size, 1)) as *mut c_void
+    match nmemb.checked_mul(size).and_then(|size| Some((size, block_layout(size)?))) {
+        Some((size, layout)) => block_init(alloc::alloc_zeroed(layout), size),
+        None => ptr::null_mut(),
+    }
 }
 
 /// Changes the size of the memory block pointed to by `ptr` to `size` bytes.
@@ -102,9 +123,20 @@ pub unsafe extern "C" fn calloc(nmemb: size_t, size: size_t) -> *mut c_void {
 /// # Safety
 ///
 /// This function works with raw pointers.
-#[cfg_attr(not(feature = "std"), no_mangle)]
+#[cfg_attr(all(feature = "malloc", not(feature = "std")), no_mangle)]
 pub unsafe extern "C" fn realloc(ptr: *mut c_void, size: size_t) -> *mut c_void {
-    alloc::realloc(ptr as *mut u8, Layout::from_size_align_unchecked(1, 1), size) as *mut c_void
+    if ptr.is_null() {
+        return malloc(size);
+    }
+    if size == 0 {
+        free(ptr);
+        return ptr::null_mut();
+    }
+    let (block, layout) = block_from_ptr(ptr);
+    match block_layout(size) {
+        Some(new_layout) => block_init(alloc::realloc(block, layout, new_layout.size()), size),
+        None => ptr::null_mut(),
+    }
 }
 
 /// Frees the memory space pointed to by `ptr`, which must have been returned by
@@ -116,7 +148,61 @@ pub unsafe extern "C" fn realloc(ptr: *mut c_void, size: size_t) -> *mut c_void
 /// # Safety
 ///
 /// This function works with raw pointers.
-#[cfg_attr(not(feature = "std"), no_mangle)]
+#[cfg_attr(all(feature = "malloc", not(feature = "std")), no_mangle)]
 pub unsafe extern "C" fn free(ptr: *mut c_void) {
-    alloc::dealloc(ptr as *mut u8, Layout::from_size_align_unchecked(1, 1))
+    if !ptr.is_null() {
+        let (block, layout) = block_from_ptr(ptr);
+        alloc::dealloc(block, layout);
+    }
+}
+
+fn block_layout(size: size_t) -> Option<Layout> {
+    Layout::from_size_align(size.checked_add(HEADER)?, MALLOC_ALIGN).ok()
+}
+
+unsafe fn block_init(block: *mut u8, size: size_t) -> *mut c_void {
+    if block.is_null() {
+        return ptr::null_mut();
+    }
+    #[allow(clippy::cast_ptr_alignment)]
+    ptr::write(block as *mut usize, size);
+    block.add(HEADER) as *mut c_void
+}
+
+unsafe fn block_from_ptr(ptr: *mut c_void) -> (*mut u8, Layout) {
+    let block = (ptr as *mut u8).sub(HEADER);
+    #[allow(clippy::cast_ptr_alignment)]
+    let size = ptr::read(block as *const usize);
+    (block, Layout::from_size_align_unchecked(size + HEADER, MALLOC_ALIGN))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malloc_realloc_free() {
+        unsafe {
+            let ptr = malloc(3) as *mut u8;
+            assert_eq!(ptr as usize % MALLOC_ALIGN, 0);
+            ptr::copy_nonoverlapping(b"abc".as_ptr(), ptr, 3);
+            let ptr = realloc(ptr as *mut c_void, 100) as *mut u8;
+            assert_eq!(ptr as usize % MALLOC_ALIGN, 0);
+            assert_eq!(core::slice::from_raw_parts(ptr, 3), b"abc");
+            let ptr = realloc(ptr as *mut c_void, 2) as *mut u8;
+            assert_eq!(core::slice::from_raw_parts(ptr, 2), b"ab");
+            free(ptr as *mut c_void);
+            free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn calloc_zeroed() {
+        unsafe {
+            let ptr = calloc(4, 8) as *mut u8;
+            assert!(core::slice::from_raw_parts(ptr, 32).iter().all(|&x| x == 0));
+            free(ptr as *mut c_void);
+            assert!(calloc(usize::MAX, 2).is_null());
+        }
+    }
 }
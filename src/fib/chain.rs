@@ -1,13 +1,20 @@
-use crate::fib::FiberRoot;
-use core::{
-    pin::Pin,
-    ptr,
-    sync::atomic::{AtomicPtr, Ordering},
+use crate::{
+    atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+    fib::FiberRoot,
 };
+use core::{pin::Pin, ptr};
 
 /// A lock-free stack of fibers.
+///
+/// By default fibers are resumed in the reverse order of addition on each
+/// [`drain`](Chain::drain). In the round-robin mode, enabled with
+/// [`set_round_robin`](Chain::set_round_robin), the starting fiber rotates on
+/// each invocation, so a frequently yielding fiber at the top of the chain
+/// doesn't systematically delay the fibers below it.
 pub struct Chain {
     head: AtomicPtr<Node>,
+    round_robin: AtomicBool,
+    offset: AtomicUsize,
 }
 
 struct Node {
@@ -18,7 +25,16 @@ struct Node {
 impl Chain {
     /// Creates an empty fiber chain.
     pub const fn new() -> Self {
-        Self { head: AtomicPtr::new(ptr::null_mut()) }
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            round_robin: AtomicBool::new(false),
+            offset: AtomicUsize::new(0),
+        }
+    }
+
+    /// Enables or disables the round-robin mode.
+    pub fn set_round_robin(&self, enabled: bool) {
+        self.round_robin.store(enabled, Ordering::Relaxed);
     }
 
     /// Adds the fiber `fib` first to the chain.
@@ -38,24 +54,57 @@ impl Chain {
     /// This method is not reentrant.
     #[inline(never)]
     pub unsafe fn drain(&self) {
+        let head = self.head.load(Ordering::Acquire);
+        if !self.round_robin.load(Ordering::Relaxed) {
+            self.advance(ptr::null_mut(), head, usize::MAX);
+            return;
+        }
+        let mut offset = self.offset.load(Ordering::Relaxed);
         let mut prev = ptr::null_mut();
-        let mut curr = self.head.load(Ordering::Acquire);
-        while !curr.is_null() {
+        let mut curr = head;
+        for _ in 0..offset {
+            if curr.is_null() {
+                break;
+            }
+            prev = curr;
+            curr = (*curr).next;
+        }
+        if curr.is_null() {
+            offset = 0;
+            prev = ptr::null_mut();
+            curr = head;
+        }
+        self.offset.store(offset + 1, Ordering::Relaxed);
+        self.advance(prev, curr, usize::MAX);
+        if offset > 0 {
+            // The first `offset` nodes are never removed by the previous pass.
+            self.advance(ptr::null_mut(), head, offset);
+        }
+    }
+
+    // Advances at most `count` fibers starting from `curr`. A null `prev` means
+    // `curr` can be the head of the chain.
+    unsafe fn advance(&self, mut prev: *mut Node, mut curr: *mut Node, mut count: usize) {
+        while !curr.is_null() && count > 0 {
+            count -= 1;
             let next = (*curr).next;
             if (*curr).fib.as_mut().advance() {
                 prev = curr;
             } else {
                 if prev.is_null() {
-                    prev = self.head.compare_and_swap(curr, next, Ordering::Relaxed);
+                    prev = self
+                        .head
+                        .compare_exchange(curr, next, Ordering::Relaxed, Ordering::Relaxed)
+                        .unwrap_or_else(|head| head);
                     if prev == curr {
                         prev = ptr::null_mut();
                     } else {
                         loop {
-                            prev = (*prev).next;
-                            if prev == curr {
+                            if (*prev).next == curr {
                                 (*prev).next = next;
                                 break;
                             }
+                            prev = (*prev).next;
                         }
                     }
                 } else {
@@ -72,7 +121,8 @@ impl Chain {
         loop {
             let head = self.head.load(Ordering::Relaxed);
             unsafe { (*node).next = head };
-            if self.head.compare_and_swap(head, node, Ordering::Release) == head {
+            if self.head.compare_exchange(head, node, Ordering::Release, Ordering::Relaxed).is_ok()
+            {
                 break;
             }
         }
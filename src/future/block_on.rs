@@ -0,0 +1,73 @@
+#![cfg_attr(feature = "std", allow(unreachable_code))]
+
+use super::drone_wait_for_event;
+use crate::atomic::{AtomicBool, Ordering};
+use alloc::sync::Arc;
+use core::{
+    future::Future,
+    task::{Context, Poll},
+};
+use futures::{
+    pin_mut,
+    task::{waker, ArcWake},
+};
+
+struct Signal(AtomicBool);
+
+impl ArcWake for Signal {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.store(true, Ordering::Release);
+    }
+}
+
+/// Runs `future` to completion on the current stack, sleeping with the
+/// platform's wait-for-event instruction while it is pending.
+///
+/// This is intended for `main` and boot sequences, which need to `.await` a
+/// few Drone channels before the thread executors are set up. The future is
+/// polled again only after it has been woken.
+///
+/// See [`block_on_with`] for details.
+#[inline]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    block_on_with(future, wait_for_event)
+}
+
+/// Runs `future` to completion on the current stack, calling `wait` while it is
+/// pending.
+///
+/// `wait` should suspend the processor until something happens, and must
+/// return after any interrupt, like the `WFE` instruction does. Otherwise a
+/// wake-up occurring right before the call could be missed. `wait` is allowed
+/// to return spuriously.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::{future, sync::spsc::oneshot};
+///
+/// let (tx, rx) = oneshot::channel::<u32>();
+/// tx.send(42).unwrap();
+/// assert_eq!(future::block_on_with(rx, || {}), Ok(42));
+/// ```
+pub fn block_on_with<F: Future, W: FnMut()>(future: F, mut wait: W) -> F::Output {
+    let signal = Arc::new(Signal(AtomicBool::new(true)));
+    let waker = waker(Arc::clone(&signal));
+    let mut cx = Context::from_waker(&waker);
+    pin_mut!(future);
+    loop {
+        if signal.0.swap(false, Ordering::Acquire) {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        } else {
+            wait();
+        }
+    }
+}
+
+fn wait_for_event() {
+    #[cfg(feature = "std")]
+    return std::thread::yield_now();
+    unsafe { drone_wait_for_event() };
+}
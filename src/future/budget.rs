@@ -0,0 +1,155 @@
+use core::{
+    cell::Cell,
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+/// Future for the [`budget`] and [`budget_cycles`] functions.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Budget<F: Future> {
+    future: F,
+    limit: u32,
+    cycles: Option<fn() -> u32>,
+    used: u32,
+}
+
+/// Wraps `future` to give up the executor after every `polls` polls.
+///
+/// The budget bounds a busy slice: a run of consecutive polls, in which the
+/// inner future wakes its own task through the context waker before returning
+/// `Pending`. Once the inner future has been polled `polls` times in one
+/// slice, the next poll of the wrapper doesn't reach the inner future. Instead
+/// it wakes its own task and returns `Pending`, so other fibers of the same
+/// thread and pending interrupts of equal priority get a chance to run. A
+/// `Pending` without such a wake means the inner future waits for an external
+/// event, which ends the slice. In both cases the budget is refilled.
+///
+/// The wrapper acts only between polls of the inner future: a single long
+/// poll, or a poll which never returns, can't be interrupted. It is most
+/// useful for futures, which wake themselves frequently, e.g. nested executors
+/// or streams of immediately ready items.
+///
+/// # Panics
+///
+/// If `polls` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::future;
+///
+/// async fn busy() {
+///     for _ in 0..10 {
+///         future::yield_now().await;
+///     }
+/// }
+///
+/// future::block_on_with(future::budget(busy(), 4), || {});
+/// ```
+#[inline]
+pub fn budget<F: Future>(future: F, polls: u32) -> Budget<F> {
+    assert!(polls > 0, "zero poll budget");
+    Budget { future, limit: polls, cycles: None, used: 0 }
+}
+
+/// Wraps `future` to give up the executor after every `max_cycles` cycles
+/// spent inside its `poll`.
+///
+/// `cycles` should return the value of a free-running cycle counter, e.g.
+/// `DWT.CYCCNT` on Cortex-M. The check is made before each poll of the inner
+/// future, so a slice can overrun `max_cycles` by the duration of the last
+/// poll. See [`budget`] for what counts as a slice.
+///
+/// # Panics
+///
+/// If `max_cycles` is zero.
+#[inline]
+pub fn budget_cycles<F: Future>(future: F, max_cycles: u32, cycles: fn() -> u32) -> Budget<F> {
+    assert!(max_cycles > 0, "zero cycle budget");
+    Budget { future, limit: max_cycles, cycles: Some(cycles), used: 0 }
+}
+
+impl<F: Future> Budget<F> {
+    /// Returns the part of the current slice budget already consumed, in polls
+    /// or cycles.
+    #[inline]
+    pub fn used(&self) -> u32 {
+        self.used
+    }
+
+    /// Consumes the wrapper, returning the inner future.
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Budget<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let Self { future, limit, cycles, used } = unsafe { self.get_unchecked_mut() };
+        if *used >= *limit {
+            *used = 0;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let future = unsafe { Pin::new_unchecked(future) };
+        let probe = WakeProbe { waker: cx.waker(), woken: Cell::new(false) };
+        let waker = unsafe { probe.waker() };
+        let mut probe_cx = Context::from_waker(&waker);
+        let poll = match cycles {
+            Some(cycles) => {
+                let begin = cycles();
+                let poll = future.poll(&mut probe_cx);
+                *used = used.saturating_add(cycles().wrapping_sub(begin));
+                poll
+            }
+            None => {
+                *used += 1;
+                future.poll(&mut probe_cx)
+            }
+        };
+        if poll.is_pending() && !probe.woken.get() {
+            *used = 0;
+        }
+        poll
+    }
+}
+
+/// Records whether the inner future woke its task during a poll.
+struct WakeProbe<'a> {
+    waker: &'a Waker,
+    woken: Cell<bool>,
+}
+
+static PROBE_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(probe_clone, probe_wake, probe_wake, probe_drop);
+
+impl WakeProbe<'_> {
+    /// Returns a waker, which forwards to the original one.
+    ///
+    /// # Safety
+    ///
+    /// The returned waker must not outlive `self`. It is only lent to the inner
+    /// future by reference, and its clones are clones of the original waker.
+    unsafe fn waker(&self) -> Waker {
+        Waker::from_raw(RawWaker::new(self as *const Self as *const (), &PROBE_VTABLE))
+    }
+}
+
+unsafe fn probe_clone(data: *const ()) -> RawWaker {
+    let probe = &*(data as *const WakeProbe<'_>);
+    // `Waker` is a transparent wrapper around `RawWaker`.
+    mem::transmute::<Waker, RawWaker>(probe.waker.clone())
+}
+
+unsafe fn probe_wake(data: *const ()) {
+    let probe = &*(data as *const WakeProbe<'_>);
+    probe.woken.set(true);
+    probe.waker.wake_by_ref();
+}
+
+unsafe fn probe_drop(_data: *const ()) {}
@@ -0,0 +1,89 @@
+use super::{Deadline, Tick};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures::Stream;
+
+/// Stream for the [`debounce`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Debounce<T: Tick, S: Stream> {
+    stream: S,
+    duration: Duration,
+    pending: Option<(S::Item, Deadline<T>)>,
+    done: bool,
+}
+
+/// Wraps `stream` to yield an item only after the inner stream has been quiet
+/// for `duration`.
+///
+/// Each new item replaces the pending one and restarts the quiet period, so a
+/// burst of items results in the last item of the burst. When the inner stream
+/// ends, the pending item is yielded immediately. Useful for noisy inputs like
+/// mechanical buttons and jittery sensors.
+///
+/// # Examples
+///
+/// ```
+/// # use core::task::Waker;
+/// # struct SysTick;
+/// # impl drone_core::future::Tick for SysTick {
+/// #     const FREQ: u64 = 1_000;
+/// #     fn now() -> u64 { 0 }
+/// #     fn schedule_wakeup(_at: u64, _waker: &Waker) {}
+/// # }
+/// use core::time::Duration;
+/// use drone_core::future;
+/// use futures::prelude::*;
+///
+/// async fn on_press(button: impl Stream<Item = bool>) {
+///     let presses = future::debounce::<SysTick, _>(button, Duration::from_millis(20));
+///     futures::pin_mut!(presses);
+///     while let Some(_pressed) = presses.next().await {
+///         // handle the stable state
+///     }
+/// }
+/// ```
+#[inline]
+pub fn debounce<T: Tick, S: Stream>(stream: S, duration: Duration) -> Debounce<T, S> {
+    Debounce { stream, duration, pending: None, done: false }
+}
+
+impl<T: Tick, S: Stream> Debounce<T, S> {
+    /// Returns the deadline of the pending item, if any.
+    #[inline]
+    pub fn deadline(&self) -> Option<&Deadline<T>> {
+        self.pending.as_ref().map(|(_, deadline)| deadline)
+    }
+}
+
+impl<T: Tick, S: Stream> Stream for Debounce<T, S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let Self { stream, duration, pending, done } = unsafe { self.get_unchecked_mut() };
+        if !*done {
+            let mut stream = unsafe { Pin::new_unchecked(stream) };
+            loop {
+                match stream.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(item)) => *pending = Some((item, Deadline::after(*duration))),
+                    Poll::Ready(None) => {
+                        *done = true;
+                        return Poll::Ready(pending.take().map(|(item, _)| item));
+                    }
+                    Poll::Pending => break,
+                }
+            }
+        }
+        match pending {
+            Some((_, deadline)) => match Pin::new(deadline).poll(cx) {
+                Poll::Ready(()) => Poll::Ready(pending.take().map(|(item, _)| item)),
+                Poll::Pending => Poll::Pending,
+            },
+            None if *done => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
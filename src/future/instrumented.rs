@@ -0,0 +1,136 @@
+use crate::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Poll statistics of an [`Instrumented`] future.
+///
+/// The statistics are updated only by the thread polling the future, and can
+/// be read from anywhere, e.g. from a debug shell, even after the future has
+/// been dropped. Time is measured in cycles of the counter function passed to
+/// [`instrument`], and wraps around on overflow.
+pub struct PollStats {
+    polls: AtomicUsize,
+    busy: AtomicU32,
+    max_poll: AtomicU32,
+    completed: AtomicBool,
+    completion: AtomicU32,
+}
+
+/// Future for the [`instrument`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Instrumented<'a, F: Future> {
+    future: F,
+    stats: &'a PollStats,
+    cycles: fn() -> u32,
+    start: Option<u32>,
+}
+
+/// Wraps `future` to record its poll statistics into `stats`.
+///
+/// `cycles` should return the value of a free-running cycle counter, e.g.
+/// `DWT.CYCCNT` on Cortex-M.
+///
+/// # Examples
+///
+/// ```
+/// use core::sync::atomic::{AtomicU32, Ordering};
+/// use drone_core::future::{self, PollStats};
+///
+/// static CYCLES: AtomicU32 = AtomicU32::new(0);
+/// static STATS: PollStats = PollStats::new();
+///
+/// fn cycles() -> u32 {
+///     CYCLES.fetch_add(10, Ordering::Relaxed)
+/// }
+///
+/// future::block_on_with(future::instrument(future::yield_now(), &STATS, cycles), || {});
+/// assert_eq!(STATS.polls(), 2);
+/// assert_eq!(STATS.max_poll_cycles(), 10);
+/// assert_eq!(STATS.completion_cycles(), Some(30));
+/// ```
+#[inline]
+pub fn instrument<F: Future>(
+    future: F,
+    stats: &PollStats,
+    cycles: fn() -> u32,
+) -> Instrumented<'_, F> {
+    Instrumented { future, stats, cycles, start: None }
+}
+
+impl PollStats {
+    /// Creates a new empty statistics.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            polls: AtomicUsize::new(0),
+            busy: AtomicU32::new(0),
+            max_poll: AtomicU32::new(0),
+            completed: AtomicBool::new(false),
+            completion: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns the number of polls.
+    #[inline]
+    pub fn polls(&self) -> usize {
+        self.polls.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of cycles spent inside `poll`.
+    #[inline]
+    pub fn busy_cycles(&self) -> u32 {
+        self.busy.load(Ordering::Relaxed)
+    }
+
+    /// Returns the longest single `poll` in cycles.
+    #[inline]
+    pub fn max_poll_cycles(&self) -> u32 {
+        self.max_poll.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of cycles from the first poll to the completion, or
+    /// `None` if the future hasn't completed yet.
+    #[inline]
+    pub fn completion_cycles(&self) -> Option<u32> {
+        if self.completed.load(Ordering::Acquire) {
+            Some(self.completion.load(Ordering::Relaxed))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for PollStats {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Future> Future for Instrumented<'_, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let Self { future, stats, cycles, start } = unsafe { self.get_unchecked_mut() };
+        let begin = cycles();
+        let start = *start.get_or_insert(begin);
+        let poll = unsafe { Pin::new_unchecked(future) }.poll(cx);
+        let end = cycles();
+        let elapsed = end.wrapping_sub(begin);
+        stats.polls.store(stats.polls.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+        stats
+            .busy
+            .store(stats.busy.load(Ordering::Relaxed).wrapping_add(elapsed), Ordering::Relaxed);
+        if elapsed > stats.max_poll.load(Ordering::Relaxed) {
+            stats.max_poll.store(elapsed, Ordering::Relaxed);
+        }
+        if poll.is_ready() {
+            stats.completion.store(end.wrapping_sub(start), Ordering::Relaxed);
+            stats.completed.store(true, Ordering::Release);
+        }
+        poll
+    }
+}
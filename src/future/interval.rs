@@ -0,0 +1,65 @@
+use super::{Deadline, Tick};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures::Stream;
+
+/// A stream that yields `()` every `period`.
+///
+/// Each deadline is computed from the previous one rather than from the time
+/// the item was consumed, so the schedule doesn't drift. If the consumer falls
+/// behind, the missed items are yielded immediately one after another.
+#[must_use = "streams do nothing unless polled"]
+pub struct Interval<T: Tick> {
+    deadline: Deadline<T>,
+    period: u64,
+}
+
+/// Returns a stream that yields `()` every `period`, starting one `period`
+/// from now.
+///
+/// # Panics
+///
+/// If `period` is shorter than one tick.
+#[inline]
+pub fn interval<T: Tick>(period: Duration) -> Interval<T> {
+    Interval::new(Deadline::after(period), period)
+}
+
+impl<T: Tick> Interval<T> {
+    /// Creates a new interval with the first item at `start`, and subsequent
+    /// items every `period`.
+    ///
+    /// # Panics
+    ///
+    /// If `period` is shorter than one tick.
+    #[inline]
+    pub fn new(start: Deadline<T>, period: Duration) -> Self {
+        let period = T::ticks(period);
+        assert!(period > 0, "interval period is shorter than one tick");
+        Self { deadline: start, period }
+    }
+
+    /// Returns the deadline of the next item.
+    #[inline]
+    pub fn deadline(&self) -> &Deadline<T> {
+        &self.deadline
+    }
+}
+
+impl<T: Tick> Stream for Interval<T> {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        match Pin::new(&mut self.deadline).poll(cx) {
+            Poll::Ready(()) => {
+                self.deadline = Deadline::at(self.deadline.tick() + self.period);
+                Poll::Ready(Some(()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use core::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+enum MaybeDone<F: Future> {
+    Future(F),
+    Done(F::Output),
+    Gone,
+}
+
+impl<F: Future> MaybeDone<F> {
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool {
+        let this = unsafe { self.get_unchecked_mut() };
+        match this {
+            Self::Future(future) => match unsafe { Pin::new_unchecked(future) }.poll(cx) {
+                Poll::Ready(output) => {
+                    *this = Self::Done(output);
+                    true
+                }
+                Poll::Pending => false,
+            },
+            Self::Done(_) => true,
+            Self::Gone => panic!("`Join` polled after completion"),
+        }
+    }
+
+    fn take(self: Pin<&mut Self>) -> F::Output {
+        let this = unsafe { self.get_unchecked_mut() };
+        match mem::replace(this, Self::Gone) {
+            Self::Done(output) => output,
+            _ => unreachable!(),
+        }
+    }
+}
+
+macro_rules! join {
+    (
+        $(#[$ty_attr:meta])* $ty:ident;
+        $(#[$attr:meta])* $join:ident<$($fut:ident $field:ident),*>
+    ) => {
+        $(#[$ty_attr])*
+        #[must_use = "futures do nothing unless you `.await` or poll them"]
+        pub struct $ty<$($fut: Future),*> {
+            $($field: MaybeDone<$fut>,)*
+        }
+
+        $(#[$attr])*
+        #[inline]
+        pub fn $join<$($fut: Future),*>($($field: $fut),*) -> $ty<$($fut),*> {
+            $ty { $($field: MaybeDone::Future($field),)* }
+        }
+
+        impl<$($fut: Future),*> Future for $ty<$($fut),*> {
+            type Output = ($($fut::Output,)*);
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let this = unsafe { self.get_unchecked_mut() };
+                let mut done = true;
+                $(done &= unsafe { Pin::new_unchecked(&mut this.$field) }.poll(cx);)*
+                if done {
+                    Poll::Ready(($(unsafe { Pin::new_unchecked(&mut this.$field) }.take(),)*))
+                } else {
+                    Poll::Pending
+                }
+            }
+        }
+    };
+}
+
+join! {
+    /// Future for the [`join2`] function.
+    Join2;
+    /// Polls two futures concurrently, and resolves to a tuple of their outputs
+    /// when both are complete.
+    ///
+    /// The futures are stored inline, no allocation is made.
+    join2<A a, B b>
+}
+
+join! {
+    /// Future for the [`join3`] function.
+    Join3;
+    /// Polls three futures concurrently, and resolves to a tuple of their
+    /// outputs when all are complete.
+    ///
+    /// The futures are stored inline, no allocation is made.
+    join3<A a, B b, C c>
+}
+
+join! {
+    /// Future for the [`join4`] function.
+    Join4;
+    /// Polls four futures concurrently, and resolves to a tuple of their
+    /// outputs when all are complete.
+    ///
+    /// The futures are stored inline, no allocation is made.
+    join4<A a, B b, C c, D d>
+}
@@ -0,0 +1,65 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures::Stream;
+
+/// Stream for the [`latest`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Latest<S: Stream> {
+    stream: S,
+    done: bool,
+}
+
+/// Wraps `stream` to skip the backlog of items.
+///
+/// On each poll the inner stream is drained of all ready items, and only the
+/// most recent one is yielded. Useful for consumers, which care only about the
+/// current state, like a sensor reading, and can't keep up with the producer.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::future;
+/// use futures::prelude::*;
+///
+/// let stream = future::latest(stream::iter(1..=3));
+/// assert_eq!(future::block_on_with(stream.collect::<Vec<_>>(), || {}), [3]);
+/// ```
+#[inline]
+pub fn latest<S: Stream>(stream: S) -> Latest<S> {
+    Latest { stream, done: false }
+}
+
+impl<S: Stream> Latest<S> {
+    /// Consumes the adapter, returning the inner stream.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Stream> Stream for Latest<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let Self { stream, done } = unsafe { self.get_unchecked_mut() };
+        if *done {
+            return Poll::Ready(None);
+        }
+        let mut stream = unsafe { Pin::new_unchecked(stream) };
+        let mut last = None;
+        loop {
+            match stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => last = Some(item),
+                Poll::Ready(None) => {
+                    *done = true;
+                    return Poll::Ready(last);
+                }
+                Poll::Pending => {
+                    return if last.is_some() { Poll::Ready(last) } else { Poll::Pending };
+                }
+            }
+        }
+    }
+}
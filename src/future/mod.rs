@@ -0,0 +1,68 @@
+//! Utilities for working with futures.
+//!
+//! Drone threads are executors themselves: a future is attached to a thread
+//! with [`ThrFiberFuture::add_future`](crate::fib::ThrFiberFuture::add_future)
+//! or similar methods, and the thread interrupt resumes it. This module
+//! provides lightweight helpers, which don't depend on a particular thread
+//! setup.
+//!
+//! Timing utilities, like [`sleep`] and [`timeout`], are generic over the
+//! [`Tick`] trait, which platform crates implement for their timers:
+//!
+//! ```
+//! # use core::task::Waker;
+//! # struct SysTick;
+//! # impl drone_core::future::Tick for SysTick {
+//! #     const FREQ: u64 = 1_000;
+//! #     fn now() -> u64 { 0 }
+//! #     fn schedule_wakeup(_at: u64, _waker: &Waker) {}
+//! # }
+//! use core::time::Duration;
+//! use drone_core::{future, sync::spsc::oneshot};
+//!
+//! async fn receive(rx: oneshot::Receiver<u32>) -> Option<u32> {
+//!     future::timeout::<SysTick, _>(rx, Duration::from_millis(100)).await.ok()?.ok()
+//! }
+//! ```
+//!
+//! Pending deadlines are tracked globally, so a platform idle hook can ask for
+//! the next one with [`next_wakeup`] and stop the periodic tick when idle.
+
+mod block_on;
+mod budget;
+mod debounce;
+mod instrumented;
+mod interval;
+mod join;
+mod latest;
+mod on_cancel;
+mod select;
+#[cfg(feature = "nightly")]
+mod task_arena;
+mod tick;
+mod tickless;
+mod yield_now;
+
+pub use self::{
+    block_on::{block_on, block_on_with},
+    budget::{budget, budget_cycles, Budget},
+    debounce::{debounce, Debounce},
+    instrumented::{instrument, Instrumented, PollStats},
+    interval::{interval, Interval},
+    join::{join2, join3, join4, Join2, Join3, Join4},
+    latest::{latest, Latest},
+    on_cancel::{on_cancel, CancelGuard, OnCancel},
+    select::{select, Select},
+    tick::{sleep, timeout, Deadline, Tick, TimedOut, Timeout},
+    tickless::{next_wakeup, Wakeup, WAKEUP_CAPACITY},
+    yield_now::{yield_now, YieldNow},
+};
+
+#[cfg(feature = "nightly")]
+pub use self::task_arena::TaskArena;
+
+extern "C" {
+    /// Suspends the processor until an interrupt or an event occurs. Provided
+    /// by the platform crate.
+    pub fn drone_wait_for_event();
+}
@@ -0,0 +1,106 @@
+use core::{
+    future::Future,
+    mem::ManuallyDrop,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll},
+};
+
+/// Future for the [`on_cancel`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct OnCancel<F: Future, C: FnOnce()> {
+    future: F,
+    cleanup: Option<C>,
+}
+
+/// A guard, which runs a closure when dropped, unless defused.
+///
+/// Useful inside `async` blocks: the guard is dropped when the enclosing
+/// future is dropped at an `.await` point.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::future::CancelGuard;
+///
+/// async fn transfer(done: impl core::future::Future<Output = ()>) {
+///     // start DMA
+///     let guard = CancelGuard::new(|| {
+///         // stop DMA
+///     });
+///     done.await;
+///     guard.defuse();
+/// }
+/// ```
+#[must_use = "the guard runs the closure immediately if unused"]
+pub struct CancelGuard<C: FnOnce()> {
+    cleanup: ManuallyDrop<C>,
+}
+
+/// Wraps `future` to run `cleanup` if it is dropped before completion.
+///
+/// `cleanup` is dropped without being called if the future completes. This
+/// makes it easy to write cancellation-safe drivers, which must stop the
+/// hardware (e.g. a DMA transfer into a buffer owned by the future) when the
+/// operation is abandoned. `cleanup` runs before the inner future is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use core::sync::atomic::{AtomicBool, Ordering};
+/// use drone_core::future;
+///
+/// static STOPPED: AtomicBool = AtomicBool::new(false);
+///
+/// let fut = future::on_cancel(future::yield_now(), || STOPPED.store(true, Ordering::Relaxed));
+/// drop(fut);
+/// assert!(STOPPED.load(Ordering::Relaxed));
+/// ```
+#[inline]
+pub fn on_cancel<F: Future, C: FnOnce()>(future: F, cleanup: C) -> OnCancel<F, C> {
+    OnCancel { future, cleanup: Some(cleanup) }
+}
+
+impl<C: FnOnce()> CancelGuard<C> {
+    /// Creates a new guard, which calls `cleanup` when dropped.
+    #[inline]
+    pub fn new(cleanup: C) -> Self {
+        Self { cleanup: ManuallyDrop::new(cleanup) }
+    }
+
+    /// Disarms the guard, dropping the closure without calling it.
+    #[inline]
+    pub fn defuse(self) {
+        let mut guard = ManuallyDrop::new(self);
+        unsafe { ManuallyDrop::drop(&mut guard.cleanup) };
+    }
+}
+
+impl<C: FnOnce()> Drop for CancelGuard<C> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { ptr::read(&*self.cleanup)() };
+    }
+}
+
+impl<F: Future, C: FnOnce()> Future for OnCancel<F, C> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let Self { future, cleanup } = unsafe { self.get_unchecked_mut() };
+        let poll = unsafe { Pin::new_unchecked(future) }.poll(cx);
+        if poll.is_ready() {
+            cleanup.take();
+        }
+        poll
+    }
+}
+
+impl<F: Future, C: FnOnce()> Drop for OnCancel<F, C> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(cleanup) = self.cleanup.take() {
+            cleanup();
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures::future::Either;
+
+/// Future for the [`select`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Select<A: Future, B: Future> {
+    a: A,
+    b: B,
+}
+
+/// Polls two futures concurrently, and resolves to the output of the one which
+/// completes first. The other future is dropped together with the `Select`.
+///
+/// The futures are stored inline, no allocation is made. The first future is
+/// always polled first, so it wins if both are ready.
+#[inline]
+pub fn select<A: Future, B: Future>(a: A, b: B) -> Select<A, B> {
+    Select { a, b }
+}
+
+impl<A: Future, B: Future> Future for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Self { a, b } = unsafe { self.get_unchecked_mut() };
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(a) }.poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        unsafe { Pin::new_unchecked(b) }.poll(cx).map(Either::Right)
+    }
+}
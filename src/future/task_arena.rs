@@ -0,0 +1,147 @@
+use core::{
+    future::Future,
+    marker::PhantomPinned,
+    mem::{align_of, size_of, MaybeUninit},
+    pin::Pin,
+    ptr,
+    task::{Context, Poll},
+};
+
+type PollFn = unsafe fn(*mut u8, &mut Context<'_>) -> Poll<()>;
+type DropFn = unsafe fn(*mut u8);
+
+/// A fixed-capacity container of up to `N` spawned futures.
+///
+/// Each future is moved into one of `N` inline slots of `W` 64-bit words, and
+/// its type is erased. No allocation is made. A future that doesn't fit into a
+/// slot is rejected by [`spawn`](TaskArena::spawn).
+///
+/// The arena doesn't track which task has been woken. Every
+/// [`poll_all`](TaskArena::poll_all) polls all the stored futures with the
+/// given context, therefore it is best suited for a small number of tasks
+/// driven by a single thread.
+///
+/// # Examples
+///
+/// ```
+/// use core::task::Poll;
+/// use drone_core::future::{self, TaskArena};
+/// use futures::pin_mut;
+///
+/// let arena = TaskArena::<4, 8>::new();
+/// pin_mut!(arena);
+/// arena.as_mut().spawn(async {}).ok().unwrap();
+/// arena.as_mut().spawn(future::yield_now()).ok().unwrap();
+/// assert_eq!(arena.len(), 2);
+///
+/// let waker = futures::task::noop_waker();
+/// let mut cx = core::task::Context::from_waker(&waker);
+/// assert_eq!(arena.as_mut().poll_all(&mut cx), Poll::Pending);
+/// assert_eq!(arena.as_mut().poll_all(&mut cx), Poll::Ready(()));
+/// assert!(arena.is_empty());
+/// ```
+pub struct TaskArena<const N: usize, const W: usize> {
+    slots: [Slot<W>; N],
+    _pinned: PhantomPinned,
+}
+
+struct Slot<const W: usize> {
+    storage: MaybeUninit<[u64; W]>,
+    task: Option<(PollFn, DropFn)>,
+}
+
+impl<const N: usize, const W: usize> TaskArena<N, W> {
+    /// Creates a new empty arena.
+    pub fn new() -> Self {
+        let mut slots = MaybeUninit::<[Slot<W>; N]>::uninit();
+        let first = slots.as_mut_ptr().cast::<Slot<W>>();
+        for i in 0..N {
+            unsafe { first.add(i).write(Slot { storage: MaybeUninit::uninit(), task: None }) };
+        }
+        Self { slots: unsafe { slots.assume_init() }, _pinned: PhantomPinned }
+    }
+
+    /// Moves `future` into a free slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `future` back if there is no free slot, or if the future
+    /// doesn't fit into a slot.
+    pub fn spawn<F>(self: Pin<&mut Self>, future: F) -> Result<(), F>
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        if size_of::<F>() > size_of::<[u64; W]>() || align_of::<F>() > align_of::<u64>() {
+            return Err(future);
+        }
+        let this = unsafe { self.get_unchecked_mut() };
+        let slot = match this.slots.iter_mut().find(|slot| slot.task.is_none()) {
+            Some(slot) => slot,
+            None => return Err(future),
+        };
+        unsafe { slot.storage.as_mut_ptr().cast::<F>().write(future) };
+        slot.task = Some((poll_fn::<F>, drop_fn::<F>));
+        Ok(())
+    }
+
+    /// Polls all stored futures once, and frees the slots of the completed
+    /// ones.
+    ///
+    /// Returns `Poll::Ready(())` if the arena is empty afterwards.
+    pub fn poll_all(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut pending = false;
+        for slot in &mut this.slots {
+            if let Some((poll, drop_task)) = slot.task {
+                let data = slot.storage.as_mut_ptr().cast::<u8>();
+                if unsafe { poll(data, cx) }.is_ready() {
+                    slot.task = None;
+                    unsafe { drop_task(data) };
+                } else {
+                    pending = true;
+                }
+            }
+        }
+        if pending { Poll::Pending } else { Poll::Ready(()) }
+    }
+
+    /// Returns the number of stored futures.
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.task.is_some()).count()
+    }
+
+    /// Returns `true` if there are no stored futures.
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| slot.task.is_none())
+    }
+
+    /// Returns the maximum number of stored futures.
+    #[allow(clippy::unused_self)]
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize, const W: usize> Default for TaskArena<N, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const W: usize> Drop for TaskArena<N, W> {
+    fn drop(&mut self) {
+        for slot in &mut self.slots {
+            if let Some((_, drop_task)) = slot.task.take() {
+                unsafe { drop_task(slot.storage.as_mut_ptr().cast::<u8>()) };
+            }
+        }
+    }
+}
+
+unsafe fn poll_fn<F: Future<Output = ()>>(data: *mut u8, cx: &mut Context<'_>) -> Poll<()> {
+    Pin::new_unchecked(&mut *data.cast::<F>()).poll(cx)
+}
+
+unsafe fn drop_fn<F>(data: *mut u8) {
+    ptr::drop_in_place(data.cast::<F>());
+}
@@ -0,0 +1,160 @@
+use super::Wakeup;
+use core::{
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// A monotonic tick source with a wake-up alarm. Implemented once per platform
+/// crate, usually with a hardware timer.
+pub trait Tick: 'static {
+    /// The tick frequency in Hz.
+    const FREQ: u64;
+
+    /// Returns the current number of ticks. Must not decrease.
+    fn now() -> u64;
+
+    /// Arranges `waker` to be woken at or after the tick `at`.
+    ///
+    /// If `at` is in the past, `waker` should be woken as soon as possible. An
+    /// implementation may wake the waker spuriously, and may keep only the
+    /// latest waker.
+    fn schedule_wakeup(at: u64, waker: &Waker);
+
+    /// Converts `duration` to a number of ticks, rounding down.
+    #[inline]
+    fn ticks(duration: Duration) -> u64 {
+        duration.as_secs() * Self::FREQ
+            + u64::from(duration.subsec_nanos()) * Self::FREQ / 1_000_000_000
+    }
+}
+
+/// A future that resolves when the tick source `T` reaches a deadline.
+///
+/// While the future is pending, the deadline is registered for
+/// [`next_wakeup`](super::next_wakeup). With the `evtrace` feature, the ticks
+/// elapsed since the previous tick event are recorded when the future
+/// resolves.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Deadline<T: Tick> {
+    at: u64,
+    wakeup: Option<Wakeup>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// A future that resolves to the output of `F`, or to [`TimedOut`] if the
+/// deadline comes first.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Timeout<T: Tick, F: Future> {
+    future: F,
+    deadline: Deadline<T>,
+}
+
+/// Error returned from a [`Timeout`] when the deadline has elapsed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimedOut;
+
+/// Returns a future that resolves after `duration`.
+#[inline]
+pub fn sleep<T: Tick>(duration: Duration) -> Deadline<T> {
+    Deadline::after(duration)
+}
+
+/// Runs `future` with a time limit of `duration`.
+#[inline]
+pub fn timeout<T: Tick, F: Future>(future: F, duration: Duration) -> Timeout<T, F> {
+    Timeout { future, deadline: Deadline::after(duration) }
+}
+
+impl<T: Tick> Deadline<T> {
+    /// Creates a new deadline at the tick `at`.
+    #[inline]
+    pub fn at(at: u64) -> Self {
+        Self { at, wakeup: None, _marker: PhantomData }
+    }
+
+    /// Creates a new deadline `duration` from now.
+    #[inline]
+    pub fn after(duration: Duration) -> Self {
+        Self::at(T::now() + T::ticks(duration))
+    }
+
+    /// Returns the tick of the deadline.
+    #[inline]
+    pub fn tick(&self) -> u64 {
+        self.at
+    }
+
+    /// Returns `true` if the deadline has been reached.
+    #[inline]
+    pub fn is_elapsed(&self) -> bool {
+        T::now() >= self.at
+    }
+
+    /// Returns a deadline `duration` after this one.
+    ///
+    /// Unlike [`Deadline::after`], the current time is not taken into account,
+    /// so a chain of deadlines doesn't accumulate drift.
+    #[inline]
+    pub fn next(&self, duration: Duration) -> Self {
+        Self::at(self.at + T::ticks(duration))
+    }
+
+    fn elapse(&mut self) -> Poll<()> {
+        self.wakeup = None;
+        #[cfg(feature = "evtrace")]
+        crate::evtrace::tick_at(T::now());
+        Poll::Ready(())
+    }
+}
+
+impl<T: Tick> Future for Deadline<T> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.is_elapsed() {
+            return self.elapse();
+        }
+        if self.wakeup.is_none() {
+            self.wakeup = Some(Wakeup::register(self.at));
+        }
+        T::schedule_wakeup(self.at, cx.waker());
+        if self.is_elapsed() { self.elapse() } else { Poll::Pending }
+    }
+}
+
+impl<T: Tick, F: Future> Timeout<T, F> {
+    /// Returns the deadline of this timeout.
+    #[inline]
+    pub fn deadline(&self) -> &Deadline<T> {
+        &self.deadline
+    }
+
+    /// Consumes the timeout, returning the inner future.
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<T: Tick, F: Future> Future for Timeout<T, F> {
+    type Output = Result<F::Output, TimedOut>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Self { future, deadline } = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(deadline).poll(cx).map(|()| Err(TimedOut))
+    }
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
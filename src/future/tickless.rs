@@ -0,0 +1,127 @@
+use crate::critical;
+
+/// The number of wake-up deadlines tracked precisely.
+///
+/// Deadlines registered while the table is full are folded into a single
+/// earliest overflow tick, which is kept until all of them are dropped. This
+/// makes [`next_wakeup`] return a tick, which is never later than the actual
+/// earliest deadline, but possibly earlier.
+pub const WAKEUP_CAPACITY: usize = 16;
+
+static mut PENDING: Pending = Pending::new();
+
+struct Pending {
+    ticks: [u64; WAKEUP_CAPACITY],
+    len: usize,
+    overflow: usize,
+    overflow_min: u64,
+}
+
+/// A registered wake-up deadline.
+///
+/// Timer-driven utilities register their deadlines while they are waiting, so
+/// that [`next_wakeup`] knows when the processor needs to run next. The
+/// deadline is unregistered when the `Wakeup` is dropped. [`Deadline`], and
+/// therefore [`sleep`], [`timeout`], and [`Interval`], do this automatically.
+///
+/// [`Deadline`]: super::Deadline
+/// [`sleep`]: super::sleep
+/// [`timeout`]: super::timeout
+/// [`Interval`]: super::Interval
+#[must_use = "the deadline is unregistered when dropped"]
+pub struct Wakeup {
+    at: u64,
+    overflow: bool,
+}
+
+/// Returns the earliest registered wake-up tick, or `None` if no deadlines are
+/// pending.
+///
+/// This is intended for platform idle hooks: instead of keeping the periodic
+/// tick running, the idle hook can program a low-power timer to fire at the
+/// returned tick, or stop the timer entirely if there is nothing to wait for.
+///
+/// The ticks of all [`Tick`](super::Tick) sources are mixed together, so an
+/// application relying on this function should use a single tick source. See
+/// [`WAKEUP_CAPACITY`] for the number of deadlines tracked precisely.
+///
+/// The registry is guarded by [`critical`] sections, which need the platform
+/// hooks described in [its docs](crate::critical#platform-hooks).
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::future::{next_wakeup, Wakeup};
+///
+/// assert_eq!(next_wakeup(), None);
+/// let later = Wakeup::register(200);
+/// let sooner = Wakeup::register(100);
+/// assert_eq!(next_wakeup(), Some(100));
+/// drop(sooner);
+/// assert_eq!(next_wakeup(), Some(200));
+/// drop(later);
+/// assert_eq!(next_wakeup(), None);
+/// ```
+pub fn next_wakeup() -> Option<u64> {
+    critical::with(|_| unsafe { PENDING.next() })
+}
+
+impl Wakeup {
+    /// Registers a wake-up deadline at the tick `at`.
+    pub fn register(at: u64) -> Self {
+        let overflow = critical::with(|_| unsafe { !PENDING.push(at) });
+        Self { at, overflow }
+    }
+
+    /// Returns the tick of the deadline.
+    #[inline]
+    pub fn tick(&self) -> u64 {
+        self.at
+    }
+}
+
+impl Drop for Wakeup {
+    fn drop(&mut self) {
+        critical::with(|_| unsafe { PENDING.remove(self.at, self.overflow) });
+    }
+}
+
+impl Pending {
+    const fn new() -> Self {
+        Self { ticks: [0; WAKEUP_CAPACITY], len: 0, overflow: 0, overflow_min: u64::MAX }
+    }
+
+    fn next(&self) -> Option<u64> {
+        let next = self.ticks[..self.len].iter().copied().min();
+        if self.overflow == 0 {
+            next
+        } else {
+            Some(next.map_or(self.overflow_min, |next| next.min(self.overflow_min)))
+        }
+    }
+
+    /// Returns `false` if the deadline went to the overflow tick.
+    fn push(&mut self, at: u64) -> bool {
+        if self.len < WAKEUP_CAPACITY {
+            self.ticks[self.len] = at;
+            self.len += 1;
+            true
+        } else {
+            self.overflow += 1;
+            self.overflow_min = self.overflow_min.min(at);
+            false
+        }
+    }
+
+    fn remove(&mut self, at: u64, overflow: bool) {
+        if overflow {
+            self.overflow -= 1;
+            if self.overflow == 0 {
+                self.overflow_min = u64::MAX;
+            }
+        } else if let Some(index) = self.ticks[..self.len].iter().position(|&tick| tick == at) {
+            self.len -= 1;
+            self.ticks[index] = self.ticks[self.len];
+        }
+    }
+}
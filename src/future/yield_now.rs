@@ -0,0 +1,52 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Future for the [`yield_now`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+/// Yields execution back to the executor.
+///
+/// The returned future wakes its task and returns `Pending` on the first poll,
+/// and resolves on the second one. For a future attached to a Drone thread,
+/// this re-triggers the thread interrupt, so pending interrupts of higher or
+/// equal priority and other fibers of the same thread get a chance to run
+/// before the task continues.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::future;
+///
+/// async fn checksum(data: &[u8]) -> u32 {
+///     let mut sum = 0_u32;
+///     for chunk in data.chunks(256) {
+///         sum = chunk.iter().fold(sum, |sum, &x| sum.wrapping_add(u32::from(x)));
+///         future::yield_now().await;
+///     }
+///     sum
+/// }
+/// ```
+#[inline]
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
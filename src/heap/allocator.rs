@@ -1,4 +1,14 @@
-use super::pool::{Fits, Pool};
+#[cfg(feature = "heapguard")]
+use super::guard;
+#[cfg(feature = "heapstats")]
+use super::stats::{self, Stats};
+#[cfg(feature = "heaptag")]
+use super::tag::{self, TagTable, TagUsage};
+use super::{
+    oom::oom,
+    pool::{Fits, Pool},
+    region::{Region, RegionAttrs},
+};
 use core::{
     alloc::{AllocErr, AllocInit, Layout, MemoryBlock, ReallocPlacement},
     ptr,
@@ -16,6 +26,9 @@ pub trait Allocator: Sized {
     /// The total number of memory pools.
     const POOL_COUNT: usize;
 
+    /// The number of memory regions.
+    const REGION_COUNT: usize = 1;
+
     /// Returns a reference to a pool or subslice, without doing bounds
     /// checking.
     ///
@@ -25,12 +38,142 @@ pub trait Allocator: Sized {
     unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
     where
         I: SliceIndex<[Pool]>;
+
+    /// Returns the region at `index`, which must be less than
+    /// [`REGION_COUNT`](Allocator::REGION_COUNT).
+    ///
+    /// The default implementation returns a single region of all pools.
+    #[inline]
+    fn region(index: usize) -> Region {
+        let _ = index;
+        Region { first_pool: 0, pool_count: Self::POOL_COUNT, attrs: RegionAttrs::default() }
+    }
+
+    /// Returns the side table of allocation tags, if the heap has one.
+    ///
+    /// The default implementation returns `None`, so the tags are discarded.
+    #[cfg(feature = "heaptag")]
+    #[inline]
+    fn tags(&self) -> Option<&TagTable<[u8]>> {
+        None
+    }
+
+    /// Returns the index of the first entry of the pool at `pool_idx` in the
+    /// [`tags`](Allocator::tags) table.
+    ///
+    /// The default implementation sums up the capacities of the preceding
+    /// pools. [`heap`](crate::heap) macro generates a table of precomputed
+    /// offsets instead.
+    #[cfg(feature = "heaptag")]
+    #[inline]
+    fn tag_offset(&self, pool_idx: usize) -> usize {
+        (0..pool_idx).map(|index| unsafe { self.get_pool_unchecked(index) }.capacity()).sum()
+    }
+
+    /// Returns an iterator over the run-time counters of each pool, region by
+    /// region, in the order of increasing block size inside each region.
+    #[cfg(feature = "heapstats")]
+    #[inline]
+    fn stats(&self) -> Stats<'_, Self> {
+        Stats::new(self)
+    }
+}
+
+/// Extension methods for [`Allocator`] types.
+pub trait AllocatorExt: Allocator {
+    /// Allocates a block of memory for `layout` in the region at `region`.
+    ///
+    /// The block is deallocated in the usual way, the region is determined by
+    /// the block address.
+    ///
+    /// # Errors
+    ///
+    /// If the region is exhausted.
+    ///
+    /// # Panics
+    ///
+    /// If `region` is out of bounds.
+    #[inline]
+    fn alloc_in(&self, region: usize, layout: Layout) -> Result<MemoryBlock, AllocErr> {
+        assert!(region < Self::REGION_COUNT, "region index out of bounds");
+        alloc_in(self, region, layout, AllocInit::Uninitialized)
+    }
+
+    /// Allocates a block of memory for `layout`, and attributes it to `tag`.
+    ///
+    /// The tag is kept in the side table returned by
+    /// [`tags`](Allocator::tags), and is cleared on deallocation. The tag `0`
+    /// is reserved for untagged allocations.
+    ///
+    /// # Errors
+    ///
+    /// If the heap is exhausted.
+    #[cfg(feature = "heaptag")]
+    fn alloc_tagged(&self, layout: Layout, tag: u8) -> Result<MemoryBlock, AllocErr> {
+        let memory = alloc(self, layout, AllocInit::Uninitialized)?;
+        if layout.size() != 0 {
+            tag::set(self, memory.ptr, tag);
+        }
+        Ok(memory)
+    }
+
+    /// Returns the number and the total size of the live allocations with
+    /// `tag`.
+    ///
+    /// This method walks the whole side table, and the result is a snapshot,
+    /// which may be inconsistent under concurrent allocations.
+    #[cfg(feature = "heaptag")]
+    fn tag_usage(&self, tag: u8) -> TagUsage {
+        tag::usage(self, tag)
+    }
+
+    /// Reports the usage of each tag present in the side table over the heap
+    /// trace port.
+    ///
+    /// For each tag, three words are written: `0xE1` with the tag and the
+    /// upper half of the number of blocks, `0xE2` with the lower half of the
+    /// number of blocks and the upper 8 bits of the total size, and `0xE3`
+    /// with the lower 24 bits of the total size, all XOR-ed with
+    /// [`HEAPTRACE_KEY`](super::HEAPTRACE_KEY).
+    #[cfg(feature = "heaptag")]
+    fn trace_tags(&self) {
+        tag::trace(self);
+    }
+
+    /// Reports the counters returned by [`stats`](Allocator::stats) over the
+    /// heap trace port.
+    ///
+    /// For each pool, eight words are written: `0xF0` with the pool index,
+    /// followed by `0xF1` to `0xF7` with the block size, the capacity, the
+    /// current and the peak numbers of blocks, and the numbers of
+    /// allocations, deallocations, and failed allocations. Each value is
+    /// truncated to the lower 24 bits, and each word is XOR-ed with
+    /// [`HEAPTRACE_KEY`](super::HEAPTRACE_KEY).
+    #[cfg(feature = "heapstats")]
+    fn trace_stats(&self) {
+        stats::trace(self);
+    }
+
+    /// Returns the index of the first region, which has all of the `attrs`
+    /// set, or `None` if there is no such region.
+    fn find_region(&self, attrs: RegionAttrs) -> Option<usize> {
+        (0..Self::REGION_COUNT).find(|&index| Self::region(index).attrs.contains(attrs))
+    }
 }
 
-/// Does a binary search for the pool with the smallest block size to fit
-/// `value`.
+impl<A: Allocator> AllocatorExt for A {}
+
+/// Does a binary search for the pool of the main region with the smallest
+/// block size to fit `value`.
+///
+/// The pools are sorted by block size only inside each region, so the other
+/// regions are not searched.
 pub fn binary_search<A: Allocator, T: Fits>(heap: &A, value: T) -> usize {
-    let (mut left, mut right) = (0, A::POOL_COUNT);
+    let Region { first_pool, pool_count, .. } = A::region(0);
+    search(heap, first_pool, first_pool + pool_count, value)
+}
+
+fn search<A: Allocator, T: Fits>(heap: &A, mut left: usize, mut right: usize, value: T) -> usize {
     while right > left {
         let middle = left + ((right - left) >> 1);
         let pool = unsafe { heap.get_pool_unchecked(middle) };
@@ -43,25 +186,81 @@ pub fn binary_search<A: Allocator, T: Fits>(heap: &A, value: T) -> usize {
     left
 }
 
+/// Returns the index of the region containing `ptr`.
+fn region_of<A: Allocator>(heap: &A, ptr: NonNull<u8>) -> usize {
+    if A::REGION_COUNT == 1 {
+        return 0;
+    }
+    (0..A::REGION_COUNT)
+        .find(|&index| {
+            let Region { first_pool, pool_count, .. } = A::region(index);
+            if pool_count == 0 {
+                return false;
+            }
+            let (first, last) = unsafe {
+                (
+                    heap.get_pool_unchecked(first_pool),
+                    heap.get_pool_unchecked(first_pool + pool_count - 1),
+                )
+            };
+            ptr.as_ptr() >= first.start() && ptr.as_ptr() < last.edge()
+        })
+        .unwrap_or(0)
+}
+
+/// Returns the size of a block of `pool` allocated for `size` bytes, which is
+/// reported to the caller. With `heapguard`, the rest of the block holds the
+/// canary.
+#[inline]
+fn usable_size(pool: &Pool, size: usize) -> usize {
+    if cfg!(feature = "heapguard") { size } else { pool.size() }
+}
+
+/// Returns the index of the pool containing `ptr`.
+pub(super) fn pool_of<A: Allocator>(heap: &A, ptr: NonNull<u8>) -> usize {
+    let Region { first_pool, pool_count, .. } = A::region(region_of(heap, ptr));
+    search(heap, first_pool, first_pool + pool_count, ptr)
+}
+
 #[doc(hidden)]
 pub fn alloc<A: Allocator>(
     heap: &A,
     layout: Layout,
     init: AllocInit,
+) -> Result<MemoryBlock, AllocErr> {
+    alloc_in(heap, 0, layout, init)
+}
+
+#[doc(hidden)]
+pub fn alloc_in<A: Allocator>(
+    heap: &A,
+    region: usize,
+    layout: Layout,
+    init: AllocInit,
 ) -> Result<MemoryBlock, AllocErr> {
     #[cfg(feature = "heaptrace")]
     trace::alloc(layout);
     if layout.size() == 0 {
         return Ok(MemoryBlock { ptr: layout.dangling(), size: 0 });
     }
-    for pool_idx in binary_search(heap, &layout)..A::POOL_COUNT {
+    let Region { first_pool, pool_count, .. } = A::region(region);
+    let first_idx = search(heap, first_pool, first_pool + pool_count, &layout);
+    for pool_idx in first_idx..first_pool + pool_count {
         let pool = unsafe { heap.get_pool_unchecked(pool_idx) };
+        if !pool.is_aligned(layout.align()) {
+            continue;
+        }
         if let Some(ptr) = pool.alloc() {
-            let memory = MemoryBlock { ptr, size: pool.size() };
-            unsafe { init.init(memory) };
+            let memory = MemoryBlock { ptr, size: usable_size(pool, layout.size()) };
+            unsafe {
+                #[cfg(feature = "heapguard")]
+                guard::set_canary(ptr.as_ptr(), layout.size(), pool.size());
+                init.init(memory);
+            }
             return Ok(memory);
         }
     }
+    oom(layout, first_idx);
     Err(AllocErr)
 }
 
@@ -72,7 +271,11 @@ pub unsafe fn dealloc<A: Allocator>(heap: &A, ptr: NonNull<u8>, layout: Layout)
     if layout.size() == 0 {
         return;
     }
-    let pool = heap.get_pool_unchecked(binary_search(heap, ptr));
+    let pool = heap.get_pool_unchecked(pool_of(heap, ptr));
+    #[cfg(feature = "heapguard")]
+    guard::check_canary(ptr.as_ptr(), layout.size(), pool.size());
+    #[cfg(feature = "heaptag")]
+    tag::set(heap, ptr, 0);
     pool.dealloc(ptr);
 }
 
@@ -87,15 +290,32 @@ pub unsafe fn grow<A: Allocator>(
 ) -> Result<MemoryBlock, AllocErr> {
     #[cfg(feature = "heaptrace")]
     trace::grow(layout, new_size);
+    let size = layout.size();
+    if size != 0 {
+        let pool = heap.get_pool_unchecked(pool_of(heap, ptr));
+        if new_size <= pool.size() {
+            #[cfg(feature = "heapguard")]
+            guard::check_canary(ptr.as_ptr(), size, pool.size());
+            let memory = MemoryBlock { ptr, size: usable_size(pool, new_size) };
+            init.init_offset(memory, size);
+            #[cfg(feature = "heapguard")]
+            guard::set_canary(ptr.as_ptr(), new_size, pool.size());
+            return Ok(memory);
+        }
+    }
     match placement {
         ReallocPlacement::InPlace => Err(AllocErr),
         ReallocPlacement::MayMove => {
-            let size = layout.size();
             if new_size == size {
                 return Ok(MemoryBlock { ptr, size });
             }
             let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
-            let new_memory = alloc(heap, new_layout, init)?;
+            let region = if size == 0 { 0 } else { region_of(heap, ptr) };
+            let new_memory = alloc_in(heap, region, new_layout, init)?;
+            #[cfg(feature = "heaptag")]
+            if size != 0 {
+                tag::set(heap, new_memory.ptr, tag::get(heap, ptr));
+            }
             ptr::copy_nonoverlapping(ptr.as_ptr(), new_memory.ptr.as_ptr(), size);
             dealloc(heap, ptr, layout);
             Ok(new_memory)
@@ -114,14 +334,30 @@ pub unsafe fn shrink<A: Allocator>(
     #[cfg(feature = "heaptrace")]
     trace::shrink(layout, new_size);
     match placement {
-        ReallocPlacement::InPlace => Err(AllocErr),
+        ReallocPlacement::InPlace => {
+            if new_size == 0 {
+                return Err(AllocErr);
+            }
+            let pool = heap.get_pool_unchecked(pool_of(heap, ptr));
+            #[cfg(feature = "heapguard")]
+            {
+                guard::check_canary(ptr.as_ptr(), layout.size(), pool.size());
+                guard::set_canary(ptr.as_ptr(), new_size, pool.size());
+            }
+            Ok(MemoryBlock { ptr, size: usable_size(pool, new_size) })
+        }
         ReallocPlacement::MayMove => {
             let size = layout.size();
             if new_size == size {
                 return Ok(MemoryBlock { ptr, size });
             }
             let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
-            let new_memory = alloc(heap, new_layout, AllocInit::Uninitialized)?;
+            let region = region_of(heap, ptr);
+            let new_memory = alloc_in(heap, region, new_layout, AllocInit::Uninitialized)?;
+            #[cfg(feature = "heaptag")]
+            if new_size != 0 {
+                tag::set(heap, new_memory.ptr, tag::get(heap, ptr));
+            }
             ptr::copy_nonoverlapping(ptr.as_ptr(), new_memory.ptr.as_ptr(), new_size);
             dealloc(heap, ptr, layout);
             Ok(new_memory)
@@ -201,6 +437,10 @@ mod trace {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::heap::AllocatorExt;
+    #[cfg(feature = "heapstats")]
+    use crate::heap::PoolStats;
+    use core::sync::atomic::{AtomicUsize, Ordering};
 
     struct TestHeap {
         pools: [Pool; 10],
@@ -217,6 +457,44 @@ mod tests {
         }
     }
 
+    struct RegionHeap {
+        pools: [Pool; 4],
+    }
+
+    /// Returns the size reported for a block of `block_size` bytes allocated
+    /// for `size` bytes.
+    fn reported_size(size: usize, block_size: usize) -> usize {
+        if cfg!(feature = "heapguard") { size } else { block_size }
+    }
+
+    impl Allocator for RegionHeap {
+        const POOL_COUNT: usize = 4;
+        const REGION_COUNT: usize = 2;
+
+        unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
+        where
+            I: SliceIndex<[Pool]>,
+        {
+            self.pools.get_unchecked(index)
+        }
+
+        fn region(index: usize) -> Region {
+            const REGIONS: [Region; 2] = [
+                Region {
+                    first_pool: 0,
+                    pool_count: 2,
+                    attrs: RegionAttrs { dma: false, zero_wait: false },
+                },
+                Region {
+                    first_pool: 2,
+                    pool_count: 2,
+                    attrs: RegionAttrs { dma: true, zero_wait: false },
+                },
+            ];
+            REGIONS[index]
+        }
+    }
+
     #[test]
     fn test_binary_search() {
         fn search_layout(heap: &TestHeap, size: usize) -> Option<usize> {
@@ -277,39 +555,209 @@ mod tests {
                 value;
         }
         let mut m = [0u8; 3230];
-        let o = &mut m as *mut _ as usize;
+        let o = m.as_mut_ptr();
         let heap = TestHeap {
             pools: [
-                Pool::new(o + 0, 2, 10),
-                Pool::new(o + 20, 5, 10),
-                Pool::new(o + 70, 8, 10),
-                Pool::new(o + 150, 12, 10),
-                Pool::new(o + 270, 16, 10),
-                Pool::new(o + 430, 23, 10),
-                Pool::new(o + 660, 38, 10),
-                Pool::new(o + 1040, 56, 10),
-                Pool::new(o + 1600, 72, 10),
-                Pool::new(o + 2320, 91, 10),
+                Pool::from_ptr(o, 2, 10),
+                Pool::from_ptr(o.wrapping_add(20), 5, 10),
+                Pool::from_ptr(o.wrapping_add(70), 8, 10),
+                Pool::from_ptr(o.wrapping_add(150), 12, 10),
+                Pool::from_ptr(o.wrapping_add(270), 16, 10),
+                Pool::from_ptr(o.wrapping_add(430), 23, 10),
+                Pool::from_ptr(o.wrapping_add(660), 38, 10),
+                Pool::from_ptr(o.wrapping_add(1040), 56, 10),
+                Pool::from_ptr(o.wrapping_add(1600), 72, 10),
+                Pool::from_ptr(o.wrapping_add(2320), 91, 10),
             ],
         };
+        let link = |offset| unsafe { ptr::read_unaligned(o.add(offset) as *const *mut u8) };
         let layout = Layout::from_size_align(32, 1).unwrap();
         unsafe {
             alloc_and_set(&heap, layout, 111);
-            assert_eq!(m[660], 111);
+            assert_eq!(*o.add(660), 111);
             alloc_and_set(&heap, layout, 222);
-            assert_eq!(m[698], 222);
+            assert_eq!(*o.add(698), 222);
             alloc_and_set(&heap, layout, 123);
-            assert_eq!(m[736], 123);
-            dealloc(&heap, NonNull::new_unchecked((o + 660) as *mut u8), layout);
-            assert_eq!(m[660], 0);
-            dealloc(&heap, NonNull::new_unchecked((o + 736) as *mut u8), layout);
-            assert_eq!(*(&m[736] as *const _ as *const usize), o + 660);
+            assert_eq!(*o.add(736), 123);
+            dealloc(&heap, NonNull::new_unchecked(o.add(660)), layout);
+            assert_eq!(*o.add(660), 0);
+            dealloc(&heap, NonNull::new_unchecked(o.add(736)), layout);
+            assert_eq!(link(736), o.add(660));
             alloc_and_set(&heap, layout, 202);
-            assert_eq!(m[736], 202);
-            dealloc(&heap, NonNull::new_unchecked((o + 698) as *mut u8), layout);
-            assert_eq!(*(&m[698] as *const _ as *const usize), o + 660);
-            dealloc(&heap, NonNull::new_unchecked((o + 736) as *mut u8), layout);
-            assert_eq!(*(&m[736] as *const _ as *const usize), o + 698);
+            assert_eq!(*o.add(736), 202);
+            dealloc(&heap, NonNull::new_unchecked(o.add(698)), layout);
+            assert_eq!(link(698), o.add(660));
+            dealloc(&heap, NonNull::new_unchecked(o.add(736)), layout);
+            assert_eq!(link(736), o.add(698));
+        }
+    }
+
+    #[test]
+    fn realloc_in_place() {
+        let mut m = [0u8; 3230];
+        let o = m.as_mut_ptr();
+        let heap = TestHeap {
+            pools: [
+                Pool::from_ptr(o, 2, 10),
+                Pool::from_ptr(o.wrapping_add(20), 5, 10),
+                Pool::from_ptr(o.wrapping_add(70), 8, 10),
+                Pool::from_ptr(o.wrapping_add(150), 12, 10),
+                Pool::from_ptr(o.wrapping_add(270), 16, 10),
+                Pool::from_ptr(o.wrapping_add(430), 23, 10),
+                Pool::from_ptr(o.wrapping_add(660), 38, 10),
+                Pool::from_ptr(o.wrapping_add(1040), 56, 10),
+                Pool::from_ptr(o.wrapping_add(1600), 72, 10),
+                Pool::from_ptr(o.wrapping_add(2320), 91, 10),
+            ],
+        };
+        let layout = Layout::from_size_align(17, 1).unwrap();
+        unsafe {
+            let memory = alloc(&heap, layout, AllocInit::Uninitialized).unwrap();
+            let ptr = memory.ptr;
+            assert_eq!(ptr.as_ptr(), o.add(430));
+            *o.add(452) = 0xFF;
+            let memory =
+                grow(&heap, ptr, layout, 20, ReallocPlacement::InPlace, AllocInit::Zeroed).unwrap();
+            assert_eq!((memory.ptr, memory.size), (ptr, reported_size(20, 23)));
+            // With `heapguard`, only the requested bytes are zeroed.
+            assert_eq!(*o.add(452), if cfg!(feature = "heapguard") { 0xFF } else { 0 });
+            let layout = Layout::from_size_align(20, 1).unwrap();
+            assert!(
+                grow(&heap, ptr, layout, 24, ReallocPlacement::InPlace, AllocInit::Zeroed).is_err()
+            );
+            let memory = shrink(&heap, ptr, layout, 1, ReallocPlacement::InPlace).unwrap();
+            assert_eq!((memory.ptr, memory.size), (ptr, reported_size(1, 23)));
+            assert!(shrink(&heap, ptr, layout, 0, ReallocPlacement::InPlace).is_err());
+            let memory =
+                grow(&heap, ptr, layout, 24, ReallocPlacement::MayMove, AllocInit::Uninitialized)
+                    .unwrap();
+            assert_eq!((memory.ptr.as_ptr(), memory.size), (o.add(660), reported_size(24, 38)));
         }
     }
+
+    #[test]
+    fn oom_hook() {
+        static POOL_IDX: AtomicUsize = AtomicUsize::new(0);
+        fn hook(layout: Layout, pool_idx: usize) {
+            if layout.size() == 100 {
+                POOL_IDX.store(pool_idx, Ordering::Relaxed);
+            }
+        }
+        let heap = TestHeap {
+            pools: [
+                Pool::new(20, 2, 0),
+                Pool::new(20, 5, 0),
+                Pool::new(20, 8, 0),
+                Pool::new(20, 12, 0),
+                Pool::new(20, 16, 0),
+                Pool::new(20, 23, 0),
+                Pool::new(20, 38, 0),
+                Pool::new(20, 56, 0),
+                Pool::new(20, 72, 0),
+                Pool::new(20, 91, 0),
+            ],
+        };
+        crate::heap::set_oom_hook(hook);
+        let layout = Layout::from_size_align(100, 1).unwrap();
+        assert!(alloc(&heap, layout, AllocInit::Uninitialized).is_err());
+        assert_eq!(POOL_IDX.load(Ordering::Relaxed), TestHeap::POOL_COUNT);
+        assert!(crate::heap::take_oom_hook().is_some());
+        assert!(crate::heap::take_oom_hook().is_none());
+    }
+
+    #[cfg(feature = "heapstats")]
+    #[test]
+    fn stats() {
+        let mut m = [0u8; 40];
+        let o = m.as_mut_ptr();
+        let heap = TestHeap {
+            pools: [
+                Pool::from_ptr(o, 2, 2),
+                Pool::from_ptr(o.wrapping_add(4), 4, 9),
+                Pool::from_ptr(o.wrapping_add(40), 8, 0),
+                Pool::from_ptr(o.wrapping_add(40), 12, 0),
+                Pool::from_ptr(o.wrapping_add(40), 16, 0),
+                Pool::from_ptr(o.wrapping_add(40), 23, 0),
+                Pool::from_ptr(o.wrapping_add(40), 38, 0),
+                Pool::from_ptr(o.wrapping_add(40), 56, 0),
+                Pool::from_ptr(o.wrapping_add(40), 72, 0),
+                Pool::from_ptr(o.wrapping_add(40), 91, 0),
+            ],
+        };
+        let layout = Layout::from_size_align(2, 1).unwrap();
+        unsafe {
+            let a = alloc(&heap, layout, AllocInit::Uninitialized).unwrap().ptr;
+            let b = alloc(&heap, layout, AllocInit::Uninitialized).unwrap().ptr;
+            let c = alloc(&heap, layout, AllocInit::Uninitialized).unwrap().ptr;
+            assert_eq!(c.as_ptr(), o.add(4));
+            dealloc(&heap, a, layout);
+            dealloc(&heap, b, layout);
+        }
+        let stats = heap.stats().collect::<Vec<_>>();
+        assert_eq!(stats.len(), 10);
+        assert_eq!(stats[0], PoolStats {
+            block_size: 2,
+            capacity: 2,
+            current: 0,
+            peak: 2,
+            allocs: 2,
+            frees: 2,
+            failed: 1,
+        });
+        assert_eq!((stats[1].current, stats[1].allocs, stats[1].failed), (1, 1, 0));
+    }
+
+    #[test]
+    fn regions() {
+        let mut m = [0u8; 64];
+        let o = m.as_mut_ptr();
+        let heap = RegionHeap {
+            pools: [
+                Pool::from_ptr(o.wrapping_add(32), 4, 2),
+                Pool::from_ptr(o.wrapping_add(40), 8, 2),
+                Pool::from_ptr(o, 4, 2),
+                Pool::from_ptr(o.wrapping_add(8), 8, 2),
+            ],
+        };
+        assert_eq!(heap.find_region(RegionAttrs { dma: true, zero_wait: false }), Some(1));
+        assert_eq!(heap.find_region(RegionAttrs { dma: false, zero_wait: true }), None);
+        assert_eq!(binary_search(&heap, &Layout::from_size_align(16, 1).unwrap()), 2);
+        let layout = Layout::from_size_align(4, 1).unwrap();
+        unsafe {
+            let a = alloc(&heap, layout, AllocInit::Uninitialized).unwrap().ptr;
+            assert_eq!(a.as_ptr(), o.add(32));
+            let b = heap.alloc_in(1, layout).unwrap().ptr;
+            assert_eq!(b.as_ptr(), o);
+            dealloc(&heap, b, layout);
+            assert_eq!(heap.alloc_in(1, layout).unwrap().ptr, b);
+            let memory =
+                grow(&heap, b, layout, 6, ReallocPlacement::MayMove, AllocInit::Uninitialized)
+                    .unwrap();
+            assert_eq!((memory.ptr.as_ptr(), memory.size), (o.add(8), reported_size(6, 8)));
+            assert_eq!(heap.alloc_in(1, layout).unwrap().ptr, b);
+        }
+    }
+
+    #[test]
+    fn alignment() {
+        #[repr(align(8))]
+        struct Memory([u8; 56]);
+        let mut m = Memory([0; 56]);
+        let o = m.0.as_mut_ptr();
+        let heap = RegionHeap {
+            pools: [
+                Pool::from_ptr(o, 4, 2),
+                Pool::from_ptr(o.wrapping_add(8), 8, 2),
+                Pool::from_ptr(o.wrapping_add(24), 5, 2),
+                Pool::from_ptr(o.wrapping_add(40), 8, 2),
+            ],
+        };
+        let layout = |align| Layout::from_size_align(4, align).unwrap();
+        let alloc = |layout| alloc(&heap, layout, AllocInit::Uninitialized).unwrap().ptr.as_ptr();
+        let alloc_in = |layout| heap.alloc_in(1, layout).unwrap().ptr.as_ptr();
+        assert_eq!(alloc(layout(4)), o);
+        assert_eq!(alloc(layout(8)), o.wrapping_add(8));
+        assert_eq!(alloc_in(layout(2)), o.wrapping_add(40));
+        assert_eq!(alloc_in(layout(1)), o.wrapping_add(24));
+    }
 }
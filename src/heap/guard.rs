@@ -0,0 +1,158 @@
+use super::{HEAPGUARD_CANARY, HEAPGUARD_POISON};
+use crate::atomic::{AtomicPtr, Ordering};
+use core::{fmt, mem, ptr};
+
+/// A heap corruption hook.
+pub type GuardHook = fn(Corruption);
+
+/// A heap corruption detected by the `heapguard` feature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Corruption {
+    /// A free block was written after deallocation. Holds the address of the
+    /// first corrupted byte.
+    UseAfterFree(usize),
+    /// The canary word past an allocated block was overwritten. Holds the
+    /// address of the canary.
+    Overflow(usize),
+}
+
+static HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+const LINK_SIZE: usize = mem::size_of::<*mut u8>();
+const CANARY_SIZE: usize = mem::size_of::<u32>();
+
+/// Registers `hook` to be called when a heap corruption is detected. Replaces
+/// the previously registered hook.
+///
+/// The hook runs in the context of the allocation or deallocation, which
+/// detected the corruption. If the hook returns, the operation proceeds as
+/// usual. Without a hook, the corruption causes a panic.
+#[inline]
+pub fn set_guard_hook(hook: GuardHook) {
+    HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+/// Unregisters the heap corruption hook, and returns it.
+#[inline]
+pub fn take_guard_hook() -> Option<GuardHook> {
+    let hook = HOOK.swap(ptr::null_mut(), Ordering::AcqRel);
+    if hook.is_null() { None } else { Some(unsafe { mem::transmute::<*mut (), GuardHook>(hook) }) }
+}
+
+/// Fills a freed block with the poison pattern, skipping the free list link.
+pub(super) unsafe fn poison(block: *mut u8, size: usize) {
+    if size > LINK_SIZE {
+        ptr::write_bytes(block.add(LINK_SIZE), HEAPGUARD_POISON, size - LINK_SIZE);
+    }
+}
+
+/// Checks that a block taken from the free list still holds the poison
+/// pattern.
+pub(super) unsafe fn check_poison(block: *mut u8, size: usize) {
+    for offset in LINK_SIZE..size {
+        let byte = block.add(offset);
+        if *byte != HEAPGUARD_POISON {
+            corruption(Corruption::UseAfterFree(byte as usize));
+            break;
+        }
+    }
+}
+
+/// Writes the canary word past `size` bytes of the block, if the block has
+/// enough room for it.
+pub(super) unsafe fn set_canary(block: *mut u8, size: usize, block_size: usize) {
+    if size + CANARY_SIZE <= block_size {
+        ptr::write_unaligned(block.add(size) as *mut u32, HEAPGUARD_CANARY);
+    }
+}
+
+/// Checks the canary word written by [`set_canary`].
+pub(super) unsafe fn check_canary(block: *mut u8, size: usize, block_size: usize) {
+    if size + CANARY_SIZE <= block_size {
+        let canary = block.add(size);
+        if ptr::read_unaligned(canary as *const u32) != HEAPGUARD_CANARY {
+            corruption(Corruption::Overflow(canary as usize));
+        }
+    }
+}
+
+fn corruption(corruption: Corruption) {
+    let hook = HOOK.load(Ordering::Acquire);
+    if hook.is_null() {
+        panic!("heap corruption: {}", corruption);
+    }
+    let hook = unsafe { mem::transmute::<*mut (), GuardHook>(hook) };
+    hook(corruption);
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UseAfterFree(address) => write!(f, "free block modified at {:#x}", address),
+            Self::Overflow(address) => write!(f, "canary overwritten at {:#x}", address),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heap::{alloc, dealloc, Allocator, Pool};
+    use core::{
+        alloc::{AllocInit, Layout},
+        ptr::NonNull,
+        slice::SliceIndex,
+        sync::atomic::AtomicUsize,
+    };
+
+    struct TestHeap {
+        pools: [Pool; 2],
+    }
+
+    impl Allocator for TestHeap {
+        const POOL_COUNT: usize = 2;
+
+        unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
+        where
+            I: SliceIndex<[Pool]>,
+        {
+            self.pools.get_unchecked(index)
+        }
+    }
+
+    #[test]
+    fn corruption() {
+        static USE_AFTER_FREE: AtomicUsize = AtomicUsize::new(0);
+        static OVERFLOW: AtomicUsize = AtomicUsize::new(0);
+        fn hook(corruption: Corruption) {
+            match corruption {
+                Corruption::UseAfterFree(address) => {
+                    USE_AFTER_FREE.store(address, Ordering::Relaxed)
+                }
+                Corruption::Overflow(address) => OVERFLOW.store(address, Ordering::Relaxed),
+            }
+        }
+        let mut m = [0u8; 96];
+        let o = m.as_mut_ptr();
+        let heap = TestHeap {
+            pools: [Pool::from_ptr(o, 16, 2), Pool::from_ptr(o.wrapping_add(32), 32, 2)],
+        };
+        set_guard_hook(hook);
+        let layout = Layout::from_size_align(10, 1).unwrap();
+        unsafe {
+            let memory = alloc(&heap, layout, AllocInit::Uninitialized).unwrap();
+            assert_eq!((memory.ptr.as_ptr(), memory.size), (o, 10));
+            assert_eq!(ptr::read_unaligned(o.add(10) as *const u32), HEAPGUARD_CANARY);
+            dealloc(&heap, memory.ptr, layout);
+            assert!((8..16).all(|offset| *o.add(offset) == HEAPGUARD_POISON));
+            *o.add(12) = 0;
+            let memory = alloc(&heap, layout, AllocInit::Uninitialized).unwrap();
+            assert_eq!(memory.ptr.as_ptr(), o);
+            *o.add(10) = 0;
+            dealloc(&heap, NonNull::new_unchecked(o), layout);
+        }
+        assert!(take_guard_hook().is_some());
+        assert_eq!(USE_AFTER_FREE.load(Ordering::Relaxed), o as usize + 12);
+        assert_eq!(OVERFLOW.load(Ordering::Relaxed), o as usize + 10);
+    }
+}
@@ -27,6 +27,14 @@
 //!
 //! The `size` field should match the resulting size of the pools.
 //!
+//! An allocation is served only by a pool, which blocks are aligned for the
+//! requested layout, i.e. the pool start address and the block size are both
+//! multiples of the alignment. Other pools are skipped, so block sizes should
+//! be multiples of the alignment of the types allocated from them. To catch
+//! misaligned layouts early, `heap!` rejects a block size, which is not a
+//! multiple of the largest power of two not exceeding it, up to 4 bytes (e.g.
+//! `6` or `3`), and a pool, which start address is not aligned the same way.
+//!
 //! Then in the application code:
 //!
 //! ```no_run
@@ -69,14 +77,271 @@
 //!
 //! The actual steps are platform-specific. Refer to the platform crate
 //! documentation for instructions.
+//!
+//! Instead of copying the generated layout into the `Drone.toml`, it can be
+//! kept in a separate TOML or JSON file with the same `[heap]` section. The
+//! path is relative to the crate root:
+//!
+//! ```text
+//! heap! {
+//!     /// The heap structure.
+//!     pub struct Heap;
+//!     layout = "heap.toml";
+//! }
+//! ```
+//!
+//! The memory region is still taken from the `Drone.toml`. Files with `.json`
+//! extension are parsed as JSON, and all other files as TOML. The crate is
+//! rebuilt when the file changes. The pools must take exactly `size` bytes,
+//! otherwise the macro reports an error.
+//!
+//! # Memory Regions
+//!
+//! Besides the main region in RAM, the heap can span additional disjoint
+//! memory regions, like CCM or a DMA-capable SRAM bank, each with its own set
+//! of pools. Each additional region is declared with its origin address, a
+//! layout file, and optional attributes:
+//!
+//! ```text
+//! heap! {
+//!     /// The heap structure.
+//!     pub struct Heap;
+//!     // Attributes of the main region.
+//!     attrs = dma;
+//!
+//!     /// Core-coupled memory.
+//!     region CCM {
+//!         origin = 0x1000_0000;
+//!         layout = "heap_ccm.toml";
+//!         attrs = zero_wait;
+//!     }
+//! }
+//! ```
+//!
+//! The regions must not overlap each other or the main region, otherwise the
+//! macro reports an error.
+//!
+//! Regular allocations are served by the main region, which has the index 0.
+//! The macro generates an associated constant with the region index for each
+//! additional region, e.g. `Heap::CCM`. Drivers allocate in a specific region
+//! with [`AllocatorExt::alloc_in`], and can look up a region with the required
+//! [`RegionAttrs`], e.g. DMA-capable memory, with
+//! [`AllocatorExt::find_region`]. Blocks from any region are deallocated in
+//! the usual way.
+//!
+//! # Out of Memory
+//!
+//! When no pool can satisfy an allocation, the hook registered with
+//! [`set_oom_hook`] is called with the failing layout and the index of the
+//! first matching pool, before the error is returned. Products can use it to
+//! log diagnostics or to trigger a graceful reset, instead of handling an
+//! opaque allocation failure.
+//!
+//! # Corruption Detection
+//!
+//! With the `heapguard` feature, freed blocks are filled with the
+//! [`HEAPGUARD_POISON`] pattern, which is validated when the block is taken
+//! from the free list again, catching writes through dangling pointers. Each
+//! allocated block also gets the [`HEAPGUARD_CANARY`] word right past the
+//! requested size, if the block has room for it, which is validated on
+//! deallocation, catching buffer overflows. To keep the canary intact,
+//! allocations report exactly the requested size instead of the block size.
+//!
+//! A detected corruption is passed to the hook registered with
+//! [`set_guard_hook`], or causes a panic if there is no hook. The checks take
+//! time proportional to the block size, so the feature is intended for
+//! debugging memory bugs on-device.
+//!
+//! # Statistics
+//!
+//! With the `heapstats` feature, each pool maintains run-time counters: the
+//! number of currently allocated blocks, the high-water mark, the total
+//! numbers of allocations and deallocations, and the number of allocations
+//! failed because the pool was exhausted. The counters are returned by
+//! [`Allocator::stats`] as [`PoolStats`], one per pool, so the firmware can
+//! report fragmentation and sizing problems in the field:
+//!
+//! ```text
+//! for (index, stats) in HEAP.stats().enumerate() {
+//!     println!("pool {}: {} / {} blocks, peak {}", index, stats.current,
+//!              stats.capacity, stats.peak);
+//! }
+//! ```
+//!
+//! [`AllocatorExt::trace_stats`] reports the same counters over the heap trace
+//! port. It can be triggered from the host with the
+//! [`CMD_HEAP_STATS`](crate::log::control::CMD_HEAP_STATS) command, after the
+//! heap is registered with
+//! [`control::register_heap`](crate::log::control::register_heap).
+//!
+//! # Allocation Tags
+//!
+//! With the `heaptag` feature, the [`heap`](crate::heap) macro generates a side
+//! table with one tag byte per block, and allocations can be attributed to
+//! subsystems with [`AllocatorExt::alloc_tagged`]. The tag follows the
+//! allocation when it is moved by reallocation, and is cleared on
+//! deallocation. The tag `0` marks untagged allocations and free blocks:
+//!
+//! ```text
+//! const TAG_NET: u8 = 1;
+//!
+//! let memory = HEAP.alloc_tagged(layout, TAG_NET)?;
+//! let usage = HEAP.tag_usage(TAG_NET);
+//! println!("net: {} blocks, {} bytes", usage.blocks, usage.bytes);
+//! ```
+//!
+//! [`AllocatorExt::trace_tags`] reports the usage of each tag over the heap
+//! trace port, so the host tooling can attribute the heap usage without
+//! string formatting on the device.
+
+#[cfg(feature = "std")]
+pub mod model;
 
 mod allocator;
+#[cfg(feature = "heapguard")]
+mod guard;
+mod oom;
 mod pool;
+mod region;
+#[cfg(feature = "heapstats")]
+mod stats;
+#[cfg(feature = "heaptag")]
+mod tag;
 
 pub use self::{
-    allocator::{alloc, binary_search, dealloc, grow, shrink, Allocator},
-    pool::Pool,
+    allocator::{alloc, alloc_in, binary_search, dealloc, grow, shrink, Allocator, AllocatorExt},
+    oom::{set_oom_hook, take_oom_hook, OomHook},
+    pool::{Pool, PoolError},
+    region::{Region, RegionAttrs},
 };
 
+#[cfg(feature = "heapguard")]
+pub use self::guard::{set_guard_hook, take_guard_hook, Corruption, GuardHook};
+#[cfg(feature = "heapstats")]
+pub use self::stats::{PoolStats, Stats};
+#[cfg(feature = "heaptag")]
+pub use self::tag::{TagTable, TagUsage};
+
 /// XOR pattern for heap trace output.
 pub const HEAPTRACE_KEY: u32 = 0xC5AC_CE55;
+
+/// Fill pattern for freed blocks in `heapguard` mode.
+pub const HEAPGUARD_POISON: u8 = 0xA5;
+
+/// Canary word past allocated blocks in `heapguard` mode.
+pub const HEAPGUARD_CANARY: u32 = 0xBAAD_F00D;
+
+mod compile_tests {
+    //! ```compile_fail
+    //! # #![feature(allocator_api)]
+    //! # drone_core::config_override! { "
+    //! # [memory]
+    //! # flash = { size = \"128K\", origin = 0x08000000 }
+    //! # ram = { size = \"20K\", origin = 0x20000000 }
+    //! # [heap]
+    //! # size = \"10K\"
+    //! # pools = [{ block = \"4\", capacity = 2560 }]
+    //! # " }
+    //! drone_core::heap! {
+    //!     pub struct Heap;
+    //!     layout = "tests/heap_layout_mismatch.toml";
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! # #![feature(allocator_api)]
+    //! # drone_core::config_override! { "
+    //! # [memory]
+    //! # flash = { size = \"128K\", origin = 0x08000000 }
+    //! # ram = { size = \"20K\", origin = 0x20000000 }
+    //! # [heap]
+    //! # size = \"10K\"
+    //! # pools = [{ block = \"4\", capacity = 2560 }]
+    //! # " }
+    //! drone_core::heap! {
+    //!     pub struct Heap;
+    //!     layout = "tests/heap_layout_overflow.toml";
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! # #![feature(allocator_api)]
+    //! # drone_core::config_override! { "
+    //! # [memory]
+    //! # flash = { size = \"128K\", origin = 0x08000000 }
+    //! # ram = { size = \"20K\", origin = 0x20000000 }
+    //! # [heap]
+    //! # size = \"10K\"
+    //! # pools = [{ block = \"4\", capacity = 2560 }]
+    //! # " }
+    //! drone_core::heap! {
+    //!     pub struct Heap;
+    //!     region SRAM2 {
+    //!         origin = 0x2000_4000;
+    //!         layout = "tests/heap_layout.toml";
+    //!     }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! # #![feature(allocator_api)]
+    //! # drone_core::config_override! { "
+    //! # [memory]
+    //! # flash = { size = \"128K\", origin = 0x08000000 }
+    //! # ram = { size = \"20K\", origin = 0x20000000 }
+    //! # [heap]
+    //! # size = \"10K\"
+    //! # pools = [{ block = \"4\", capacity = 2560 }]
+    //! # " }
+    //! drone_core::heap! {
+    //!     pub struct Heap;
+    //!     region CCM {
+    //!         origin = 0x1000_0000;
+    //!         layout = "tests/heap_layout.toml";
+    //!     }
+    //!     region CCM2 {
+    //!         origin = 0x1000_2000;
+    //!         layout = "tests/heap_layout.toml";
+    //!     }
+    //! }
+    //! fn main() {}
+    //! ```    //!
+    //! ```compile_fail
+    //! # #![feature(allocator_api)]
+    //! # drone_core::config_override! { "
+    //! # [memory]
+    //! # flash = { size = \"128K\", origin = 0x08000000 }
+    //! # ram = { size = \"20K\", origin = 0x20000000 }
+    //! # [heap]
+    //! # size = \"10K\"
+    //! # pools = [{ block = \"4\", capacity = 2557 }, { block = \"6\", capacity = 2 }]
+    //! # " }
+    //! drone_core::heap! {
+    //!     pub struct Heap;
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! # #![feature(allocator_api)]
+    //! # drone_core::config_override! { "
+    //! # [memory]
+    //! # flash = { size = \"128K\", origin = 0x08000000 }
+    //! # ram = { size = \"20K\", origin = 0x20000000 }
+    //! # [heap]
+    //! # size = \"10K\"
+    //! # pools = [{ block = \"4\", capacity = 2560 }]
+    //! # " }
+    //! drone_core::heap! {
+    //!     pub struct Heap;
+    //!     region CCM {
+    //!         origin = 0x1000_0002;
+    //!         layout = "tests/heap_layout.toml";
+    //!     }
+    //! }
+    //! fn main() {}
+    //! ```
+}
@@ -0,0 +1,290 @@
+//! Host-side heap model for property testing.
+//!
+//! This module is available with the `std` feature. [`HeapModel`] places a
+//! heap into a host memory buffer, keeps track of live allocations, and checks
+//! the heap invariants:
+//!
+//! * the free list of each pool is consistent, see [`Pool::check`];
+//! * live allocations and free blocks don't overlap.
+//!
+//! Allocated memory is filled with a pattern, so a block handed out twice
+//! corrupts the free list, and gets caught by the check.
+//!
+//! Alloc/free sequences can be generated by [`random_ops`] from a seed, which
+//! makes failures reproducible, or by an external property-testing framework
+//! through [`HeapModel::apply`].
+//!
+//! ```
+//! use core::slice::SliceIndex;
+//! use drone_core::heap::{model::HeapModel, Allocator, Pool};
+//!
+//! struct TestHeap {
+//!     pools: [Pool; 3],
+//! }
+//!
+//! impl Allocator for TestHeap {
+//!     const POOL_COUNT: usize = 3;
+//!
+//!     unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
+//!     where
+//!         I: SliceIndex<[Pool]>,
+//!     {
+//!         self.pools.get_unchecked(index)
+//!     }
+//! }
+//!
+//! let mut model = HeapModel::new(4 * 32 + 16 * 16 + 64 * 8, |base| TestHeap {
+//!     pools: [
+//!         Pool::from_ptr(base, 4, 32),
+//!         Pool::from_ptr(base.wrapping_add(128), 16, 16),
+//!         Pool::from_ptr(base.wrapping_add(384), 64, 8),
+//!     ],
+//! });
+//! model.run(0xDEAD_BEEF, 64, 1000).unwrap();
+//! ```
+
+use super::{alloc, dealloc, Allocator, Pool, PoolError};
+use core::{
+    alloc::{AllocInit, Layout},
+    fmt,
+    ptr::NonNull,
+    slice::SliceIndex,
+};
+use std::collections::BTreeMap;
+
+const FILL_PATTERN: u8 = 0xA5;
+
+/// A heap placed into a host memory buffer, which tracks live allocations.
+pub struct HeapModel<A: Allocator> {
+    heap: A,
+    live: BTreeMap<usize, Layout>,
+    // Must be declared after `heap` to outlive it.
+    _memory: Box<[u8]>,
+}
+
+/// A single heap operation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Op {
+    /// Allocate a block with the given layout.
+    Alloc(Layout),
+    /// Deallocate a live allocation. The index is taken modulo the number of
+    /// live allocations, and the operation is ignored if there are none.
+    Dealloc(usize),
+}
+
+/// A violated heap invariant.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvariantError {
+    /// The free list of the pool at `index` is inconsistent.
+    Pool {
+        /// Pool index.
+        index: usize,
+        /// Free list error.
+        error: PoolError,
+    },
+    /// Two memory regions, live or free, overlap.
+    Overlap(usize, usize),
+}
+
+/// An iterator of pseudo-random heap operations.
+///
+/// This `struct` is created by the [`random_ops`] function.
+pub struct RandomOps {
+    state: u64,
+    max_size: usize,
+}
+
+/// Returns an infinite iterator of pseudo-random heap operations, which is
+/// determined by `seed`. Allocation sizes are within `1..=max_size`.
+pub fn random_ops(seed: u64, max_size: usize) -> RandomOps {
+    RandomOps { state: seed | 1, max_size }
+}
+
+impl<A: Allocator> HeapModel<A> {
+    /// Allocates a zeroed memory buffer of `size` bytes, and creates the heap
+    /// by passing the buffer address to `init`.
+    pub fn new(size: usize, init: impl FnOnce(*mut u8) -> A) -> Self {
+        let mut memory = vec![0; size].into_boxed_slice();
+        let heap = init(memory.as_mut_ptr());
+        Self { heap, live: BTreeMap::new(), _memory: memory }
+    }
+
+    /// Returns a reference to the heap.
+    pub fn heap(&self) -> &A {
+        &self.heap
+    }
+
+    /// Returns an iterator over the live allocations ordered by address.
+    pub fn live(&self) -> impl Iterator<Item = (NonNull<u8>, Layout)> + '_ {
+        self.live
+            .iter()
+            .map(|(&ptr, &layout)| (unsafe { NonNull::new_unchecked(ptr as _) }, layout))
+    }
+
+    /// Allocates a block of memory for `layout`, and fills it with a pattern.
+    /// Returns `None` if the heap is exhausted.
+    pub fn alloc(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        let memory = alloc(&self.heap, layout, AllocInit::Uninitialized).ok()?;
+        if layout.size() > 0 {
+            unsafe { memory.ptr.as_ptr().write_bytes(FILL_PATTERN, layout.size()) };
+            self.live.insert(memory.ptr.as_ptr() as usize, layout);
+        }
+        Some(memory.ptr)
+    }
+
+    /// Deallocates a live allocation.
+    ///
+    /// # Panics
+    ///
+    /// If `ptr` is not a live allocation.
+    pub fn dealloc(&mut self, ptr: NonNull<u8>) {
+        let layout = self.live.remove(&(ptr.as_ptr() as usize)).expect("not a live allocation");
+        unsafe { dealloc(&self.heap, ptr, layout) };
+    }
+
+    /// Applies a single operation.
+    pub fn apply(&mut self, op: Op) {
+        match op {
+            Op::Alloc(layout) => {
+                self.alloc(layout);
+            }
+            Op::Dealloc(index) => {
+                if !self.live.is_empty() {
+                    let address = *self.live.keys().nth(index % self.live.len()).unwrap();
+                    self.dealloc(unsafe { NonNull::new_unchecked(address as _) });
+                }
+            }
+        }
+    }
+
+    /// Checks the heap invariants.
+    pub fn check(&self) -> Result<(), InvariantError> {
+        let mut regions =
+            self.live.iter().map(|(&ptr, layout)| (ptr, layout.size())).collect::<Vec<_>>();
+        for index in 0..A::POOL_COUNT {
+            let pool = unsafe { self.heap.get_pool_unchecked(index) };
+            let size = pool.size();
+            unsafe { pool.check(|ptr| regions.push((ptr.as_ptr() as usize, size))) }
+                .map_err(|error| InvariantError::Pool { index, error })?;
+        }
+        regions.sort_unstable();
+        for pair in regions.windows(2) {
+            let ((first, size), (second, _)) = (pair[0], pair[1]);
+            if first + size > second {
+                return Err(InvariantError::Overlap(first, second));
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `steps` operations from [`random_ops`], checking the invariants
+    /// after each one. Returns the failed step number along with the error.
+    pub fn run(
+        &mut self,
+        seed: u64,
+        max_size: usize,
+        steps: usize,
+    ) -> Result<(), (usize, InvariantError)> {
+        for (step, op) in random_ops(seed, max_size).take(steps).enumerate() {
+            self.apply(op);
+            self.check().map_err(|err| (step, err))?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: Allocator> Allocator for HeapModel<A> {
+    const POOL_COUNT: usize = A::POOL_COUNT;
+
+    unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
+    where
+        I: SliceIndex<[Pool]>,
+    {
+        self.heap.get_pool_unchecked(index)
+    }
+}
+
+impl Iterator for RandomOps {
+    type Item = Op;
+
+    fn next(&mut self) -> Option<Op> {
+        let value = self.next_u64();
+        Some(if value & 1 == 0 {
+            let size = (value >> 1) as usize % self.max_size + 1;
+            Op::Alloc(Layout::from_size_align(size, 1).unwrap())
+        } else {
+            Op::Dealloc((value >> 1) as usize)
+        })
+    }
+}
+
+impl RandomOps {
+    // xorshift64*
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pool { index, error } => write!(f, "pool {}: {}", index, error),
+            Self::Overlap(first, second) => {
+                write!(f, "regions at {:#x} and {:#x} overlap", first, second)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestHeap {
+        pools: [Pool; 3],
+    }
+
+    impl Allocator for TestHeap {
+        const POOL_COUNT: usize = 3;
+
+        unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
+        where
+            I: SliceIndex<[Pool]>,
+        {
+            self.pools.get_unchecked(index)
+        }
+    }
+
+    fn model() -> HeapModel<TestHeap> {
+        HeapModel::new(3 * 20 + 8 * 10 + 33 * 5, |base| TestHeap {
+            pools: [
+                Pool::from_ptr(base, 3, 20),
+                Pool::from_ptr(base.wrapping_add(60), 8, 10),
+                Pool::from_ptr(base.wrapping_add(140), 33, 5),
+            ],
+        })
+    }
+
+    #[test]
+    fn random_sequences() {
+        for seed in 0..50 {
+            let mut model = model();
+            assert_eq!(model.run(seed, 40, 500), Ok(()));
+        }
+    }
+
+    #[test]
+    fn double_free() {
+        let mut model = model();
+        let layout = Layout::from_size_align(8, 1).unwrap();
+        let ptr = model.alloc(layout).unwrap();
+        unsafe {
+            dealloc(model.heap(), ptr, layout);
+            dealloc(model.heap(), ptr, layout);
+        }
+        assert_eq!(model.check(), Err(InvariantError::Pool { index: 1, error: PoolError::Cycle }));
+    }
+}
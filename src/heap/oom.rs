@@ -0,0 +1,51 @@
+use crate::atomic::{AtomicPtr, Ordering};
+use core::{alloc::Layout, mem, ptr};
+
+/// An out-of-memory hook.
+///
+/// The hook is called with the failing layout, and the index of the first
+/// pool, which block size fits the layout. If the layout doesn't fit any pool,
+/// the index equals [`Allocator::POOL_COUNT`](super::Allocator::POOL_COUNT).
+pub type OomHook = fn(Layout, usize);
+
+static HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Registers `hook` to be called when an allocation fails, before the error
+/// is returned to the caller. Replaces the previously registered hook.
+///
+/// The hook runs in the context of the failing allocation, possibly inside an
+/// interrupt handler, so it should be short. It may log diagnostics or trigger
+/// a graceful reset.
+///
+/// # Examples
+///
+/// ```
+/// use core::alloc::Layout;
+/// use drone_core::heap;
+///
+/// fn oom(layout: Layout, pool_idx: usize) {
+///     panic!("out of memory: {} bytes at pool {}", layout.size(), pool_idx);
+/// }
+///
+/// heap::set_oom_hook(oom);
+/// # heap::take_oom_hook();
+/// ```
+#[inline]
+pub fn set_oom_hook(hook: OomHook) {
+    HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+/// Unregisters the out-of-memory hook, and returns it.
+#[inline]
+pub fn take_oom_hook() -> Option<OomHook> {
+    let hook = HOOK.swap(ptr::null_mut(), Ordering::AcqRel);
+    if hook.is_null() { None } else { Some(unsafe { mem::transmute::<*mut (), OomHook>(hook) }) }
+}
+
+pub(super) fn oom(layout: Layout, pool_idx: usize) {
+    let hook = HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        let hook = unsafe { mem::transmute::<*mut (), OomHook>(hook) };
+        hook(layout, pool_idx);
+    }
+}
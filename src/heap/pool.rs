@@ -1,7 +1,12 @@
+#[cfg(feature = "heapguard")]
+use super::guard;
+#[cfg(feature = "heapstats")]
+use super::stats::{Counters, PoolStats};
+use crate::atomic::{AtomicPtr, Ordering};
 use core::{
     alloc::Layout,
+    fmt,
     ptr::{self, NonNull},
-    sync::atomic::{AtomicPtr, Ordering},
 };
 
 /// The set of free memory blocks.
@@ -12,6 +17,8 @@ use core::{
 pub struct Pool {
     /// Block size. Doesn't change in the run-time.
     size: usize,
+    /// Address of the first element. Doesn't change in the run-time.
+    start: *mut u8,
     /// Address of the byte past the last element. Doesn't change in the
     /// run-time.
     edge: *mut u8,
@@ -19,6 +26,21 @@ pub struct Pool {
     free: AtomicPtr<u8>,
     /// Pointer growing from the starting address until it reaches the `edge`.
     uninit: AtomicPtr<u8>,
+    /// Run-time counters.
+    #[cfg(feature = "heapstats")]
+    counters: Counters,
+}
+
+/// An inconsistency found by [`Pool::check`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolError {
+    /// A free list entry points outside of the initialized part of the pool.
+    OutOfBounds(usize),
+    /// A free list entry points inside a block instead of its start.
+    Misaligned(usize),
+    /// The free list is longer than the number of initialized blocks, i.e. it
+    /// contains a cycle or a block freed twice.
+    Cycle,
 }
 
 unsafe impl Sync for Pool {}
@@ -28,9 +50,28 @@ impl Pool {
     pub const fn new(address: usize, size: usize, capacity: usize) -> Self {
         Self {
             size,
+            start: address as *mut u8,
             edge: (address + size * capacity) as *mut u8,
             free: AtomicPtr::new(ptr::null_mut()),
             uninit: AtomicPtr::new(address as *mut u8),
+            #[cfg(feature = "heapstats")]
+            counters: Counters::new(),
+        }
+    }
+
+    /// Creates a new `Pool` from a pointer to the pool memory.
+    ///
+    /// Unlike [`Pool::new`], the resulting pool keeps the provenance of `base`,
+    /// which makes it usable under Miri.
+    pub fn from_ptr(base: *mut u8, size: usize, capacity: usize) -> Self {
+        Self {
+            size,
+            start: base,
+            edge: base.wrapping_add(size * capacity),
+            free: AtomicPtr::new(ptr::null_mut()),
+            uninit: AtomicPtr::new(base),
+            #[cfg(feature = "heapstats")]
+            counters: Counters::new(),
         }
     }
 
@@ -48,7 +89,13 @@ impl Pool {
     ///
     /// This operation is lock-free and has *O(1)* time complexity.
     pub fn alloc(&self) -> Option<NonNull<u8>> {
-        unsafe { self.alloc_free().or_else(|| self.alloc_uninit()) }
+        let ptr = unsafe { self.alloc_free().or_else(|| self.alloc_uninit()) };
+        #[cfg(feature = "heapstats")]
+        match ptr {
+            Some(_) => self.counters.alloc(),
+            None => self.counters.fail(),
+        }
+        ptr
     }
 
     /// Deallocates the block referenced by `ptr`.
@@ -60,27 +107,97 @@ impl Pool {
     /// * `ptr` must point to a block previously allocated by
     ///   [`alloc`](Pool::alloc).
     /// * `ptr` must not be used after deallocation.
-    #[allow(clippy::cast_ptr_alignment)]
     pub unsafe fn dealloc(&self, ptr: NonNull<u8>) {
+        #[cfg(feature = "heapstats")]
+        self.counters.dealloc();
+        #[cfg(feature = "heapguard")]
+        guard::poison(ptr.as_ptr(), self.size);
         loop {
             let curr = self.free.load(Ordering::Acquire);
-            ptr::write(ptr.as_ptr() as *mut *mut u8, curr);
+            write_link(ptr.as_ptr(), curr);
             let next = ptr.as_ptr() as *mut u8;
-            if self.free.compare_and_swap(curr, next, Ordering::AcqRel) == curr {
+            if self.free.compare_exchange(curr, next, Ordering::AcqRel, Ordering::Acquire).is_ok() {
                 break;
             }
         }
     }
 
-    #[allow(clippy::cast_ptr_alignment)]
+    /// Returns `true` if every block of the pool is aligned to `align`, which
+    /// must be a power of two.
+    #[inline]
+    pub(super) fn is_aligned(&self, align: usize) -> bool {
+        (self.start as usize | self.size) & (align - 1) == 0
+    }
+
+    #[inline]
+    pub(super) fn start(&self) -> *mut u8 {
+        self.start
+    }
+
+    #[inline]
+    pub(super) fn edge(&self) -> *mut u8 {
+        self.edge
+    }
+
+    /// Returns the total number of blocks.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        (self.edge as usize - self.start as usize) / self.size
+    }
+
+    /// Returns a snapshot of the run-time counters.
+    #[cfg(feature = "heapstats")]
+    pub fn stats(&self) -> PoolStats {
+        self.counters.load(self.size, self.capacity())
+    }
+
+    /// Walks the free list and checks its consistency, calling `f` for each
+    /// free block. Returns the number of free blocks, including the blocks
+    /// never allocated yet.
+    ///
+    /// This method is intended for testing, it has *O(n)* time complexity.
+    ///
+    /// # Safety
+    ///
+    /// The pool must not be used concurrently, and the pool memory must be
+    /// readable.
+    pub unsafe fn check(&self, mut f: impl FnMut(NonNull<u8>)) -> Result<usize, PoolError> {
+        let uninit = self.uninit.load(Ordering::Relaxed);
+        let initialized = (uninit as usize - self.start as usize) / self.size;
+        let mut count = 0;
+        let mut curr = self.free.load(Ordering::Acquire);
+        while !curr.is_null() {
+            if curr < self.start || curr >= uninit {
+                return Err(PoolError::OutOfBounds(curr as usize));
+            }
+            if (curr as usize - self.start as usize) % self.size != 0 {
+                return Err(PoolError::Misaligned(curr as usize));
+            }
+            count += 1;
+            if count > initialized {
+                return Err(PoolError::Cycle);
+            }
+            f(NonNull::new_unchecked(curr));
+            curr = read_link(curr);
+        }
+        let mut block = uninit;
+        while block != self.edge {
+            f(NonNull::new_unchecked(block));
+            block = block.add(self.size);
+        }
+        Ok(count + (self.edge as usize - uninit as usize) / self.size)
+    }
+
     unsafe fn alloc_free(&self) -> Option<NonNull<u8>> {
         loop {
             let curr = self.free.load(Ordering::Acquire);
             if curr.is_null() {
                 break None;
             }
-            let next = ptr::read(curr as *const *mut u8);
-            if self.free.compare_and_swap(curr, next, Ordering::AcqRel) == curr {
+            let next = read_link(curr);
+            if self.free.compare_exchange(curr, next, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+                #[cfg(feature = "heapguard")]
+                guard::check_poison(curr, self.size);
                 break Some(NonNull::new_unchecked(curr));
             }
         }
@@ -93,13 +210,41 @@ impl Pool {
                 break None;
             }
             let next = curr.add(self.size);
-            if self.uninit.compare_and_swap(curr, next, Ordering::Relaxed) == curr {
+            if self
+                .uninit
+                .compare_exchange(curr, next, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+            {
                 break Some(NonNull::new_unchecked(curr));
             }
         }
     }
 }
 
+// Blocks are not necessarily aligned for a pointer, e.g. when the block size is
+// odd, so the free list links are accessed with unaligned reads and writes.
+#[allow(clippy::cast_ptr_alignment)]
+#[inline]
+unsafe fn read_link(block: *mut u8) -> *mut u8 {
+    ptr::read_unaligned(block as *const *mut u8)
+}
+
+#[allow(clippy::cast_ptr_alignment)]
+#[inline]
+unsafe fn write_link(block: *mut u8, link: *mut u8) {
+    ptr::write_unaligned(block as *mut *mut u8, link);
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds(address) => write!(f, "free block {:#x} is out of bounds", address),
+            Self::Misaligned(address) => write!(f, "free block {:#x} is misaligned", address),
+            Self::Cycle => write!(f, "free list contains a cycle"),
+        }
+    }
+}
+
 pub trait Fits: Copy {
     fn fits(self, pool: &Pool) -> bool;
 }
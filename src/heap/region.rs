@@ -0,0 +1,27 @@
+/// Attributes of a heap memory region.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RegionAttrs {
+    /// The region is accessible by DMA controllers.
+    pub dma: bool,
+    /// The region is accessed without wait states.
+    pub zero_wait: bool,
+}
+
+/// A heap memory region, which is a continuous range of pools.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Region {
+    /// The index of the first pool of the region.
+    pub first_pool: usize,
+    /// The number of pools in the region.
+    pub pool_count: usize,
+    /// The region attributes.
+    pub attrs: RegionAttrs,
+}
+
+impl RegionAttrs {
+    /// Returns `true` if all attributes set in `other` are also set in `self`.
+    #[inline]
+    pub fn contains(self, other: Self) -> bool {
+        (self.dma || !other.dma) && (self.zero_wait || !other.zero_wait)
+    }
+}
@@ -0,0 +1,136 @@
+use super::{Allocator, HEAPTRACE_KEY};
+use crate::{
+    atomic::{AtomicUsize, Ordering},
+    log::{Port, HEAPTRACE_PORT},
+};
+
+/// Run-time counters of a memory pool.
+///
+/// This `struct` is created by the [`stats`](Allocator::stats) method.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PoolStats {
+    /// The block size of the pool.
+    pub block_size: usize,
+    /// The total number of blocks in the pool.
+    pub capacity: usize,
+    /// The number of currently allocated blocks.
+    pub current: usize,
+    /// The maximum number of simultaneously allocated blocks.
+    pub peak: usize,
+    /// The total number of allocations.
+    pub allocs: usize,
+    /// The total number of deallocations.
+    pub frees: usize,
+    /// The number of allocation attempts, which failed because the pool was
+    /// exhausted.
+    pub failed: usize,
+}
+
+/// An iterator over the [`PoolStats`] of each pool of a heap.
+///
+/// This `struct` is created by the [`stats`](Allocator::stats) method.
+pub struct Stats<'a, A: Allocator> {
+    heap: &'a A,
+    index: usize,
+}
+
+pub(super) struct Counters {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    allocs: AtomicUsize,
+    frees: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl<'a, A: Allocator> Stats<'a, A> {
+    pub(super) fn new(heap: &'a A) -> Self {
+        Self { heap, index: 0 }
+    }
+}
+
+impl<A: Allocator> Iterator for Stats<'_, A> {
+    type Item = PoolStats;
+
+    fn next(&mut self) -> Option<PoolStats> {
+        if self.index == A::POOL_COUNT {
+            return None;
+        }
+        let pool = unsafe { self.heap.get_pool_unchecked(self.index) };
+        self.index += 1;
+        Some(pool.stats())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = A::POOL_COUNT - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<A: Allocator> ExactSizeIterator for Stats<'_, A> {}
+
+/// Reports the counters of each pool over the heap trace port.
+pub(super) fn trace<A: Allocator>(heap: &A) {
+    let port = Port::new(HEAPTRACE_PORT);
+    if !port.is_enabled() {
+        return;
+    }
+    for (index, stats) in heap.stats().enumerate() {
+        let PoolStats { block_size, capacity, current, peak, allocs, frees, failed } = stats;
+        let values = [block_size, capacity, current, peak, allocs, frees, failed];
+        port.write::<u32>((0xF0 << 24 | index as u32 & 0xFF_FFFF) ^ HEAPTRACE_KEY);
+        for (tag, &value) in (0xF1..).zip(&values) {
+            port.write::<u32>((tag << 24 | value as u32 & 0xFF_FFFF) ^ HEAPTRACE_KEY);
+        }
+    }
+}
+
+impl Counters {
+    pub(super) const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            allocs: AtomicUsize::new(0),
+            frees: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        }
+    }
+
+    pub(super) fn alloc(&self) {
+        self.allocs.fetch_add(1, Ordering::Relaxed);
+        let current = self.current.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut peak = self.peak.load(Ordering::Relaxed);
+        while current > peak {
+            match self.peak.compare_exchange_weak(
+                peak,
+                current,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(next) => peak = next,
+            }
+        }
+    }
+
+    pub(super) fn dealloc(&self) {
+        self.frees.fetch_add(1, Ordering::Relaxed);
+        self.current.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn fail(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn load(&self, block_size: usize, capacity: usize) -> PoolStats {
+        PoolStats {
+            block_size,
+            capacity,
+            current: self.current.load(Ordering::Relaxed),
+            peak: self.peak.load(Ordering::Relaxed),
+            allocs: self.allocs.load(Ordering::Relaxed),
+            frees: self.frees.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use super::{allocator::pool_of, Allocator, HEAPTRACE_KEY};
+use crate::{
+    atomic::{AtomicU8, Ordering},
+    log::{Port, HEAPTRACE_PORT},
+};
+use core::{cell::UnsafeCell, ptr::NonNull};
+
+/// Usage of the heap by allocations with the same tag.
+///
+/// This `struct` is created by the
+/// [`tag_usage`](super::AllocatorExt::tag_usage) method.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TagUsage {
+    /// The number of live allocations.
+    pub blocks: usize,
+    /// The total size of the blocks of the live allocations.
+    pub bytes: usize,
+}
+
+/// The side table of allocation tags, one byte per heap block.
+///
+/// The [`heap`](crate::heap) macro generates the table when the `heaptag`
+/// feature is enabled.
+pub struct TagTable<T: ?Sized>(UnsafeCell<T>);
+
+unsafe impl Sync for TagTable<[u8]> {}
+
+unsafe impl<const N: usize> Sync for TagTable<[u8; N]> {}
+
+impl<T> TagTable<T> {
+    /// Creates a new `TagTable` with the initial `tags`.
+    pub const fn new(tags: T) -> Self {
+        Self(UnsafeCell::new(tags))
+    }
+}
+
+impl TagTable<[u8]> {
+    /// Returns the number of entries in the table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        unsafe { (*self.0.get()).len() }
+    }
+
+    /// Returns `true` if the table has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the tag at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> u8 {
+        self.entry(index).load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn set(&self, index: usize, tag: u8) {
+        self.entry(index).store(tag, Ordering::Relaxed);
+    }
+
+    #[allow(clippy::cast_ptr_alignment)]
+    #[inline]
+    fn entry(&self, index: usize) -> &AtomicU8 {
+        assert!(index < self.len(), "tag index out of bounds");
+        // `AtomicU8` has the same in-memory representation as `u8`.
+        unsafe { &*((self.0.get() as *mut u8).add(index) as *const AtomicU8) }
+    }
+}
+
+/// Returns the index of the block at `ptr` in the tag table.
+fn block_index<A: Allocator>(heap: &A, ptr: NonNull<u8>) -> usize {
+    let pool_idx = pool_of(heap, ptr);
+    let pool = unsafe { heap.get_pool_unchecked(pool_idx) };
+    heap.tag_offset(pool_idx) + (ptr.as_ptr() as usize - pool.start() as usize) / pool.size()
+}
+
+/// Returns the tag of the block at `ptr`.
+pub(super) fn get<A: Allocator>(heap: &A, ptr: NonNull<u8>) -> u8 {
+    heap.tags().map_or(0, |tags| tags.get(block_index(heap, ptr)))
+}
+
+/// Sets the tag of the block at `ptr`.
+pub(super) fn set<A: Allocator>(heap: &A, ptr: NonNull<u8>, tag: u8) {
+    if let Some(tags) = heap.tags() {
+        tags.set(block_index(heap, ptr), tag);
+    }
+}
+
+/// Walks the tag table and sums up the blocks with `tag`.
+pub(super) fn usage<A: Allocator>(heap: &A, tag: u8) -> TagUsage {
+    let mut usage = TagUsage::default();
+    if let Some(tags) = heap.tags() {
+        let mut index = 0;
+        for pool_idx in 0..A::POOL_COUNT {
+            let pool = unsafe { heap.get_pool_unchecked(pool_idx) };
+            for _ in 0..pool.capacity() {
+                if tags.get(index) == tag {
+                    usage.blocks += 1;
+                    usage.bytes += pool.size();
+                }
+                index += 1;
+            }
+        }
+    }
+    usage
+}
+
+/// Reports the usage of each present tag over the heap trace port.
+pub(super) fn trace<A: Allocator>(heap: &A) {
+    let tags = match heap.tags() {
+        Some(tags) if Port::new(HEAPTRACE_PORT).is_enabled() => tags,
+        _ => return,
+    };
+    let mut present = [0_u32; 8];
+    for index in 0..tags.len() {
+        let tag = tags.get(index);
+        present[usize::from(tag >> 5)] |= 1 << (tag & 0x1F);
+    }
+    for tag in 1..=0xFF_u8 {
+        if present[usize::from(tag >> 5)] & 1 << (tag & 0x1F) == 0 {
+            continue;
+        }
+        let TagUsage { blocks, bytes } = usage(heap, tag);
+        Port::new(HEAPTRACE_PORT)
+            .write::<u32>((0xE1 << 24 | u32::from(tag) << 16 | blocks as u32 >> 16) ^ HEAPTRACE_KEY)
+            .write::<u32>(
+                (0xE2 << 24 | (blocks as u32 & 0xFFFF) << 8 | bytes as u32 >> 24) ^ HEAPTRACE_KEY,
+            )
+            .write::<u32>((0xE3 << 24 | bytes as u32 & 0xFF_FFFF) ^ HEAPTRACE_KEY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heap::{dealloc, grow, AllocatorExt, Pool};
+    use core::{
+        alloc::{AllocInit, Layout, ReallocPlacement},
+        slice::SliceIndex,
+    };
+
+    struct TestHeap {
+        pools: [Pool; 2],
+        tags: TagTable<[u8; 6]>,
+    }
+
+    impl Allocator for TestHeap {
+        const POOL_COUNT: usize = 2;
+
+        unsafe fn get_pool_unchecked<I>(&self, index: I) -> &I::Output
+        where
+            I: SliceIndex<[Pool]>,
+        {
+            self.pools.get_unchecked(index)
+        }
+
+        fn tags(&self) -> Option<&TagTable<[u8]>> {
+            Some(&self.tags)
+        }
+    }
+
+    #[test]
+    fn tags() {
+        let mut m = [0u8; 96];
+        let o = m.as_mut_ptr();
+        let heap = TestHeap {
+            pools: [Pool::from_ptr(o, 8, 4), Pool::from_ptr(o.wrapping_add(32), 32, 2)],
+            tags: TagTable::new([0; 6]),
+        };
+        let small = Layout::from_size_align(8, 1).unwrap();
+        let large = Layout::from_size_align(20, 1).unwrap();
+        unsafe {
+            let a = heap.alloc_tagged(small, 3).unwrap().ptr;
+            let b = heap.alloc_tagged(large, 3).unwrap().ptr;
+            let c = heap.alloc_tagged(small, 7).unwrap().ptr;
+            assert_eq!(heap.tag_usage(3), TagUsage { blocks: 2, bytes: 40 });
+            assert_eq!(heap.tag_usage(7), TagUsage { blocks: 1, bytes: 8 });
+            assert_eq!(heap.tags().unwrap().get(4), 3);
+            assert_eq!(heap.tag_offset(1), 4);
+            let c = grow(&heap, c, small, 16, ReallocPlacement::MayMove, AllocInit::Uninitialized)
+                .unwrap()
+                .ptr;
+            assert_eq!(heap.tag_usage(7), TagUsage { blocks: 1, bytes: 32 });
+            dealloc(&heap, a, small);
+            dealloc(&heap, b, large);
+            dealloc(&heap, c, Layout::from_size_align(16, 1).unwrap());
+        }
+        assert_eq!(heap.tag_usage(3), TagUsage::default());
+        assert_eq!(heap.tag_usage(7), TagUsage::default());
+        assert_eq!(heap.tag_usage(0), TagUsage { blocks: 6, bytes: 96 });
+    }
+}
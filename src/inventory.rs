@@ -206,8 +206,10 @@
 //! }
 //! ```
 
+use crate::atomic::{AtomicBool, Ordering};
 use core::{
     marker::PhantomData,
+    mem,
     ops::{Deref, DerefMut},
 };
 
@@ -220,6 +222,36 @@ pub struct Inventory<T: Item, const C: usize> {
     item: T,
 }
 
+/// A lazily initialized [`Inventory`].
+///
+/// The item is constructed by the registered init function on the first
+/// [`acquire`](LazyInventory::acquire), so expensive driver setup is deferred
+/// until the resource is actually used. The init function runs at most once,
+/// even if `acquire` is called concurrently from different threads or
+/// interrupts; only the winning caller receives the inventory object.
+///
+/// ```
+/// use drone_core::inventory::{self, Inventory, LazyInventory};
+///
+/// pub struct Uart;
+///
+/// impl inventory::Item for Uart {
+///     fn teardown(&mut self, _token: &mut inventory::GuardToken<Self>) {}
+/// }
+///
+/// static UART: LazyInventory<Uart> = LazyInventory::new(|| Uart);
+///
+/// assert!(!UART.is_acquired());
+/// let uart: Inventory<Uart, 0> = UART.acquire().unwrap();
+/// assert!(UART.is_acquired());
+/// assert!(UART.acquire().is_none());
+/// ```
+pub struct LazyInventory<T: Item> {
+    init: fn() -> T,
+    acquired: AtomicBool,
+    _marker: PhantomData<*const T>,
+}
+
 /// An RAII scoped guard for the inventory item `T`. Will call
 /// [`Item::teardown`] on `drop`.
 #[must_use = "if unused the item will immediately teardown"]
@@ -351,6 +383,53 @@ impl<T: Item, const C: usize> DerefMut for Inventory<T, C> {
     }
 }
 
+/// Clears the acquired flag if the init function unwinds.
+struct ResetOnUnwind<'a>(&'a AtomicBool);
+
+impl Drop for ResetOnUnwind<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+unsafe impl<T: Item + Send> Send for LazyInventory<T> {}
+unsafe impl<T: Item + Send> Sync for LazyInventory<T> {}
+
+impl<T: Item> LazyInventory<T> {
+    /// Creates a new [`LazyInventory`] with the init function `init`.
+    ///
+    /// `init` should construct an item containing some form of token.
+    #[inline]
+    pub const fn new(init: fn() -> T) -> Self {
+        Self { init, acquired: AtomicBool::new(false), _marker: PhantomData }
+    }
+
+    /// Constructs the item and returns it wrapped in an [`Inventory`] in the
+    /// inactive state with zero tokens emitted.
+    ///
+    /// Returns `None` if the item has been already acquired. If the init
+    /// function panics, the item is left unacquired, so a later call retries
+    /// the initialization.
+    #[inline]
+    pub fn acquire(&self) -> Option<Inventory<T, 0>> {
+        if self.acquired.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_err()
+        {
+            None
+        } else {
+            let reset = ResetOnUnwind(&self.acquired);
+            let item = (self.init)();
+            mem::forget(reset);
+            Some(Inventory::new(item))
+        }
+    }
+
+    /// Returns `true` if the item has been already acquired.
+    #[inline]
+    pub fn is_acquired(&self) -> bool {
+        self.acquired.load(Ordering::Acquire)
+    }
+}
+
 impl<T: Item> Token<T> {
     /// Creates a new [`Token`].
     ///
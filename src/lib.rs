@@ -20,27 +20,44 @@
 //! [features]
 //! std = ["drone-core/std"]
 //! ```
+//!
+//! # Stable Rust
+//!
+//! Most of the crate depends on nightly Rust features, and these parts are
+//! enabled by the default `nightly` feature. With default features disabled,
+//! the crate builds on a stable toolchain, providing only [`arch`],
+//! [`bitfield`], [`critical`], [`future`] (except
+//! [`TaskArena`](future::TaskArena)), [`io`], [`log`], [`mem`], [`sync`], and
+//! [`token`]. This allows libraries built on drone-core channels, bitfields,
+//! and tokens to support stable toolchains, while applications still need
+//! nightly.
+//!
+//! ```toml
+//! [dependencies]
+//! drone-core = { version = "0.12.1", default-features = false }
+//! ```
 
-#![feature(alloc_layout_extra)]
-#![feature(alloc_prelude)]
-#![feature(allocator_api)]
-#![feature(const_generics)]
-#![feature(const_raw_ptr_deref)]
-#![feature(core_intrinsics)]
-#![feature(exhaustive_patterns)]
-#![feature(generator_trait)]
-#![feature(generators)]
-#![feature(lang_items)]
-#![feature(marker_trait_attr)]
-#![feature(maybe_uninit_extra)]
-#![feature(maybe_uninit_ref)]
-#![feature(negative_impls)]
-#![feature(never_type)]
-#![feature(never_type_fallback)]
-#![feature(prelude_import)]
-#![feature(raw_vec_internals)]
-#![feature(slice_internals)]
-#![feature(untagged_unions)]
+#![cfg_attr(feature = "nightly", feature(alloc_layout_extra))]
+#![cfg_attr(feature = "nightly", feature(alloc_prelude))]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+#![cfg_attr(feature = "nightly", feature(const_fn))]
+#![cfg_attr(feature = "nightly", feature(const_generics))]
+#![cfg_attr(feature = "nightly", feature(const_raw_ptr_deref))]
+#![cfg_attr(feature = "nightly", feature(core_intrinsics))]
+#![cfg_attr(feature = "nightly", feature(exhaustive_patterns))]
+#![cfg_attr(feature = "nightly", feature(generator_trait))]
+#![cfg_attr(feature = "nightly", feature(generators))]
+#![cfg_attr(feature = "nightly", feature(lang_items))]
+#![cfg_attr(feature = "nightly", feature(marker_trait_attr))]
+#![cfg_attr(feature = "nightly", feature(maybe_uninit_extra))]
+#![cfg_attr(feature = "nightly", feature(maybe_uninit_ref))]
+#![cfg_attr(feature = "nightly", feature(negative_impls))]
+#![cfg_attr(feature = "nightly", feature(never_type))]
+#![cfg_attr(feature = "nightly", feature(never_type_fallback))]
+#![cfg_attr(feature = "nightly", feature(prelude_import))]
+#![cfg_attr(feature = "nightly", feature(raw_vec_internals))]
+#![cfg_attr(feature = "nightly", feature(slice_internals))]
+#![cfg_attr(feature = "nightly", feature(untagged_unions))]
 #![warn(missing_docs)]
 #![warn(clippy::pedantic)]
 #![allow(
@@ -61,52 +78,158 @@
 
 extern crate alloc;
 
+pub mod arch;
 pub mod bitfield;
+pub mod critical;
+#[cfg(feature = "evtrace")]
+pub mod evtrace;
+#[cfg(feature = "nightly")]
 pub mod ffi;
+#[cfg(feature = "nightly")]
 pub mod fib;
+pub mod future;
+#[cfg(feature = "nightly")]
 pub mod heap;
+#[cfg(feature = "nightly")]
 pub mod inventory;
 pub mod io;
 pub mod log;
 pub mod mem;
+#[cfg(feature = "nightly")]
 pub mod periph;
+#[cfg(feature = "nightly")]
 pub mod prelude;
+#[cfg(feature = "nightly")]
 pub mod proc_loop;
+#[cfg(feature = "nightly")]
 pub mod reg;
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(all(feature = "mock", not(feature = "sim")))]
+mod sim;
 pub mod sync;
+#[cfg(feature = "nightly")]
 pub mod thr;
 pub mod token;
 
-#[cfg(not(feature = "std"))]
+mod atomic;
+#[cfg(all(feature = "nightly", not(feature = "std")))]
 mod lang_items;
 
 /// Defines dynamic memory structures.
 ///
 /// See [the module level documentation](heap) for details.
+#[cfg(feature = "nightly")]
 #[doc(inline)]
 pub use drone_core_macros::heap;
 
 /// Defines a new generic peripheral.
 ///
 /// See [the module level documentation](periph) for details.
+#[cfg(feature = "nightly")]
 #[doc(inline)]
 pub use drone_core_macros::periph;
 
 /// Defines a memory-mapped register.
 ///
 /// See [the module level documentation](reg) for details.
+#[cfg(feature = "nightly")]
 #[doc(inline)]
 pub use drone_core_macros::reg;
 
 /// Defines the thread type.
 ///
 /// See [the module level documentation](thr) for details.
+#[cfg(feature = "nightly")]
 #[doc(inline)]
 pub use drone_core_macros::thr;
 
+/// Defines the application configuration from `Drone.toml` in one place.
+///
+/// The macro generates a module with memory layout constants, and optionally
+/// expands the following sections:
+///
+/// * `heap` takes the input of [`heap!`](heap), including layout files and
+///   additional regions. The main heap constants are computed from the layout
+///   file if it is given, and `<REGION>_ORIGIN` and `<REGION>_SIZE` constants
+///   are generated for each additional region.
+/// * `log` declares application log ports as `NAME = number;` items, which
+///   become `<NAME>_PORT` constants. Reserved, out of range, and duplicate
+///   ports are rejected.
+/// * `thr` starts with a `threads = [...];` list, followed by the input of
+///   [`thr!`](thr) without the `use` line. The `THREADS` array is generated
+///   from the list, along with the `THR_COUNT` and `<THREAD>_THR_NUM`
+///   constants.
+///
+/// Since every item is derived from the same description, the constants, the
+/// heap layout, and the thread array can't drift apart.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # #![feature(const_fn)]
+/// # drone_core::config_override! { "
+/// # [memory]
+/// # flash = { size = \"128K\", origin = 0x08000000 }
+/// # ram = { size = \"20K\", origin = 0x20000000 }
+/// # [heap]
+/// # size = \"10K\"
+/// # pools = [
+/// #     { block = \"4\", capacity = 896 },
+/// #     { block = \"32\", capacity = 80 },
+/// #     { block = \"256\", capacity = 16 },
+/// # ]
+/// # [probe]
+/// # gdb-client-command = \"gdb-multiarch\"
+/// # [log.dso]
+/// # baud-rate = 115200
+/// # serial-endpoint = \"/dev/ttyACM0\"
+/// # " }
+/// drone_core::config! {
+///     /// Application configuration.
+///     pub mod consts;
+///
+///     heap {
+///         /// The heap structure.
+///         pub struct Heap;
+///     }
+///
+///     log {
+///         /// Port for telemetry records.
+///         TELEMETRY = 5;
+///     }
+///
+///     thr {
+///         threads = [SYS_TICK, UART];
+///
+///         /// The thread object.
+///         pub struct Thr {}
+///
+///         /// The thread-local storage.
+///         pub struct ThrLocal {}
+///     }
+/// }
+///
+/// fn main() {
+///     assert_eq!(consts::RAM_ORIGIN, 0x2000_0000);
+///     assert_eq!(consts::HEAP_ORIGIN, 0x2000_0000 + 10 * 1024);
+///     assert_eq!(consts::HEAP_SIZE, 10 * 1024);
+///     assert_eq!(consts::LOG_BAUD_RATE, 115_200);
+///     assert_eq!(consts::TELEMETRY_PORT, 5);
+///     assert_eq!(consts::THR_COUNT, 2);
+///     assert_eq!(consts::UART_THR_NUM, 1);
+///     let _heap = Heap::new();
+/// }
+/// ```
+#[cfg(feature = "nightly")]
+#[doc(inline)]
+pub use drone_core_macros::config;
+
 #[doc(hidden)]
 pub use drone_core_macros::config_override;
 
+#[cfg(feature = "nightly")]
 #[prelude_import]
 #[allow(unused_imports)]
 use crate::prelude::*;
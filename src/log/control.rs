@@ -0,0 +1,303 @@
+//! Host-to-target control channel.
+//!
+//! The debug probe can send commands back to the target. The platform
+//! transport passes the received bytes to [`receive`], and the dispatch loop
+//! decodes them into frames and calls the command handlers. The dispatch loop
+//! runs as a fiber added with `spawn`, or by calling [`dispatch_pending`]
+//! directly.
+//!
+//! Each frame consists of a command byte, a payload length byte, and the
+//! payload. The following commands are built-in:
+//!
+//! * [`CMD_ENABLE_PORT`] - unmutes the port given by the first payload byte;
+//! * [`CMD_DISABLE_PORT`] - mutes the port given by the first payload byte,
+//!   making [`Port::is_enabled`](super::Port::is_enabled) return `false`;
+//! * [`CMD_SET_LEVEL`] - sets the [log level](super::set_level) to the first
+//!   payload byte;
+//! * [`CMD_HEAP_STATS`] - reports the heap statistics over the heap trace port
+//!   with `trace_stats`, if a heap is registered with `register_heap` (needs
+//!   the `heapstats` feature).
+//!
+//! Handlers for any other command, or additional handlers for the built-in
+//! ones, are registered with [`register`]. Up to [`HANDLERS_CAPACITY`]
+//! handlers can be registered at the same time.
+//!
+//! # Examples
+//!
+//! ```
+//! use drone_core::log::control;
+//!
+//! fn ping(payload: &[u8]) {
+//!     assert_eq!(payload, b"hi");
+//! }
+//!
+//! control::register(0x80, ping);
+//! // Normally called by the platform transport.
+//! control::receive(&[0x80, 2, b'h', b'i']);
+//! assert_eq!(control::dispatch_pending(), 1);
+//! ```
+
+use super::{set_level, Level, PORTS_COUNT};
+#[cfg(all(feature = "nightly", feature = "heapstats"))]
+use crate::heap::{Allocator, AllocatorExt};
+use crate::{
+    atomic::{AtomicU32, Ordering},
+    critical,
+};
+#[cfg(feature = "nightly")]
+use crate::{fib::FiberState, thr::prelude::*};
+
+/// Capacity of the receive buffer in bytes. Frames longer than this are
+/// discarded.
+pub const RX_CAPACITY: usize = 64;
+
+/// Unmutes a log port.
+pub const CMD_ENABLE_PORT: u8 = 0x01;
+
+/// Mutes a log port.
+pub const CMD_DISABLE_PORT: u8 = 0x02;
+
+/// Sets the log level.
+pub const CMD_SET_LEVEL: u8 = 0x03;
+
+/// Requests heap statistics.
+pub const CMD_HEAP_STATS: u8 = 0x04;
+
+/// The maximum number of registered command handlers.
+pub const HANDLERS_CAPACITY: usize = 8;
+
+const HEADER_SIZE: usize = 2;
+
+static MUTED: AtomicU32 = AtomicU32::new(0);
+
+static mut RX: Rx = Rx { buf: [0; RX_CAPACITY], head: 0, len: 0, skip: 0 };
+
+static mut HANDLERS: [Option<(u8, fn(&[u8]))>; HANDLERS_CAPACITY] = [None; HANDLERS_CAPACITY];
+
+#[cfg(all(feature = "nightly", feature = "heapstats"))]
+static mut HEAP: Option<(*const (), unsafe fn(*const ()))> = None;
+
+struct Rx {
+    buf: [u8; RX_CAPACITY],
+    head: usize,
+    len: usize,
+    skip: usize,
+}
+
+/// Passes bytes received from the debug probe to the control channel.
+/// Returns the number of bytes accepted, which is less than `bytes.len()` if
+/// the receive buffer is full.
+///
+/// Should be called by the platform transport.
+pub fn receive(bytes: &[u8]) -> usize {
+    critical::with(|_| unsafe {
+        let count = bytes.len().min(RX_CAPACITY - RX.len);
+        for &byte in &bytes[..count] {
+            RX.buf[(RX.head + RX.len) % RX_CAPACITY] = byte;
+            RX.len += 1;
+        }
+        count
+    })
+}
+
+/// Registers `handler` for the command `command`, replacing the previously
+/// registered one. The handler is called with the frame payload.
+///
+/// # Panics
+///
+/// If [`HANDLERS_CAPACITY`] handlers for other commands are already
+/// registered.
+pub fn register(command: u8, handler: fn(&[u8])) {
+    let registered = critical::with(|_| unsafe {
+        let mut free = None;
+        for entry in HANDLERS.iter_mut() {
+            match entry {
+                Some((cmd, _)) if *cmd == command => {
+                    *entry = Some((command, handler));
+                    return true;
+                }
+                Some(_) => {}
+                None => free = free.or(Some(entry)),
+            }
+        }
+        free.map(|entry| *entry = Some((command, handler))).is_some()
+    });
+    assert!(registered, "too many control handlers");
+}
+
+/// Removes the handler for the command `command`.
+pub fn unregister(command: u8) {
+    critical::with(|_| unsafe {
+        for entry in HANDLERS.iter_mut() {
+            if matches!(entry, Some((cmd, _)) if *cmd == command) {
+                *entry = None;
+            }
+        }
+    });
+}
+
+/// Registers `heap` as the source of the [`CMD_HEAP_STATS`] command, replacing
+/// the previously registered one.
+///
+/// On the command, the counters of `heap` are reported with
+/// [`AllocatorExt::trace_stats`].
+#[cfg(all(feature = "nightly", feature = "heapstats"))]
+pub fn register_heap<A: Allocator + Sync>(heap: &'static A) {
+    unsafe fn trace_stats<A: Allocator>(heap: *const ()) {
+        (*(heap as *const A)).trace_stats();
+    }
+    critical::with(|_| unsafe {
+        HEAP = Some((heap as *const A as *const (), trace_stats::<A>));
+    });
+}
+
+/// Dispatches all complete frames from the receive buffer. Returns the number
+/// of dispatched frames.
+pub fn dispatch_pending() -> usize {
+    let mut count = 0;
+    let mut payload = [0; RX_CAPACITY];
+    while let Some((command, length)) = next_frame(&mut payload) {
+        dispatch(command, &payload[..length]);
+        count += 1;
+    }
+    count
+}
+
+/// Adds the dispatch loop fiber to the thread `thr`.
+///
+/// The fiber dispatches all pending frames each time the thread is resumed,
+/// so the platform transport should trigger the thread after calling
+/// [`receive`].
+#[cfg(feature = "nightly")]
+pub fn spawn<T: ThrToken>(thr: T) {
+    thr.add_fn(|| {
+        dispatch_pending();
+        FiberState::Yielded::<(), !>(())
+    });
+}
+
+pub(super) fn is_muted(port: u8) -> bool {
+    MUTED.load(Ordering::Relaxed) & 1 << port != 0
+}
+
+fn next_frame(payload: &mut [u8; RX_CAPACITY]) -> Option<(u8, usize)> {
+    critical::with(|_| unsafe {
+        loop {
+            if RX.skip > 0 {
+                // Discard the rest of a frame, which can never be completed.
+                let count = RX.skip.min(RX.len);
+                RX.head = (RX.head + count) % RX_CAPACITY;
+                RX.len -= count;
+                RX.skip -= count;
+                if RX.skip > 0 {
+                    return None;
+                }
+            }
+            if RX.len < HEADER_SIZE {
+                return None;
+            }
+            let command = RX.buf[RX.head];
+            let length = usize::from(RX.buf[(RX.head + 1) % RX_CAPACITY]);
+            if HEADER_SIZE + length > RX_CAPACITY {
+                // Can never be completed, skip the whole frame to resynchronize.
+                RX.head = (RX.head + HEADER_SIZE) % RX_CAPACITY;
+                RX.len -= HEADER_SIZE;
+                RX.skip = length;
+                continue;
+            }
+            if RX.len < HEADER_SIZE + length {
+                return None;
+            }
+            for (i, byte) in payload[..length].iter_mut().enumerate() {
+                *byte = RX.buf[(RX.head + HEADER_SIZE + i) % RX_CAPACITY];
+            }
+            RX.head = (RX.head + HEADER_SIZE + length) % RX_CAPACITY;
+            RX.len -= HEADER_SIZE + length;
+            return Some((command, length));
+        }
+    })
+}
+
+fn dispatch(command: u8, payload: &[u8]) {
+    match (command, payload.first()) {
+        (CMD_ENABLE_PORT, Some(&port)) if port < PORTS_COUNT => {
+            MUTED.fetch_and(!(1 << port), Ordering::Relaxed);
+        }
+        (CMD_DISABLE_PORT, Some(&port)) if port < PORTS_COUNT => {
+            MUTED.fetch_or(1 << port, Ordering::Relaxed);
+        }
+        (CMD_SET_LEVEL, Some(&level)) => {
+            if let Some(level) = Level::from_u8(level) {
+                set_level(level);
+            }
+        }
+        #[cfg(all(feature = "nightly", feature = "heapstats"))]
+        (CMD_HEAP_STATS, _) => {
+            if let Some((heap, trace_stats)) = critical::with(|_| unsafe { HEAP }) {
+                unsafe { trace_stats(heap) };
+            }
+        }
+        _ => {}
+    }
+    let handler = critical::with(|_| unsafe {
+        HANDLERS.iter().flatten().find(|(cmd, _)| *cmd == command).map(|&(_, handler)| handler)
+    });
+    if let Some(handler) = handler {
+        handler(payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{atomic::AtomicUsize, log::level};
+
+    static SEEN: AtomicUsize = AtomicUsize::new(0);
+
+    fn handler(payload: &[u8]) {
+        SEEN.fetch_add(payload.len(), Ordering::Relaxed);
+    }
+
+    #[test]
+    fn frames() {
+        register(0x40, handler);
+        assert_eq!(receive(&[0x40, 3, 1, 2]), 4);
+        assert_eq!(dispatch_pending(), 0);
+        assert_eq!(receive(&[3, CMD_DISABLE_PORT, 1, 7]), 4);
+        assert_eq!(dispatch_pending(), 2);
+        assert_eq!(SEEN.load(Ordering::Relaxed), 3);
+        assert!(is_muted(7));
+        assert_eq!(receive(&[CMD_ENABLE_PORT, 1, 7, 0x40, 0]), 5);
+        assert_eq!(dispatch_pending(), 2);
+        assert!(!is_muted(7));
+        unregister(0x40);
+        assert_eq!(receive(&[0x40, 1, 1]), 3);
+        assert_eq!(dispatch_pending(), 1);
+        assert_eq!(SEEN.load(Ordering::Relaxed), 3);
+        assert_eq!(receive(&[CMD_SET_LEVEL, 1, Level::Warn as u8]), 3);
+        assert_eq!(dispatch_pending(), 1);
+        assert_eq!(level(), Level::Warn);
+        set_level(Level::Trace);
+        for command in 0..HANDLERS_CAPACITY as u8 {
+            register(command, handler);
+        }
+        register(0, handler);
+        assert!(std::panic::catch_unwind(|| register(0x40, handler)).is_err());
+        for command in 0..HANDLERS_CAPACITY as u8 {
+            unregister(command);
+        }
+        register(0x40, handler);
+        unregister(0x40);
+        let mut frame = [CMD_DISABLE_PORT; HEADER_SIZE + 0xFF];
+        frame[..HEADER_SIZE].copy_from_slice(&[0x40, 0xFF]);
+        for chunk in frame.chunks(RX_CAPACITY) {
+            assert_eq!(receive(chunk), chunk.len());
+            assert_eq!(dispatch_pending(), 0);
+        }
+        assert!(!is_muted(CMD_DISABLE_PORT));
+        assert_eq!(receive(&[CMD_DISABLE_PORT, 1, 7]), 3);
+        assert_eq!(dispatch_pending(), 1);
+        assert!(is_muted(7));
+        MUTED.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,151 @@
+#![cfg_attr(feature = "std", allow(unreachable_code))]
+
+use super::{drone_log_free_space, drone_log_is_flushed, Port};
+use crate::critical;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// The number of tasks, which can wait for the transport buffer to drain at
+/// the same time. Further tasks are woken up immediately and poll again.
+const FLUSH_WAITERS: usize = 4;
+
+static mut FLUSH_WAKERS: [Option<Waker>; FLUSH_WAITERS] = [None, None, None, None];
+
+/// Future for the [`flush_async`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Flush {
+    _private: (),
+}
+
+/// Future for the [`write_all`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteAll<'a> {
+    port: Port,
+    bytes: &'a [u8],
+}
+
+/// Returns the number of bytes, which can be written to the log transport
+/// without blocking.
+///
+/// With the `std` feature this function returns `usize::MAX`.
+#[inline]
+pub fn free_space() -> usize {
+    #[cfg(feature = "std")]
+    return usize::MAX;
+    unsafe { drone_log_free_space() }
+}
+
+/// Returns a future, which resolves when all pending packets are transmitted.
+///
+/// Unlike [`flush`](super::flush), this doesn't block the thread. The task is
+/// woken up by the platform transport with [`wake_flushed`].
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::{future, log};
+///
+/// log::write_str(log::STDOUT_PORT, "hello there!\n");
+/// future::block_on_with(log::flush_async(), || {});
+/// ```
+#[inline]
+pub fn flush_async() -> Flush {
+    Flush { _private: () }
+}
+
+/// Returns a future, which writes all of `bytes` to `port`, waiting for the
+/// transport buffer to drain whenever it is full.
+///
+/// This allows latency-tolerant tasks to emit large dumps without blocking a
+/// thread and without dropping output. The future resolves immediately if
+/// the port is not enabled.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::{future, log, log::Port};
+///
+/// let dump = [0xAA; 4096];
+/// future::block_on_with(log::write_all(Port::new(11), &dump), || {});
+/// ```
+#[inline]
+pub fn write_all(port: Port, bytes: &[u8]) -> WriteAll<'_> {
+    WriteAll { port, bytes }
+}
+
+/// Wakes all tasks waiting for the transport buffer to drain. Should be called
+/// by the platform transport when all pending packets are transmitted.
+pub fn wake_flushed() {
+    for index in 0..FLUSH_WAITERS {
+        if let Some(waker) = critical::with(|_| unsafe { FLUSH_WAKERS[index].take() }) {
+            waker.wake();
+        }
+    }
+}
+
+fn is_flushed() -> bool {
+    #[cfg(feature = "sim")]
+    crate::sim::log_flush();
+    #[cfg(feature = "std")]
+    return true;
+    unsafe { drone_log_is_flushed() }
+}
+
+fn poll_flushed(cx: &mut Context<'_>) -> Poll<()> {
+    if is_flushed() {
+        return Poll::Ready(());
+    }
+    let registered = critical::with(|_| unsafe { register(cx.waker()) });
+    if !registered {
+        cx.waker().wake_by_ref();
+    }
+    if is_flushed() { Poll::Ready(()) } else { Poll::Pending }
+}
+
+/// Stores `waker` in a free slot, unless it's already stored. Returns `false`
+/// if all slots are taken.
+unsafe fn register(waker: &Waker) -> bool {
+    let mut free = None;
+    for slot in FLUSH_WAKERS.iter_mut() {
+        match slot {
+            Some(stored) if stored.will_wake(waker) => return true,
+            Some(_) => {}
+            None => free = free.or(Some(slot)),
+        }
+    }
+    free.map(|slot| *slot = Some(waker.clone())).is_some()
+}
+
+impl Future for Flush {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        poll_flushed(cx)
+    }
+}
+
+impl Future for WriteAll<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if !self.port.is_enabled() {
+            return Poll::Ready(());
+        }
+        while !self.bytes.is_empty() {
+            let space = free_space();
+            if space == 0 {
+                if poll_flushed(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                continue;
+            }
+            let (chunk, rest) = self.bytes.split_at(space.min(self.bytes.len()));
+            self.port.write_bytes(chunk);
+            self.bytes = rest;
+        }
+        Poll::Ready(())
+    }
+}
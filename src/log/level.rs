@@ -0,0 +1,73 @@
+use crate::atomic::{AtomicU8, Ordering};
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Trace as u8);
+
+/// Log verbosity level.
+///
+/// Levels are ordered from the least verbose to the most verbose.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[repr(u8)]
+pub enum Level {
+    /// Logging is disabled.
+    Off = 0,
+    /// Unrecoverable errors.
+    Error = 1,
+    /// Recoverable problems.
+    Warn = 2,
+    /// Useful information.
+    Info = 3,
+    /// Debugging information.
+    Debug = 4,
+    /// Very verbose debugging information.
+    Trace = 5,
+}
+
+impl Level {
+    /// Converts a raw value into a level. Returns `None` if `value` is out of
+    /// range.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Off),
+            1 => Some(Self::Error),
+            2 => Some(Self::Warn),
+            3 => Some(Self::Info),
+            4 => Some(Self::Debug),
+            5 => Some(Self::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the current log level. The initial level is [`Level::Trace`].
+#[inline]
+pub fn level() -> Level {
+    Level::from_u8(LEVEL.load(Ordering::Relaxed)).unwrap_or(Level::Trace)
+}
+
+/// Sets the current log level.
+#[inline]
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns `true` if messages of the level `level` should be emitted.
+///
+/// The [`print!`](crate::print) family of macros emits at [`Level::Info`],
+/// the [`eprint!`](crate::eprint) family at [`Level::Error`], and
+/// [`dbg!`](crate::dbg) at [`Level::Debug`]. The level can also be changed by
+/// the host with the [`CMD_SET_LEVEL`](super::control::CMD_SET_LEVEL) command.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::{log, log::Level};
+///
+/// log::set_level(Level::Warn);
+/// assert!(log::level_enabled(Level::Error));
+/// assert!(!log::level_enabled(Level::Info));
+/// log::set_level(Level::Trace);
+/// ```
+#[inline]
+pub fn level_enabled(level: Level) -> bool {
+    level != Level::Off && level <= self::level()
+}
@@ -10,6 +10,9 @@
 /// Use `print!` only for the primary output of your program. Use [`eprint!`]
 /// instead to print error and progress messages.
 ///
+/// The message is emitted only if the [log level](crate::log::level) enables
+/// [`Level::Info`](crate::log::Level::Info).
+///
 /// # Examples
 ///
 /// ```
@@ -32,12 +35,16 @@
 #[macro_export]
 macro_rules! print {
     ($str:expr) => {
-        if $crate::log::stdout().is_enabled() {
+        if $crate::log::level_enabled($crate::log::Level::Info)
+            && $crate::log::stdout().is_enabled()
+        {
             $crate::log::write_str($crate::log::STDOUT_PORT, $str);
         }
     };
     ($($arg:tt)*) => {
-        if $crate::log::stdout().is_enabled() {
+        if $crate::log::level_enabled($crate::log::Level::Info)
+            && $crate::log::stdout().is_enabled()
+        {
             $crate::log::write_fmt($crate::log::STDOUT_PORT, format_args!($($arg)*));
         }
     };
@@ -81,6 +88,9 @@ macro_rules! println {
 /// Use `eprint!` only for error and progress messages. Use `print!` instead for
 /// the primary output of your program.
 ///
+/// The message is emitted only if the [log level](crate::log::level) enables
+/// [`Level::Error`](crate::log::Level::Error).
+///
 /// # Examples
 ///
 /// ```
@@ -89,12 +99,16 @@ macro_rules! println {
 #[macro_export]
 macro_rules! eprint {
     ($str:expr) => {
-        if $crate::log::stderr().is_enabled() {
+        if $crate::log::level_enabled($crate::log::Level::Error)
+            && $crate::log::stderr().is_enabled()
+        {
             $crate::log::write_str($crate::log::STDERR_PORT, $str);
         }
     };
     ($($arg:tt)*) => {
-        if $crate::log::stderr().is_enabled() {
+        if $crate::log::level_enabled($crate::log::Level::Error)
+            && $crate::log::stderr().is_enabled()
+        {
             $crate::log::write_fmt($crate::log::STDERR_PORT, format_args!($($arg)*));
         }
     };
@@ -139,6 +153,10 @@ macro_rules! eprintln {
 /// does not implement `Copy` and you don't want to give up ownership, you can
 /// instead borrow with `dbg!(&expr)` for some expression `expr`.
 ///
+/// The value is printed only if the [log level](crate::log::level) enables
+/// [`Level::Debug`](crate::log::Level::Debug). The expression is evaluated
+/// regardless of the level.
+///
 /// # Examples
 ///
 /// ```
@@ -150,12 +168,22 @@ macro_rules! eprintln {
 #[macro_export]
 macro_rules! dbg {
     () => {
-        $crate::eprintln!("[{}:{}]", file!(), line!());
+        if $crate::log::level_enabled($crate::log::Level::Debug) {
+            $crate::eprintln!("[{}:{}]", file!(), line!());
+        }
     };
     ($val:expr) => {
         match $val {
             tmp => {
-                $crate::eprintln!("[{}:{}] {} = {:#?}", file!(), line!(), stringify!($val), &tmp);
+                if $crate::log::level_enabled($crate::log::Level::Debug) {
+                    $crate::eprintln!(
+                        "[{}:{}] {} = {:#?}",
+                        file!(),
+                        line!(),
+                        stringify!($val),
+                        &tmp
+                    );
+                }
                 tmp
             }
         }
@@ -4,14 +4,28 @@
 //! standard library. This is a facade module. Concrete output implementation
 //! should be provided by downstream crates.
 //!
+//! Depending on the transport, bytes which don't fit into the transport buffer
+//! may be dropped. Tasks emitting large amounts of data can check
+//! [`free_space`], or use the [`write_all`] and [`flush_async`] futures, which
+//! wait for the transport buffer to drain without blocking the thread.
+//!
+//! The debug probe can also send commands back to the target through the
+//! [`control`] channel, for example to mute a port or to change the [`level`].
+//!
 //! Reserved ports:
 //!
 //! * `0` - standard output
 //! * `1` - standard error
+//! * `29` - register trace
+//! * `30` - event trace
 //! * `31` - heap trace
 
 #![cfg_attr(feature = "std", allow(unreachable_code, unused_variables))]
 
+pub mod control;
+
+mod flush;
+mod level;
 mod macros;
 mod port;
 
@@ -41,7 +55,11 @@ mod port;
 #[doc(inline)]
 pub use drone_core_macros::log_baud_rate as baud_rate;
 
-pub use self::port::Port;
+pub use self::{
+    flush::{flush_async, free_space, wake_flushed, write_all, Flush, WriteAll},
+    level::{level, level_enabled, set_level, Level},
+    port::Port,
+};
 
 use core::{fmt, fmt::Write};
 
@@ -52,6 +70,8 @@ extern "C" {
     pub fn drone_log_write_u16(port: u8, value: u16);
     pub fn drone_log_write_u32(port: u8, value: u32);
     pub fn drone_log_flush();
+    pub fn drone_log_free_space() -> usize;
+    pub fn drone_log_is_flushed() -> bool;
 }
 
 /// Number of ports.
@@ -63,6 +83,12 @@ pub const STDOUT_PORT: u8 = 0;
 /// Port number of the standard error stream.
 pub const STDERR_PORT: u8 = 1;
 
+/// Port number of the register trace stream.
+pub const REGTRACE_PORT: u8 = 29;
+
+/// Port number of the event trace stream.
+pub const EVTRACE_PORT: u8 = 30;
+
 /// Port number of the heap trace stream.
 pub const HEAPTRACE_PORT: u8 = 31;
 
@@ -123,6 +149,8 @@ pub fn write_fmt(port: u8, args: fmt::Arguments<'_>) {
 /// This function is a no-op if no debug probe is connected and listening.
 #[inline]
 pub fn flush() {
+    #[cfg(feature = "sim")]
+    crate::sim::log_flush();
     #[cfg(feature = "std")]
     return;
     unsafe { drone_log_flush() };
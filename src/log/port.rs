@@ -25,9 +25,16 @@ impl Port {
     }
 
     /// Returns `true` if the debug probe is connected and listening to the
-    /// `port` stream.
+    /// `port` stream, and the port is not muted by the [`control`] channel.
+    ///
+    /// [`control`]: super::control
     #[inline]
     pub fn is_enabled(self) -> bool {
+        if super::control::is_muted(self.0) {
+            return false;
+        }
+        #[cfg(feature = "sim")]
+        return crate::sim::log_is_enabled(self.0);
         #[cfg(feature = "std")]
         return false;
         let Self(port) = self;
@@ -41,6 +48,8 @@ impl Port {
     /// atomic byte sequences.
     #[inline]
     pub fn write_bytes(self, bytes: &[u8]) -> Self {
+        #[cfg(feature = "sim")]
+        crate::sim::log_write(self.0, bytes);
         #[cfg(feature = "std")]
         return self;
         let Self(port) = self;
@@ -71,6 +80,8 @@ impl Write for Port {
 
 impl PortWrite for u8 {
     fn port_write(port: u8, value: Self) {
+        #[cfg(feature = "sim")]
+        crate::sim::log_write(port, &value.to_be_bytes());
         #[cfg(feature = "std")]
         return;
         unsafe { drone_log_write_u8(port, value) };
@@ -79,6 +90,8 @@ impl PortWrite for u8 {
 
 impl PortWrite for u16 {
     fn port_write(port: u8, value: Self) {
+        #[cfg(feature = "sim")]
+        crate::sim::log_write(port, &value.to_be_bytes());
         #[cfg(feature = "std")]
         return;
         unsafe { drone_log_write_u16(port, value) };
@@ -87,6 +100,8 @@ impl PortWrite for u16 {
 
 impl PortWrite for u32 {
     fn port_write(port: u8, value: Self) {
+        #[cfg(feature = "sim")]
+        crate::sim::log_write(port, &value.to_be_bytes());
         #[cfg(feature = "std")]
         return;
         unsafe { drone_log_write_u32(port, value) };
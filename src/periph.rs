@@ -221,6 +221,358 @@
 //! {
 //! }
 //! ```
+//!
+//! # Composing Peripherals
+//!
+//! A singular peripheral can include registers and fields from any number of
+//! blocks. In addition, it can aggregate whole peripherals defined elsewhere,
+//! for example a generic UART peripheral together with its DMA channel and
+//! its clock-enable bit. Such an item has the form
+//! `ident: Type = extraction_macro_path;`:
+//!
+//! ```text
+//! periph::singular! {
+//!     pub macro periph_uart4_dma;
+//!     pub struct Uart4DmaPeriph;
+//!     crate;
+//!     crate;
+//!
+//!     uart: UartPeriph<Uart4> = periph_uart4;
+//!     dma: DmaChPeriph<Dma2Ch5> = periph_dma2_ch5;
+//!
+//!     RCC {
+//!         AHB1ENR { DMA2EN; }
+//!     }
+//! }
+//! ```
+//!
+//! # Partial Extraction
+//!
+//! Extraction macros move only the listed register and field tokens out of
+//! the register index. The rest of the index stays available, including the
+//! remaining fields of shared registers. Therefore a single hardware block can
+//! be split between several drivers by defining several singular peripherals
+//! over disjoint parts of it:
+//!
+//! ```text
+//! periph::singular! {
+//!     pub macro periph_tim1_pwm;
+//!     pub struct Tim1PwmPeriph;
+//!     crate;
+//!     crate;
+//!
+//!     TIM1 { CCMR1_Output; CCR1; CCR2; }
+//! }
+//!
+//! periph::singular! {
+//!     pub macro periph_tim1_capture;
+//!     pub struct Tim1CapturePeriph;
+//!     crate;
+//!     crate;
+//!
+//!     TIM1 { CCMR2_Input; CCR3; CCR4; }
+//! }
+//!
+//! let pwm = periph_tim1_pwm!(reg);
+//! let capture = periph_tim1_capture!(reg);
+//! // Other TIM1 registers are still available as `reg.tim1_*`.
+//! ```
+//!
+//! Taking the same token twice is rejected by the borrow checker.
+//!
+//! # Releasing Peripherals
+//!
+//! Every extraction macro has a second form, which decomposes a peripheral
+//! struct and puts its tokens back into the register index. The index binding
+//! must be mutable. This allows reconfiguration flows, like switching a set of
+//! pins from one driver to another, without unsafe re-creation of tokens:
+//!
+//! ```text
+//! let mut reg = reg;
+//! let uart = periph_uart4!(reg);
+//! // ...
+//! periph_uart4!(release uart => reg);
+//! let spi = periph_spi3!(reg);
+//! ```
+//!
+//! After all peripherals are released, the register index is whole again.
+//!
+//! # Instance-Generic Drivers
+//!
+//! The `UartMap` trait generated by `periph!` captures all register and field
+//! types of the peripheral as associated types. Concrete peripherals implement
+//! it with `periph::map!`. Therefore a driver can be written once for the
+//! generic `UartPeriph<T>` and reused for every instance:
+//!
+//! ```text
+//! use crate::uart::{traits::*, UartMap, UartPeriph};
+//!
+//! pub struct UartDrv<T: UartMap> {
+//!     periph: UartPeriph<T>,
+//! }
+//!
+//! impl<T: UartMap> UartDrv<T> {
+//!     pub fn init(periph: UartPeriph<T>) -> Self {
+//!         let mut val = periph.uart_cr1.load().val();
+//!         periph.uart_cr1.cmie().set(&mut val);
+//!         periph.uart_cr1.store_val(val);
+//!         Self { periph }
+//!     }
+//!
+//!     pub fn free(self) -> UartPeriph<T> {
+//!         self.periph
+//!     }
+//! }
+//!
+//! let uart4 = UartDrv::init(periph_uart4!(reg));
+//! ```
+//!
+//! The `traits` module generated alongside contains all per-register traits,
+//! and should be glob-imported to call methods on the associated types.
+//!
+//! # Capabilities
+//!
+//! Items placed inside the `periph!` trait body are copied into the generated
+//! trait, and implemented in the `impl` block of `periph::map!`. Associated
+//! constants are a natural way to describe optional capabilities of each
+//! instance. Defaults can be given for the common case:
+//!
+//! ```text
+//! periph! {
+//!     pub trait UartMap {
+//!         const HAS_DMA: bool = false;
+//!         const HAS_HW_FLOWCTL: bool = false;
+//!         const FIFO_DEPTH: usize;
+//!     }
+//!     // ...
+//! }
+//!
+//! periph::map! {
+//!     // ...
+//!     impl UartMap for Usart1 {
+//!         const HAS_DMA: bool = true;
+//!         const FIFO_DEPTH: usize = 8;
+//!     }
+//!     // ...
+//! }
+//!
+//! impl<T: UartMap> UartDrv<T> {
+//!     pub fn write(&mut self, buf: &[u8]) {
+//!         if T::HAS_DMA && buf.len() > T::FIFO_DEPTH {
+//!             // ...
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The conditions are resolved at compile time for each instance, and the
+//! unused branches are removed.
+//!
+//! # Clock Gate
+//!
+//! A single-bit field of a `Shared` register can be marked with `Clock` in
+//! `periph!`. The generic peripheral struct will implement [`PeriphClock`]
+//! trait, with `enable_clock`, `disable_clock`, and `clock_guard` methods. The
+//! latter returns an RAII guard, which restores the previous clock state when
+//! dropped:
+//!
+//! ```text
+//! periph! {
+//!     pub trait UartMap {}
+//!     pub struct UartPeriph;
+//!
+//!     RCC {
+//!         APBENR {
+//!             0x20 RwReg Shared;
+//!             UARTEN { RwRwRegFieldBit Clock }
+//!         }
+//!     }
+//! }
+//!
+//! fn transmit<T: UartMap>(uart: &UartPeriph<T>) {
+//!     let _clock = uart.clock_guard();
+//!     // ...
+//! }
+//! ```
+//!
+//! The register must be 32-bit wide. The bit is modified with exclusive
+//! accesses through the [`arch`](crate::arch) hooks, or inside a
+//! [`critical`](crate::critical) section when the `sim` or `mock` feature is
+//! enabled.
+//!
+//! # Register Dump
+//!
+//! A generic peripheral struct has a `dump` method, which reads all its owned
+//! readable registers (marked with `RwReg` or `RoReg`), and passes their names
+//! and raw values to a closure. It is handy during bring-up:
+//!
+//! ```text
+//! uart.dump(|name, value| println!("{} = {:#010x}", name, value));
+//! // UART_CR1 = 0x0000000d
+//! // UART_ISR = 0x000000c0
+//! ```
+//!
+//! # Register Table
+//!
+//! A generic peripheral struct declared as `pub struct UartPeriph: RegTable;`
+//! gets a `REG_TABLE` associated constant, which lists the
+//! [`RegLayout`](crate::reg::RegLayout) of each non-optional register used by
+//! the peripheral. The table comes straight from the register definitions, so
+//! bootloaders, DMA scatter-gather setup, and external test rigs can rely on
+//! it:
+//!
+//! ```text
+//! for reg in UartPeriph::<Uart1>::REG_TABLE {
+//!     println!("{} @ {:#010x} ({} bits)", reg.name, reg.address, reg.size);
+//! }
+//! // USART1_CR1 @ 0x40013800 (32 bits)
+//! // USART1_ISR @ 0x4001381c (32 bits)
+//! ```
+//!
+//! `RegTable` can be combined with `Inventory` as `Inventory + RegTable`.
+//!
+//! # Indexed Instances
+//!
+//! Platform crates usually wrap `periph::map!` into a `macro_rules!` macro
+//! taking the varying parts of an instance. `periph::instances!` invokes such
+//! a macro for each row of a compact table. Besides plain rows, a `for` loop
+//! over an integer range is accepted. Inside a loop, `#N` is replaced with
+//! the current index, and `IDENT#N` with a concatenated identifier:
+//!
+//! ```
+//! use drone_core::periph;
+//!
+//! macro_rules! uart_irq {
+//!     ($name:ident, $irq:expr) => {
+//!         const $name: u32 = $irq;
+//!     };
+//! }
+//!
+//! periph::instances! {
+//!     uart_irq!;
+//!     for N in 1..=3 {
+//!         (USART#N, 36 + #N);
+//!     }
+//!     (LPUART1, 70);
+//! }
+//!
+//! assert_eq!(USART1, 37);
+//! assert_eq!(USART3, 39);
+//! assert_eq!(LPUART1, 70);
+//! ```
+//!
+//! # Object-Safe Handles
+//!
+//! Generic peripheral structs of different variants are different types. When
+//! full monomorphization is impractical, e.g. in a registry or a debug shell,
+//! a generic peripheral struct can be declared with the `Dyn` option as `pub
+//! struct UartPeriph: Dyn;` (it can be combined with the other options, e.g.
+//! `Inventory + Dyn`). The struct will implement [`PeriphDyn`], and `periph!`
+//! will additionally generate:
+//!
+//! * `UartPeriphDyn` - an object-safe capability trait, which extends
+//!   [`PeriphDyn`], and [`PeriphClock`] if the peripheral has a `Clock` field.
+//!   With `RegTable`, it also has a `reg_table` method;
+//! * `UartPeriphRef<'a>` - a wrapper of `&'a mut dyn UartPeriphDyn`, which
+//!   can be created from a mutable reference to any variant, and gives a
+//!   single nameable type for all of them.
+//!
+//! ```text
+//! let periphs = [UartPeriphRef::from(&mut uart4), UartPeriphRef::from(&mut uart5)];
+//! for periph in &periphs {
+//!     let _clock = periph.clock_guard();
+//!     periph.dump_dyn(&mut |name, value| println!("{} = {:#x}", name, value));
+//! }
+//! ```
+//!
+//! Capabilities specific to a driver can be layered the same way: define an
+//! object-safe trait (e.g. `ByteSink`), implement it for the generic driver
+//! struct `UartDrv<T: UartMap>`, and store the drivers as `&mut dyn ByteSink`.
+//!
+//! # Field Aliases
+//!
+//! Field names in `periph!` are canonical, and don't have to match the
+//! underlying register fields. In `periph::map!`, the first identifier inside
+//! the field braces names the actual field. When a vendor renamed a bit between
+//! chip families, the field can be listed several times under mutually
+//! exclusive `#[cfg]` attributes, and a shared driver will see a single
+//! `TXE` field regardless of the target:
+//!
+//! ```text
+//! periph::map! {
+//!     // ...
+//!     UART {
+//!         UART1;
+//!         SR {
+//!             ISR Shared;
+//!             #[cfg(drone_mcu = "stm32f4")]
+//!             TXE { TXE }
+//!             #[cfg(drone_mcu = "stm32l4")]
+//!             TXE { TXFNF }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! An owned register must have all its underlying fields mapped, therefore
+//! per-family aliasing is most convenient with `Shared` registers.
+//!
+//! # Inventory Items
+//!
+//! A generic peripheral struct declared as `pub struct SpiPeriph: Inventory;`
+//! implements [`inventory::Item`](crate::inventory::Item). It can be wrapped
+//! into an [`Inventory`](crate::inventory::Inventory), and lent to different
+//! drivers in turns with scoped guards. If the peripheral has a `Clock` field,
+//! the guard disables the clock on drop:
+//!
+//! ```text
+//! let mut spi = Inventory::new(periph_spi1!(reg));
+//! {
+//!     let spi = Inventory::guard(&mut spi);
+//!     spi.enable_clock();
+//!     display.refresh(&spi);
+//! } // the clock is disabled here
+//! {
+//!     let spi = Inventory::guard(&mut spi);
+//!     spi.enable_clock();
+//!     flash.read(&spi, &mut buf);
+//! }
+//! let spi = Inventory::free(spi);
+//! ```
+//!
+//! # Conditional Compilation
+//!
+//! A register or a field in `periph!` and `periph::map!` can be preceded by a
+//! `#[cfg(key = "value")]` or `#[cfg(any(key = "value", ...))]` attribute. The
+//! register or field will be omitted from the generated traits, the peripheral
+//! struct, and the extraction macro, when the condition is false. This way a
+//! single peripheral definition can serve a whole chip family:
+//!
+//! ```text
+//! periph! {
+//!     pub trait UartMap {}
+//!     pub struct UartPeriph;
+//!
+//!     UART {
+//!         CR1 {
+//!             0x20 RwReg;
+//!             CMIE { RwRwRegFieldBit }
+//!             #[cfg(any(drone_mcu = "stm32l4r5", drone_mcu = "stm32l4s9"))]
+//!             FIFOEN { RwRwRegFieldBit }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The same condition should be repeated in each `periph::map!` definition.
+//! `Option` marks registers and fields, which are present in some instances of
+//! a single chip, while `#[cfg(...)]` marks those present in some chips only.
+
+/// Invokes a peripheral-defining macro for each row of a table.
+///
+/// See [the module level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::periph_instances as instances;
 
 /// Implements the generic peripheral.
 ///
@@ -233,3 +585,110 @@ pub use drone_core_macros::periph_map as map;
 /// See [the module level documentation](self) for details.
 #[doc(inline)]
 pub use drone_core_macros::periph_singular as singular;
+
+/// A peripheral with a clock gate.
+///
+/// Implemented by generic peripheral structs, which have a field marked with
+/// `Clock`. See [the module level documentation](self) for details.
+pub trait PeriphClock {
+    /// Enables the peripheral clock.
+    ///
+    /// The clock enable write is completed with
+    /// [`arch::sync_barrier`](crate::arch::sync_barrier) before returning, so
+    /// the peripheral registers can be accessed immediately.
+    fn enable_clock(&self);
+
+    /// Disables the peripheral clock.
+    fn disable_clock(&self);
+
+    /// Returns `true` if the peripheral clock is enabled.
+    fn is_clock_enabled(&self) -> bool;
+
+    /// Enables the peripheral clock, and returns a guard, which disables the
+    /// clock when dropped, unless it was already enabled.
+    #[inline]
+    fn clock_guard(&self) -> ClockGuard<'_, Self>
+    where
+        Self: Sized,
+    {
+        let enabled = self.is_clock_enabled();
+        self.enable_clock();
+        ClockGuard { periph: self, enabled }
+    }
+}
+
+impl<'a> dyn PeriphClock + 'a {
+    /// Enables the peripheral clock, and returns a guard, which disables the
+    /// clock when dropped, unless it was already enabled.
+    #[inline]
+    pub fn clock_guard(&self) -> ClockGuard<'_, dyn PeriphClock + 'a> {
+        let enabled = self.is_clock_enabled();
+        self.enable_clock();
+        ClockGuard { periph: self, enabled }
+    }
+}
+
+/// An object-safe view of a generic peripheral struct.
+///
+/// Implemented by generic peripheral structs declared with the `Dyn` option.
+/// Allows to store heterogeneous peripherals behind `&dyn PeriphDyn`. See [the
+/// module level documentation](self) for details.
+pub trait PeriphDyn {
+    /// Returns the type name of the peripheral variant.
+    fn variant_name(&self) -> &'static str;
+
+    /// Reads all readable registers owned by the peripheral, and passes their
+    /// names and values to `f`.
+    fn dump_dyn(&self, f: &mut dyn FnMut(&'static str, u64));
+}
+
+/// An RAII guard of a peripheral clock. The previous clock state is restored
+/// when this structure is dropped.
+///
+/// This structure is created by [`PeriphClock::clock_guard`].
+#[must_use = "if unused the clock will immediately be disabled"]
+pub struct ClockGuard<'a, T: PeriphClock + ?Sized> {
+    periph: &'a T,
+    enabled: bool,
+}
+
+impl<T: PeriphClock + ?Sized> Drop for ClockGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.enabled {
+            self.periph.disable_clock();
+        }
+    }
+}
+
+#[doc(hidden)]
+#[inline]
+pub unsafe fn modify_clock(address: usize, mask: u32, enable: bool) {
+    let update = |bits: u32| if enable { bits | mask } else { bits & !mask };
+    #[cfg(any(feature = "sim", feature = "mock"))]
+    crate::critical::with(|_| {
+        crate::reg::store_part(address, update(crate::reg::load_part::<u32>(address)));
+    });
+    #[cfg(not(any(feature = "sim", feature = "mock")))]
+    crate::arch::modify_exclusive(address as *mut u32, update);
+    if enable {
+        // The peripheral can be accessed right after this call, so the clock
+        // enable write must complete first.
+        crate::arch::sync_barrier();
+    }
+}
+
+mod compile_tests {
+    //! ```compile_fail
+    //! # #![feature(proc_macro_hygiene)]
+    //! use drone_core::{periph, periph::PeriphDyn};
+    //! periph! {
+    //!     pub trait FooMap {}
+    //!     pub struct FooPeriph;
+    //! }
+    //! fn erase<T: FooMap>(periph: &FooPeriph<T>) -> &dyn PeriphDyn {
+    //!     periph
+    //! }
+    //! fn main() {}
+    //! ```
+}
@@ -0,0 +1,134 @@
+use super::{
+    field::{RRRegField, RegFieldBit, WWRegField},
+    tag::RegTag,
+    volatile::{read_volatile, write_volatile},
+    RReg, Reg, WReg,
+};
+use core::marker::PhantomData;
+
+/// The size of a bit-band region.
+const REGION_SIZE: usize = 0x0010_0000;
+
+/// The offset of the alias region from its bit-band region.
+const ALIAS_OFFSET: usize = 0x0200_0000;
+
+/// Register located in a bit-band region.
+///
+/// Each bit of a bit-band region is mapped to a word in the corresponding
+/// alias region, so a single bit can be read or written with one memory
+/// access. For each single-bit field of such register, [`reg!`](crate::reg)
+/// generates a `foo_bb()` accessor, which returns the [`BitBand`] alias of the
+/// field.
+///
+/// [`reg!`](crate::reg) checks at compile time that the register lies inside a
+/// bit-band region, and that it has no write-one-to-clear fields. The hardware
+/// performs a bit-band write as a read-modify-write of the whole word, which
+/// would clear pending flags.
+pub trait RegBitBand<T: RegTag>: Reg<T> {}
+
+/// The bit-band alias of a single-bit register field.
+///
+/// Writes through the alias are atomic, so they are available for all tags.
+///
+/// This `struct` is created by the autogenerated `foo_bb()` methods of
+/// [`RegBitBand`] register tokens.
+pub struct BitBand<'a, T: RegTag, R: RegFieldBit<T>> {
+    field: &'a R,
+    _tag: PhantomData<T>,
+}
+
+/// Returns the bit-band alias address of the bit `bit` counting from
+/// `address`, for the Cortex-M SRAM and peripheral bit-band regions.
+///
+/// # Panics
+///
+/// If the bit is outside of the bit-band regions.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::reg::bit_band_alias;
+///
+/// assert_eq!(bit_band_alias(0x4002_1000, 0), 0x4242_0000);
+/// assert_eq!(bit_band_alias(0x2000_0300, 2), 0x2200_6008);
+/// ```
+pub fn bit_band_alias(address: usize, bit: usize) -> usize {
+    let base = address & !(REGION_SIZE - 1);
+    assert!(
+        (base == 0x2000_0000 || base == 0x4000_0000) && address - base + bit / 8 < REGION_SIZE,
+        "address {:#x} is outside of the bit-band regions",
+        address
+    );
+    alias(address, bit)
+}
+
+/// Computes the alias address without checking the region bounds.
+#[inline]
+fn alias(address: usize, bit: usize) -> usize {
+    let base = address & !(REGION_SIZE - 1);
+    base + ALIAS_OFFSET + (address - base) * 32 + bit * 4
+}
+
+impl<'a, T: RegTag, R: RegFieldBit<T>> BitBand<'a, T, R>
+where
+    R::Reg: RegBitBand<T>,
+{
+    /// Creates a new bit-band alias of the field token `field`.
+    #[inline]
+    pub fn new(field: &'a R) -> Self {
+        Self { field, _tag: PhantomData }
+    }
+
+    /// Returns the field token.
+    #[inline]
+    pub fn field(&self) -> &'a R {
+        self.field
+    }
+
+    /// Returns the alias address of the field.
+    #[inline]
+    pub fn address(&self) -> usize {
+        // The register bounds are checked by `reg!`.
+        alias(R::Reg::ADDRESS, R::OFFSET)
+    }
+
+    /// Reads the field bit through the alias.
+    #[inline]
+    pub fn read(&self) -> bool
+    where
+        R: RRRegField<T>,
+        R::Reg: RReg<T>,
+    {
+        unsafe { read_volatile(self.address() as *const u32) & 1 != 0 }
+    }
+
+    /// Writes `value` to the field bit through the alias.
+    #[inline]
+    pub fn write(&self, value: bool)
+    where
+        R: WWRegField<T>,
+        R::Reg: WReg<T>,
+    {
+        unsafe { write_volatile(self.address() as *mut u32, u32::from(value)) };
+    }
+
+    /// Sets the field bit through the alias.
+    #[inline]
+    pub fn set(&self)
+    where
+        R: WWRegField<T>,
+        R::Reg: WReg<T>,
+    {
+        self.write(true);
+    }
+
+    /// Clears the field bit through the alias.
+    #[inline]
+    pub fn clear(&self)
+    where
+        R: WWRegField<T>,
+        R::Reg: WReg<T>,
+    {
+        self.write(false);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::critical::{self, CriticalSection};
+
+/// Executes the closure `f` with the register tokens `regs` inside a critical
+/// section, and returns the closure result.
+///
+/// `regs` is usually a tuple of mutable references to register tokens. No
+/// interrupt can happen between the accesses made by `f`, which is required
+/// for sequences like "write two registers with no interrupt in between". The
+/// critical section is entered with the platform hooks described in
+/// [`critical`](crate::critical).
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(proc_macro_hygiene)]
+/// use drone_core::{reg, reg::prelude::*, token::Token};
+///
+/// drone_core::reg! {
+///     pub mod TIM2 ARR;
+///     0x4000_002C 0x20 0x0000_FFFF
+///     RReg WReg;
+///     ARR { 0 16 RRRegField WWRegField }
+/// }
+///
+/// drone_core::reg! {
+///     pub mod TIM2 CCR1;
+///     0x4000_0034 0x20 0x0000_0000
+///     RReg WReg;
+///     CCR1 { 0 16 RRRegField WWRegField }
+/// }
+///
+/// # fn main() {
+/// # fn check() {
+/// let mut arr = unsafe { tim2_arr::Reg::<Urt>::take() };
+/// let mut ccr1 = unsafe { tim2_ccr1::Reg::<Urt>::take() };
+/// reg::critical((&mut arr, &mut ccr1), |(arr, ccr1), _cs| {
+///     arr.store(|r| r.write_arr(1000));
+///     ccr1.store(|r| r.write_ccr1(500));
+/// });
+/// # }
+/// # }
+/// ```
+#[inline]
+pub fn critical<G, F, O>(regs: G, f: F) -> O
+where
+    F: FnOnce(G, CriticalSection<'_>) -> O,
+{
+    critical::with(|cs| f(regs, cs))
+}
@@ -0,0 +1,51 @@
+use super::RegLayout;
+use core::fmt;
+
+/// An object-safe interface to a register token.
+///
+/// Every register token defined with [`reg!`](crate::reg) implements this
+/// trait, so registers of different types can be accessed through `&dyn
+/// DynReg`, e.g. by a firmware shell, which pokes registers by name. Register
+/// index structs marked with `#[dyn_reg]` provide a registry of their
+/// registers, see [the module level documentation](crate::reg).
+///
+/// The raw accesses bypass the register fields, so write-one-to-clear and
+/// read-to-clear side-effects are not masked.
+pub trait DynReg {
+    /// Returns the register layout, which is [`Reg::LAYOUT`](super::Reg::LAYOUT)
+    /// of the register token.
+    fn layout(&self) -> RegLayout;
+
+    /// Reads the raw bits from the register memory.
+    ///
+    /// # Errors
+    ///
+    /// If the register is not readable.
+    fn load_raw(&self) -> Result<u64, DynRegError>;
+
+    /// Writes the lower [`size`](RegLayout::size) bits of `bits` into the
+    /// register memory.
+    ///
+    /// # Errors
+    ///
+    /// If the register is not writable.
+    fn store_raw(&self, bits: u64) -> Result<(), DynRegError>;
+}
+
+/// The error type returned by [`DynReg`] accesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DynRegError {
+    /// The register is not readable.
+    NotReadable,
+    /// The register is not writable.
+    NotWritable,
+}
+
+impl fmt::Display for DynRegError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotReadable => write!(f, "register is not readable"),
+            Self::NotWritable => write!(f, "register is not writable"),
+        }
+    }
+}
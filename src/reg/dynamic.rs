@@ -0,0 +1,152 @@
+use super::{
+    load_rmw,
+    tag::{RegOwned, RegTag},
+    volatile::{read_volatile, write_volatile},
+    RReg, Reg, RegHold, RegRef, WReg,
+};
+use crate::bitfield::Bitfield;
+use core::marker::PhantomData;
+
+/// A register token of a peripheral instance, which base address is known only
+/// at run-time.
+///
+/// Some peripherals are instantiated several times at addresses discovered at
+/// run-time, e.g. from a device tree or a PCI-like enumeration. Such registers
+/// are declared with [`reg!`](crate::reg) once, with the register offset
+/// inside the block in place of the address. `RegDyn` adds the block base
+/// address to the offset, while the fields stay compile-time.
+///
+/// The wrapped token is never handed out, because its own accessors would
+/// treat the offset as an absolute address.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(proc_macro_hygiene)]
+/// use drone_core::{
+///     reg::{prelude::*, RegDyn},
+///     token::Token,
+/// };
+///
+/// drone_core::reg! {
+///     pub mod UART CR;
+///     0x0C 0x20 0x0000_0000
+///     RReg WReg;
+///     UE { 13 1 RRRegField WWRegField }
+/// }
+///
+/// # fn main() {
+/// # fn check(base: usize) {
+/// let cr = unsafe { RegDyn::new(uart_cr::Reg::<Urt>::take(), base) };
+/// assert_eq!(cr.address(), base + 0x0C);
+/// # }
+/// # }
+/// ```
+pub struct RegDyn<T: RegTag, R: Reg<T>> {
+    reg: R,
+    base: usize,
+    _tag: PhantomData<T>,
+}
+
+impl<T: RegTag, R: Reg<T>> RegDyn<T, R> {
+    /// Binds the register token `reg` to the block at `base`.
+    ///
+    /// # Safety
+    ///
+    /// `base` must be the base address of a block instance, which has the
+    /// register at offset [`Reg::ADDRESS`]. No other token may access the same
+    /// register of the same instance. `reg` must not have been used to access
+    /// the memory on its own.
+    #[inline]
+    pub unsafe fn new(reg: R, base: usize) -> Self {
+        Self { reg, base, _tag: PhantomData }
+    }
+
+    /// Returns the block base address.
+    #[inline]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Returns the register address in memory.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.base + R::ADDRESS
+    }
+
+    /// Reads the raw bits from the register memory.
+    #[inline]
+    pub fn load_bits(&self) -> <R::Val as Bitfield>::Bits
+    where
+        R: RReg<T>,
+    {
+        unsafe { read_volatile(self.address() as *const <R::Val as Bitfield>::Bits) }
+    }
+
+    /// Reads the value from the register memory to the opaque value type.
+    #[inline]
+    pub fn load_val(&self) -> R::Val
+    where
+        R: RReg<T>,
+    {
+        unsafe { R::val_from(self.load_bits()) }
+    }
+
+    /// Reads the value from the register memory to the exposed value type.
+    #[inline]
+    pub fn load<'a>(&'a self) -> <R as RegRef<'a, T>>::Hold
+    where
+        R: RReg<T> + RegRef<'a, T>,
+    {
+        self.reg.hold(self.load_val())
+    }
+
+    /// Writes `bits` into the register memory.
+    #[inline]
+    pub fn store_bits(&mut self, bits: <R::Val as Bitfield>::Bits)
+    where
+        R: WReg<T>,
+    {
+        unsafe { write_volatile(self.address() as *mut <R::Val as Bitfield>::Bits, bits) };
+    }
+
+    /// Writes an opaque value `val` into the register memory.
+    #[inline]
+    pub fn store_val(&mut self, val: R::Val)
+    where
+        R: WReg<T>,
+    {
+        self.store_bits(val.bits());
+    }
+
+    /// Passes the opaque reset value to the closure `f`, then writes the result
+    /// of the closure into the register memory.
+    #[inline]
+    pub fn store<'a, F>(&'a mut self, f: F)
+    where
+        R: WReg<T> + RegRef<'a, T>,
+        F: for<'b> FnOnce(&'b mut <R as RegRef<'a, T>>::Hold) -> &'b mut <R as RegRef<'a, T>>::Hold,
+    {
+        let address = self.address();
+        let bits = f(&mut self.reg.default()).val().bits();
+        unsafe { write_volatile(address as *mut <R::Val as Bitfield>::Bits, bits) };
+    }
+
+    /// Reads the value from the register memory and passes it to the closure
+    /// `f`, then writes the result of the closure back into the register
+    /// memory.
+    ///
+    /// This operation is not atomic, so it is available only for affine tags.
+    #[inline]
+    pub fn modify<'a, F>(&'a mut self, f: F)
+    where
+        T: RegOwned,
+        R: RReg<T> + WReg<T> + RegRef<'a, T>,
+        F: for<'b> FnOnce(&'b mut <R as RegRef<'a, T>>::Hold) -> &'b mut <R as RegRef<'a, T>>::Hold,
+    {
+        let address = self.address();
+        let val = unsafe { load_rmw::<T, R>(address) };
+        let bits = f(&mut self.reg.hold(val)).val().bits() & !R::W1C_MASK;
+        unsafe { write_volatile(address as *mut <R::Val as Bitfield>::Bits, bits) };
+    }
+}
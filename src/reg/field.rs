@@ -5,12 +5,15 @@
 use crate::{
     bitfield::{Bitfield, Bits},
     reg::{
-        tag::{Crt, RegAtomic, RegTag, Srt, Urt},
+        load_rmw,
+        marker::PureReadReg,
+        tag::{Crt, RegAtomic, RegOwned, RegTag, Srt, Urt},
+        volatile::{read_volatile, write_volatile},
         RReg, Reg, WReg, WoReg,
     },
     token::Token,
 };
-use core::ptr::{read_volatile, write_volatile};
+use core::{marker::PhantomData, ops::Deref};
 
 /// The base trait for a field token of a memory-mapped register.
 pub trait RegField<T: RegTag>: Token + Sync {
@@ -59,6 +62,21 @@ pub trait RegField<T: RegTag>: Token + Sync {
         unsafe { Self::CRegField::take() }
     }
 
+    /// Converts a copyable field token back into an affine field token.
+    ///
+    /// # Safety
+    ///
+    /// Copyable tokens can be freely duplicated. The caller must ensure that
+    /// no other copies of `field` are used while the returned token is alive.
+    #[inline]
+    unsafe fn from_copy(field: Self::CRegField) -> Self
+    where
+        T: RegOwned,
+    {
+        drop(field);
+        Self::take()
+    }
+
     /// Returns a reference to the synchronized field token.
     #[inline]
     fn as_sync(&self) -> &Self::SRegField
@@ -84,11 +102,7 @@ where
     /// Reads the value from the register memory to the opaque value type.
     #[inline]
     fn load_val(&self) -> <Self::Reg as Reg<T>>::Val {
-        unsafe {
-            Self::Reg::val_from(read_volatile(
-                Self::Reg::ADDRESS as *const <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
-            ))
-        }
+        load_val::<T, Self::Reg>()
     }
 }
 
@@ -116,6 +130,44 @@ where
 {
 }
 
+/// Write-one-to-clear field of writable register.
+///
+/// Such fields are excluded from read-modify-write operations through
+/// [`Reg::W1C_MASK`].
+pub trait W1cRegField<T: RegTag>
+where
+    Self: RegField<T>,
+    Self::Reg: WReg<T>,
+{
+}
+
+/// Read-to-clear field of readable register.
+///
+/// Reading the register clears such fields, so the value obtained by the read
+/// is the only copy left. The trait is used in place of [`RRRegField`]: all
+/// reads of the field, including the accessors of the register
+/// [`Hold`](crate::reg::RegRef::Hold), return the value wrapped into
+/// [`ReadCleared`], which must be used.
+pub trait RcRegField<T: RegTag>
+where
+    Self: RegField<T>,
+    Self::Reg: RReg<T>,
+{
+}
+
+/// Writable field of writable register, which writes have side effects.
+///
+/// Writing such fields triggers hardware actions beyond storing the value.
+/// Such fields are excluded from the values read by read-modify-write
+/// operations through [`Reg::WS_MASK`], so that modifying a sibling field
+/// doesn't repeat the action.
+pub trait WsRegField<T: RegTag>
+where
+    Self: WWRegField<T>,
+    Self::Reg: WReg<T>,
+{
+}
+
 /// Write-only field of write-only register.
 pub trait WoWoRegField<T: RegTag>
 where
@@ -186,6 +238,37 @@ where
     fn toggle_bit(&self);
 }
 
+/// Write-one-to-clear single-bit field of writable register.
+pub trait W1cRegFieldBit<T: RegTag>
+where
+    Self: RegFieldBit<T> + W1cRegField<T>,
+    Self::Reg: WReg<T>,
+{
+    /// Clears the flag by writing a value with only this bit set into the
+    /// register memory.
+    ///
+    /// The operation is a single write without a read, so it doesn't race with
+    /// other threads and doesn't trigger read side effects. All other fields,
+    /// including sibling flags, are written as zeros. To keep the other fields
+    /// of an unsynchronized register intact, use
+    /// [`FieldRef::clear_flag`].
+    fn clear_bit(&self);
+}
+
+/// Read-to-clear single-bit field of readable register.
+pub trait RcRegFieldBit<T: RegTag>
+where
+    Self: RegFieldBit<T> + RcRegField<T>,
+    Self::Reg: RReg<T>,
+{
+    /// Returns `true` if the bit is set in `val`.
+    fn read(&self, val: &<Self::Reg as Reg<T>>::Val) -> ReadCleared<bool>;
+
+    /// Reads the state of the bit from the register memory, which clears the
+    /// field.
+    fn read_clear_bit(&self) -> ReadCleared<bool>;
+}
+
 /// Readable multiple-bit field of readable register.
 pub trait RRRegFieldBits<T: RegTag>
 where
@@ -202,6 +285,23 @@ where
     fn read_bits(&self) -> <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits;
 }
 
+/// Read-to-clear multiple-bit field of readable register.
+pub trait RcRegFieldBits<T: RegTag>
+where
+    Self: RegFieldBits<T> + RcRegField<T>,
+    Self::Reg: RReg<T>,
+{
+    /// Extracts the field bits from `val`.
+    fn read(
+        &self,
+        val: &<Self::Reg as Reg<T>>::Val,
+    ) -> ReadCleared<<<Self::Reg as Reg<T>>::Val as Bitfield>::Bits>;
+
+    /// Reads the value from the register memory and extracts the field bits,
+    /// which clears the field.
+    fn read_clear_bits(&self) -> ReadCleared<<<Self::Reg as Reg<T>>::Val as Bitfield>::Bits>;
+}
+
 /// Writable multiple-bit field of writable register.
 pub trait WWRegFieldBits<T: RegTag>
 where
@@ -214,6 +314,22 @@ where
         val: &mut <Self::Reg as Reg<T>>::Val,
         bits: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
     );
+
+    /// Inverts the field bits in `val`, which are set in `mask`.
+    fn toggle(
+        &self,
+        val: &mut <Self::Reg as Reg<T>>::Val,
+        mask: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+    );
+
+    /// Replaces the field bits in `val`, which are set in `mask`, by the
+    /// corresponding bits of `bits`. Other field bits are left intact.
+    fn update_masked(
+        &self,
+        val: &mut <Self::Reg as Reg<T>>::Val,
+        mask: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+        bits: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+    );
 }
 
 /// Write-only multiple-bit field of write-only register.
@@ -227,6 +343,40 @@ where
     fn write_bits(&self, bits: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits);
 }
 
+/// Multiple-bits register field with enumerated values.
+pub trait RegFieldEnum<T: RegTag>: RegFieldBits<T> {
+    /// The enumeration of the field values.
+    type Enum: Copy;
+
+    /// Converts raw field `bits` into the enumerated value. Returns `None` if
+    /// `bits` doesn't correspond to any of the values.
+    fn enum_from_bits(bits: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits) -> Option<Self::Enum>;
+
+    /// Converts the enumerated `value` into raw field bits.
+    fn enum_into_bits(value: Self::Enum) -> <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits;
+}
+
+/// Readable field with enumerated values of readable register.
+pub trait RRRegFieldEnum<T: RegTag>
+where
+    Self: RegFieldEnum<T> + RRRegFieldBits<T>,
+    Self::Reg: RReg<T>,
+{
+    /// Extracts the field value from `val`. Returns `None` if the field bits
+    /// don't correspond to any of the enumerated values.
+    fn read_enum(&self, val: &<Self::Reg as Reg<T>>::Val) -> Option<Self::Enum>;
+}
+
+/// Writable field with enumerated values of writable register.
+pub trait WWRegFieldEnum<T: RegTag>
+where
+    Self: RegFieldEnum<T> + WWRegFieldBits<T>,
+    Self::Reg: WReg<T>,
+{
+    /// Replaces the field bits in `val` by the bits of `value`.
+    fn write_enum(&self, val: &mut <Self::Reg as Reg<T>>::Val, value: Self::Enum);
+}
+
 impl<T, R> WoWoRegField<T> for R
 where
     T: RegTag,
@@ -278,6 +428,25 @@ where
     }
 }
 
+impl<T, R> RcRegFieldBit<T> for R
+where
+    T: RegTag,
+    R: RegFieldBit<T> + RcRegField<T>,
+    R::Reg: RReg<T>,
+{
+    #[inline]
+    fn read(&self, val: &<Self::Reg as Reg<T>>::Val) -> ReadCleared<bool> {
+        ReadCleared(unsafe {
+            val.read_bit(<<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET))
+        })
+    }
+
+    #[inline]
+    fn read_clear_bit(&self) -> ReadCleared<bool> {
+        RcRegFieldBit::read(self, &load_val::<T, Self::Reg>())
+    }
+}
+
 impl<T, R> WWRegFieldBit<T> for R
 where
     T: RegTag,
@@ -336,6 +505,25 @@ where
     }
 }
 
+impl<T, R> W1cRegFieldBit<T> for R
+where
+    T: RegTag,
+    R: RegFieldBit<T> + W1cRegField<T>,
+    R::Reg: WReg<T>,
+{
+    #[inline]
+    fn clear_bit(&self) {
+        let bit = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(1)
+            << <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+        unsafe {
+            write_volatile(
+                Self::Reg::ADDRESS as *mut <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+                bit,
+            );
+        }
+    }
+}
+
 impl<T, R> RRRegFieldBits<T> for R
 where
     T: RegTag,
@@ -361,6 +549,31 @@ where
     }
 }
 
+impl<T, R> RcRegFieldBits<T> for R
+where
+    T: RegTag,
+    R: RegFieldBits<T> + RcRegField<T>,
+    R::Reg: RReg<T>,
+{
+    #[inline]
+    fn read(
+        &self,
+        val: &<Self::Reg as Reg<T>>::Val,
+    ) -> ReadCleared<<<Self::Reg as Reg<T>>::Val as Bitfield>::Bits> {
+        ReadCleared(unsafe {
+            val.read_bits(
+                <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET),
+                <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::WIDTH),
+            )
+        })
+    }
+
+    #[inline]
+    fn read_clear_bits(&self) -> ReadCleared<<<Self::Reg as Reg<T>>::Val as Bitfield>::Bits> {
+        RcRegFieldBits::read(self, &load_val::<T, Self::Reg>())
+    }
+}
+
 impl<T, R> WWRegFieldBits<T> for R
 where
     T: RegTag,
@@ -381,6 +594,35 @@ where
             );
         }
     }
+
+    #[inline]
+    fn toggle(
+        &self,
+        val: &mut <Self::Reg as Reg<T>>::Val,
+        mask: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+    ) {
+        let offset = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+        let width = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::WIDTH);
+        unsafe {
+            let bits = val.read_bits(offset, width);
+            val.write_bits(offset, width, bits ^ mask);
+        }
+    }
+
+    #[inline]
+    fn update_masked(
+        &self,
+        val: &mut <Self::Reg as Reg<T>>::Val,
+        mask: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+        bits: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+    ) {
+        let offset = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+        let width = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::WIDTH);
+        unsafe {
+            let current = val.read_bits(offset, width);
+            val.write_bits(offset, width, current & !mask | bits & mask);
+        }
+    }
 }
 
 impl<T, R> WoWoRegFieldBits<T> for R
@@ -396,3 +638,191 @@ where
         });
     }
 }
+
+impl<T, R> RRRegFieldEnum<T> for R
+where
+    T: RegTag,
+    R: RegFieldEnum<T> + RRRegFieldBits<T>,
+    R::Reg: RReg<T>,
+{
+    #[inline]
+    fn read_enum(&self, val: &<Self::Reg as Reg<T>>::Val) -> Option<Self::Enum> {
+        Self::enum_from_bits(self.read(val))
+    }
+}
+
+impl<T, R> WWRegFieldEnum<T> for R
+where
+    T: RegTag,
+    R: RegFieldEnum<T> + WWRegFieldBits<T>,
+    R::Reg: WReg<T>,
+{
+    #[inline]
+    fn write_enum(&self, val: &mut <Self::Reg as Reg<T>>::Val, value: Self::Enum) {
+        self.write(val, Self::enum_into_bits(value));
+    }
+}
+
+/// A value of a read-to-clear field.
+///
+/// The field is already cleared in the register memory, so the value is lost
+/// if discarded.
+///
+/// This `struct` is returned by the
+/// [`read_clear_bit`](RcRegFieldBit::read_clear_bit) and
+/// [`read_clear_bits`](RcRegFieldBits::read_clear_bits) methods, and by the
+/// accessors of read-to-clear fields on the register
+/// [`Hold`](crate::reg::RegRef::Hold).
+#[must_use = "the field is cleared by the read, and the value is lost if discarded"]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ReadCleared<T>(T);
+
+impl<T> ReadCleared<T> {
+    /// Returns the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// A field token borrowed from an unsynchronized register token.
+///
+/// Created by the `split_fields` method of unsynchronized register tokens,
+/// which splits the register token into independent field tokens. Each field
+/// token can be passed to a different part of a driver, while the register
+/// token stays borrowed. The register is modified with read-modify-write
+/// operations, therefore the field tokens can't be sent to other threads.
+pub struct FieldRef<'a, R> {
+    field: &'a mut R,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a, R: RegField<Urt>> FieldRef<'a, R> {
+    /// Borrows the field token `field`.
+    #[inline]
+    pub fn new(field: &'a mut R) -> Self {
+        Self { field, _not_send: PhantomData }
+    }
+}
+
+impl<'a, R> FieldRef<'a, R>
+where
+    R: RRRegField<Urt> + WWRegField<Urt>,
+    R::Reg: RReg<Urt> + WReg<Urt>,
+{
+    /// Reads the register value, passes it to the closure `f`, then writes the
+    /// result of the closure back into the register memory. Write-one-to-clear
+    /// bits are written as zeros.
+    #[inline]
+    pub fn modify<F>(&self, f: F)
+    where
+        F: FnOnce(&R, &mut <R::Reg as Reg<Urt>>::Val),
+    {
+        let mut val = unsafe { load_rmw::<Urt, R::Reg>(R::Reg::ADDRESS) };
+        f(&*self.field, &mut val);
+        unsafe {
+            write_volatile(
+                R::Reg::ADDRESS as *mut <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits,
+                val.bits() & !R::Reg::W1C_MASK,
+            );
+        }
+    }
+}
+
+impl<'a, R> FieldRef<'a, R>
+where
+    R: RegFieldBit<Urt> + RRRegField<Urt> + WWRegField<Urt>,
+    R::Reg: RReg<Urt> + WReg<Urt>,
+{
+    /// Sets the bit in the register memory.
+    #[inline]
+    pub fn set_bit(&self) {
+        self.modify(|field, val| field.set(val));
+    }
+
+    /// Clears the bit in the register memory.
+    #[inline]
+    pub fn clear_bit(&self) {
+        self.modify(|field, val| field.clear(val));
+    }
+
+    /// Toggles the bit in the register memory.
+    #[inline]
+    pub fn toggle_bit(&self) {
+        self.modify(|field, val| field.toggle(val));
+    }
+}
+
+impl<'a, R> FieldRef<'a, R>
+where
+    R: RegFieldBits<Urt> + RRRegField<Urt> + WWRegField<Urt>,
+    R::Reg: RReg<Urt> + WReg<Urt>,
+{
+    /// Writes `bits` to the field in the register memory.
+    #[inline]
+    pub fn write_bits(&self, bits: <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits) {
+        self.modify(|field, val| {
+            field.write(val, bits);
+        });
+    }
+
+    /// Inverts the field bits, which are set in `mask`, in the register
+    /// memory.
+    #[inline]
+    pub fn toggle_bits(&self, mask: <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits) {
+        self.modify(|field, val| {
+            WWRegFieldBits::toggle(field, val, mask);
+        });
+    }
+
+    /// Writes the bits of `bits`, which are set in `mask`, to the field in the
+    /// register memory. Other field bits are left intact.
+    #[inline]
+    pub fn write_bits_masked(
+        &self,
+        mask: <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits,
+        bits: <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits,
+    ) {
+        self.modify(|field, val| {
+            field.update_masked(val, mask, bits);
+        });
+    }
+}
+
+impl<'a, R> FieldRef<'a, R>
+where
+    R: RegFieldBit<Urt> + W1cRegField<Urt>,
+    R::Reg: PureReadReg<Urt> + WReg<Urt>,
+{
+    /// Clears the flag by writing the register value with this bit set back
+    /// into the register memory.
+    ///
+    /// Unlike [`W1cRegFieldBit::clear_bit`], the other fields keep their
+    /// current values. The other write-one-to-clear bits are written as zeros,
+    /// so sibling flags stay intact. The register must not have read side
+    /// effects.
+    #[inline]
+    pub fn clear_flag(&self) {
+        let address = R::Reg::ADDRESS as *mut <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits;
+        let bit = <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits::from_usize(1)
+            << <<R::Reg as Reg<Urt>>::Val as Bitfield>::Bits::from_usize(R::OFFSET);
+        unsafe {
+            let other = load_rmw::<Urt, R::Reg>(R::Reg::ADDRESS).bits() & !R::Reg::W1C_MASK;
+            write_volatile(address, other | bit);
+        }
+    }
+}
+
+impl<'a, R> Deref for FieldRef<'a, R> {
+    type Target = R;
+
+    #[inline]
+    fn deref(&self) -> &R {
+        self.field
+    }
+}
+
+#[inline]
+fn load_val<T: RegTag, R: Reg<T>>() -> R::Val {
+    unsafe { R::val_from(read_volatile(R::ADDRESS as *const <R::Val as Bitfield>::Bits)) }
+}
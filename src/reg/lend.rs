@@ -0,0 +1,112 @@
+use super::{tag::Urt, Reg};
+use core::{
+    ops::{Deref, DerefMut},
+    sync::atomic::{spin_loop_hint, AtomicBool, Ordering},
+};
+
+/// An unsynchronized register token lent with [`lend`].
+///
+/// The token is returned to the lender when this value is dropped.
+pub struct RegLent<R: Reg<Urt>> {
+    reg: R,
+    returned: *const AtomicBool,
+}
+
+unsafe impl<R: Reg<Urt> + Send> Send for RegLent<R> {}
+
+/// Lends the unsynchronized register token `reg` to the closure `f` for the
+/// duration of the call.
+///
+/// The closure receives a [`RegLent`] handle, which is `'static` and can be
+/// moved into a fiber of a higher-priority thread, e.g. an interrupt handler.
+/// `reg` stays mutably borrowed until the handle is dropped, so the outer
+/// context can't access the register concurrently, and doesn't need to fall
+/// back to an `Srt` token. After `f` returns, `lend` busy-waits for the handle
+/// to be dropped. If `f` panics, `lend` still waits for the handle before
+/// unwinding further.
+///
+/// # Safety
+///
+/// The handle must be dropped either before `f` returns, or by a thread,
+/// which preempts the caller. Otherwise `lend` never returns, and the calling
+/// thread is deadlocked along with all threads it preempts. This happens when
+/// the handle is leaked, e.g. with [`core::mem::forget`] or in a fiber, which
+/// never completes, or when it is moved into a thread of the same or lower
+/// priority than the caller.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(proc_macro_hygiene)]
+/// use drone_core::{reg, reg::prelude::*, token::Token};
+///
+/// drone_core::reg! {
+///     pub mod RCC CR;
+///     0x4002_1000 0x20 0x0000_0000
+///     RReg WReg;
+///     HSION { 0 1 RRRegField WWRegField }
+/// }
+///
+/// # fn main() {
+/// # fn check() {
+/// let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+/// // The handle is dropped before the closure returns.
+/// unsafe {
+///     reg::lend(&mut cr, |mut lent| {
+///         // Normally the handle is moved into an interrupt handler fiber.
+///         lent.modify(|r| r.set_hsion());
+///     });
+/// }
+/// assert!(cr.load().hsion());
+/// # }
+/// # }
+/// ```
+pub unsafe fn lend<R, F, O>(reg: &mut R, f: F) -> O
+where
+    R: Reg<Urt>,
+    F: FnOnce(RegLent<R>) -> O,
+{
+    let returned = AtomicBool::new(false);
+    let guard = ReturnGuard(&returned);
+    let lent = RegLent { reg: R::take(), returned: guard.0 };
+    let output = f(lent);
+    drop(guard);
+    let _ = reg;
+    output
+}
+
+/// Busy-waits for the lent handle to be dropped, including on unwinding, so
+/// the handle never outlives the flag it points to.
+struct ReturnGuard<'a>(&'a AtomicBool);
+
+impl Drop for ReturnGuard<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        while !self.0.load(Ordering::Acquire) {
+            spin_loop_hint();
+        }
+    }
+}
+
+impl<R: Reg<Urt>> Deref for RegLent<R> {
+    type Target = R;
+
+    #[inline]
+    fn deref(&self) -> &R {
+        &self.reg
+    }
+}
+
+impl<R: Reg<Urt>> DerefMut for RegLent<R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut R {
+        &mut self.reg
+    }
+}
+
+impl<R: Reg<Urt>> Drop for RegLent<R> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { (*self.returned).store(true, Ordering::Release) };
+    }
+}
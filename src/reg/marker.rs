@@ -11,9 +11,31 @@ use crate::reg::{
         RRRegFieldBit, RRRegFieldBits, RoRRegField, WWRegFieldBit, WWRegFieldBits, WoWRegField,
     },
     tag::{Crt, RegTag, Srt, Urt},
-    RReg, RwRegUnsync, WReg, WRegAtomic, WRegUnsync,
+    RReg, Reg, RwRegUnsync, WReg, WRegAtomic, WRegUnsync,
 };
 
+/// Register accessible only from privileged code.
+///
+/// [`reg!`](crate::reg) implements this trait for registers marked with
+/// `#[privileged]`, and [`UnprivilegedReg`] for the rest.
+pub trait PrivilegedReg<T: RegTag>: Reg<T> {}
+
+/// Register accessible from unprivileged code.
+///
+/// Code running in unprivileged threads should require this trait for the
+/// register tokens it owns, so passing a token of a `#[privileged]` register
+/// is a compile-time error.
+pub trait UnprivilegedReg<T: RegTag>: Reg<T> {}
+
+/// Readable register, which reads have no side effects.
+///
+/// [`reg!`](crate::reg) implements this trait for readable registers without
+/// read-to-clear fields, which are not FIFOs. Read-modify-write operations,
+/// which write the other fields back unchanged, like
+/// [`FieldRef::clear_flag`](crate::reg::field::FieldRef::clear_flag), require
+/// this trait.
+pub trait PureReadReg<T: RegTag>: RReg<T> {}
+
 /// Read-write register.
 #[marker]
 pub trait RwReg<T: RegTag>
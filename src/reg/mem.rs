@@ -0,0 +1,199 @@
+//! Volatile memory regions.
+//!
+//! Not every memory-mapped resource is a register. Mailboxes, packet buffers,
+//! or RAM shared with a coprocessor are plain arrays, which must be accessed
+//! with volatile operations. Such regions are declared with the [`region!`]
+//! macro, which defines a [`Token`] for the region. Like register tokens,
+//! region tokens are unique, so the region can't be aliased.
+//!
+//! The region elements are unsigned integers, which are valid for any bit
+//! pattern the hardware may leave in the memory. Unlike register accesses,
+//! region accesses are not traced by the `regtrace` feature.
+//!
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{reg::mem::MmioRegion, token::Token};
+//!
+//! drone_core::reg::mem::region! {
+//!     /// Inter-processor mailbox.
+//!     pub struct Mailbox: [u32; 16] = 0x2000_F000;
+//! }
+//!
+//! # fn main() {
+//! assert_eq!(Mailbox::ADDRESS, 0x2000_F000);
+//! assert_eq!(Mailbox::LEN, 16);
+//! # fn check() {
+//! let mut mailbox = unsafe { Mailbox::take() }.into_slice();
+//! mailbox.write(0, 0xCAFE);
+//! mailbox.fence();
+//! assert_eq!(mailbox.read(0), 0xCAFE);
+//! # }
+//! # }
+//! ```
+
+use crate::{bitfield::Bits, reg::volatile, token::Token};
+use core::sync::atomic::{fence, Ordering};
+
+/// Defines a new memory region token.
+///
+/// See [the module level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::reg_mem as region;
+
+/// A token of a memory-mapped region.
+///
+/// # Safety
+///
+/// The region must be valid for reads and writes of `LEN` elements of type
+/// `Elem` at `ADDRESS`, and must not overlap other tokens.
+pub unsafe trait MmioRegion: Token {
+    /// The type of the region elements.
+    type Elem: Bits;
+
+    /// The region address in memory.
+    const ADDRESS: usize;
+
+    /// The number of elements in the region.
+    const LEN: usize;
+
+    /// Converts the token into an [`MmioSlice`] covering the whole region.
+    #[inline]
+    fn into_slice(self) -> MmioSlice<Self::Elem> {
+        drop(self);
+        unsafe { MmioSlice::from_raw_parts(Self::ADDRESS as *mut Self::Elem, Self::LEN) }
+    }
+}
+
+/// An owned view of a memory-mapped region.
+///
+/// All accesses are volatile. The slice can be split into disjoint parts,
+/// which can be passed to different owners.
+pub struct MmioSlice<T: Bits> {
+    ptr: *mut T,
+    len: usize,
+}
+
+unsafe impl<T: Bits + Send> Send for MmioSlice<T> {}
+
+impl<T: Bits> MmioSlice<T> {
+    /// Creates a slice of `len` elements at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// The memory must be valid for volatile reads and writes of `len`
+    /// elements, and must not be accessed through other means while the slice
+    /// is alive.
+    #[inline]
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize) -> Self {
+        Self { ptr, len }
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the slice has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the address of the first element.
+    #[inline]
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+
+    /// Reads the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    #[inline]
+    pub fn read(&self, index: usize) -> T {
+        assert!(index < self.len, "index {} out of range for length {}", index, self.len);
+        unsafe { volatile::read_mem(self.ptr.add(index)) }
+    }
+
+    /// Writes `value` to the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    #[inline]
+    pub fn write(&mut self, index: usize, value: T) {
+        assert!(index < self.len, "index {} out of range for length {}", index, self.len);
+        unsafe { volatile::write_mem(self.ptr.add(index), value) };
+    }
+
+    /// Reads `buf.len()` elements starting at `offset` into `buf`.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds.
+    pub fn read_into(&self, offset: usize, buf: &mut [T]) {
+        self.check_range(offset, buf.len());
+        for (i, elem) in buf.iter_mut().enumerate() {
+            *elem = unsafe { volatile::read_mem(self.ptr.add(offset + i)) };
+        }
+    }
+
+    /// Writes the elements of `buf` starting at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds.
+    pub fn write_from(&mut self, offset: usize, buf: &[T]) {
+        self.check_range(offset, buf.len());
+        for (i, &elem) in buf.iter().enumerate() {
+            unsafe { volatile::write_mem(self.ptr.add(offset + i), elem) };
+        }
+    }
+
+    /// Divides the slice into two at `mid`.
+    ///
+    /// # Panics
+    ///
+    /// If `mid > len`.
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        assert!(mid <= self.len, "mid {} out of range for length {}", mid, self.len);
+        unsafe {
+            (
+                Self::from_raw_parts(self.ptr, mid),
+                Self::from_raw_parts(self.ptr.add(mid), self.len - mid),
+            )
+        }
+    }
+
+    /// Issues a sequentially consistent memory fence.
+    ///
+    /// Volatile accesses are not reordered with each other by the compiler,
+    /// but they can be reordered with ordinary memory accesses, and by the
+    /// hardware. Use the fence to order the region accesses with the
+    /// surrounding code, e.g. before notifying a coprocessor.
+    #[inline]
+    pub fn fence(&self) {
+        fence(Ordering::SeqCst);
+    }
+
+    /// Issues a memory fence with the `order`.
+    ///
+    /// See [`fence`](MmioSlice::fence) for the details.
+    #[inline]
+    pub fn fence_with(&self, order: Ordering) {
+        fence(order);
+    }
+
+    fn check_range(&self, offset: usize, len: usize) {
+        assert!(
+            offset <= self.len && len <= self.len - offset,
+            "range {}..{} out of range for length {}",
+            offset,
+            offset + len,
+            self.len
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Memory-backed mock registers.
+//!
+//! With the `mock` feature, which implies `std`, register tokens don't access
+//! the fixed MMIO addresses. Instead they hit the same simulated memory as with
+//! the `sim` feature, so drivers built on register tokens can be unit tested on
+//! the host without an emulator.
+//!
+//! Unlike the `sim` feature, which simulates the entire runtime, this feature
+//! affects only register accesses. The memory is local to the current host
+//! thread, so concurrently running tests don't interfere with each other. If
+//! both features are enabled, this module accesses the memory of the `sim`
+//! module.
+//!
+//! The memory is zero-initialized, and can be inspected and preset with
+//! [`peek`] and [`poke`]:
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{reg, reg::{mock, prelude::*}, token::Token};
+//!
+//! reg!(pub mod RCC CR; 0x4002_1000 32 0 RReg WReg; HSION { 0 1 RRRegField WWRegField });
+//!
+//! # fn main() {
+//! let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+//! mock::poke::<u32>(0x4002_1000, 0x80);
+//! cr.modify(|r| r.set_hsion());
+//! assert_eq!(mock::peek::<u32>(0x4002_1000), 0x81);
+//! # }
+//! ```
+
+use crate::{bitfield::Bits, sim};
+
+/// Reads an integer of type `T` from the mock memory at `address`.
+pub fn peek<T: Bits>(address: usize) -> T {
+    sim::peek(address)
+}
+
+/// Writes an integer `value` into the mock memory at `address`.
+pub fn poke<T: Bits>(address: usize, value: T) {
+    sim::poke(address, value);
+}
+
+/// Clears the mock memory of the current host thread.
+pub fn reset() {
+    sim::reset_memory();
+}
@@ -73,71 +73,184 @@
 //!
 //! ## Field Token
 //!
-//! |                                           | Field Width | Field Mode | Register Mode |
-//! |-----------------------------------------------------|-----------|-------|------------|
-//! | [`into_unsync`](field::RegField::into_unsync)       |           |       |            |
-//! | [`into_sync`](field::RegField::into_sync)           |           |       |            |
-//! | [`into_copy`](field::RegField::into_copy)           |           |       |            |
-//! | [`as_sync`](field::RegField::as_sync)               |           |       |            |
-//! | [`load_val`](field::RRRegField::load_val)           |           | read  | read       |
-//! | [`default_val`](field::WoWoRegField::default_val)   |           | write | write-only |
-//! | [`store_val`](field::WoWoRegField::store_val)       |           | write | write-only |
-//! | [`store`](field::WoWoRegField::store)               |           | write | write-only |
-//! | [`read`](field::RRRegFieldBit::read)                | one-bit   | read  | read       |
-//! | [`read_bit`](field::RRRegFieldBit::read_bit)        | one-bit   | read  | read       |
-//! | [`set`](field::WWRegFieldBit::set)                  | one-bit   | write | write      |
-//! | [`clear`](field::WWRegFieldBit::clear)              | one-bit   | write | write      |
-//! | [`toggle`](field::WWRegFieldBit::toggle)            | one-bit   | write | write      |
-//! | [`set_bit`](field::WoWoRegFieldBit::set_bit)        | one-bit   | write | write-only |
-//! | [`clear_bit`](field::WoWoRegFieldBit::clear_bit)    | one-bit   | write | write-only |
-//! | [`toggle_bit`](field::WoWoRegFieldBit::toggle_bit)  | one-bit   | write | write-only |
-//! | [`read`](field::RRRegFieldBits::read)               | multi-bit | read  | read       |
-//! | [`read_bits`](field::RRRegFieldBits::read_bits)     | multi-bit | read  | read       |
-//! | [`write`](field::WWRegFieldBits::write)             | multi-bit | write | write      |
-//! | [`write_bits`](field::WoWoRegFieldBits::write_bits) | multi-bit | write | write-only |
+//! |                                                             | Field Width | Field Mode | Register Mode |
+//! |-------------------------------------------------------------|-------------|------------|---------------|
+//! | [`into_unsync`](field::RegField::into_unsync)               |             |            |               |
+//! | [`into_sync`](field::RegField::into_sync)                   |             |            |               |
+//! | [`into_copy`](field::RegField::into_copy)                   |             |            |               |
+//! | [`from_copy`](field::RegField::from_copy)                   |             |            |               |
+//! | [`as_sync`](field::RegField::as_sync)                       |             |            |               |
+//! | [`load_val`](field::RRRegField::load_val)                   |             | read       | read          |
+//! | [`default_val`](field::WoWoRegField::default_val)           |             | write      | write-only    |
+//! | [`store_val`](field::WoWoRegField::store_val)               |             | write      | write-only    |
+//! | [`store`](field::WoWoRegField::store)                       |             | write      | write-only    |
+//! | [`read`](field::RRRegFieldBit::read)                        | one-bit     | read       | read          |
+//! | [`read_bit`](field::RRRegFieldBit::read_bit)                | one-bit     | read       | read          |
+//! | [`set`](field::WWRegFieldBit::set)                          | one-bit     | write      | write         |
+//! | [`clear`](field::WWRegFieldBit::clear)                      | one-bit     | write      | write         |
+//! | [`toggle`](field::WWRegFieldBit::toggle)                    | one-bit     | write      | write         |
+//! | [`set_bit`](field::WoWoRegFieldBit::set_bit)                | one-bit     | write      | write-only    |
+//! | [`clear_bit`](field::WoWoRegFieldBit::clear_bit)            | one-bit     | write      | write-only    |
+//! | [`toggle_bit`](field::WoWoRegFieldBit::toggle_bit)          | one-bit     | write      | write-only    |
+//! | [`clear_bit`](field::W1cRegFieldBit::clear_bit)             | one-bit     | w1c        | write         |
+//! | [`read`](field::RcRegFieldBit::read)                        | one-bit     | rc         | read          |
+//! | [`read_clear_bit`](field::RcRegFieldBit::read_clear_bit)    | one-bit     | rc         | read          |
+//! | [`read`](field::RRRegFieldBits::read)                       | multi-bit   | read       | read          |
+//! | [`read_bits`](field::RRRegFieldBits::read_bits)             | multi-bit   | read       | read          |
+//! | [`read`](field::RcRegFieldBits::read)                       | multi-bit   | rc         | read          |
+//! | [`read_clear_bits`](field::RcRegFieldBits::read_clear_bits) | multi-bit   | rc         | read          |
+//! | [`write`](field::WWRegFieldBits::write)                     | multi-bit   | write      | write         |
+//! | [`toggle`](field::WWRegFieldBits::toggle)                   | multi-bit   | write      | write         |
+//! | [`update_masked`](field::WWRegFieldBits::update_masked)     | multi-bit   | write      | write         |
+//! | [`write_bits`](field::WoWoRegFieldBits::write_bits)         | multi-bit   | write      | write-only    |
+//! | [`read_enum`](field::RRRegFieldEnum::read_enum)             | multi-bit   | read       | read          |
+//! | [`write_enum`](field::WWRegFieldEnum::write_enum)           | multi-bit   | write      | write         |
 //!
 //! ## Register Token
 //!
-//! |                                         | Mode       | Tag      |
-//! |-----------------------------------------|------------|----------|
-//! | [`into_unsync`](Reg::into_unsync)       |            |          |
-//! | [`into_sync`](Reg::into_sync)           |            |          |
-//! | [`into_copy`](Reg::into_copy)           |            |          |
-//! | [`as_sync`](Reg::as_sync)               |            |          |
-//! | [`default_val`](Reg::default_val)       |            |          |
-//! | [`default`](RegRef::default)            |            |          |
-//! | [`hold`](RegRef::hold)                  |            |          |
-//! | [`load`](RReg::load)                    | read       |          |
-//! | [`load_val`](RReg::load_val)            | read       |          |
-//! | [`load_bits`](RReg::load_bits)          | read       |          |
-//! | [`as_ptr`](RReg::as_ptr)                | read       |          |
-//! | [`as_mut_ptr`](WReg::as_mut_ptr)        | write      |          |
-//! | [`store`](WRegUnsync::store)            | write      | Urt      |
-//! | [`store`](WRegAtomic::store)            | write      | Srt, Crt |
-//! | [`store_reg`](WRegUnsync::store_reg)    | write      | Urt      |
-//! | [`store_reg`](WRegAtomic::store_reg)    | write      | Srt, Crt |
-//! | [`store_val`](WRegUnsync::store_val)    | write      | Urt      |
-//! | [`store_val`](WRegAtomic::store_val)    | write      | Srt, Crt |
-//! | [`store_bits`](WRegUnsync::store_bits)  | write      | Urt      |
-//! | [`store_bits`](WRegAtomic::store_bits)  | write      | Srt, Crt |
-//! | [`reset`](WRegUnsync::reset)            | write      | Urt      |
-//! | [`reset`](WRegAtomic::reset)            | write      | Srt, Crt |
-//! | [`modify`](RwRegUnsync::modify)         | read-write | Urt      |
-//! | [`modify_reg`](RwRegUnsync::modify_reg) | read-write | Urt      |
+//! |                                               | Mode       | Tag      |
+//! |-----------------------------------------------|------------|----------|
+//! | [`into_unsync`](Reg::into_unsync)             |            |          |
+//! | [`into_sync`](Reg::into_sync)                 |            |          |
+//! | [`into_copy`](Reg::into_copy)                 |            |          |
+//! | [`from_copy`](Reg::from_copy)                 |            |          |
+//! | [`as_sync`](Reg::as_sync)                     |            |          |
+//! | [`default_val`](Reg::default_val)             |            |          |
+//! | [`default`](RegRef::default)                  |            |          |
+//! | [`hold`](RegRef::hold)                        |            |          |
+//! | [`load`](RReg::load)                          | read       |          |
+//! | [`load_val`](RReg::load_val)                  | read       |          |
+//! | [`load_bits`](RReg::load_bits)                | read       |          |
+//! | [`as_ptr`](RReg::as_ptr)                      | read       |          |
+//! | [`view`](RReg::view)                          | read       |          |
+//! | [`is_reset`](RReg::is_reset)                  | read       |          |
+//! | [`assert_reset`](RReg::assert_reset)          | read       |          |
+//! | [`as_mut_ptr`](WReg::as_mut_ptr)              | write      |          |
+//! | [`store`](WRegUnsync::store)                  | write      | Urt      |
+//! | [`store`](WRegAtomic::store)                  | write      | Srt, Crt |
+//! | [`store_reg`](WRegUnsync::store_reg)          | write      | Urt      |
+//! | [`store_reg`](WRegAtomic::store_reg)          | write      | Srt, Crt |
+//! | [`store_val`](WRegUnsync::store_val)          | write      | Urt      |
+//! | [`store_val`](WRegAtomic::store_val)          | write      | Srt, Crt |
+//! | [`store_bits`](WRegUnsync::store_bits)        | write      | Urt      |
+//! | [`store_bits`](WRegAtomic::store_bits)        | write      | Srt, Crt |
+//! | [`reset`](WRegUnsync::reset)                  | write      | Urt      |
+//! | [`reset`](WRegAtomic::reset)                  | write      | Srt, Crt |
+//! | [`modify`](RwRegUnsync::modify)               | read-write | Urt      |
+//! | [`modify_reg`](RwRegUnsync::modify_reg)       | read-write | Urt      |
+//! | [`begin`](RwRegUnsync::begin)                 | read-write | Urt      |
+//! | [`modify_atomic`](RwRegAtomic::modify_atomic) | read-write | Srt, Crt |
+//! | [`read_iter`](RegFifo::read_iter)             | read       |          |
+//! | [`read_into`](RegFifo::read_into)             | read       |          |
+//! | [`write_from`](RegFifo::write_from)           | write      |          |
+//!
+//! Unsynchronized register tokens with fields also have an autogenerated
+//! `split_fields` method. It returns a `SplitFields` struct of
+//! [`FieldRef`](field::FieldRef)s, which borrow the register token, so
+//! different parts of a driver can own disjoint fields of one register.
+//!
+//! Registers, which hardware allows to access by smaller parts, like 16-bit
+//! timer or 8-bit UART data registers mapped into 32-bit slots, can list
+//! the widths in the `#[access(...)]` attribute. For each width `N`,
+//! [`reg!`](crate::reg) generates `load_uN(index)` and `store_uN(index,
+//! value)` methods, which access the `index`-th part of the register with a
+//! single `N`-bit access:
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{reg::prelude::*, token::Token};
+//!
+//! drone_core::reg! {
+//!     /// Data register.
+//!     #[access(0x10, 0x08)]
+//!     pub mod USART DR;
+//!     0x4001_3804 0x20 0x0000_0000
+//!     RReg WReg;
+//!     DR { 0 9 RRRegField WWRegField }
+//! }
+//!
+//! # fn main() {
+//! # fn check() {
+//! let dr = unsafe { usart_dr::Reg::<Urt>::take() };
+//! dr.store_u8(0, b'x');
+//! let low = dr.load_u16(0);
+//! # }
+//! # }
+//! ```
+//!
+//! Unsynchronized write-only register tokens can be wrapped into
+//! [`WRegShadow`], which keeps a RAM copy of the last written value, and
+//! provides [`modify`](WRegShadow::modify) on that copy.
 //!
 //! ## Register Value
 //!
 //! Autogenerated field methods for [`RegHold`] (`foo` as an example field
 //! name):
 //!
-//! |                                                             | Field Width | Mode |
-//! |-------------------------------------------------------------|-----------|-------|
-//! | `foo()` ([`read`](field::RRRegFieldBit::read))              | one-bit   | read  |
-//! | `foo()` ([`read`](field::RRRegFieldBits::read))             | multi-bit | read  |
-//! | `set_foo()` ([`set`](field::WWRegFieldBit::set))            | one-bit   | write |
-//! | `clear_foo()` ([`clear`](field::WWRegFieldBit::clear))      | one-bit   | write |
-//! | `toggle_foo()` ([`toggle`](field::WWRegFieldBit::toggle))   | one-bit   | write |
-//! | `write_foo(bits)` ([`write`](field::WWRegFieldBits::write)) | multi-bit | write |
+//! |                                                                             | Field Width | Mode  |
+//! |-----------------------------------------------------------------------------|-------------|-------|
+//! | `foo()` ([`read`](field::RRRegFieldBit::read))                              | one-bit     | read  |
+//! | `foo()` ([`read`](field::RRRegFieldBits::read))                             | multi-bit   | read  |
+//! | `set_foo()` ([`set`](field::WWRegFieldBit::set))                            | one-bit     | write |
+//! | `clear_foo()` ([`clear`](field::WWRegFieldBit::clear))                      | one-bit     | write |
+//! | `toggle_foo()` ([`toggle`](field::WWRegFieldBit::toggle))                   | one-bit     | write |
+//! | `write_foo(bits)` ([`write`](field::WWRegFieldBits::write))                 | multi-bit   | write |
+//! | `foo_enum()` ([`read_enum`](field::RRRegFieldEnum::read_enum))              | multi-bit   | read  |
+//! | `write_foo_enum(value)` ([`write_enum`](field::WWRegFieldEnum::write_enum)) | multi-bit   | write |
+//!
+//! The `_enum` methods are generated only for fields with [enumerated
+//! values](#enumerated-values).
+//!
+//! Each register hold also has a `diff_fields(&other)` method, which returns
+//! an iterator of [`FieldChange`]s for the fields, which values differ between
+//! the two holds. It is useful to trace hardware state transitions.
+//!
+//! With the `regfmt` feature, [`reg!`](crate::reg) also implements `Debug` and
+//! `Display` for register holds, which print the field values like `CTRL {
+//! ENABLE: 1, TICKINT: 0 }`. This is handy for logging, but costs code size
+//! per register, so it is disabled by default.
+//!
+//! # Enumerated Values
+//!
+//! A multi-bit field can list its named values in braces after the traits.
+//! [`reg!`](crate::reg) generates an enum named after the field with the `Enum`
+//! suffix, which is used by [`read_enum`](field::RRRegFieldEnum::read_enum) and
+//! [`write_enum`](field::WWRegFieldEnum::write_enum) instead of raw bits:
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{reg::prelude::*, token::Token};
+//!
+//! drone_core::reg! {
+//!     /// Port mode register.
+//!     pub mod GPIOA MODER;
+//!     0x4800_0000 0x20 0x0000_0000
+//!     RReg WReg;
+//!     /// Port 0 mode.
+//!     MODER0 {
+//!         0 2 RRRegField WWRegField {
+//!             /// Input mode.
+//!             Input = 0,
+//!             /// General purpose output mode.
+//!             Output = 1,
+//!             /// Alternate function mode.
+//!             Alternate = 2,
+//!         }
+//!     }
+//! }
+//!
+//! # fn main() {
+//! use gpioa_moder::Moder0Enum;
+//!
+//! let reg = unsafe { gpioa_moder::Reg::<Urt>::take() };
+//! let mut val = reg.default();
+//! val.write_moder0_enum(Moder0Enum::Alternate);
+//! assert_eq!(val.moder0(), 2);
+//! assert_eq!(val.moder0_enum(), Some(Moder0Enum::Alternate));
+//! val.write_moder0(3);
+//! assert_eq!(reg.moder0.read_enum(&val.val()), None);
+//! # }
+//! ```
 //!
 //! # Tags
 //!
@@ -181,6 +294,87 @@
 //! | Srt       | -     | **+** | **+** |
 //! | Crt       | -     | -     | **+** |
 //!
+//! The missing conversions from `Crt` tokens are available through the unsafe
+//! `from_copy` associated functions, e.g. [`Reg::from_copy`]. The caller must
+//! guarantee that no other copies of the token are used afterwards.
+//!
+//! ## Synchronization Policy
+//!
+//! Some registers, like watchdog or clock configuration registers, are better
+//! never accessed with `Urt` tokens. Such registers can be marked with the
+//! `#[sync_only]` attribute in [`reg!`](crate::reg). With the `sync-policy`
+//! feature enabled, the marked registers and their fields implement register
+//! traits only for atomic tags, so any access through an `Urt` token is a
+//! compile-time error. Without the feature the attribute has no effect, which
+//! allows platform crates to mark the registers unconditionally, and
+//! applications to opt in to the policy.
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::reg::prelude::*;
+//!
+//! drone_core::reg! {
+//!     /// Key register.
+//!     #[sync_only]
+//!     pub mod IWDG KR;
+//!     0x4000_3000 0x20 0x0000_0000
+//!     WReg WoReg;
+//!     KEY { 0 16 WWRegField WoWRegField }
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ## Privilege Levels
+//!
+//! Registers, which should be accessed only by privileged code, like MPU or
+//! system control registers, can be marked with the `#[privileged]` attribute
+//! in [`reg!`](crate::reg). Marked registers implement
+//! [`PrivilegedReg`](marker::PrivilegedReg), and the others implement
+//! [`UnprivilegedReg`](marker::UnprivilegedReg). Code running in unprivileged
+//! threads bounds the register tokens it owns with the latter, so it can't be
+//! given a token of a privileged register:
+//!
+//! ```compile_fail
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{
+//!     reg::{marker::UnprivilegedReg, prelude::*},
+//!     token::Token,
+//! };
+//!
+//! drone_core::reg! {
+//!     /// MPU control register.
+//!     #[privileged]
+//!     pub mod MPU CTRL;
+//!     0xE000_ED94 0x20 0x0000_0000
+//!     RReg WReg;
+//!     ENABLE { 0 1 RRRegField WWRegField }
+//! }
+//!
+//! fn unprivileged_task<R: UnprivilegedReg<Srt>>(_reg: R) {}
+//!
+//! # fn main() {
+//! unprivileged_task(unsafe { mpu_ctrl::Reg::<Srt>::take() });
+//! # }
+//! ```
+//!
+//! ## Lending to Interrupt Handlers
+//!
+//! An `Urt` register token, which is mostly used by one thread, can be lent
+//! temporarily to a fiber of a higher-priority thread with [`lend`]. The
+//! token stays borrowed by [`lend`] until the lent handle is dropped, so the
+//! compiler prevents the lender from using the register in the meantime, and
+//! the token doesn't have to be converted to `Srt` just for one interrupt
+//! handler. The function is unsafe, because it busy-waits for the handle, and
+//! never returns if the handle is leaked or given to a thread, which can't
+//! preempt the lender.
+//!
+//! ## Critical Sequences
+//!
+//! Accesses to several registers, which must not be separated by an
+//! interrupt, are wrapped with [`critical`](critical()). It passes the given
+//! tokens, usually a tuple of mutable references, to a closure executed inside
+//! a [critical section](crate::critical).
+//!
 //! # Mappings
 //!
 //! We define concrete register mappings in platform crates. Usually the user
@@ -203,12 +397,15 @@
 //!     /// SysTick control and status register.
 //!     pub mod STK CTRL;
 //!     0xE000_E010 // the register address in memory
-//!     0x20        // size of the register in bits
+//!     0x20        // size of the register in bits: 0x08, 0x10, 0x20, or 0x40
 //!     0x0000_0000 // reset value of the register
 //!     // Traits to implement for the register token. The most common sets are:
 //!     //     RReg RoReg - read-only register
 //!     //     RReg WReg  - read-write register
 //!     //     WReg WoReg - write-only register
+//!     // `RegFifo` can be added to the set for FIFO data registers, and
+//!     // `#[access(0x10, 0x08)]` to the attributes for registers allowing
+//!     // narrower accesses.
 //!     RReg WReg;
 //!
 //!     // Here we define register fields.
@@ -220,6 +417,10 @@
 //!         //     RRRegField RoRRegField - read-only field
 //!         //     RRRegField WWRegField  - read-write field
 //!         //     WWRegField WoWRegField - read-write field
+//!         //     RRRegField W1cRegField - write-one-to-clear flag
+//!         //     RcRegField             - read-to-clear flag
+//!         // `WsRegField` can be added to the set for fields, which writes have
+//!         // side effects.
 //!         RRRegField WWRegField
 //!     }
 //! }
@@ -244,6 +445,7 @@
 //!         // Declare all registers for this block. This produces:
 //!         // pub mod stk {
 //!         //     pub use crate::stk_ctrl as ctrl;
+//!         //     pub const MAP: &[RegLayout] = &[/* layout of STK_CTRL */];
 //!         // }
 //!         CTRL;
 //!     }
@@ -251,7 +453,7 @@
 //!
 //! // ----- this is drone_stm32 crate -----
 //! // This crate parses SVD files provided by the manufacturer and generates more
-//! // registers.
+//! // registers, e.g. with the `drone-core-svd` crate.
 //!
 //! // Same as above, except it will reuse the upstream macro, resulting in a
 //! // combined register tokens index. Note `use macro cortex_m_reg_tokens`.
@@ -282,18 +484,156 @@
 //!     assert_eq!(size_of_val(&reg), 0);
 //!     assert_eq!(size_of_val(&reg.stk_ctrl), 0);
 //!     assert_eq!(size_of_val(&reg.stk_ctrl.enable), 0);
+//!     // The layouts of the block registers are available as constants.
+//!     assert_eq!(stk::MAP[0].address, 0xE000_E010);
 //!     // Pass the index to your safe entry point.
 //!     trunk(reg);
 //! }
 //!
 //! fn trunk(reg: Regs) {}
 //! ```
+//!
+//! The same chaining can be used inside a single crate. A full-chip index can
+//! be split into several `reg::tokens!` invocations, e.g. one per peripheral
+//! group, each reusing the previous macro with `use macro`. Only the last macro
+//! in the chain needs to be invoked, and it still produces one coherent index.
+//!
+//! The registers of the index can be distributed between several subset
+//! structs, so that each thread or task statically owns only the registers it
+//! needs. The subsets are declared in a `split` block after the excluded
+//! registers, and the generated `split` method moves the register tokens from
+//! the index into the subsets. A register can't be listed in more than one
+//! subset:
+//!
+//! ```text
+//! stm32_reg_tokens! {
+//!     /// Register tokens.
+//!     pub struct Regs;
+//!     !scb_ccr;
+//!     split {
+//!         /// Clock control register tokens.
+//!         pub struct RccRegs { rcc_cr; rcc_cfgr; }
+//!         /// SysTick register tokens.
+//!         pub struct StkRegs { stk_ctrl; stk_load; stk_val; }
+//!     }
+//! }
+//!
+//! let (rcc, stk) = regs.split();
+//! ```
+//!
+//! If the index struct is marked with `#[summary]`, an unsafe associated
+//! function `summary` is also generated. It reads every readable register in
+//! the index, and writes the register addresses and values to a log
+//! [`Port`](crate::log::Port) as pairs of `u32` words. Registers with read side
+//! effects, like [`RegFifo`] registers and registers with
+//! [`RcRegField`](field::RcRegField) fields, are skipped. It doesn't require
+//! the register tokens, so it can be called from a panic handler for
+//! post-mortem dumps.
+//!
+//! Every register token implements the object-safe [`DynReg`] trait. If the
+//! index struct is marked with `#[dyn_reg]`, two more methods are generated:
+//! `dyn_reg(&self, name)` looks up a register by its name, like `RCC_CR`, and
+//! `for_each_dyn_reg(&self, f)` passes each register of the index to the
+//! closure `f` as `&dyn DynReg`. This allows a firmware shell to access
+//! registers by name at run-time.
+//!
+//! # Register Arrays
+//!
+//! Identical registers placed at a fixed distance from each other, like DMA
+//! channel registers, can be declared once with the number of elements and the
+//! stride in bytes in brackets. For `pub mod DMA1 CCR[7; 0x14];`,
+//! [`reg!`](crate::reg) generates ordinary register modules `dma1_ccr0` to
+//! `dma1_ccr6`, which can be listed in `reg::tokens!` one by one, and a
+//! `dma1_ccr` module with an `Array` of the element tokens. Constant indices
+//! are checked at compile-time through the `Array` fields, while
+//! `Array::get` selects an element at run-time:
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{reg::prelude::*, token::Token};
+//!
+//! drone_core::reg! {
+//!     /// DMA channel configuration register.
+//!     pub mod DMA1 CCR[3; 0x14];
+//!     0x4002_0008 0x20 0x0000_0000
+//!     RReg WReg;
+//!     /// Channel enable.
+//!     EN { 0 1 RRRegField WWRegField }
+//! }
+//!
+//! # fn main() {
+//! let mut ccr = unsafe {
+//!     dma1_ccr::Array::<Urt> { ccr0: Token::take(), ccr1: Token::take(), ccr2: Token::take() }
+//! };
+//! assert_eq!(dma1_ccr2::Reg::<Urt>::ADDRESS, 0x4002_0030);
+//! assert_eq!(ccr.get(2).map(|elem| elem.address()), Some(0x4002_0030));
+//! assert!(ccr.get(3).is_none());
+//! # }
+//! ```
+//!
+//! # Run-time Base Addresses
+//!
+//! Blocks instantiated at addresses known only at run-time are declared once,
+//! with register offsets inside the block in place of addresses. The
+//! [`RegDyn`] wrapper binds such a register token to the base address of a
+//! block instance, and performs the accesses at the sum of the two. The
+//! wrapper consumes the token for good, since the token alone would access the
+//! offset as an absolute address.
+//!
+//! # Memory Regions
+//!
+//! Memory-mapped resources, which are not registers, like mailboxes or RAM
+//! shared with a coprocessor, are declared with [`mem::region!`]. The
+//! resulting tokens provide volatile access to the region through
+//! [`mem::MmioSlice`].
+//!
+//! # Register Trace
+//!
+//! With the `regtrace` feature, every register read and write performed
+//! through register and field tokens is traced to the
+//! [`REGTRACE_PORT`](crate::log::REGTRACE_PORT) log port, if the port is
+//! enabled. This helps to debug driver initialization sequences. Each record
+//! consists of the following `u32` words:
+//!
+//! * a tag in the most significant byte, `0xE1` for reads and `0xE2` for
+//!   writes, and the register size in bits in the least significant byte;
+//! * the register address;
+//! * the lower 32 bits of the value;
+//! * the upper 32 bits of the value, for 64-bit registers only.
+//!
+//! Each record is written inside a [`critical`](crate::critical) section, so
+//! records of accesses from different threads don't interleave.
+//!
+//! Atomic modifications made with
+//! [`modify_atomic`](RwRegAtomic::modify_atomic) on the target are not traced.
 
 pub mod field;
 pub mod marker;
+pub mod mem;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod prelude;
 pub mod tag;
 
+mod bit_band;
+mod critical;
+mod dyn_reg;
+mod dynamic;
+mod lend;
+mod shadow;
+mod view;
+mod volatile;
+
+pub use self::{
+    bit_band::{bit_band_alias, BitBand, RegBitBand},
+    critical::critical,
+    dyn_reg::{DynReg, DynRegError},
+    dynamic::RegDyn,
+    lend::{lend, RegLent},
+    shadow::WRegShadow,
+    view::{FieldChange, FieldChanges, FieldLayout, RegLayout, RegValue, RegView},
+};
+
 /// A macro to define a macro to define a set of register tokens.
 ///
 /// See [the module level documentation](self) for details.
@@ -307,9 +647,20 @@ pub use drone_core_macros::reg_assert_taken as assert_taken;
 #[doc(hidden)]
 pub use drone_core_macros::reg_tokens_inner as tokens_inner;
 
-use self::tag::{Crt, RegAtomic, RegOwned, RegTag, Srt, Urt};
+#[doc(hidden)]
+pub use self::view::{fmt_fields, RegSummary};
+
+use self::{
+    tag::{Crt, RegAtomic, RegOwned, RegTag, Srt, Urt},
+    volatile::{read_volatile, write_volatile},
+};
+#[cfg(not(any(feature = "sim", feature = "mock")))]
+use crate::arch;
 use crate::{bitfield::Bitfield, token::Token};
-use core::ptr::{read_volatile, write_volatile};
+use core::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
 /// The base trait for a memory-mapped register token.
 pub trait Reg<T: RegTag>: Token + Sync {
@@ -331,12 +682,36 @@ pub trait Reg<T: RegTag>: Token + Sync {
     /// Corresponding copyable register token.
     type CReg: Reg<Crt>;
 
+    /// The run-time layout of the register: the name, like `GPIOA_ODR`, the
+    /// address, the size, the reset value, and the layouts of the fields.
+    const LAYOUT: RegLayout;
+
     /// The register address in memory.
     const ADDRESS: usize;
 
     /// The register default value.
     const RESET: <Self::Val as Bitfield>::Bits;
 
+    /// The mask of write-one-to-clear bits.
+    ///
+    /// These bits are written as zeros by read-modify-write operations, so
+    /// that modifying one field doesn't clear pending flags in sibling fields.
+    /// Write-one-to-clear fields are cleared with
+    /// [`clear_bit`](field::W1cRegFieldBit::clear_bit).
+    ///
+    /// Defaults to no bits.
+    const W1C_MASK: <Self::Val as Bitfield>::Bits = <Self::Val as Bitfield>::Bits::ZERO;
+
+    /// The mask of bits with write side effects.
+    ///
+    /// These bits are cleared in the values read by read-modify-write
+    /// operations, so that they are written as ones only if the modification
+    /// sets them, and modifying one field doesn't repeat the side effects of
+    /// sibling fields. See [`WsRegField`](field::WsRegField).
+    ///
+    /// Defaults to no bits.
+    const WS_MASK: <Self::Val as Bitfield>::Bits = <Self::Val as Bitfield>::Bits::ZERO;
+
     /// Creates a new instance of [`Reg::Val`] from raw `bits`.
     ///
     /// # Safety
@@ -368,6 +743,21 @@ pub trait Reg<T: RegTag>: Token + Sync {
         unsafe { Self::CReg::take() }
     }
 
+    /// Converts a copyable register token back into an affine register token.
+    ///
+    /// # Safety
+    ///
+    /// Copyable tokens can be freely duplicated. The caller must ensure that
+    /// no other copies of `reg` are used while the returned token is alive.
+    #[inline]
+    unsafe fn from_copy(reg: Self::CReg) -> Self
+    where
+        T: RegOwned,
+    {
+        drop(reg);
+        Self::take()
+    }
+
     /// Returns a reference to the synchronized register token.
     #[inline]
     fn as_sync(&self) -> &Self::SReg
@@ -460,6 +850,65 @@ pub trait RReg<T: RegTag>: Reg<T> {
     fn as_ptr(&self) -> *const <Self::Val as Bitfield>::Bits {
         Self::ADDRESS as *const <Self::Val as Bitfield>::Bits
     }
+
+    /// Returns a run-time view of the register.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(proc_macro_hygiene)]
+    /// use drone_core::{reg::prelude::*, token::Token};
+    ///
+    /// drone_core::reg! {
+    ///     pub mod GPIOA ODR;
+    ///     0x4800_0014 0x20 0x0000_0000
+    ///     RReg WReg;
+    ///     ODR0 { 0 1 RRRegField WWRegField }
+    ///     ODR5 { 5 1 RRRegField WWRegField }
+    /// }
+    ///
+    /// # fn main() {
+    /// let odr = unsafe { gpioa_odr::Reg::<Urt>::take() };
+    /// let view = odr.view();
+    /// assert_eq!(view.address(), 0x4800_0014);
+    /// assert_eq!(view.fields().len(), 2);
+    /// let value = view.with_bits(0x20);
+    /// assert_eq!(value.field("ODR5"), Some(1));
+    /// assert_eq!(value.to_string(), "GPIOA_ODR = 0x00000020 ODR0=0x0 ODR5=0x1");
+    /// # }
+    /// ```
+    #[inline]
+    fn view(&self) -> RegView {
+        unsafe { RegView::new(Self::LAYOUT) }
+    }
+
+    /// Returns `true` if the register memory holds the reset value.
+    ///
+    /// See also [`assert_reset`](RReg::assert_reset).
+    #[inline]
+    fn is_reset(&self) -> bool {
+        self.load_bits() == Self::RESET
+    }
+
+    /// Asserts that the register memory holds the reset value. Useful during
+    /// driver bring-up and in integration tests.
+    ///
+    /// See also [`is_reset`](RReg::is_reset),
+    /// [`reset`](WRegUnsync::reset).
+    ///
+    /// # Panics
+    ///
+    /// If the register value differs from [`Reg::RESET`].
+    fn assert_reset(&self) {
+        let bits = self.load_bits();
+        assert!(
+            bits == Self::RESET,
+            "{} = {:#x?}, expected the reset value {:#x?}",
+            Self::LAYOUT.name,
+            bits,
+            Self::RESET
+        );
+    }
 }
 
 /// Writable register.
@@ -479,6 +928,110 @@ pub trait RoReg<T: RegTag>: RReg<T> {}
 /// Write-only register.
 pub trait WoReg<T: RegTag>: WReg<T> {}
 
+/// FIFO register.
+///
+/// Each access to a FIFO register pops or pushes one element, so the register
+/// is accessed repeatedly at the same address. All accesses are volatile and
+/// have the register width.
+pub trait RegFifo<T: RegTag>: Reg<T> {
+    /// Returns an iterator, which pops `n` elements from the FIFO.
+    ///
+    /// See also [`read_into`](RegFifo::read_into).
+    #[inline]
+    fn read_iter(&mut self, n: usize) -> FifoIter<'_, T, Self>
+    where
+        Self: RReg<T>,
+    {
+        FifoIter { reg: self, remaining: n, _tag: PhantomData }
+    }
+
+    /// Pops elements from the FIFO until `buf` is filled.
+    ///
+    /// See also [`read_iter`](RegFifo::read_iter).
+    #[inline]
+    fn read_into(&mut self, buf: &mut [<Self::Val as Bitfield>::Bits])
+    where
+        Self: RReg<T>,
+    {
+        for bits in buf {
+            *bits = self.load_bits();
+        }
+    }
+
+    /// Pushes all elements of `data` to the FIFO in order.
+    #[inline]
+    fn write_from(&mut self, data: &[<Self::Val as Bitfield>::Bits])
+    where
+        Self: WReg<T>,
+    {
+        for &bits in data {
+            unsafe { write_volatile(self.as_mut_ptr(), bits) };
+        }
+    }
+}
+
+/// An iterator, which pops elements from a FIFO register.
+///
+/// This `struct` is created by the [`read_iter`](RegFifo::read_iter) method.
+pub struct FifoIter<'a, T: RegTag, R: RegFifo<T> + RReg<T>> {
+    reg: &'a mut R,
+    remaining: usize,
+    _tag: PhantomData<T>,
+}
+
+/// A deferred read-modify-write of an unsynchronized register.
+///
+/// This `struct` is created by the [`begin`](RwRegUnsync::begin) method. It
+/// dereferences to the register [`Hold`](RegRef::Hold), and holds the register
+/// token mutably borrowed until [`commit`](Transaction::commit). Dropping the
+/// transaction discards the modifications.
+#[must_use = "the modified value is written only by `commit`"]
+pub struct Transaction<'a, R: RwRegUnsync<'a>> {
+    hold: <R as RegRef<'a, Urt>>::Hold,
+}
+
+impl<'a, R: RwRegUnsync<'a>> Transaction<'a, R> {
+    /// Writes the modified value into the register memory. Write-one-to-clear
+    /// bits are written as zeros.
+    #[inline]
+    pub fn commit(self) {
+        unsafe {
+            write_volatile(
+                R::ADDRESS as *mut <R::Val as Bitfield>::Bits,
+                self.hold.val().bits() & !R::W1C_MASK,
+            );
+        }
+    }
+}
+
+impl<'a, R: RwRegUnsync<'a>> Deref for Transaction<'a, R> {
+    type Target = <R as RegRef<'a, Urt>>::Hold;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.hold
+    }
+}
+
+impl<'a, R: RwRegUnsync<'a>> DerefMut for Transaction<'a, R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.hold
+    }
+}
+
+/// An element of a register array selected at run-time.
+///
+/// This `struct` is created by the `get` method of a register array generated
+/// by [`reg!`](crate::reg). All elements of an array share the layout of the
+/// first element, so the element values are exposed through the
+/// [`RegHold`] of the first element.
+pub struct ArrayElem<'a, T: RegTag, R: Reg<T>> {
+    reg: &'a R,
+    address: usize,
+    _tag: PhantomData<T>,
+}
+
 /// Non-atomic operations for writable register.
 // FIXME https://github.com/rust-lang/rust/issues/46397
 pub trait WRegUnsync<'a>: WReg<Urt> + RegRef<'a, Urt> {
@@ -589,6 +1142,213 @@ pub trait RwRegUnsync<'a>: RReg<Urt> + WRegUnsync<'a> + RegRef<'a, Urt> {
     fn modify_reg<F>(&'a mut self, f: F)
     where
         F: for<'b> FnOnce(&'b Self, &'b mut Self::Val);
+
+    /// Reads the value from the register memory, and returns a
+    /// [`Transaction`], which defers writing the modified value until
+    /// [`commit`](Transaction::commit).
+    ///
+    /// Unlike [`modify`](RwRegUnsync::modify), the field mutations don't have
+    /// to fit into one closure. The register is still read once and written
+    /// once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![feature(proc_macro_hygiene)]
+    /// use drone_core::{reg::prelude::*, token::Token};
+    ///
+    /// drone_core::reg! {
+    ///     pub mod STK CTRL;
+    ///     0xE000_E010 0x20 0x0000_0000
+    ///     RReg WReg;
+    ///     ENABLE { 0 1 RRRegField WWRegField }
+    ///     CLKSOURCE { 2 1 RRRegField WWRegField }
+    /// }
+    ///
+    /// # fn main() {
+    /// # fn check() {
+    /// let mut ctrl = unsafe { stk_ctrl::Reg::<Urt>::take() };
+    /// let mut tx = ctrl.begin();
+    /// tx.set_enable();
+    /// if tx.enable() {
+    ///     tx.set_clksource();
+    /// }
+    /// tx.commit();
+    /// # }
+    /// # }
+    /// ```
+    fn begin(&'a mut self) -> Transaction<'a, Self>;
+}
+
+/// Atomic operations for read-write register.
+// FIXME https://github.com/rust-lang/rust/issues/46397
+pub trait RwRegAtomic<'a, T: RegAtomic>: RReg<T> + WRegAtomic<'a, T> + RegRef<'a, T> {
+    /// Reads the value from the register memory, then passes the value to the
+    /// closure `f`, then writes the result of the closure back to the register
+    /// memory, all as a single atomic operation.
+    ///
+    /// The sequence is implemented with exclusive accesses through the
+    /// [`arch`](crate::arch) hooks, so the token can be shared between
+    /// preemption levels without a critical section. If the register is
+    /// modified concurrently, the exclusive store fails and `f` is called again
+    /// with the new value.
+    ///
+    /// In the host simulation the sequence is executed inside a
+    /// [`critical`](crate::critical) section.
+    fn modify_atomic<F>(&'a self, f: F)
+    where
+        F: for<'b> FnMut(
+            &'b mut <Self as RegRef<'a, T>>::Hold,
+        ) -> &'b mut <Self as RegRef<'a, T>>::Hold;
+}
+
+/// Reads the value of the register `R` at `address` for a read-modify-write
+/// operation. The [write side-effect](Reg::WS_MASK) bits are cleared, so that
+/// they are written back only if set by the modification.
+#[inline]
+pub(crate) unsafe fn load_rmw<T: RegTag, R: Reg<T>>(address: usize) -> R::Val {
+    R::val_from(read_volatile(address as *const <R::Val as Bitfield>::Bits) & !R::WS_MASK)
+}
+
+#[doc(hidden)]
+#[inline]
+pub unsafe fn load_part<U: Copy>(address: usize) -> U {
+    read_volatile(address as *const U)
+}
+
+#[doc(hidden)]
+#[inline]
+pub unsafe fn store_part<U: Copy>(address: usize, value: U) {
+    write_volatile(address as *mut U, value);
+}
+
+/// Defines a register field token. Used by `reg!` to keep its expansion
+/// compact.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! reg_field {
+    (
+        $(#[$attr:meta])*
+        $field:ident, $offset:expr, $width:expr, $kind:ident, $bound:path, [$($marker:ident),*]
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy)]
+        pub struct $field<_T: $crate::reg::tag::RegTag>(_T);
+
+        unsafe impl<_T: $crate::reg::tag::RegTag> $crate::token::Token for $field<_T> {
+            #[inline]
+            unsafe fn take() -> Self {
+                $field(_T::default())
+            }
+        }
+
+        impl<_T: $crate::reg::tag::RegTag> $crate::reg::field::RegField<_T> for $field<_T> {
+            type Reg = Reg<_T>;
+            type URegField = $field<$crate::reg::tag::Urt>;
+            type SRegField = $field<$crate::reg::tag::Srt>;
+            type CRegField = $field<$crate::reg::tag::Crt>;
+
+            const OFFSET: usize = $offset;
+            const WIDTH: usize = $width;
+        }
+
+        impl<_T: $crate::reg::tag::RegTag> $crate::reg::field::$kind<_T> for $field<_T> {}
+
+        $(impl<_T: $bound> $marker<_T> for $field<_T> {})*
+    };
+}
+
+impl<T: RegTag, R: RegFifo<T> + RReg<T>> Iterator for FifoIter<'_, T, R> {
+    type Item = <R::Val as Bitfield>::Bits;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.reg.load_bits())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: RegTag, R: RegFifo<T> + RReg<T>> ExactSizeIterator for FifoIter<'_, T, R> {}
+
+impl<'a, T: RegTag, R: Reg<T>> ArrayElem<'a, T, R> {
+    /// Creates a new handle for the element at `address`, which has the layout
+    /// of `reg`.
+    ///
+    /// # Safety
+    ///
+    /// `address` must be the address of an element of the same array as
+    /// `reg`, and the caller must own the element.
+    #[inline]
+    pub unsafe fn new(reg: &'a R, address: usize) -> Self {
+        Self { reg, address, _tag: PhantomData }
+    }
+
+    /// Returns the element address in memory.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Reads the value from the element memory to the raw value type.
+    #[inline]
+    pub fn load_bits(&self) -> <R::Val as Bitfield>::Bits
+    where
+        R: RReg<T>,
+    {
+        unsafe { read_volatile(self.address as *const <R::Val as Bitfield>::Bits) }
+    }
+
+    /// Reads the value from the element memory to the exposed value type.
+    #[inline]
+    pub fn load(&self) -> <R as RegRef<'a, T>>::Hold
+    where
+        R: RReg<T> + RegRef<'a, T>,
+    {
+        self.reg.hold(unsafe { R::val_from(self.load_bits()) })
+    }
+
+    /// Writes raw `bits` into the element memory.
+    #[inline]
+    pub fn store_bits(&self, bits: <R::Val as Bitfield>::Bits)
+    where
+        R: WReg<T>,
+    {
+        unsafe { write_volatile(self.address as *mut <R::Val as Bitfield>::Bits, bits) };
+    }
+
+    /// Passes the reset value to the closure `f`, then writes the result of the
+    /// closure into the element memory.
+    #[inline]
+    pub fn store<F>(&self, f: F)
+    where
+        R: WReg<T> + RegRef<'a, T>,
+        F: for<'b> FnOnce(&'b mut <R as RegRef<'a, T>>::Hold) -> &'b mut <R as RegRef<'a, T>>::Hold,
+    {
+        self.store_bits(f(&mut self.reg.default()).val().bits());
+    }
+
+    /// Reads the value from the element memory, then passes the value to the
+    /// closure `f`, then writes the result of the closure back to the element
+    /// memory.
+    ///
+    /// This operation is non-atomic.
+    #[inline]
+    pub fn modify<F>(&self, f: F)
+    where
+        R: RReg<T> + WReg<T> + RegRef<'a, T>,
+        F: for<'b> FnOnce(&'b mut <R as RegRef<'a, T>>::Hold) -> &'b mut <R as RegRef<'a, T>>::Hold,
+    {
+        let val = unsafe { load_rmw::<T, R>(self.address) };
+        self.store_bits(f(&mut self.reg.hold(val)).val().bits() & !R::W1C_MASK);
+    }
 }
 
 impl<'a, R> WRegUnsync<'a> for R
@@ -688,7 +1448,11 @@ where
         ) -> &'b mut <Self as RegRef<'a, Urt>>::Hold,
     {
         unsafe {
-            write_volatile(self.as_mut_ptr(), f(&mut self.load()).val().bits());
+            let val = load_rmw::<Urt, Self>(Self::ADDRESS);
+            write_volatile(
+                self.as_mut_ptr(),
+                f(&mut self.hold(val)).val().bits() & !Self::W1C_MASK,
+            );
         }
     }
 
@@ -697,12 +1461,78 @@ where
     where
         F: for<'b> FnOnce(&'b Self, &'b mut Self::Val),
     {
-        let mut val = self.load_val();
+        let mut val = unsafe { load_rmw::<Urt, Self>(Self::ADDRESS) };
         f(self, &mut val);
-        self.store_val(val);
+        self.store_bits(val.bits() & !Self::W1C_MASK);
+    }
+
+    #[inline]
+    fn begin(&'a mut self) -> Transaction<'a, Self> {
+        Transaction { hold: self.hold(unsafe { load_rmw::<Urt, Self>(Self::ADDRESS) }) }
+    }
+}
+
+impl<'a, T, R> RwRegAtomic<'a, T> for R
+where
+    T: RegAtomic,
+    R: RReg<T> + WRegAtomic<'a, T> + RegRef<'a, T>,
+    R::Val: Bitfield<Bits = u32>,
+{
+    #[inline]
+    fn modify_atomic<F>(&'a self, mut f: F)
+    where
+        F: for<'b> FnMut(
+            &'b mut <Self as RegRef<'a, T>>::Hold,
+        ) -> &'b mut <Self as RegRef<'a, T>>::Hold,
+    {
+        #[cfg(any(feature = "sim", feature = "mock"))]
+        crate::critical::with(|_| {
+            let val = unsafe { load_rmw::<T, Self>(Self::ADDRESS) };
+            self.store_bits(f(&mut self.hold(val)).val().bits() & !Self::W1C_MASK);
+        });
+        #[cfg(not(any(feature = "sim", feature = "mock")))]
+        unsafe {
+            arch::modify_exclusive(self.as_mut_ptr(), |bits| {
+                f(&mut self.hold(Self::val_from(bits & !Self::WS_MASK))).val().bits()
+                    & !Self::W1C_MASK
+            });
+        }
     }
 }
 
+#[cfg(feature = "sync-policy")]
+mod sync_policy_compile_tests {
+    //! ```compile_fail
+    //! # #![feature(proc_macro_hygiene)]
+    //! use drone_core::{reg::prelude::*, token::Token};
+    //! drone_core::reg! {
+    //!     #[sync_only]
+    //!     pub mod TST TST_SYNC_REG;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     TST_BIT { 0 1 RRRegField WWRegField }
+    //! }
+    //! fn main() {
+    //!     let mut reg = unsafe { tst_tst_sync_reg::Reg::<Urt>::take() };
+    //!     reg.store(|r| r.set_tst_bit());
+    //! }
+    //! ```
+    //!
+    //! ```
+    //! # #![feature(proc_macro_hygiene)]
+    //! use drone_core::{reg::prelude::*, token::Token};
+    //! drone_core::reg! {
+    //!     #[sync_only]
+    //!     pub mod TST TST_SYNC_REG;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     TST_BIT { 0 1 RRRegField WWRegField }
+    //! }
+    //! fn main() {
+    //!     let reg = unsafe { tst_tst_sync_reg::Reg::<Srt>::take() };
+    //!     let _ = || reg.store(|r| r.set_tst_bit());
+    //! }
+    //! ```
+}
+
 mod compile_tests {
     //! ```compile_fail
     //! use drone_core::reg::prelude::*;
@@ -898,6 +1728,21 @@ mod compile_tests {
     //!
     //! ```compile_fail
     //! #![feature(proc_macro_hygiene)]
+    //! use drone_core::token::Token;
+    //! drone_core::reg!(pub mod FOO BAR; 0xDEAD_BEEF 0x20 0xBEEF_CACE;);
+    //! drone_core::reg::tokens!(macro reg_tokens; crate; crate; pub mod FOO { BAR; });
+    //! reg_tokens! {
+    //!     struct Regs;
+    //!     split {
+    //!         struct Regs1 { foo_bar; }
+    //!         struct Regs2 { foo_bar; }
+    //!     }
+    //! }
+    //! fn main() { unsafe { Regs::take() }.split(); }
+    //! ```
+    //!
+    //! ```compile_fail
+    //! #![feature(proc_macro_hygiene)]
     //! drone_core::reg::assert_taken!("foo_bar");
     //! drone_core::reg::assert_taken!(concat!("foo", "_bar"));
     //! ```
@@ -907,4 +1752,200 @@ mod compile_tests {
     //! drone_core::reg::assert_taken!("foo_bar");
     //! drone_core::reg::assert_taken!(concat!("foo", "_baz"));
     //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg;
+    //!     BAZ { 0 1 RRRegField WWRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg!(pub mod FOO BAR; 0xDEAD_BEEF 0x18 0xBEEF_CACE RReg WReg;);
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 1 RRRegField WWRegField { Off = 0, On = 1 } }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0x6000_0000 0x20 0x0000_0000 RReg WReg RegBitBand;
+    //!     BAZ { 0 1 RRRegField WWRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0x4000_1000 0x20 0x0000_0000 RReg WReg RegBitBand;
+    //!     BAZ { 0 1 RRRegField W1cRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0x400F_FFFC 0x20 0x0000_0000 RReg WReg RegBitBand;
+    //!     BAZ { 31 1 RRRegField WWRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 2 RRRegField WWRegField { Off = 0, On = 4 } }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 2 RRRegField WWRegField { Off = 0, On = 1, Enabled = 1 } }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 2 RRRegField WWRegField { Off = 0, On = 3 } }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 1 RRRegField WWRegField }
+    //!     BAZ { 1 1 RRRegField WWRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 4 RRRegField WWRegField }
+    //!     QUX { 3 1 RRRegField WWRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x10 0xCACE RReg WReg;
+    //!     BAZ { 12 8 RRRegField WWRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg!(pub mod FOO BAR; 0xDEAD_BEEF 0x20 0xBEEF_CACE; BAZ { 0 1 });
+    //! drone_core::reg::tokens! {
+    //!     macro reg_tokens; crate; crate;
+    //!     mod FOO { BAR; BAR; }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg!(pub mod FOO BAR; 0xDEAD_BEEF 0x20 0xBEEF_CACE RReg; BAZ { 0 1 RRRegField });
+    //! drone_core::reg::tokens! {
+    //!     macro reg_tokens; crate; crate;
+    //!     mod FOO { BAR; QUX; }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::{reg::prelude::*, token::Token};
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 1 RcRegField }
+    //! }
+    //! fn main() {
+    //!     let bar = unsafe { foo_bar::Reg::<Urt>::take() };
+    //!     let _: bool = bar.baz.read_bit();
+    //! }
+    //! ```
+    //!
+    //! ```compile_fail
+    //! #![deny(unused_must_use)]
+    //! use drone_core::{reg::prelude::*, token::Token};
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 1 RcRegField }
+    //! }
+    //! fn main() {
+    //!     let bar = unsafe { foo_bar::Reg::<Urt>::take() };
+    //!     bar.load();
+    //! }
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 1 RRRegField RcRegField }
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! drone_core::reg::mem::region! {
+    //!     pub struct Flags: [bool; 4] = 0x2000_F000;
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::{reg::prelude::*, token::Token};
+    //! drone_core::reg! {
+    //!     pub mod FOO BAR;
+    //!     0xDEAD_BEEF 0x20 0xBEEF_CACE RReg WReg;
+    //!     BAZ { 0 1 RRRegField W1cRegField }
+    //!     QUX { 1 1 RcRegField }
+    //! }
+    //! fn main() {
+    //!     let mut bar = unsafe { foo_bar::Reg::<Urt>::take() };
+    //!     bar.split_fields().baz.clear_flag();
+    //! }
+    //! ```
 }
@@ -10,17 +10,21 @@
 
 #[doc(no_inline)]
 pub use crate::reg::{
-    field::{RRRegField, RegField, RoRRegField, WWRegField, WoWRegField},
+    field::{
+        RRRegField, RcRegField, RegField, RoRRegField, W1cRegField, WWRegField, WoWRegField,
+        WsRegField,
+    },
     tag::{Crt, RegAtomic, RegOwned, RegTag, Srt, Urt},
-    RReg, Reg, RegHold, RoReg, WReg, WoReg,
+    RReg, Reg, RegBitBand, RegFifo, RegHold, RoReg, WReg, WoReg,
 };
 
 #[doc(no_inline)]
 pub use crate::reg::{
     field::{
-        RRRegFieldBit as _, RRRegFieldBits as _, RegFieldBit as _, RegFieldBits as _,
-        WWRegFieldBit as _, WWRegFieldBits as _, WoWoRegField as _, WoWoRegFieldBit as _,
-        WoWoRegFieldBits as _,
+        RRRegFieldBit as _, RRRegFieldBits as _, RRRegFieldEnum as _, RcRegFieldBit as _,
+        RcRegFieldBits as _, RegFieldBit as _, RegFieldBits as _, RegFieldEnum as _,
+        W1cRegFieldBit as _, WWRegFieldBit as _, WWRegFieldBits as _, WWRegFieldEnum as _,
+        WoWoRegField as _, WoWoRegFieldBit as _, WoWoRegFieldBits as _,
     },
-    RegRef as _, RwRegUnsync as _, WRegAtomic as _, WRegUnsync as _,
+    RegRef as _, RwRegAtomic as _, RwRegUnsync as _, WRegAtomic as _, WRegUnsync as _,
 };
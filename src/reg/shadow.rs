@@ -0,0 +1,126 @@
+use super::{tag::Urt, volatile::write_volatile, Reg, RegHold, RegRef, WReg, WoReg};
+use crate::bitfield::Bitfield;
+
+/// A write-only register token with a RAM copy of the last written value.
+///
+/// Write-only registers can't be read back, so modifying a single field
+/// requires the values of all other fields. The shadow keeps the last value
+/// written through it, and exposes read-modify-write operations on that copy.
+///
+/// The shadow is valid only if the register is written exclusively through
+/// this wrapper.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(proc_macro_hygiene)]
+/// use drone_core::{
+///     reg::{prelude::*, WRegShadow},
+///     token::Token,
+/// };
+///
+/// drone_core::reg! {
+///     /// Write-only output latch on the external memory bus.
+///     pub mod LATCH OUT;
+///     0x6000_0000 0x20 0x0000_0000
+///     WReg WoReg;
+///     LED0 { 0 1 WWRegField WoWRegField }
+///     LED1 { 1 1 WWRegField WoWRegField }
+/// }
+///
+/// # fn main() {
+/// # fn check() {
+/// let out = unsafe { latch_out::Reg::<Urt>::take() };
+/// let mut out = WRegShadow::new(out);
+/// out.store(|r| r.set_led0());
+/// out.modify(|r| r.set_led1());
+/// assert_eq!(out.bits(), 0b11);
+/// # }
+/// # }
+/// ```
+pub struct WRegShadow<R: WoReg<Urt>> {
+    reg: R,
+    bits: <R::Val as Bitfield>::Bits,
+}
+
+impl<R: WoReg<Urt>> WRegShadow<R> {
+    /// Wraps the register token `reg`. The shadow is initialized with the
+    /// register reset value.
+    #[inline]
+    pub fn new(reg: R) -> Self {
+        Self::with_bits(reg, R::RESET)
+    }
+
+    /// Wraps the register token `reg`, assuming the register holds `bits`.
+    #[inline]
+    pub fn with_bits(reg: R, bits: <R::Val as Bitfield>::Bits) -> Self {
+        Self { reg, bits }
+    }
+
+    /// Returns the shadow copy of the register value.
+    #[inline]
+    pub fn bits(&self) -> <R::Val as Bitfield>::Bits {
+        self.bits
+    }
+
+    /// Returns the shadow copy of the register value in the opaque value type.
+    #[inline]
+    pub fn val(&self) -> R::Val {
+        unsafe { R::val_from(self.bits) }
+    }
+
+    /// Returns the shadow copy of the register value in the exposed value
+    /// type.
+    #[inline]
+    pub fn load<'a>(&'a self) -> <R as RegRef<'a, Urt>>::Hold
+    where
+        R: RegRef<'a, Urt>,
+    {
+        self.reg.hold(self.val())
+    }
+
+    /// Writes `bits` into the register memory and the shadow.
+    #[inline]
+    pub fn store_bits(&mut self, bits: <R::Val as Bitfield>::Bits) {
+        unsafe { write_volatile(self.reg.as_mut_ptr(), bits) };
+        self.bits = bits;
+    }
+
+    /// Passes the opaque reset value to the closure `f`, then writes the result
+    /// of the closure into the register memory and the shadow.
+    #[inline]
+    pub fn store<'a, F>(&'a mut self, f: F)
+    where
+        R: RegRef<'a, Urt>,
+        F: for<'b> FnOnce(
+            &'b mut <R as RegRef<'a, Urt>>::Hold,
+        ) -> &'b mut <R as RegRef<'a, Urt>>::Hold,
+    {
+        let bits = f(&mut self.reg.default()).val().bits();
+        unsafe { write_volatile(self.reg.as_mut_ptr(), bits) };
+        self.bits = bits;
+    }
+
+    /// Passes the shadow copy of the register value to the closure `f`, then
+    /// writes the result of the closure into the register memory and the
+    /// shadow. Write side-effect bits are passed as zeros.
+    #[inline]
+    pub fn modify<'a, F>(&'a mut self, f: F)
+    where
+        R: RegRef<'a, Urt>,
+        F: for<'b> FnOnce(
+            &'b mut <R as RegRef<'a, Urt>>::Hold,
+        ) -> &'b mut <R as RegRef<'a, Urt>>::Hold,
+    {
+        let val = unsafe { R::val_from(self.bits & !R::WS_MASK) };
+        let bits = f(&mut self.reg.hold(val)).val().bits();
+        unsafe { write_volatile(self.reg.as_mut_ptr(), bits) };
+        self.bits = bits;
+    }
+
+    /// Unwraps the register token.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reg
+    }
+}
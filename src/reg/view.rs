@@ -0,0 +1,266 @@
+use super::volatile::read_volatile;
+use crate::log::Port;
+use core::fmt;
+
+/// The layout of a register field.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FieldLayout {
+    /// The field name as declared in [`reg!`](crate::reg).
+    pub name: &'static str,
+    /// The offset of the field inside the register.
+    pub offset: u8,
+    /// The bit-width of the field.
+    pub width: u8,
+}
+
+/// A change of a register field value.
+///
+/// Produced by the `diff_fields` method of register holds, which
+/// [`reg!`](crate::reg) generates.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FieldChange {
+    /// The field name as declared in [`reg!`](crate::reg).
+    pub name: &'static str,
+    /// The old value of the field.
+    pub old: u64,
+    /// The new value of the field.
+    pub new: u64,
+}
+
+/// An iterator over the fields, which values differ between two register
+/// values.
+///
+/// This `struct` is created by the `diff_fields` method of register holds.
+#[derive(Clone, Debug)]
+pub struct FieldChanges {
+    fields: &'static [FieldLayout],
+    old: u64,
+    new: u64,
+}
+
+/// The layout of a register.
+///
+/// Every register token provides its layout as [`Reg::LAYOUT`](super::Reg::LAYOUT).
+/// The layouts are also collected into the `MAP` tables of register blocks,
+/// which [`reg::tokens!`](crate::reg::tokens) generates, and into the
+/// `REG_TABLE` tables of generic peripherals, so generic code and test
+/// harnesses can inspect the memory layout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RegLayout {
+    /// The register name as declared in [`reg!`](crate::reg).
+    pub name: &'static str,
+    /// The register address in memory.
+    pub address: usize,
+    /// The register size in bits.
+    pub size: u8,
+    /// The register reset value.
+    pub reset: u64,
+    /// The layouts of the register fields.
+    pub fields: &'static [FieldLayout],
+}
+
+/// A run-time view of a register.
+///
+/// Unlike register tokens, the view is not zero-sized. It stores the register
+/// address and layout, so registers of different types can be handled
+/// uniformly, e.g. dumped over a log port, or introspected in host-side tests.
+///
+/// This `struct` is created by the [`view`](super::RReg::view) method.
+#[derive(Clone, Copy, Debug)]
+pub struct RegView {
+    layout: RegLayout,
+}
+
+/// Provides the [`RegView`] of a register for index summaries.
+///
+/// Implemented by [`reg!`](crate::reg) for every register.
+#[doc(hidden)]
+pub trait RegSummary {
+    /// Returns the view of the register, or `None` if the register is not
+    /// readable, or reading it has side effects.
+    fn summary_view() -> Option<RegView>;
+}
+
+/// A register value together with its [`RegView`].
+///
+/// The value is formatted with [`Display`](fmt::Display) as the register name,
+/// the raw value, and the values of all fields.
+#[derive(Clone, Copy, Debug)]
+pub struct RegValue {
+    view: RegView,
+    bits: u64,
+}
+
+impl RegView {
+    /// Creates a new view of the register with `layout`.
+    ///
+    /// # Safety
+    ///
+    /// `layout` must describe a readable register.
+    #[inline]
+    pub unsafe fn new(layout: RegLayout) -> Self {
+        Self { layout }
+    }
+
+    /// Returns the register layout.
+    #[inline]
+    pub fn layout(&self) -> &RegLayout {
+        &self.layout
+    }
+
+    /// Returns the register name.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.layout.name
+    }
+
+    /// Returns the register address in memory.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.layout.address
+    }
+
+    /// Returns the register size in bits.
+    #[inline]
+    pub fn size(&self) -> u8 {
+        self.layout.size
+    }
+
+    /// Returns the layouts of the register fields.
+    #[inline]
+    pub fn fields(&self) -> &'static [FieldLayout] {
+        self.layout.fields
+    }
+
+    /// Reads the value from the register memory.
+    pub fn load(self) -> RegValue {
+        let RegLayout { address, size, .. } = self.layout;
+        let bits = unsafe {
+            match size {
+                8 => u64::from(read_volatile(address as *const u8)),
+                16 => u64::from(read_volatile(address as *const u16)),
+                32 => u64::from(read_volatile(address as *const u32)),
+                _ => read_volatile(address as *const u64),
+            }
+        };
+        self.with_bits(bits)
+    }
+
+    /// Attaches raw `bits` to the view without reading the register memory.
+    #[inline]
+    pub fn with_bits(self, bits: u64) -> RegValue {
+        RegValue { view: self, bits }
+    }
+}
+
+impl RegValue {
+    /// Returns the register view.
+    #[inline]
+    pub fn view(&self) -> &RegView {
+        &self.view
+    }
+
+    /// Returns the raw value.
+    #[inline]
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns the value of the field named `name`, or `None` if the register
+    /// has no such field.
+    pub fn field(&self, name: &str) -> Option<u64> {
+        self.view
+            .fields()
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| extract(self.bits, field))
+    }
+
+    /// Returns an iterator over the field names and values.
+    pub fn fields(&self) -> impl Iterator<Item = (&'static str, u64)> + '_ {
+        self.view.fields().iter().map(move |field| (field.name, extract(self.bits, field)))
+    }
+
+    /// Writes the register address and the raw value to `port` as a pair of
+    /// `u32` words. 64-bit values are written as two pairs, one for the low
+    /// word at `address`, and one for the high word at `address + 4`.
+    pub fn write_to(&self, port: Port) {
+        let address = self.view.address() as u32;
+        port.write(address).write(self.bits as u32);
+        if self.view.size() == 64 {
+            port.write(address + 4).write((self.bits >> 32) as u32);
+        }
+    }
+}
+
+impl FieldChanges {
+    /// Creates an iterator over the `fields`, which values differ between the
+    /// raw values `old` and `new`.
+    #[inline]
+    pub fn new(fields: &'static [FieldLayout], old: u64, new: u64) -> Self {
+        Self { fields, old, new }
+    }
+}
+
+impl Iterator for FieldChanges {
+    type Item = FieldChange;
+
+    fn next(&mut self) -> Option<FieldChange> {
+        while let Some((field, rest)) = self.fields.split_first() {
+            self.fields = rest;
+            let old = extract(self.old, field);
+            let new = extract(self.new, field);
+            if old != new {
+                return Some(FieldChange { name: field.name, old, new });
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.fields.len()))
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: 0x{:X} -> 0x{:X}", self.name, self.old, self.new)
+    }
+}
+
+impl fmt::Display for RegValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = usize::from(self.view.size() / 4);
+        write!(f, "{} = {:#0w$x}", self.view.name(), self.bits, w = width + 2)?;
+        for (name, value) in self.fields() {
+            write!(f, " {}={:#x}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the register `name` and the values of its `fields` extracted from
+/// `bits` as a struct, like `CTRL { ENABLE: 1, TICKINT: 0 }`.
+///
+/// Used by the `Debug` and `Display` implementations, which
+/// [`reg!`](crate::reg) generates for register holds with the `regfmt`
+/// feature.
+#[doc(hidden)]
+pub fn fmt_fields(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    fields: &[FieldLayout],
+    bits: u64,
+) -> fmt::Result {
+    let mut debug = f.debug_struct(name);
+    for field in fields {
+        debug.field(field.name, &extract(bits, field));
+    }
+    debug.finish()
+}
+
+fn extract(bits: u64, field: &FieldLayout) -> u64 {
+    let mask = if field.width >= 64 { !0 } else { (1 << field.width) - 1 };
+    bits >> field.offset & mask
+}
@@ -0,0 +1,93 @@
+use crate::bitfield::Bits;
+#[cfg(any(feature = "sim", feature = "mock"))]
+use crate::sim as backend;
+#[cfg(not(any(feature = "sim", feature = "mock")))]
+use core::ptr as backend;
+
+/// Reads a register value from `src`.
+///
+/// Depending on the enabled features, the value is read from the simulated
+/// memory or the real address space. With the `regtrace` feature the access is
+/// traced.
+#[inline]
+pub(crate) unsafe fn read_volatile<T: Copy>(src: *const T) -> T {
+    let value = backend::read_volatile(src);
+    #[cfg(feature = "regtrace")]
+    trace::load(src as usize, value);
+    value
+}
+
+/// Writes a register value `src` to `dst`.
+///
+/// See [`read_volatile`] for the details.
+#[inline]
+pub(crate) unsafe fn write_volatile<T: Copy>(dst: *mut T, src: T) {
+    #[cfg(feature = "regtrace")]
+    trace::store(dst as usize, src);
+    backend::write_volatile(dst, src);
+}
+
+/// Reads a memory region element from `src`.
+///
+/// Unlike [`read_volatile`], the access is not traced.
+#[inline]
+pub(crate) unsafe fn read_mem<T: Bits>(src: *const T) -> T {
+    backend::read_volatile(src)
+}
+
+/// Writes a memory region element `src` to `dst`.
+///
+/// See [`read_mem`] for the details.
+#[inline]
+pub(crate) unsafe fn write_mem<T: Bits>(dst: *mut T, src: T) {
+    backend::write_volatile(dst, src);
+}
+
+#[cfg(feature = "regtrace")]
+mod trace {
+    use crate::{
+        critical,
+        log::{Port, REGTRACE_PORT},
+    };
+    use core::{mem::size_of, slice};
+
+    /// Register read tag.
+    const TAG_LOAD: u32 = 0xE1;
+    /// Register write tag.
+    const TAG_STORE: u32 = 0xE2;
+
+    #[inline(always)]
+    pub(super) fn load<T: Copy>(address: usize, value: T) {
+        if Port::new(REGTRACE_PORT).is_enabled() {
+            trace(TAG_LOAD, address, bits(value), size_of::<T>());
+        }
+    }
+
+    #[inline(always)]
+    pub(super) fn store<T: Copy>(address: usize, value: T) {
+        if Port::new(REGTRACE_PORT).is_enabled() {
+            trace(TAG_STORE, address, bits(value), size_of::<T>());
+        }
+    }
+
+    /// Writes the record inside a critical section, so that records from
+    /// preempting threads don't interleave.
+    #[inline(never)]
+    fn trace(tag: u32, address: usize, bits: u64, size: usize) {
+        critical::with(|_| {
+            let port = Port::new(REGTRACE_PORT)
+                .write::<u32>(tag << 24 | (size * 8) as u32)
+                .write::<u32>(address as u32)
+                .write::<u32>(bits as u32);
+            if size > 4 {
+                port.write::<u32>((bits >> 32) as u32);
+            }
+        });
+    }
+
+    fn bits<T: Copy>(value: T) -> u64 {
+        let bytes =
+            unsafe { slice::from_raw_parts(&value as *const T as *const u8, size_of::<T>()) };
+        bytes.iter().rev().fold(0, |value, &byte| value << 8 | u64::from(byte))
+    }
+}
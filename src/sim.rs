@@ -0,0 +1,331 @@
+//! Host simulation of the runtime.
+//!
+//! This module is available with the `sim` feature, which implies `std`. It
+//! allows running the logic of an entire Drone application on the host, e.g.
+//! in CI unit tests:
+//!
+//! * Register accesses hit a simulated memory instead of the real address
+//!   space. The memory is zero-initialized, and can be inspected and preset
+//!   with [`peek`] and [`poke`]. Read-to-clear bits are modeled with
+//!   [`clear_on_read`].
+//! * Threads are simulated by a deterministic scheduler. A thread is
+//!   [`trigger`]ed in place of pending its interrupt, and the pending threads
+//!   are resumed by [`run`] in the order of triggering.
+//! * Log ports `0` and `1` write to the standard output and the standard error
+//!   of the host process. The output of other ports can be captured with
+//!   [`capture_log`] and inspected with [`take_log`], otherwise they are
+//!   disabled.
+//!
+//! All state is local to the current host thread, so concurrently running
+//! tests don't interfere with each other.
+//!
+//! Memory writes can be recorded with [`record`], and checked against an
+//! expected sequence with [`assert_writes`]. This allows golden-sequence tests
+//! for driver initialization code:
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{reg, reg::prelude::*, sim, sim::Expect, token::Token};
+//!
+//! reg!(pub mod RCC CR; 0x4002_1000 32 0 RReg WReg; HSION { 0 1 RRRegField WWRegField });
+//!
+//! # fn main() {
+//! let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+//! sim::record();
+//! cr.store(|r| r.set_hsion());
+//! cr.modify(|r| r.clear_hsion());
+//! sim::assert_writes(&[Expect::write(0x4002_1000, 1_u32), Expect::any()]);
+//! # }
+//! ```
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{reg, reg::prelude::*, sim, token::Token};
+//!
+//! reg!(pub mod RCC CR; 0x4002_1000 32 0 RReg WReg; HSION { 0 1 RRRegField WWRegField });
+//!
+//! # fn main() {
+//! let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+//! cr.store(|r| r.set_hsion());
+//! assert_eq!(sim::peek::<u32>(0x4002_1000), 1);
+//! # }
+//! ```
+//!
+//! With the `mock` feature alone, this module is private, and only its memory
+//! backs the `reg::mock` registers.
+
+#![cfg_attr(not(feature = "sim"), allow(dead_code))]
+
+use crate::{
+    bitfield::Bits,
+    thr::{thread_resume, ThrToken},
+};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    io::{self, Write},
+    mem::{size_of, MaybeUninit},
+    slice,
+    vec::Vec,
+};
+
+std::thread_local! {
+    static MEMORY: RefCell<BTreeMap<usize, u8>> = RefCell::new(BTreeMap::new());
+    static PENDING: RefCell<VecDeque<unsafe fn()>> = RefCell::new(VecDeque::new());
+    static RECORD: RefCell<Option<Vec<MemWrite>>> = RefCell::new(None);
+    static LOG: RefCell<BTreeMap<u8, Vec<u8>>> = RefCell::new(BTreeMap::new());
+    static CLEAR_ON_READ: RefCell<BTreeMap<usize, u8>> = RefCell::new(BTreeMap::new());
+}
+
+/// A recorded memory write.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MemWrite {
+    /// Destination address.
+    pub address: usize,
+    /// Written value, zero-extended.
+    pub value: u64,
+    /// Width of the write in bits.
+    pub width: usize,
+}
+
+/// An item of an expected writes sequence for [`assert_writes`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Expect {
+    /// A single write to `address` of `width` bits, which has the `mask` bits
+    /// equal to `value`.
+    Write {
+        /// Destination address.
+        address: usize,
+        /// Expected value.
+        value: u64,
+        /// Bits to compare.
+        mask: u64,
+        /// Width of the write in bits.
+        width: usize,
+    },
+    /// Any single write.
+    Any,
+    /// Any number of writes, including zero.
+    Skip,
+}
+
+/// Reads an integer of type `T` from the simulated memory at `address`.
+///
+/// Unlike register reads, this function doesn't clear the bits set up with
+/// [`clear_on_read`].
+pub fn peek<T: Bits>(address: usize) -> T {
+    unsafe { load(address as *const T, false) }
+}
+
+/// Writes an integer `value` into the simulated memory at `address`.
+pub fn poke<T: Bits>(address: usize, value: T) {
+    unsafe { write_volatile(address as *mut T, value) };
+}
+
+/// Makes the `mask` bits of the integer of type `T` at `address` cleared by
+/// every read of the simulated memory, as read-to-clear register fields are.
+pub fn clear_on_read<T: Bits>(address: usize, mask: T) {
+    let bytes = unsafe { slice::from_raw_parts(&mask as *const T as *const u8, size_of::<T>()) };
+    CLEAR_ON_READ.with(|clear| {
+        let mut clear = clear.borrow_mut();
+        for (offset, &byte) in bytes.iter().enumerate() {
+            *clear.entry(address + offset).or_default() |= byte;
+        }
+    });
+}
+
+/// Clears the simulated memory, the read-to-clear bits, and the pending
+/// threads queue, and stops recording memory writes and capturing log ports.
+pub fn reset() {
+    reset_memory();
+    PENDING.with(|pending| pending.borrow_mut().clear());
+    RECORD.with(|record| record.borrow_mut().take());
+    LOG.with(|log| log.borrow_mut().clear());
+}
+
+pub(crate) fn reset_memory() {
+    MEMORY.with(|memory| memory.borrow_mut().clear());
+    CLEAR_ON_READ.with(|clear| clear.borrow_mut().clear());
+}
+
+/// Starts recording memory writes, discarding previously recorded ones.
+pub fn record() {
+    RECORD.with(|record| *record.borrow_mut() = Some(Vec::new()));
+}
+
+/// Returns memory writes recorded since the last call, or since [`record`].
+/// Returns an empty vector if the recording is not started.
+pub fn take_writes() -> Vec<MemWrite> {
+    RECORD.with(|record| record.borrow_mut().as_mut().map(std::mem::take).unwrap_or_default())
+}
+
+/// Enables the log port `port`, and starts capturing its output, discarding
+/// previously captured output.
+pub fn capture_log(port: u8) {
+    LOG.with(|log| log.borrow_mut().insert(port, Vec::new()));
+}
+
+/// Returns the output of the log port `port` captured since the last call, or
+/// since [`capture_log`]. Returns an empty vector if the port is not captured.
+pub fn take_log(port: u8) -> Vec<u8> {
+    LOG.with(|log| log.borrow_mut().get_mut(&port).map(std::mem::take).unwrap_or_default())
+}
+
+/// Takes the recorded memory writes with [`take_writes`], and asserts that
+/// they match the `expected` sequence.
+///
+/// # Panics
+///
+/// If the recorded writes don't match the sequence.
+pub fn assert_writes(expected: &[Expect]) {
+    let writes = take_writes();
+    if !Expect::matches(expected, &writes) {
+        panic!(
+            "memory writes don't match\n  expected: {:?}\n    actual: [{}]",
+            expected,
+            writes.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+        );
+    }
+}
+
+/// Marks the thread `T` as pending.
+///
+/// This is the simulated counterpart of pending the thread interrupt. A thread
+/// can be triggered multiple times, in which case it will be resumed the same
+/// number of times.
+pub fn trigger<T: ThrToken>(_thr: T) {
+    PENDING.with(|pending| pending.borrow_mut().push_back(thread_resume::<T>));
+}
+
+/// Resumes pending threads until there are no more pending threads. Threads
+/// triggered during the run are resumed too.
+///
+/// Returns the number of thread resumptions.
+pub fn run() -> usize {
+    let mut count = 0;
+    while let Some(resume) = PENDING.with(|pending| pending.borrow_mut().pop_front()) {
+        unsafe { resume() };
+        count += 1;
+    }
+    count
+}
+
+/// Simulated counterpart of [`core::ptr::read_volatile`].
+pub(crate) unsafe fn read_volatile<T: Copy>(src: *const T) -> T {
+    load(src, true)
+}
+
+unsafe fn load<T: Copy>(src: *const T, clear_on_read: bool) -> T {
+    let mut value = MaybeUninit::<T>::uninit();
+    let bytes = slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, size_of::<T>());
+    MEMORY.with(|memory| {
+        let mut memory = memory.borrow_mut();
+        for (offset, byte) in bytes.iter_mut().enumerate() {
+            let address = src as usize + offset;
+            *byte = memory.get(&address).copied().unwrap_or(0);
+            if clear_on_read {
+                let mask = CLEAR_ON_READ.with(|clear| clear.borrow().get(&address).copied());
+                if let Some(mask) = mask {
+                    memory.insert(address, *byte & !mask);
+                }
+            }
+        }
+    });
+    value.assume_init()
+}
+
+/// Simulated counterpart of [`core::ptr::write_volatile`].
+pub(crate) unsafe fn write_volatile<T: Copy>(dst: *mut T, src: T) {
+    let bytes = slice::from_raw_parts(&src as *const T as *const u8, size_of::<T>());
+    MEMORY.with(|memory| {
+        let mut memory = memory.borrow_mut();
+        for (offset, &byte) in bytes.iter().enumerate() {
+            memory.insert(dst as usize + offset, byte);
+        }
+    });
+    RECORD.with(|record| {
+        if let Some(record) = record.borrow_mut().as_mut() {
+            let value = bytes.iter().rev().fold(0, |value, &byte| value << 8 | u64::from(byte));
+            record.push(MemWrite { address: dst as usize, value, width: bytes.len() * 8 });
+        }
+    });
+}
+
+impl Expect {
+    /// A write of `value` to `address`. The width of the write is taken from
+    /// the type of `value`.
+    pub fn write<T: Into<u64>>(address: usize, value: T) -> Self {
+        Self::masked(address, value, !0)
+    }
+
+    /// A write to `address`, which has the `mask` bits equal to `value`. The
+    /// width of the write is taken from the type of `value`.
+    pub fn masked<T: Into<u64>>(address: usize, value: T, mask: u64) -> Self {
+        Self::Write { address, value: value.into(), mask, width: size_of::<T>() * 8 }
+    }
+
+    /// Any single write.
+    pub fn any() -> Self {
+        Self::Any
+    }
+
+    /// Any number of writes, including zero.
+    pub fn skip() -> Self {
+        Self::Skip
+    }
+
+    fn matches(expected: &[Self], writes: &[MemWrite]) -> bool {
+        match (expected.split_first(), writes.split_first()) {
+            (None, None) => true,
+            (Some((Self::Skip, rest)), _) => {
+                Self::matches(rest, writes)
+                    || !writes.is_empty() && Self::matches(expected, &writes[1..])
+            }
+            (Some((head, rest)), Some((write, writes))) => {
+                head.matches_write(write) && Self::matches(rest, writes)
+            }
+            _ => false,
+        }
+    }
+
+    fn matches_write(&self, write: &MemWrite) -> bool {
+        match *self {
+            Self::Write { address, value, mask, width } => {
+                write.address == address
+                    && write.width == width
+                    && write.value & mask == value & mask
+            }
+            Self::Any | Self::Skip => true,
+        }
+    }
+}
+
+impl fmt::Display for MemWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x} <- {:#x} (u{})", self.address, self.value, self.width)
+    }
+}
+
+pub(crate) fn log_is_enabled(port: u8) -> bool {
+    port < 2 || LOG.with(|log| log.borrow().contains_key(&port))
+}
+
+pub(crate) fn log_write(port: u8, bytes: &[u8]) {
+    let captured = LOG.with(|log| {
+        log.borrow_mut().get_mut(&port).map(|output| output.extend_from_slice(bytes)).is_some()
+    });
+    if captured {
+        return;
+    }
+    let _ = match port {
+        0 => io::stdout().write_all(bytes),
+        1 => io::stderr().write_all(bytes),
+        _ => Ok(()),
+    };
+}
+
+pub(crate) fn log_flush() {
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+}
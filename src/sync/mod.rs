@@ -9,3 +9,26 @@ pub use self::{
     mutex::{Mutex, MutexGuard},
     rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
+
+mod compile_tests {
+    //! ```compile_fail
+    //! use drone_core::sync::Mutex;
+    //! fn assert_send<T: Send>(_: T) {}
+    //! let mutex = Mutex::new(0);
+    //! assert_send(mutex.try_lock().unwrap());
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::sync::RwLock;
+    //! fn assert_send<T: Send>(_: T) {}
+    //! let rw_lock = RwLock::new(0);
+    //! assert_send(rw_lock.try_read().unwrap());
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::sync::RwLock;
+    //! fn assert_send<T: Send>(_: T) {}
+    //! let rw_lock = RwLock::new(0);
+    //! assert_send(rw_lock.try_write().unwrap());
+    //! ```
+}
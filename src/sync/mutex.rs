@@ -1,8 +1,9 @@
+use crate::atomic::{AtomicBool, Ordering};
 use core::{
     cell::UnsafeCell,
     fmt,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
-    sync::atomic::{AtomicBool, Ordering},
 };
 
 /// A mutual exclusion primitive useful for protecting shared data.
@@ -28,12 +29,12 @@ pub struct Mutex<T: ?Sized> {
 #[must_use = "if unused the Mutex will immediately unlock"]
 pub struct MutexGuard<'a, T: ?Sized> {
     mutex: &'a Mutex<T>,
+    _marker: PhantomData<*const ()>,
 }
 
 unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
 unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
 
-impl<T: ?Sized> !Send for MutexGuard<'_, T> {}
 unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
 
 impl<T> Mutex<T> {
@@ -100,7 +101,7 @@ impl<T: ?Sized> Mutex<T> {
         if self.state.swap(true, Ordering::Acquire) {
             None
         } else {
-            Some(MutexGuard { mutex: self })
+            Some(MutexGuard { mutex: self, _marker: PhantomData })
         }
     }
 
@@ -1,8 +1,9 @@
+use crate::atomic::{AtomicUsize, Ordering};
 use core::{
     cell::UnsafeCell,
     fmt,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
-    sync::atomic::{AtomicUsize, Ordering},
 };
 
 const WRITE_LOCK: usize = usize::max_value();
@@ -38,6 +39,7 @@ pub struct RwLock<T: ?Sized> {
 #[must_use = "if unused the RwLock will immediately unlock"]
 pub struct RwLockReadGuard<'a, T: ?Sized> {
     rw_lock: &'a RwLock<T>,
+    _marker: PhantomData<*const ()>,
 }
 
 /// RAII structure used to release the exclusive write access of a lock when
@@ -48,15 +50,14 @@ pub struct RwLockReadGuard<'a, T: ?Sized> {
 #[must_use = "if unused the RwLock will immediately unlock"]
 pub struct RwLockWriteGuard<'a, T: ?Sized> {
     rw_lock: &'a RwLock<T>,
+    _marker: PhantomData<*const ()>,
 }
 
 unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
 unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
 
-impl<T: ?Sized> !Send for RwLockReadGuard<'_, T> {}
 unsafe impl<T: ?Sized + Sync> Sync for RwLockReadGuard<'_, T> {}
 
-impl<T: ?Sized> !Send for RwLockWriteGuard<'_, T> {}
 unsafe impl<T: ?Sized + Sync> Sync for RwLockWriteGuard<'_, T> {}
 
 impl<T> RwLock<T> {
@@ -124,8 +125,12 @@ impl<T: ?Sized> RwLock<T> {
             if current >= WRITE_LOCK - 1 {
                 break None;
             }
-            if self.state.compare_and_swap(current, current + 1, Ordering::Acquire) == current {
-                break Some(RwLockReadGuard { rw_lock: self });
+            if self
+                .state
+                .compare_exchange(current, current + 1, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                break Some(RwLockReadGuard { rw_lock: self, _marker: PhantomData });
             }
         }
     }
@@ -154,8 +159,12 @@ impl<T: ?Sized> RwLock<T> {
     /// ```
     #[inline]
     pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
-        if self.state.compare_and_swap(NO_LOCK, WRITE_LOCK, Ordering::Acquire) == NO_LOCK {
-            Some(RwLockWriteGuard { rw_lock: self })
+        if self
+            .state
+            .compare_exchange(NO_LOCK, WRITE_LOCK, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            Some(RwLockWriteGuard { rw_lock: self, _marker: PhantomData })
         } else {
             None
         }
@@ -1,6 +1,8 @@
 //! Single-producer, single-consumer communication primitives.
 
+use crate::atomic::spsc::UnsafeCell;
 use core::{
+    convert::Infallible,
     mem::MaybeUninit,
     ops::{BitAnd, BitOr, BitOrAssign, BitXorAssign},
     sync::atomic::Ordering,
@@ -11,6 +13,10 @@ pub mod oneshot;
 pub mod pulse;
 pub mod ring;
 
+mod shared;
+
+pub(self) use self::shared::Shared;
+
 pub(self) trait SpscInner<A, I>
 where
     I: Copy + Eq + BitAnd<Output = I> + BitOr<Output = I> + BitOrAssign + BitXorAssign,
@@ -30,11 +36,9 @@ where
         failure: Ordering,
     ) -> Result<I, I>;
 
-    #[allow(clippy::mut_from_ref)]
-    unsafe fn rx_waker_mut(&self) -> &mut MaybeUninit<Waker>;
+    fn rx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>>;
 
-    #[allow(clippy::mut_from_ref)]
-    unsafe fn tx_waker_mut(&self) -> &mut MaybeUninit<Waker>;
+    fn tx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>>;
 
     #[inline]
     fn transaction<R, E>(
@@ -81,14 +85,15 @@ where
         if value.is_ready() || state & waker_stored != Self::ZERO {
             return value;
         }
-        unsafe {
-            let waker = if is_tx_half { self.tx_waker_mut() } else { self.rx_waker_mut() };
-            waker.write(cx.waker().clone());
-        }
-        let Ok(state) = self.transaction(state, cas_order, read_order, |state| {
+        let waker = if is_tx_half { self.tx_waker() } else { self.rx_waker() };
+        waker.with_mut(|waker| unsafe { (*waker).as_mut_ptr().write(cx.waker().clone()) });
+        let state = match self.transaction(state, cas_order, read_order, |state| {
             *state |= waker_stored;
-            Ok::<_, !>(*state)
-        });
+            Ok::<_, Infallible>(*state)
+        }) {
+            Ok(state) => state,
+            Err(never) => match never {},
+        };
         take(self, state)
     }
 
@@ -110,15 +115,17 @@ where
         .or_else(|value| {
             value.map(Err).or_else(|no_waker| {
                 no_waker.and_then(|()| {
-                    unsafe {
-                        let waker =
-                            if is_tx_half { self.tx_waker_mut() } else { self.rx_waker_mut() };
-                        waker.write(cx.waker().clone());
-                    }
-                    let Ok(value) = self.transaction(state, cas_order, read_order, |state| {
-                        *state |= waker_stored;
-                        Ok::<_, !>(take_try(self, state))
+                    let waker = if is_tx_half { self.tx_waker() } else { self.rx_waker() };
+                    waker.with_mut(|waker| unsafe {
+                        (*waker).as_mut_ptr().write(cx.waker().clone());
                     });
+                    let value = match self.transaction(state, cas_order, read_order, |state| {
+                        *state |= waker_stored;
+                        Ok::<_, Infallible>(take_try(self, state))
+                    }) {
+                        Ok(value) => value,
+                        Err(never) => match never {},
+                    };
                     value.ok_or(())
                 })
             })
@@ -146,13 +153,11 @@ where
                 if waker || complete { Ok((waker, complete)) } else { Err(()) }
             })
         {
-            unsafe {
-                if waker {
-                    let waker = if is_tx_half { self.rx_waker_mut() } else { self.tx_waker_mut() };
-                    let waker = waker.read();
-                    if complete {
-                        waker.wake();
-                    }
+            if waker {
+                let waker = if is_tx_half { self.rx_waker() } else { self.tx_waker() };
+                let waker = waker.with(|waker| unsafe { (*waker).as_ptr().read() });
+                if complete {
+                    waker.wake();
                 }
             }
         }
@@ -165,20 +170,19 @@ where
 {
     type Error;
 
-    #[allow(clippy::mut_from_ref)]
-    unsafe fn err_mut(&self) -> &mut Option<Self::Error>;
+    fn err(&self) -> &UnsafeCell<Option<Self::Error>>;
 
     fn send_err(&self, err: Self::Error) -> Result<(), Self::Error> {
         if self.is_canceled(Ordering::Relaxed) {
             Err(err)
         } else {
-            unsafe { *self.err_mut() = Some(err) };
+            self.err().with_mut(|slot| unsafe { *slot = Some(err) });
             // Should we do an additional synchronization here?
             Ok(())
         }
     }
 
     fn take_err<T>(&self) -> Option<Result<T, Self::Error>> {
-        unsafe { self.err_mut().take().map(Err) }
+        self.err().with_mut(|slot| unsafe { (*slot).take().map(Err) })
     }
 }
@@ -10,14 +10,14 @@ pub use self::{
     sender::Sender,
 };
 
-use crate::sync::spsc::SpscInner;
-use alloc::sync::Arc;
-use core::{
-    cell::UnsafeCell,
-    mem::MaybeUninit,
-    sync::atomic::{AtomicU8, Ordering},
-    task::Waker,
+use crate::{
+    atomic::{
+        spsc::{AtomicU8, UnsafeCell},
+        Ordering,
+    },
+    sync::spsc::{Shared, SpscInner},
 };
+use core::{mem::MaybeUninit, task::Waker};
 
 #[allow(clippy::identity_op)]
 const TX_WAKER_STORED: u8 = 1 << 0;
@@ -38,9 +38,9 @@ struct Inner<T> {
 /// resolving to the value that was given to the [`Sender`] half.
 #[inline]
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
-    let inner = Arc::new(Inner::new());
-    let sender = Sender::new(Arc::clone(&inner));
-    let receiver = Receiver::new(inner);
+    let (tx_inner, rx_inner) = Shared::pair(Inner::new());
+    let sender = Sender::new(tx_inner);
+    let receiver = Receiver::new(rx_inner);
     (sender, receiver)
 }
 
@@ -82,13 +82,13 @@ impl<T> SpscInner<AtomicU8, u8> for Inner<T> {
     }
 
     #[inline]
-    unsafe fn rx_waker_mut(&self) -> &mut MaybeUninit<Waker> {
-        &mut *self.rx_waker.get()
+    fn rx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>> {
+        &self.rx_waker
     }
 
     #[inline]
-    unsafe fn tx_waker_mut(&self) -> &mut MaybeUninit<Waker> {
-        &mut *self.tx_waker.get()
+    fn tx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>> {
+        &self.tx_waker
     }
 }
 
@@ -1,6 +1,5 @@
 use super::{Inner, COMPLETE};
-use crate::sync::spsc::SpscInner;
-use alloc::sync::Arc;
+use crate::sync::spsc::{Shared, SpscInner};
 use core::{
     fmt,
     future::Future,
@@ -14,7 +13,7 @@ const IS_TX_HALF: bool = false;
 /// The receiving-half of [`oneshot::channel`](super::channel).
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Receiver<T> {
-    inner: Arc<Inner<T>>,
+    inner: Shared<Inner<T>>,
 }
 
 /// Error returned from a [`Receiver`] when the corresponding
@@ -23,7 +22,7 @@ pub struct Receiver<T> {
 pub struct Canceled;
 
 impl<T> Receiver<T> {
-    pub(super) fn new(inner: Arc<Inner<T>>) -> Self {
+    pub(super) fn new(inner: Shared<Inner<T>>) -> Self {
         Self { inner }
     }
 
@@ -49,7 +48,11 @@ impl<T> Receiver<T> {
     /// Returns an error if the sender was dropped.
     #[inline]
     pub fn try_recv(&mut self) -> Result<Option<T>, Canceled> {
-        self.inner.try_recv()
+        let data = self.inner.try_recv()?;
+        if data.is_some() {
+            self.inner.trace_recv();
+        }
+        Ok(data)
     }
 }
 
@@ -58,7 +61,12 @@ impl<T> Future for Receiver<T> {
 
     #[inline]
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.inner.poll_half(cx, IS_TX_HALF, Ordering::Acquire, Ordering::AcqRel, Inner::take)
+        let poll =
+            self.inner.poll_half(cx, IS_TX_HALF, Ordering::Acquire, Ordering::AcqRel, Inner::take);
+        if let Poll::Ready(Ok(_)) = poll {
+            self.inner.trace_recv();
+        }
+        poll
     }
 }
 
@@ -75,7 +83,7 @@ impl<T> Inner<T> {
         if state & COMPLETE == 0 {
             Ok(None)
         } else {
-            unsafe { &mut *self.data.get() }.take().ok_or(Canceled).map(Some)
+            self.data.with_mut(|slot| unsafe { (*slot).take() }).ok_or(Canceled).map(Some)
         }
     }
 
@@ -83,7 +91,7 @@ impl<T> Inner<T> {
         if state & COMPLETE == 0 {
             Poll::Pending
         } else {
-            Poll::Ready(unsafe { &mut *self.data.get() }.take().ok_or(Canceled))
+            Poll::Ready(self.data.with_mut(|slot| unsafe { (*slot).take() }).ok_or(Canceled))
         }
     }
 }
@@ -1,6 +1,5 @@
 use super::Inner;
-use crate::sync::spsc::SpscInner;
-use alloc::sync::Arc;
+use crate::sync::spsc::{Shared, SpscInner};
 use core::{
     sync::atomic::Ordering,
     task::{Context, Poll},
@@ -10,11 +9,11 @@ const IS_TX_HALF: bool = true;
 
 /// The sending-half of [`oneshot::channel`](super::channel).
 pub struct Sender<T> {
-    inner: Arc<Inner<T>>,
+    inner: Shared<Inner<T>>,
 }
 
 impl<T> Sender<T> {
-    pub(super) fn new(inner: Arc<Inner<T>>) -> Self {
+    pub(super) fn new(inner: Shared<Inner<T>>) -> Self {
         Self { inner }
     }
 
@@ -30,7 +29,9 @@ impl<T> Sender<T> {
     /// provided.
     #[inline]
     pub fn send(self, data: T) -> Result<(), T> {
-        self.inner.send(data)
+        self.inner.send(data)?;
+        self.inner.trace_send();
+        Ok(())
     }
 
     /// Polls this `Sender` half to detect whether its associated
@@ -80,7 +81,7 @@ impl<T> Inner<T> {
         if self.is_canceled(Ordering::Relaxed) {
             Err(data)
         } else {
-            unsafe { *self.data.get() = Some(data) };
+            self.data.with_mut(|slot| unsafe { *slot = Some(data) });
             Ok(())
         }
     }
@@ -11,12 +11,15 @@ pub use self::{
     sender::{SendError, Sender},
 };
 
-use crate::sync::spsc::{SpscInner, SpscInnerErr};
-use alloc::sync::Arc;
+use crate::{
+    atomic::{
+        spsc::{AtomicUsize, UnsafeCell},
+        Ordering,
+    },
+    sync::spsc::{Shared, SpscInner, SpscInnerErr},
+};
 use core::{
-    cell::UnsafeCell,
     mem::{size_of, MaybeUninit},
-    sync::atomic::{AtomicUsize, Ordering},
     task::Waker,
 };
 
@@ -43,9 +46,9 @@ struct Inner<E> {
 /// pulses signaled from the last polling.
 #[inline]
 pub fn channel<E>() -> (Sender<E>, Receiver<E>) {
-    let inner = Arc::new(Inner::new());
-    let sender = Sender::new(Arc::clone(&inner));
-    let receiver = Receiver::new(inner);
+    let (tx_inner, rx_inner) = Shared::pair(Inner::new());
+    let sender = Sender::new(tx_inner);
+    let receiver = Receiver::new(rx_inner);
     (sender, receiver)
 }
 
@@ -87,21 +90,22 @@ impl<E> SpscInner<AtomicUsize, usize> for Inner<E> {
     }
 
     #[inline]
-    unsafe fn rx_waker_mut(&self) -> &mut MaybeUninit<Waker> {
-        &mut *self.rx_waker.get()
+    fn rx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>> {
+        &self.rx_waker
     }
 
     #[inline]
-    unsafe fn tx_waker_mut(&self) -> &mut MaybeUninit<Waker> {
-        &mut *self.tx_waker.get()
+    fn tx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>> {
+        &self.tx_waker
     }
 }
 
 impl<E> SpscInnerErr<AtomicUsize, usize> for Inner<E> {
     type Error = E;
 
-    unsafe fn err_mut(&self) -> &mut Option<Self::Error> {
-        &mut *self.err.get()
+    #[inline]
+    fn err(&self) -> &UnsafeCell<Option<Self::Error>> {
+        &self.err
     }
 }
 
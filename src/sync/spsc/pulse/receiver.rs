@@ -1,6 +1,5 @@
 use super::{Inner, COMPLETE, OPTION_BITS};
-use crate::sync::spsc::{SpscInner, SpscInnerErr};
-use alloc::sync::Arc;
+use crate::sync::spsc::{Shared, SpscInner, SpscInnerErr};
 use core::{
     num::NonZeroUsize,
     pin::Pin,
@@ -14,11 +13,11 @@ const IS_TX_HALF: bool = false;
 /// The receiving-half of [`pulse::channel`](super::channel).
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Receiver<E> {
-    inner: Arc<Inner<E>>,
+    inner: Shared<Inner<E>>,
 }
 
 impl<E> Receiver<E> {
-    pub(super) fn new(inner: Arc<Inner<E>>) -> Self {
+    pub(super) fn new(inner: Shared<Inner<E>>) -> Self {
         Self { inner }
     }
 
@@ -42,7 +41,11 @@ impl<E> Receiver<E> {
     /// of date) unless [`close`](Receiver::close) has been called first.
     #[inline]
     pub fn try_next(&mut self) -> Result<Option<NonZeroUsize>, E> {
-        self.inner.try_next(Inner::<E>::take_try)
+        let pulses = self.inner.try_next(Inner::<E>::take_try)?;
+        if pulses.is_some() {
+            self.inner.trace_recv();
+        }
+        Ok(pulses)
     }
 }
 
@@ -51,14 +54,18 @@ impl<E> Stream for Receiver<E> {
 
     #[inline]
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.inner.poll_half_with_transaction(
+        let poll = self.inner.poll_half_with_transaction(
             cx,
             IS_TX_HALF,
             Ordering::Acquire,
             Ordering::AcqRel,
             Inner::<E>::take_try,
             Inner::take_finalize,
-        )
+        );
+        if let Poll::Ready(Some(Ok(_))) = poll {
+            self.inner.trace_recv();
+        }
+        poll
     }
 }
 
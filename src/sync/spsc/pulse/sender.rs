@@ -1,6 +1,5 @@
 use super::{Inner, COMPLETE, OPTION_BITS, RX_WAKER_STORED};
-use crate::sync::spsc::{SpscInner, SpscInnerErr};
-use alloc::sync::Arc;
+use crate::sync::spsc::{Shared, SpscInner, SpscInnerErr};
 use core::{
     fmt,
     sync::atomic::Ordering,
@@ -11,7 +10,7 @@ const IS_TX_HALF: bool = true;
 
 /// The sending-half of [`pulse::channel`](super::channel).
 pub struct Sender<E> {
-    inner: Arc<Inner<E>>,
+    inner: Shared<Inner<E>>,
 }
 
 /// The error type returned from [`Sender::send`].
@@ -24,7 +23,7 @@ pub enum SendError {
 }
 
 impl<E> Sender<E> {
-    pub(super) fn new(inner: Arc<Inner<E>>) -> Self {
+    pub(super) fn new(inner: Shared<Inner<E>>) -> Self {
         Self { inner }
     }
 
@@ -34,7 +33,7 @@ impl<E> Sender<E> {
     /// overflow.
     #[inline]
     pub fn send(&mut self, pulses: usize) -> Result<(), SendError> {
-        self.inner.send(pulses)
+        self.inner.send(pulses, || self.inner.trace_send())
     }
 
     /// Completes this channel with an `Err` result.
@@ -94,7 +93,7 @@ impl<E> Drop for Sender<E> {
 }
 
 impl<E> Inner<E> {
-    fn send(&self, pulses: usize) -> Result<(), SendError> {
+    fn send(&self, pulses: usize, trace: impl FnOnce()) -> Result<(), SendError> {
         let state = self.state_load(Ordering::Acquire);
         self.transaction(state, Ordering::Acquire, Ordering::Acquire, |state| {
             if *state & COMPLETE != 0 {
@@ -105,8 +104,9 @@ impl<E> Inner<E> {
             Ok(*state)
         })
         .map(|state| {
+            trace();
             if state & RX_WAKER_STORED != 0 {
-                unsafe { (*self.rx_waker.get()).get_ref().wake_by_ref() };
+                self.rx_waker.with(|waker| unsafe { (*(*waker).as_ptr()).wake_by_ref() });
             }
         })
     }
@@ -7,18 +7,23 @@ mod receiver;
 mod sender;
 
 pub use self::{
-    receiver::Receiver,
+    receiver::{Receiver, RecvAtLeast},
     sender::{SendError, SendErrorKind, Sender},
 };
 
-use crate::sync::spsc::{SpscInner, SpscInnerErr};
-use alloc::{raw_vec::RawVec, sync::Arc};
+use crate::{
+    atomic::{
+        spsc::{AtomicUsize, UnsafeCell},
+        Ordering,
+    },
+    sync::spsc::{Shared, SpscInner, SpscInnerErr},
+};
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
 use core::{
-    cell::UnsafeCell,
     cmp,
     mem::{size_of, MaybeUninit},
-    ptr, slice,
-    sync::atomic::{AtomicUsize, Ordering},
+    ptr::{self, NonNull},
+    slice,
     task::Waker,
 };
 
@@ -42,7 +47,9 @@ const OPTION_BITS: u32 = 4;
 // Length range: [0; MAX_CAPACITY]
 struct Inner<T, E> {
     state: AtomicUsize,
-    buffer: RawVec<T>,
+    // The minimal length to wake the receiver at.
+    threshold: AtomicUsize,
+    buffer: Buffer<T>,
     err: UnsafeCell<Option<E>>,
     rx_waker: UnsafeCell<MaybeUninit<Waker>>,
     tx_waker: UnsafeCell<MaybeUninit<Waker>>,
@@ -57,9 +64,9 @@ struct Inner<T, E> {
 /// values from the ring buffer.
 #[inline]
 pub fn channel<T, E>(capacity: usize) -> (Sender<T, E>, Receiver<T, E>) {
-    let inner = Arc::new(Inner::new(capacity));
-    let sender = Sender::new(Arc::clone(&inner));
-    let receiver = Receiver::new(inner);
+    let (tx_inner, rx_inner) = Shared::pair(Inner::new(capacity));
+    let sender = Sender::new(tx_inner);
+    let receiver = Receiver::new(rx_inner);
     (sender, receiver)
 }
 
@@ -72,7 +79,8 @@ impl<T, E> Inner<T, E> {
         assert!(capacity <= MAX_CAPACITY);
         Self {
             state: AtomicUsize::new(0),
-            buffer: RawVec::with_capacity(capacity),
+            threshold: AtomicUsize::new(1),
+            buffer: Buffer::with_capacity(capacity),
             err: UnsafeCell::new(None),
             rx_waker: UnsafeCell::new(MaybeUninit::zeroed()),
             tx_waker: UnsafeCell::new(MaybeUninit::zeroed()),
@@ -80,6 +88,43 @@ impl<T, E> Inner<T, E> {
     }
 }
 
+struct Buffer<T> {
+    ptr: NonNull<T>,
+    capacity: usize,
+}
+
+impl<T> Buffer<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        let layout = Layout::array::<T>(capacity).expect("capacity overflow");
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let ptr = unsafe { alloc(layout) } as *mut T;
+            NonNull::new(ptr).unwrap_or_else(|| handle_alloc_error(layout))
+        };
+        Self { ptr, capacity }
+    }
+
+    #[inline]
+    fn ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<T> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        let layout = Layout::array::<T>(self.capacity).unwrap();
+        if layout.size() != 0 {
+            unsafe { dealloc(self.ptr.as_ptr() as *mut u8, layout) };
+        }
+    }
+}
+
 impl<T, E> Drop for Inner<T, E> {
     fn drop(&mut self) {
         let state = self.state_load(Ordering::Acquire);
@@ -133,21 +178,22 @@ impl<T, E> SpscInner<AtomicUsize, usize> for Inner<T, E> {
     }
 
     #[inline]
-    unsafe fn rx_waker_mut(&self) -> &mut MaybeUninit<Waker> {
-        &mut *self.rx_waker.get()
+    fn rx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>> {
+        &self.rx_waker
     }
 
     #[inline]
-    unsafe fn tx_waker_mut(&self) -> &mut MaybeUninit<Waker> {
-        &mut *self.tx_waker.get()
+    fn tx_waker(&self) -> &UnsafeCell<MaybeUninit<Waker>> {
+        &self.tx_waker
     }
 }
 
 impl<T, E> SpscInnerErr<AtomicUsize, usize> for Inner<T, E> {
     type Error = E;
 
-    unsafe fn err_mut(&self) -> &mut Option<Self::Error> {
-        &mut *self.err.get()
+    #[inline]
+    fn err(&self) -> &UnsafeCell<Option<Self::Error>> {
+        &self.err
     }
 }
 
@@ -155,6 +201,7 @@ impl<T, E> SpscInnerErr<AtomicUsize, usize> for Inner<T, E> {
 mod tests {
     use super::*;
     use core::{
+        future::Future,
         pin::Pin,
         sync::atomic::AtomicUsize,
         task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
@@ -205,4 +252,53 @@ mod tests {
         assert_eq!(Pin::new(&mut rx).poll_next(&mut cx), Poll::Ready(None));
         assert_eq!(COUNTER.0.load(Ordering::SeqCst), 2);
     }
+
+    #[test]
+    fn recv_at_least() {
+        static COUNTER: Counter = Counter(AtomicUsize::new(0));
+        let (mut tx, mut rx) = channel::<usize, ()>(4);
+        let waker = COUNTER.to_waker();
+        let mut cx = Context::from_waker(&waker);
+        COUNTER.0.store(0, Ordering::SeqCst);
+        let mut buf = Vec::new();
+        let mut fut = rx.recv_at_least(3, &mut buf);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+        assert_eq!(tx.send(1).unwrap(), ());
+        assert_eq!(tx.send(2).unwrap(), ());
+        assert_eq!(COUNTER.0.load(Ordering::SeqCst), 0);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+        assert_eq!(tx.send(3).unwrap(), ());
+        assert_eq!(COUNTER.0.load(Ordering::SeqCst), 1);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Ok(3)));
+        drop(fut);
+        assert_eq!(buf, [1, 2, 3]);
+        let mut fut = rx.recv_at_least(10, &mut buf);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+        assert_eq!(tx.send(4).unwrap(), ());
+        drop(tx);
+        assert_eq!(COUNTER.0.load(Ordering::SeqCst), 2);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Ok(1)));
+        drop(fut);
+        assert_eq!(buf, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn recv_at_least_err() {
+        static COUNTER: Counter = Counter(AtomicUsize::new(0));
+        let (mut tx, mut rx) = channel::<usize, isize>(4);
+        let waker = COUNTER.to_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut buf = Vec::new();
+        assert_eq!(tx.send(1).unwrap(), ());
+        assert_eq!(tx.send(2).unwrap(), ());
+        tx.send_err(-1).unwrap();
+        let mut fut = rx.recv_at_least(3, &mut buf);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Ok(2)));
+        drop(fut);
+        assert_eq!(buf, [1, 2]);
+        let mut fut = rx.recv_at_least(3, &mut buf);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Err(-1)));
+        drop(fut);
+        assert_eq!(buf, [1, 2]);
+    }
 }
@@ -1,7 +1,11 @@
 use super::{Inner, COMPLETE, NUMBER_BITS, NUMBER_MASK};
-use crate::sync::spsc::{SpscInner, SpscInnerErr};
-use alloc::sync::Arc;
+use crate::{
+    atomic::spsc::fence,
+    sync::spsc::{Shared, SpscInner, SpscInnerErr},
+};
+use alloc::vec::Vec;
 use core::{
+    future::Future,
     pin::Pin,
     ptr,
     sync::atomic::Ordering,
@@ -14,11 +18,19 @@ const IS_TX_HALF: bool = false;
 /// The receiving-half of [`ring::channel`](super::channel).
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Receiver<T, E> {
-    inner: Arc<Inner<T, E>>,
+    inner: Shared<Inner<T, E>>,
+}
+
+/// Future for the [`Receiver::recv_at_least`] method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RecvAtLeast<'a, T, E> {
+    receiver: &'a mut Receiver<T, E>,
+    buf: &'a mut Vec<T>,
+    threshold: usize,
 }
 
 impl<T, E> Receiver<T, E> {
-    pub(super) fn new(inner: Arc<Inner<T, E>>) -> Self {
+    pub(super) fn new(inner: Shared<Inner<T, E>>) -> Self {
         Self { inner }
     }
 
@@ -42,7 +54,87 @@ impl<T, E> Receiver<T, E> {
     /// of date) unless [`close`](Receiver::close) has been called first.
     #[inline]
     pub fn try_next(&mut self) -> Result<Option<T>, E> {
-        self.inner.try_next()
+        let value = self.inner.try_next()?;
+        if value.is_some() {
+            self.inner.trace_recv();
+        }
+        Ok(value)
+    }
+
+    /// Returns a future, which waits until at least `n` values are available
+    /// or the channel is closed, and then moves all available values to the
+    /// end of `buf`.
+    ///
+    /// Unlike polling the receiver as a stream, the task is not woken up on
+    /// each received value, which suits block-oriented consumers. `n` is
+    /// clamped to the channel capacity.
+    ///
+    /// The future resolves to the number of moved values, which is less than
+    /// `n` only if the channel is closed. If the channel is closed with an
+    /// error, the error is kept in the channel until all values are received,
+    /// and the future resolves to the error only if there are no values left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use drone_core::{future, sync::spsc::ring};
+    ///
+    /// let (mut tx, mut rx) = ring::channel::<u8, ()>(8);
+    /// let mut frame = Vec::new();
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    /// tx.send(3).unwrap();
+    /// let count = future::block_on_with(rx.recv_at_least(3, &mut frame), || {});
+    /// assert_eq!(count, Ok(3));
+    /// assert_eq!(frame, [1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn recv_at_least<'a>(&'a mut self, n: usize, buf: &'a mut Vec<T>) -> RecvAtLeast<'a, T, E> {
+        let threshold = n.min(self.inner.buffer.capacity()).max(1);
+        RecvAtLeast { receiver: self, buf, threshold }
+    }
+}
+
+impl<T, E> Future for RecvAtLeast<'_, T, E> {
+    type Output = Result<usize, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<usize, E>> {
+        let Self { receiver, buf, threshold } = &mut *self;
+        let inner = &receiver.inner;
+        inner.threshold.store(*threshold, Ordering::Relaxed);
+        // Pairs with the fence in `Inner::put`.
+        fence(Ordering::SeqCst);
+        if inner
+            .poll_half(cx, IS_TX_HALF, Ordering::Acquire, Ordering::AcqRel, Inner::take_at_least)
+            .is_pending()
+        {
+            return Poll::Pending;
+        }
+        inner.threshold.store(1, Ordering::Relaxed);
+        let mut count = 0;
+        while let Some(value) = inner.try_take_value() {
+            inner.trace_recv();
+            buf.push(value);
+            count += 1;
+        }
+        if count == 0 {
+            match inner.try_next() {
+                Ok(Some(value)) => {
+                    inner.trace_recv();
+                    buf.push(value);
+                    count += 1;
+                }
+                Ok(None) => {}
+                Err(err) => return Poll::Ready(Err(err)),
+            }
+        }
+        Poll::Ready(Ok(count))
+    }
+}
+
+impl<T, E> Drop for RecvAtLeast<'_, T, E> {
+    fn drop(&mut self) {
+        self.receiver.inner.threshold.store(1, Ordering::Relaxed);
     }
 }
 
@@ -51,14 +143,18 @@ impl<T, E> Stream for Receiver<T, E> {
 
     #[inline]
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.inner.poll_half_with_transaction(
+        let poll = self.inner.poll_half_with_transaction(
             cx,
             IS_TX_HALF,
             Ordering::Acquire,
             Ordering::AcqRel,
             Inner::take_index_try,
             Inner::take_index_finalize,
-        )
+        );
+        if let Poll::Ready(Some(Ok(_))) = poll {
+            self.inner.trace_recv();
+        }
+        poll
     }
 }
 
@@ -96,6 +192,26 @@ impl<T, E> Inner<T, E> {
         .or_else(|value| value.map_or_else(|()| Ok(None), |()| self.take_err().transpose()))
     }
 
+    fn try_take_value(&self) -> Option<T> {
+        let state = self.state_load(Ordering::Acquire);
+        self.transaction(state, Ordering::AcqRel, Ordering::Acquire, |state| {
+            let length = Self::get_length(*state);
+            if length == 0 { Err(()) } else { Ok(self.take_index(state, length)) }
+        })
+        .ok()
+        .map(|index| unsafe { self.take_value(index) })
+    }
+
+    fn take_at_least(&self, state: usize) -> Poll<()> {
+        if Self::get_length(state) >= self.threshold.load(Ordering::Relaxed)
+            || state & COMPLETE != 0
+        {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
     fn take_index_try(&self, state: &mut usize) -> Option<Result<usize, ()>> {
         let length = Self::get_length(*state);
         if length != 0 {
@@ -1,6 +1,8 @@
 use super::{Inner, COMPLETE, NUMBER_BITS, NUMBER_MASK, RX_WAKER_STORED};
-use crate::sync::spsc::{SpscInner, SpscInnerErr};
-use alloc::sync::Arc;
+use crate::{
+    atomic::spsc::fence,
+    sync::spsc::{Shared, SpscInner, SpscInnerErr},
+};
 use core::{
     fmt, ptr,
     sync::atomic::Ordering,
@@ -11,7 +13,7 @@ const IS_TX_HALF: bool = true;
 
 /// The sending-half of [`ring::channel`](super::channel).
 pub struct Sender<T, E> {
-    inner: Arc<Inner<T, E>>,
+    inner: Shared<Inner<T, E>>,
 }
 
 /// The error type returned from [`Sender::send`].
@@ -33,7 +35,7 @@ pub enum SendErrorKind {
 }
 
 impl<T, E> Sender<T, E> {
-    pub(super) fn new(inner: Arc<Inner<T, E>>) -> Self {
+    pub(super) fn new(inner: Shared<Inner<T, E>>) -> Self {
         Self { inner }
     }
 
@@ -46,7 +48,7 @@ impl<T, E> Sender<T, E> {
     /// then `Err` is returned with the value provided.
     #[inline]
     pub fn send(&mut self, value: T) -> Result<(), SendError<T>> {
-        self.inner.send(value)
+        self.inner.send(value, || self.inner.trace_send())
     }
 
     /// Puts `value` to the ring buffer. The value can be immediately read by
@@ -58,7 +60,7 @@ impl<T, E> Sender<T, E> {
     /// provided.
     #[inline]
     pub fn send_overwrite(&mut self, value: T) -> Result<(), T> {
-        self.inner.send_overwrite(value)
+        self.inner.send_overwrite(value, || self.inner.trace_send())
     }
 
     /// Completes this channel with an `Err` result.
@@ -118,20 +120,20 @@ impl<T, E> Drop for Sender<T, E> {
 }
 
 impl<T, E> Inner<T, E> {
-    fn send(&self, value: T) -> Result<(), SendError<T>> {
+    fn send(&self, value: T, trace: impl FnOnce()) -> Result<(), SendError<T>> {
         let state = self.state_load(Ordering::Acquire);
         if let Some(index) = self.put_index_try(state) {
-            self.put(value, state, index)
+            self.put(value, state, index, trace)
                 .map_err(|value| SendError::new(value, SendErrorKind::Canceled))
         } else {
             Err(SendError::new(value, SendErrorKind::Overflow))
         }
     }
 
-    fn send_overwrite(&self, value: T) -> Result<(), T> {
+    fn send_overwrite(&self, value: T, trace: impl FnOnce()) -> Result<(), T> {
         let state = self.state_load(Ordering::Acquire);
         if let Some(index) = self.put_index_try(state) {
-            return self.put(value, state, index);
+            return self.put(value, state, index, trace);
         }
         match self.transaction(state, Ordering::Acquire, Ordering::Acquire, |state| {
             if *state & COMPLETE != 0 {
@@ -148,14 +150,16 @@ impl<T, E> Inner<T, E> {
         }) {
             Ok((state, index)) => {
                 unsafe { ptr::drop_in_place(self.buffer.ptr().add(index)) };
-                self.put(value, state, index)
+                self.put(value, state, index, trace)
             }
-            Err(Some((state, index))) => self.put(value, state, index),
+            Err(Some((state, index))) => self.put(value, state, index, trace),
             Err(None) => Err(value),
         }
     }
 
-    fn put(&self, value: T, state: usize, index: usize) -> Result<(), T> {
+    // `trace` is called after the value is committed, but before the receiver
+    // is woken, so the send event always precedes the receive event.
+    fn put(&self, value: T, state: usize, index: usize, trace: impl FnOnce()) -> Result<(), T> {
         let buffer_ptr = unsafe { self.buffer.ptr().add(index) };
         unsafe { ptr::write(buffer_ptr, value) };
         self.transaction(state, Ordering::AcqRel, Ordering::Acquire, |state| {
@@ -167,8 +171,13 @@ impl<T, E> Inner<T, E> {
             }
         })
         .map(|state| {
+            trace();
             if state & RX_WAKER_STORED != 0 {
-                unsafe { (*self.rx_waker.get()).get_ref().wake_by_ref() };
+                // Pairs with the fence in `RecvAtLeast::poll`.
+                fence(Ordering::SeqCst);
+                if Self::get_length(state) >= self.threshold.load(Ordering::Relaxed) {
+                    self.rx_waker.with(|waker| unsafe { (*(*waker).as_ptr()).wake_by_ref() });
+                }
             }
         })
         .map_err(|()| unsafe { ptr::read(buffer_ptr) })
@@ -0,0 +1,81 @@
+use crate::atomic::{
+    spsc::{fence, AtomicU8},
+    Ordering,
+};
+use alloc::boxed::Box;
+use core::{marker::PhantomData, ops::Deref, ptr::NonNull};
+
+/// A heap-allocated state shared by the two halves of a channel.
+///
+/// This is a minimal replacement for `Arc`, which has exactly two owners. The
+/// reference counter is one of the crate's internal atomics, so the channels
+/// work on targets without native compare-and-swap when the
+/// `portable-atomic` feature is enabled. `Arc` always uses the native
+/// atomics.
+///
+/// With the `evtrace` feature, the state also holds the channel identifier,
+/// which is allocated by [`evtrace::channel_id`](crate::evtrace::channel_id)
+/// and used for the send and receive events of the channel.
+pub(super) struct Shared<T> {
+    ptr: NonNull<SharedBox<T>>,
+    _marker: PhantomData<SharedBox<T>>,
+}
+
+struct SharedBox<T> {
+    count: AtomicU8,
+    #[cfg(feature = "evtrace")]
+    channel: u16,
+    data: T,
+}
+
+unsafe impl<T: Send + Sync> Send for Shared<T> {}
+unsafe impl<T: Send + Sync> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    /// Allocates `data` on the heap, and returns the two owners of it.
+    pub(super) fn pair(data: T) -> (Self, Self) {
+        let ptr = NonNull::from(Box::leak(Box::new(SharedBox {
+            count: AtomicU8::new(2),
+            #[cfg(feature = "evtrace")]
+            channel: crate::evtrace::channel_id(),
+            data,
+        })));
+        (Self { ptr, _marker: PhantomData }, Self { ptr, _marker: PhantomData })
+    }
+
+    /// Records a send operation on the channel, if the `evtrace` feature is
+    /// enabled.
+    #[inline]
+    pub(super) fn trace_send(&self) {
+        #[cfg(feature = "evtrace")]
+        crate::evtrace::send(unsafe { self.ptr.as_ref() }.channel);
+    }
+
+    /// Records a receive operation on the channel, if the `evtrace` feature is
+    /// enabled.
+    #[inline]
+    pub(super) fn trace_recv(&self) {
+        #[cfg(feature = "evtrace")]
+        crate::evtrace::recv(unsafe { self.ptr.as_ref() }.channel);
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { &self.ptr.as_ref().data }
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if unsafe { self.ptr.as_ref() }.count.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        fence(Ordering::Acquire);
+        unsafe { drop(Box::from_raw(self.ptr.as_ptr())) };
+    }
+}
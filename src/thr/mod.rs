@@ -132,6 +132,8 @@ where
 ///
 /// The function is not reentrant.
 pub unsafe fn thread_resume<T: ThrToken>() {
+    #[cfg(feature = "evtrace")]
+    crate::evtrace::record(crate::evtrace::Event::Trigger(T::THR_NUM as u16));
     let thr = get_thr::<T>();
     preempt(thr.local().preempted(), T::THR_NUM, || {
         thr.fib_chain().drain();
@@ -21,6 +21,10 @@
 //! constructor. It is supposed to instantiate all needed tokens at the very
 //! beginning of the program and pass the instances further to the code.
 //!
+//! Tuples of up to eight tokens are tokens themselves. This allows passing an
+//! ad-hoc group of tokens through generic code and splitting it later with
+//! pattern matching, without defining a named struct for the group.
+//!
 //! Since tokens are zero-sized, [`Token::take`] is no-op from the assembly
 //! perspective. Likewise passing the instance around doesn't consume the stack,
 //! and storing the instance inside other types doesn't consume the memory.
@@ -138,6 +142,99 @@
 //!     *foo += 1;
 //! }
 //! ```
+//!
+//! # Init Stages
+//!
+//! Boot ordering can be encoded in the type system with `init_tokens!`
+//! macro. It defines a chain of stage tokens, where only the first stage can be
+//! taken, and each next stage is obtained by consuming the previous stage
+//! together with the declared prerequisite tokens:
+//!
+//! ```
+//! use drone_core::token::{init_tokens, simple_token, Token};
+//!
+//! simple_token! {
+//!     /// Clocks are configured.
+//!     pub struct ClocksToken;
+//! }
+//!
+//! simple_token! {
+//!     /// Peripherals are initialized.
+//!     pub struct PeriphToken;
+//! }
+//!
+//! init_tokens! {
+//!     /// Nothing is initialized yet.
+//!     pub struct Reset;
+//!     /// Clocks are running.
+//!     pub struct ClocksReady(ClocksToken);
+//!     /// Peripherals are ready to start tasks.
+//!     pub struct PeriphReady(PeriphToken);
+//! }
+//!
+//! fn main() {
+//!     let (reset, clocks, periph) =
+//!         unsafe { (Reset::take(), ClocksToken::take(), PeriphToken::take()) };
+//!     // Skipping a stage won't compile.
+//!     // let stage: PeriphReady = reset.advance(periph);
+//!     let stage: PeriphReady = reset.advance(clocks).advance(periph);
+//! }
+//! ```
+//!
+//! # Token Groups
+//!
+//! A central `unsafe_simple_tokens!` invocation has to name every token it
+//! contains. For per-driver singletons defined across independent crates,
+//! `token_group!` defines a group token instead, and each crate registers its
+//! own tokens into the group with `unsafe_group_token!`. Every registration
+//! places an entry into the `.tokengroup.<group_name>` linker section, and
+//! the generated `init` method of the group walks the section at boot-time,
+//! passing each token to its initializer exactly once:
+//!
+//! ```no_run
+//! use drone_core::token::{simple_token, token_group, unsafe_group_token, Token};
+//!
+//! token_group! {
+//!     /// Driver singletons.
+//!     pub struct Drivers;
+//! }
+//!
+//! // Usually in a driver crate.
+//! simple_token! {
+//!     /// The UART driver singleton.
+//!     pub struct UartToken;
+//! }
+//!
+//! fn uart_init(uart: UartToken) {}
+//!
+//! // Here is `unsafe`, we need to ensure that `UartToken` is not used anywhere
+//! // else.
+//! unsafe_group_token!(Drivers: UartToken => uart_init);
+//!
+//! fn main() {
+//!     let drivers = unsafe { Drivers::take() };
+//!     drivers.init();
+//! }
+//! ```
+//!
+//! The linker script must place the section between
+//! `TOKENGROUP_<GROUP_NAME>_START` and `TOKENGROUP_<GROUP_NAME>_END` symbols:
+//!
+//! ```text
+//! .tokengroup.drivers : {
+//!     TOKENGROUP_DRIVERS_START = .;
+//!     KEEP(*(.tokengroup.drivers));
+//!     TOKENGROUP_DRIVERS_END = .;
+//! }
+//! ```
+//!
+//! The order of the initializers is unspecified.
+
+/// Defines a chain of boot stage tokens.
+///
+/// See [the module-level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::init_tokens;
 
 /// Defines a new simple [`Token`].
 ///
@@ -155,6 +252,23 @@ pub use drone_core_macros::simple_token;
 #[doc(inline)]
 pub use drone_core_macros::unsafe_simple_tokens;
 
+/// Defines a new group token for the tokens registered with
+/// [`unsafe_group_token!`].
+///
+/// See [the module-level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::token_group;
+
+/// Registers a simple [`Token`] into a group defined with [`token_group!`].
+///
+/// See [the module-level documentation](self) for details.
+///
+/// # Safety
+///
+/// The token must not be instantiated anywhere else.
+#[doc(inline)]
+pub use drone_core_macros::unsafe_group_token;
+
 /// Defines a new token for the set of [`StaticToken`]s.
 ///
 /// See [the module-level documentation](self) for details.
@@ -193,6 +307,26 @@ pub unsafe trait Token: Sized + Send + 'static {
     unsafe fn take() -> Self;
 }
 
+macro_rules! tuple_token {
+    ($($ty:ident),*) => {
+        unsafe impl<$($ty: Token),*> Token for ($($ty,)*) {
+            #[inline]
+            unsafe fn take() -> Self {
+                ($($ty::take(),)*)
+            }
+        }
+    };
+}
+
+tuple_token!(A);
+tuple_token!(A, B);
+tuple_token!(A, B, C);
+tuple_token!(A, B, C, D);
+tuple_token!(A, B, C, D, E);
+tuple_token!(A, B, C, D, E, F);
+tuple_token!(A, B, C, D, E, F, G);
+tuple_token!(A, B, C, D, E, F, G, H);
+
 /// A token for a mutable static variable.
 ///
 /// See [the module-level documentation](self) for details.
@@ -212,6 +346,169 @@ pub unsafe trait StaticToken: Token + Sized + Send + 'static {
     fn into_static(self) -> &'static mut Self::Target;
 }
 
+/// Moves the token `token` into the closure `f` and takes it back.
+///
+/// The signature only guarantees that the closure returns a token of the same
+/// type. It doesn't guarantee that this is the same instance: a [`Copy`] token
+/// can be returned as a copy, and any token can be recreated with the unsafe
+/// [`Token::take`]. For a token type, which is neither `Copy` nor `Clone`, and
+/// which is not taken anywhere else, the returned token is the one it was
+/// given. This makes it possible to temporarily hand a token over to a
+/// configuration routine without wrapping it into an [`Option`].
+///
+/// ```
+/// use drone_core::token::{lend, simple_token, Token};
+///
+/// simple_token! {
+///     /// The token for Foo initializer.
+///     pub struct FooInitToken;
+/// }
+///
+/// fn configure(token: FooInitToken) -> FooInitToken {
+///     // Use the token.
+///     token
+/// }
+///
+/// let token = unsafe { FooInitToken::take() };
+/// let token = lend(token, configure);
+/// ```
+#[inline]
+pub fn lend<T: Token, F: FnOnce(T) -> T>(token: T, f: F) -> T {
+    f(token)
+}
+
+/// Extends [`Token`] types with `lend` and `lend_with` methods.
+pub trait TokenLend: Token {
+    /// Moves the token into the closure `f` and takes it back.
+    ///
+    /// See [`lend`] for details.
+    #[inline]
+    fn lend<F: FnOnce(Self) -> Self>(self, f: F) -> Self {
+        f(self)
+    }
+
+    /// Moves the token into the closure `f` and takes it back along with the
+    /// closure result.
+    #[inline]
+    fn lend_with<F: FnOnce(Self) -> (Self, R), R>(self, f: F) -> (Self, R) {
+        f(self)
+    }
+}
+
+impl<T: Token> TokenLend for T {}
+
+/// A token identity record.
+///
+/// When `tokencheck` feature is enabled, token macros emit a record for every
+/// defined token into `.tokenrecords` linker section. The records can be
+/// checked at boot-time with [`check_records`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct TokenRecord {
+    /// The full path of the token, like `my_crate::drivers::UartToken`.
+    pub name: &'static str,
+    /// The address of the resource owned by the token, or `0` if the token
+    /// doesn't own an addressable resource.
+    pub address: usize,
+}
+
+impl TokenRecord {
+    /// Returns `true` if `self` and `other` claim the same resource.
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.name == other.name || self.address != 0 && self.address == other.address
+    }
+}
+
+/// Finds the first pair of records claiming the same resource.
+pub fn find_duplicate(records: &[TokenRecord]) -> Option<(&TokenRecord, &TokenRecord)> {
+    for (i, a) in records.iter().enumerate() {
+        for b in &records[i + 1..] {
+            if a.conflicts_with(b) {
+                return Some((a, b));
+            }
+        }
+    }
+    None
+}
+
+/// Checks that no two crates defined tokens for the same register address or
+/// token path.
+///
+/// The linker script must place `.tokenrecords` section between
+/// `TOKENRECORDS_START` and `TOKENRECORDS_END` symbols.
+///
+/// # Panics
+///
+/// If two token records claim the same resource.
+///
+/// # Safety
+///
+/// This function must be called after the DATA segment initialization.
+#[cfg(feature = "tokencheck")]
+pub unsafe fn check_records() {
+    extern "C" {
+        static TOKENRECORDS_START: TokenRecord;
+        static TOKENRECORDS_END: TokenRecord;
+    }
+    let start = &TOKENRECORDS_START as *const TokenRecord;
+    let end = &TOKENRECORDS_END as *const TokenRecord;
+    let len = (end as usize - start as usize) / core::mem::size_of::<TokenRecord>();
+    let records = core::slice::from_raw_parts(start, len);
+    if let Some((a, b)) = find_duplicate(records) {
+        panic!("duplicate tokens: {:?} and {:?}", a, b);
+    }
+}
+
+/// A token group entry.
+///
+/// Emitted by [`unsafe_group_token!`] into the linker section of the group.
+#[repr(C)]
+#[derive(Debug)]
+pub struct GroupEntry {
+    /// The name of the token type.
+    pub name: &'static str,
+    /// Takes the token and passes it to the initializer.
+    pub init: unsafe fn(),
+}
+
+/// Calls the initializers of all entries in `start..end`.
+///
+/// # Safety
+///
+/// `start..end` must be the bounds of a token group section, and this
+/// function must be called at most once for the group.
+#[doc(hidden)]
+pub unsafe fn init_group(start: *const GroupEntry, end: *const GroupEntry) {
+    let len = (end as usize - start as usize) / core::mem::size_of::<GroupEntry>();
+    let entries = core::slice::from_raw_parts(start, len);
+    for entry in entries {
+        (entry.init)();
+    }
+}
+
+#[cfg(feature = "tokencheck")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! token_record {
+    ($name:expr, $address:expr) => {
+        const _: () = {
+            #[used]
+            #[link_section = ".tokenrecords"]
+            static RECORD: $crate::token::TokenRecord = $crate::token::TokenRecord {
+                name: concat!(module_path!(), "::", $name),
+                address: $address,
+            };
+        };
+    };
+}
+
+#[cfg(not(feature = "tokencheck"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! token_record {
+    ($name:expr, $address:expr) => {};
+}
+
 mod compile_tests {
     //! ```compile_fail
     //! drone_core::token::simple_token!(struct Foo);
@@ -240,6 +537,17 @@ mod compile_tests {
     //!
     //! ```compile_fail
     //! use drone_core::token::Token;
+    //! drone_core::token::init_tokens! {
+    //!     struct Stage0;
+    //!     struct Stage1;
+    //! }
+    //! fn main() {
+    //!     let stage = unsafe { Stage1::take() };
+    //! }
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::token::Token;
     //! static mut FOO: usize = 0;
     //! drone_core::token::unsafe_static_tokens! {
     //!     struct Foo {
@@ -250,4 +558,11 @@ mod compile_tests {
     //!     let foo = unsafe { Foo { foo: FooToken::take(), __priv: () } };
     //! }
     //! ```
+    //!
+    //! ```compile_fail
+    //! drone_core::token::token_group!(struct Drivers);
+    //! fn main() {
+    //!     let drivers = Drivers { __priv: () };
+    //! }
+    //! ```
 }
@@ -0,0 +1,621 @@
+//! CMSIS-SVD importer for [Drone] registers.
+//!
+//! This crate parses CMSIS-SVD files provided by the manufacturers, and
+//! generates [`reg!`] invocations for all described registers, including their
+//! fields and access attributes. It is intended to be used on the host side,
+//! usually from a build script of a platform crate:
+//!
+//! ```no_run
+//! use std::{env, fs, path::Path};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let xml = fs::read_to_string("STM32F103.svd")?;
+//! let device = drone_core_svd::parse(&xml)?;
+//! for warning in &device.warnings {
+//!     println!("cargo:warning={}", warning);
+//! }
+//! let out = Path::new(&env::var("OUT_DIR")?).join("svd_reg.rs");
+//! fs::write(out, device.generate())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The generated file is then included into the platform crate with
+//! `include!(concat!(env!("OUT_DIR"), "/svd_reg.rs"));`. The register index is
+//! declared separately with [`reg::tokens!`].
+//!
+//! The following parts of the format are supported:
+//!
+//! * register properties (`size`, `access`, `resetValue`) inherited from the
+//!   device and peripheral levels;
+//! * peripherals derived from other peripherals with the `derivedFrom`
+//!   attribute;
+//! * field bit ranges in all three forms;
+//! * `oneToClear` fields, which are mapped to [`W1cRegField`];
+//! * register arrays with `dim` and `dimIncrement`, if the register name ends
+//!   with `%s` or `[%s]`.
+//!
+//! Clusters and enumerated values are not imported. Each skipped element is
+//! reported in [`Device::warnings`].
+//!
+//! [Drone]: https://github.com/drone-os/drone
+//! [`reg!`]: https://api.drone-os.com/drone-core/0.12/drone_core/macro.reg.html
+//! [`reg::tokens!`]: https://api.drone-os.com/drone-core/0.12/drone_core/reg/macro.tokens.html
+//! [`W1cRegField`]: https://api.drone-os.com/drone-core/0.12/drone_core/reg/field/trait.W1cRegField.html
+
+#![warn(missing_docs)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::missing_errors_doc,
+    clippy::must_use_candidate,
+    clippy::similar_names
+)]
+
+use std::{error::Error, fmt, fmt::Write};
+
+/// A device description.
+#[derive(Clone, Debug)]
+pub struct Device {
+    /// The device name.
+    pub name: String,
+    /// The device peripherals.
+    pub peripherals: Vec<Peripheral>,
+    /// The elements, which were skipped by the importer.
+    pub warnings: Vec<SvdWarning>,
+}
+
+/// A peripheral description.
+#[derive(Clone, Debug)]
+pub struct Peripheral {
+    /// The peripheral name.
+    pub name: String,
+    /// The peripheral description.
+    pub description: Option<String>,
+    /// The base address of the peripheral.
+    pub base_address: u32,
+    /// The peripheral registers.
+    pub registers: Vec<Register>,
+}
+
+/// A register description.
+#[derive(Clone, Debug)]
+pub struct Register {
+    /// The register name, without the array placeholder.
+    pub name: String,
+    /// The register description.
+    pub description: Option<String>,
+    /// The register address offset from the peripheral base address.
+    pub address_offset: u32,
+    /// The register size in bits.
+    pub size: u32,
+    /// The register reset value.
+    pub reset_value: u64,
+    /// The register access.
+    pub access: Access,
+    /// The number of elements and the address increment, if the register is
+    /// an array.
+    pub dim: Option<(u32, u32)>,
+    /// The register fields.
+    pub fields: Vec<Field>,
+}
+
+/// A register field description.
+#[derive(Clone, Debug)]
+pub struct Field {
+    /// The field name.
+    pub name: String,
+    /// The field description.
+    pub description: Option<String>,
+    /// The offset of the field inside the register.
+    pub offset: u32,
+    /// The bit-width of the field.
+    pub width: u32,
+    /// The field access.
+    pub access: Access,
+    /// Whether the field is cleared by writing one.
+    pub one_to_clear: bool,
+}
+
+/// The access of a register or a field.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Access {
+    /// Read-only.
+    ReadOnly,
+    /// Write-only.
+    WriteOnly,
+    /// Read-write.
+    ReadWrite,
+}
+
+/// An error returned by [`parse`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SvdError {
+    /// Malformed XML at the byte position.
+    Xml(usize),
+    /// A mandatory element is missing from the element.
+    Missing(&'static str, String),
+    /// An element has an invalid value.
+    Invalid(&'static str, String),
+    /// A `derivedFrom` attribute refers to an unknown peripheral.
+    UnknownPeripheral(String),
+}
+
+/// An element of the document, which is not supported by the importer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SvdWarning {
+    /// A register cluster in the peripheral. The registers of the cluster are
+    /// not generated.
+    Cluster(String, String),
+    /// Enumerated values of the field in the register of the peripheral. The
+    /// field is generated without the values.
+    EnumeratedValues(String, String, String),
+}
+
+struct Element<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, String)>,
+    children: Vec<Element<'a>>,
+    text: String,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Properties {
+    size: Option<u32>,
+    reset_value: Option<u64>,
+    access: Option<Access>,
+}
+
+/// Parses the CMSIS-SVD document `xml`.
+pub fn parse(xml: &str) -> Result<Device, SvdError> {
+    let root = Parser { xml, pos: 0 }.document()?;
+    if root.name != "device" {
+        return Err(SvdError::Missing("device", root.name.to_string()));
+    }
+    let name = root.required("name")?.to_string();
+    let properties = Properties::default().merge(&root)?;
+    let mut peripherals = Vec::<Peripheral>::new();
+    let mut warnings = Vec::new();
+    for element in root.child("peripherals").iter().flat_map(|e| e.children_named("peripheral")) {
+        let name = element.required("name")?.to_string();
+        let base_address = element
+            .number("baseAddress")?
+            .ok_or_else(|| SvdError::Missing("baseAddress", name.clone()))?
+            as u32;
+        let description = element.text_of("description");
+        let mut peripheral = if let Some(parent) = element.attribute("derivedFrom") {
+            let parent = peripherals
+                .iter()
+                .find(|p| p.name == parent)
+                .ok_or_else(|| SvdError::UnknownPeripheral(parent.to_string()))?;
+            Peripheral {
+                name,
+                description: description.or_else(|| parent.description.clone()),
+                base_address,
+                registers: parent.registers.clone(),
+            }
+        } else {
+            Peripheral { name, description, base_address, registers: Vec::new() }
+        };
+        let properties = properties.merge(element)?;
+        if let Some(registers) = element.child("registers") {
+            peripheral.registers.clear();
+            for register in registers.children_named("register") {
+                let register = parse_register(register, properties, &mut |register, field| {
+                    warnings.push(SvdWarning::EnumeratedValues(
+                        peripheral.name.clone(),
+                        register.to_string(),
+                        field.to_string(),
+                    ));
+                })?;
+                peripheral.registers.push(register);
+            }
+            for cluster in registers.children_named("cluster") {
+                let cluster = cluster.text_of("name").unwrap_or_default();
+                warnings.push(SvdWarning::Cluster(peripheral.name.clone(), cluster));
+            }
+        }
+        peripherals.push(peripheral);
+    }
+    Ok(Device { name, peripherals, warnings })
+}
+
+fn parse_register(
+    element: &Element<'_>,
+    properties: Properties,
+    skipped_values: &mut dyn FnMut(&str, &str),
+) -> Result<Register, SvdError> {
+    let mut name = element.required("name")?.to_string();
+    let properties = properties.merge(element)?;
+    let description = element.text_of("description");
+    let address_offset = element
+        .number("addressOffset")?
+        .ok_or_else(|| SvdError::Missing("addressOffset", name.clone()))?
+        as u32;
+    let size = properties.size.ok_or_else(|| SvdError::Missing("size", name.clone()))?;
+    if ![8, 16, 32, 64].contains(&size) {
+        return Err(SvdError::Invalid("size", name));
+    }
+    let reset_value = properties.reset_value.unwrap_or(0);
+    let access = properties.access.unwrap_or(Access::ReadWrite);
+    let dim = match element.number("dim")? {
+        Some(count) => {
+            let increment = element
+                .number("dimIncrement")?
+                .ok_or_else(|| SvdError::Missing("dimIncrement", name.clone()))?;
+            name = name.replace("[%s]", "").replace("%s", "");
+            Some((count as u32, increment as u32))
+        }
+        None => None,
+    };
+    let mut fields = Vec::new();
+    for element in element.child("fields").iter().flat_map(|e| e.children_named("field")) {
+        let field = parse_field(element, access)?;
+        if element.child("enumeratedValues").is_some() {
+            skipped_values(&name, &field.name);
+        }
+        fields.push(field);
+    }
+    Ok(Register { name, description, address_offset, size, reset_value, access, dim, fields })
+}
+
+fn parse_field(element: &Element<'_>, access: Access) -> Result<Field, SvdError> {
+    let name = element.required("name")?.to_string();
+    let description = element.text_of("description");
+    let (offset, width) = if let Some(offset) = element.number("bitOffset")? {
+        let width = element.number("bitWidth")?.unwrap_or(1);
+        (offset, width)
+    } else if let Some(lsb) = element.number("lsb")? {
+        let msb = element.number("msb")?.ok_or_else(|| SvdError::Missing("msb", name.clone()))?;
+        if msb < lsb {
+            return Err(SvdError::Invalid("msb", name));
+        }
+        (lsb, msb + 1 - lsb)
+    } else if let Some(range) = element.text_of("bitRange") {
+        let range = range.trim_start_matches('[').trim_end_matches(']');
+        let mut bounds = range.splitn(2, ':').map(|bound| parse_number(bound.trim()));
+        match (bounds.next(), bounds.next()) {
+            (Some(Some(msb)), Some(Some(lsb))) if msb >= lsb => (lsb, msb + 1 - lsb),
+            _ => return Err(SvdError::Invalid("bitRange", name)),
+        }
+    } else {
+        return Err(SvdError::Missing("bitOffset", name));
+    };
+    let access = match element.text_of("access") {
+        Some(access) => {
+            parse_access(&access).ok_or_else(|| SvdError::Invalid("access", name.clone()))?
+        }
+        None => access,
+    };
+    let one_to_clear = element.text_of("modifiedWriteValues").map_or(false, |v| v == "oneToClear");
+    Ok(Field {
+        name,
+        description,
+        offset: offset as u32,
+        width: width as u32,
+        access,
+        one_to_clear,
+    })
+}
+
+impl Device {
+    /// Generates [`reg!`] invocations for all registers of the device.
+    ///
+    /// [`reg!`]: https://api.drone-os.com/drone-core/0.12/drone_core/macro.reg.html
+    pub fn generate(&self) -> String {
+        let mut output = String::new();
+        for peripheral in &self.peripherals {
+            for register in &peripheral.registers {
+                register.generate(peripheral, &mut output).unwrap();
+            }
+        }
+        output
+    }
+}
+
+impl Register {
+    fn generate(&self, peripheral: &Peripheral, w: &mut String) -> fmt::Result {
+        writeln!(w, "drone_core::reg! {{")?;
+        if let Some(description) = &self.description {
+            write_doc(w, "    ", description)?;
+        }
+        write!(w, "    pub mod {} {}", ident(&peripheral.name), ident(&self.name))?;
+        if let Some((count, increment)) = self.dim {
+            write!(w, "[{}; {:#x}]", count, increment)?;
+        }
+        writeln!(w, ";")?;
+        writeln!(
+            w,
+            "    {} {:#x} {}",
+            hex(u64::from(peripheral.base_address + self.address_offset), 8),
+            self.size,
+            hex(self.reset_value, self.size as usize / 4)
+        )?;
+        writeln!(w, "    {};", match self.access {
+            Access::ReadOnly => "RReg RoReg",
+            Access::WriteOnly => "WReg WoReg",
+            Access::ReadWrite => "RReg WReg",
+        })?;
+        for field in &self.fields {
+            let readable = field.access != Access::WriteOnly && self.access != Access::WriteOnly;
+            let writable = field.access != Access::ReadOnly && self.access != Access::ReadOnly;
+            let traits = match (readable, writable) {
+                (true, true) if field.one_to_clear => "RRRegField W1cRegField",
+                (true, true) => "RRRegField WWRegField",
+                (true, false) => "RRRegField RoRRegField",
+                (false, true) => "WWRegField WoWRegField",
+                (false, false) => continue,
+            };
+            if let Some(description) = &field.description {
+                write_doc(w, "    ", description)?;
+            }
+            writeln!(
+                w,
+                "    {} {{ {} {} {} }}",
+                ident(&field.name),
+                field.offset,
+                field.width,
+                traits
+            )?;
+        }
+        writeln!(w, "}}")?;
+        writeln!(w)
+    }
+}
+
+impl Properties {
+    fn merge(mut self, element: &Element<'_>) -> Result<Self, SvdError> {
+        if let Some(size) = element.number("size")? {
+            self.size = Some(size as u32);
+        }
+        if let Some(reset_value) = element.number("resetValue")? {
+            self.reset_value = Some(reset_value);
+        }
+        if let Some(access) = element.text_of("access") {
+            self.access = Some(parse_access(&access).ok_or_else(|| {
+                SvdError::Invalid("access", element.text_of("name").unwrap_or_default())
+            })?);
+        }
+        Ok(self)
+    }
+}
+
+impl<'a> Element<'a> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find_map(|(key, value)| Some(value.as_str()).filter(|_| *key == name))
+    }
+
+    fn child(&self, name: &str) -> Option<&Element<'a>> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children_named<'b>(&'b self, name: &'b str) -> impl Iterator<Item = &'b Element<'a>> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn text_of(&self, name: &str) -> Option<String> {
+        self.child(name).map(|child| child.text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    fn required(&self, name: &'static str) -> Result<&str, SvdError> {
+        self.child(name)
+            .map(|child| child.text.trim())
+            .ok_or_else(|| SvdError::Missing(name, self.name.to_string()))
+    }
+
+    fn number(&self, name: &'static str) -> Result<Option<u64>, SvdError> {
+        self.child(name)
+            .map(|child| {
+                parse_number(child.text.trim()).ok_or_else(|| {
+                    SvdError::Invalid(name, self.text_of("name").unwrap_or_default())
+                })
+            })
+            .transpose()
+    }
+}
+
+struct Parser<'a> {
+    xml: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn document(mut self) -> Result<Element<'a>, SvdError> {
+        self.skip_misc()?;
+        let root = self.element()?;
+        self.skip_misc()?;
+        if self.pos == self.xml.len() { Ok(root) } else { Err(SvdError::Xml(self.pos)) }
+    }
+
+    fn skip_misc(&mut self) -> Result<(), SvdError> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Element<'a>, SvdError> {
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut element =
+            Element { name, attributes: Vec::new(), children: Vec::new(), text: String::new() };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = if self.rest().starts_with('"') { "\"" } else { "'" };
+            self.expect(quote)?;
+            let end = self.find(quote)?;
+            element.attributes.push((key, unescape(&self.xml[self.pos..end])));
+            self.pos = end + 1;
+        }
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += 2;
+                if self.name()? != name {
+                    return Err(SvdError::Xml(self.pos));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                let start = self.pos + 9;
+                self.skip_past("]]>")?;
+                element.text.push_str(&self.xml[start..self.pos - 3]);
+            } else if rest.starts_with('<') {
+                element.children.push(self.element()?);
+            } else if rest.is_empty() {
+                return Err(SvdError::Xml(self.pos));
+            } else {
+                let end = self.find("<")?;
+                element.text.push_str(&unescape(&self.xml[self.pos..end]));
+                self.pos = end;
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, SvdError> {
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':'))
+            .unwrap_or_else(|| self.rest().len());
+        if len == 0 {
+            return Err(SvdError::Xml(start));
+        }
+        self.pos += len;
+        Ok(&self.xml[start..self.pos])
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.xml[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), SvdError> {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            Err(SvdError::Xml(self.pos))
+        }
+    }
+
+    fn find(&self, token: &str) -> Result<usize, SvdError> {
+        self.rest().find(token).map(|offset| self.pos + offset).ok_or(SvdError::Xml(self.pos))
+    }
+
+    fn skip_past(&mut self, token: &str) -> Result<(), SvdError> {
+        self.pos = self.find(token)? + token.len();
+        Ok(())
+    }
+}
+
+impl fmt::Display for SvdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(pos) => write!(f, "malformed XML at position {}", pos),
+            Self::Missing(name, parent) => write!(f, "missing `{}` in `{}`", name, parent),
+            Self::Invalid(name, parent) => write!(f, "invalid `{}` in `{}`", name, parent),
+            Self::UnknownPeripheral(name) => write!(f, "unknown peripheral `{}`", name),
+        }
+    }
+}
+
+impl Error for SvdError {}
+
+impl fmt::Display for SvdWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cluster(peripheral, cluster) => {
+                write!(f, "skipped cluster `{}` in `{}`", cluster, peripheral)
+            }
+            Self::EnumeratedValues(peripheral, register, field) => write!(
+                f,
+                "skipped enumerated values of `{}` in `{} {}`",
+                field, peripheral, register
+            ),
+        }
+    }
+}
+
+fn parse_access(access: &str) -> Option<Access> {
+    match access {
+        "read-only" => Some(Access::ReadOnly),
+        "write-only" | "writeOnce" => Some(Access::WriteOnly),
+        "read-write" | "read-writeOnce" => Some(Access::ReadWrite),
+        _ => None,
+    }
+}
+
+fn parse_number(number: &str) -> Option<u64> {
+    if number.starts_with("0x") || number.starts_with("0X") {
+        u64::from_str_radix(&number[2..], 16).ok()
+    } else if number.starts_with('#') {
+        u64::from_str_radix(&number[1..].replace(|c| c == 'x' || c == 'X', "0"), 2).ok()
+    } else {
+        number.parse().ok()
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn ident(name: &str) -> String {
+    let ident = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect::<String>();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) { format!("_{}", ident) } else { ident }
+}
+
+fn hex(value: u64, digits: usize) -> String {
+    let digits = format!("{:01$x}", value, digits.max(1));
+    let mut output = String::from("0x");
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 4 == 0 {
+            output.push('_');
+        }
+        output.push(digit.to_ascii_uppercase());
+    }
+    output
+}
+
+fn write_doc(w: &mut String, indent: &str, text: &str) -> fmt::Result {
+    let text = text.trim();
+    let text = if text.ends_with('.') { text.to_string() } else { format!("{}.", text) };
+    writeln!(w, "{}/// {}", indent, text)
+}
@@ -0,0 +1,175 @@
+use drone_core_svd::{self as svd, Access, SvdError, SvdWarning};
+
+const SVD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<device schemaVersion="1.1" xmlns:xs="http://www.w3.org/2001/XMLSchema-instance">
+  <name>STM32F103</name>
+  <!-- Default register properties -->
+  <size>0x20</size>
+  <resetValue>0x00000000</resetValue>
+  <peripherals>
+    <peripheral>
+      <name>RCC</name>
+      <description>Reset and clock control</description>
+      <baseAddress>0x40021000</baseAddress>
+      <registers>
+        <register>
+          <name>CR</name>
+          <description>Clock control
+            register</description>
+          <addressOffset>0x0</addressOffset>
+          <access>read-write</access>
+          <resetValue>0x00000083</resetValue>
+          <fields>
+            <field>
+              <name>HSION</name>
+              <description>Internal High Speed clock enable</description>
+              <bitOffset>0</bitOffset>
+              <bitWidth>1</bitWidth>
+            </field>
+            <field>
+              <name>HSIRDY</name>
+              <bitRange>[1:1]</bitRange>
+              <access>read-only</access>
+            </field>
+            <field>
+              <name>HSITRIM</name>
+              <lsb>3</lsb>
+              <msb>7</msb>
+            </field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral>
+      <name>USART1</name>
+      <baseAddress>0x40013800</baseAddress>
+      <registers>
+        <register>
+          <name>SR</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field>
+              <name>TC</name>
+              <bitOffset>6</bitOffset>
+              <bitWidth>1</bitWidth>
+              <modifiedWriteValues>oneToClear</modifiedWriteValues>
+              <enumeratedValues>
+                <enumeratedValue><name>Complete</name><value>1</value></enumeratedValue>
+              </enumeratedValues>
+            </field>
+          </fields>
+        </register>
+        <register>
+          <name>DR</name>
+          <addressOffset>0x4</addressOffset>
+          <size>16</size>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="USART1">
+      <name>USART2</name>
+      <baseAddress>0x40004400</baseAddress>
+    </peripheral>
+    <peripheral>
+      <name>DMA1</name>
+      <baseAddress>0x40020000</baseAddress>
+      <access>write-only</access>
+      <registers>
+        <register>
+          <dim>3</dim>
+          <dimIncrement>0x14</dimIncrement>
+          <name>CCR%s</name>
+          <addressOffset>0x8</addressOffset>
+          <fields>
+            <field><name>EN</name><bitOffset>0</bitOffset><bitWidth>1</bitWidth></field>
+          </fields>
+        </register>
+        <cluster>
+          <name>CH%s</name>
+          <addressOffset>0x100</addressOffset>
+        </cluster>
+      </registers>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+const REG: &str = "drone_core::reg! {
+    /// Clock control register.
+    pub mod RCC CR;
+    0x4002_1000 0x20 0x0000_0083
+    RReg WReg;
+    /// Internal High Speed clock enable.
+    HSION { 0 1 RRRegField WWRegField }
+    HSIRDY { 1 1 RRRegField RoRRegField }
+    HSITRIM { 3 5 RRRegField WWRegField }
+}
+
+drone_core::reg! {
+    pub mod USART1 SR;
+    0x4001_3800 0x20 0x0000_0000
+    RReg WReg;
+    TC { 6 1 RRRegField W1cRegField }
+}
+
+drone_core::reg! {
+    pub mod USART1 DR;
+    0x4001_3804 0x10 0x0000
+    RReg WReg;
+}
+
+drone_core::reg! {
+    pub mod USART2 SR;
+    0x4000_4400 0x20 0x0000_0000
+    RReg WReg;
+    TC { 6 1 RRRegField W1cRegField }
+}
+
+drone_core::reg! {
+    pub mod USART2 DR;
+    0x4000_4404 0x10 0x0000
+    RReg WReg;
+}
+
+drone_core::reg! {
+    pub mod DMA1 CCR[3; 0x14];
+    0x4002_0008 0x20 0x0000_0000
+    WReg WoReg;
+    EN { 0 1 WWRegField WoWRegField }
+}
+
+";
+
+#[test]
+fn parse() {
+    let device = svd::parse(SVD).unwrap();
+    assert_eq!(device.name, "STM32F103");
+    assert_eq!(device.peripherals.len(), 4);
+    let rcc = &device.peripherals[0];
+    assert_eq!(rcc.description.as_deref(), Some("Reset and clock control"));
+    assert_eq!(rcc.registers[0].reset_value, 0x83);
+    assert_eq!(rcc.registers[0].fields[1].access, Access::ReadOnly);
+    assert_eq!(device.peripherals[2].registers[1].size, 16);
+    assert_eq!(device.peripherals[3].registers[0].dim, Some((3, 0x14)));
+    assert_eq!(device.warnings, [
+        SvdWarning::EnumeratedValues("USART1".into(), "SR".into(), "TC".into()),
+        SvdWarning::Cluster("DMA1".into(), "CH%s".into()),
+    ]);
+    assert_eq!(device.warnings[1].to_string(), "skipped cluster `CH%s` in `DMA1`");
+}
+
+#[test]
+fn generate() {
+    assert_eq!(svd::parse(SVD).unwrap().generate(), REG);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(svd::parse("<device><name>X</name>").unwrap_err(), SvdError::Xml(22));
+    let xml = r"<device><name>X</name><peripherals><peripheral/></peripherals></device>";
+    assert_eq!(svd::parse(xml).unwrap_err(), SvdError::Missing("name", "peripheral".into()));
+    let xml = r"<device><name>X</name><peripherals>
+        <peripheral derivedFrom='Y'><name>Z</name><baseAddress>0</baseAddress></peripheral>
+    </peripherals></device>";
+    assert_eq!(svd::parse(xml).unwrap_err(), SvdError::UnknownPeripheral("Y".into()));
+}
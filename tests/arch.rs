@@ -0,0 +1,99 @@
+use drone_core::arch::{self, Arch};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::*},
+        Arc,
+    },
+    thread,
+};
+
+struct Word(*mut u32);
+
+unsafe impl Send for Word {}
+unsafe impl Sync for Word {}
+
+#[test]
+fn broken_reservation() {
+    let mut word = 1_u32;
+    unsafe {
+        assert_eq!(arch::load_exclusive(&word), 1);
+        arch::clear_exclusive();
+        assert!(!arch::store_exclusive(&mut word, 2));
+        assert_eq!(arch::load_exclusive(&word), 1);
+        assert!(arch::store_exclusive(&mut word, 2));
+        assert!(!arch::store_exclusive(&mut word, 3));
+    }
+    assert_eq!(word, 2);
+}
+
+#[test]
+fn modify_exclusive() {
+    let word = Arc::new(Word(Box::into_raw(Box::new(0_u32))));
+    let threads = (0..4)
+        .map(|_| {
+            let word = Arc::clone(&word);
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    unsafe { arch::modify_exclusive(word.0, |x| x + 1) };
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(unsafe { *Box::from_raw(word.0) }, 4000);
+}
+
+static BARRIERS: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingArch;
+
+unsafe impl Arch for CountingArch {
+    fn data_barrier() {
+        BARRIERS.fetch_add(1, SeqCst);
+    }
+
+    fn sync_barrier() {
+        BARRIERS.fetch_add(10, SeqCst);
+    }
+
+    fn instruction_barrier() {
+        BARRIERS.fetch_add(100, SeqCst);
+    }
+
+    unsafe fn load_exclusive(address: *const u32) -> u32 {
+        *address
+    }
+
+    unsafe fn store_exclusive(address: *mut u32, value: u32) -> bool {
+        *address = value;
+        true
+    }
+
+    fn clear_exclusive() {}
+}
+
+arch::set_impl!(CountingArch);
+
+extern "C" {
+    fn drone_data_barrier();
+    fn drone_sync_barrier();
+    fn drone_instruction_barrier();
+    fn drone_load_exclusive(address: *const u32) -> u32;
+    fn drone_store_exclusive(address: *mut u32, value: u32) -> bool;
+}
+
+#[test]
+fn set_impl() {
+    let mut word = 1_u32;
+    unsafe {
+        drone_data_barrier();
+        drone_sync_barrier();
+        drone_instruction_barrier();
+        assert_eq!(drone_load_exclusive(&word), 1);
+        assert!(drone_store_exclusive(&mut word, 2));
+    }
+    assert_eq!(BARRIERS.load(SeqCst), 111);
+    assert_eq!(word, 2);
+}
@@ -1,4 +1,9 @@
-use drone_core::bitfield::Bitfield;
+use drone_core::bitfield::{Bitfield, BitfieldEnum, InvalidValue, OutOfRange};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicU8, Ordering},
+};
 
 #[derive(Bitfield, Copy, Clone)]
 #[bitfield(
@@ -8,6 +13,50 @@ use drone_core::bitfield::Bitfield;
 )]
 pub struct Byte(u8);
 
+#[derive(BitfieldEnum, Copy, Clone, Debug, PartialEq)]
+#[repr(u8)]
+pub enum Speed {
+    Low = 0,
+    Medium = 1,
+    High = 3,
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(debug, display, speed(rw, 4, 2, Speed, "Test enum-typed bits."))]
+pub struct Ctrl(u8);
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(
+    atomic,
+    builder,
+    checked,
+    flag[4](rw, 0, 1, "Test array of bits."),
+    pair[2](rw, 4, 2, "Test array of multiple bits.")
+)]
+pub struct Map(u8);
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(
+    eq,
+    hash,
+    lo(rw, 0, 4, "Test low bits of byte array."),
+    mid(rw, 4, 12, "Test cross-byte bits of byte array."),
+    hi(rw, 95, 1, "Test last bit of byte array.")
+)]
+pub struct Descriptor([u8; 12]);
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(eq, lo(rw, 0, 8, "Test low byte."), tail(rw, 140, 4, "Test bits past bit 127."))]
+pub struct Long([u8; 20]);
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(x(rw, 0, 4, i8, "Test signed bits."), y(rw, 4, 12, i16, "Test signed cross-byte bits."))]
+pub struct Signed([u8; 2]);
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(wide(rw, 60, 64, "Test wide bits of u128."))]
+pub struct Wide(u128);
+
 #[test]
 fn read_bit() {
     let x = Byte(0b1010_1010);
@@ -115,3 +164,233 @@ fn write_bits() {
     unsafe { x.write_bits(0, 8, 0b1111_1111) };
     assert_eq!(x.bits(), 0b1111_1111);
 }
+
+#[test]
+fn enum_fields() {
+    let mut x = Ctrl(0b1001_1111);
+    assert_eq!(x.speed(), Ok(Speed::Medium));
+    x.write_speed(Speed::High);
+    assert_eq!(x.speed(), Ok(Speed::High));
+    assert_eq!(x.bits(), 0b1011_1111);
+    x.write_speed(Speed::Low);
+    assert_eq!(x.bits(), 0b1000_1111);
+    let x = Ctrl(0b0010_0000);
+    assert_eq!(x.speed(), Err(InvalidValue(0b10)));
+    assert_eq!(Speed::WIDTH, 2);
+}
+
+#[test]
+fn array_fields() {
+    let mut x = Map(0b0110_0001);
+    assert!(x.flag(0));
+    assert!(!x.flag(3));
+    assert_eq!(x.pair(0), 0b10);
+    assert_eq!(x.pair(1), 0b01);
+    x.set_flag(3).clear_flag(0).toggle_flag(1);
+    x.write_pair(1, 0b11);
+    assert_eq!(x.bits(), 0b1110_1010);
+}
+
+#[test]
+#[should_panic]
+fn array_fields_out_of_range() {
+    Map(0).flag(4);
+}
+
+#[test]
+fn fmt() {
+    assert_eq!(format!("{:?}", Ctrl(0b0011_0000)), "Ctrl { speed: Ok(High) }");
+    assert_eq!(format!("{}", Ctrl(0b0010_0000)), "speed=Err(InvalidValue(2))");
+}
+
+#[test]
+fn byte_array() {
+    let mut x = Descriptor([0; 12]);
+    x.write_lo(0xA).write_mid(0xBCD).set_hi();
+    assert_eq!(x.lo(), 0xA);
+    assert_eq!(x.mid(), 0xBCD);
+    assert!(x.hi());
+    assert_eq!(x.0, [0xDA, 0xBC, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+    x.toggle_hi();
+    assert!(!x.hi());
+}
+
+#[test]
+fn wide() {
+    let mut x = Wide(0);
+    x.write_wide(0xDEAD_BEEF_CAFE_BABE);
+    assert_eq!(x.wide(), 0xDEAD_BEEF_CAFE_BABE);
+    assert_eq!(x.bits(), 0xDEAD_BEEF_CAFE_BABE << 60);
+}
+
+#[test]
+fn try_write() {
+    let mut x = Byte(0);
+    assert!(x.try_write_baz(0b111).is_ok());
+    assert_eq!(x.bits(), 0b0011_1000);
+    assert_eq!(x.try_write_baz(0b1000).map(|x| x.bits()), Err(OutOfRange));
+    assert_eq!(x.bits(), 0b0011_1000);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic)]
+fn checked_write() {
+    Map(0).write_pair(0, 0b100);
+}
+
+#[test]
+fn signed() {
+    let mut x = Signed([0; 2]);
+    x.write_x(-8).write_y(-1);
+    assert_eq!(x.0, [0xF8, 0xFF]);
+    assert_eq!(x.x(), -8);
+    assert_eq!(x.y(), -1);
+    x.write_x(7).write_y(2047);
+    assert_eq!(x.x(), 7);
+    assert_eq!(x.y(), 2047);
+    assert!(x.try_write_x(8).is_err());
+    assert!(x.try_write_x(-9).is_err());
+    assert!(x.try_write_y(-2048).is_ok());
+    assert_eq!(x.0, [0x07, 0x80]);
+}
+
+#[test]
+fn bytes() {
+    let x = Wide::from_le_bytes([0xFF; 16]);
+    assert_eq!(x.wide(), 0xFFFF_FFFF_FFFF_FFFF);
+    let x = Descriptor::from_be_bytes([0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xBC, 0xDA]);
+    assert_eq!(x.mid(), 0xBCD);
+    assert!(x.hi());
+    assert_eq!(x.to_be_bytes(), [0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xBC, 0xDA]);
+    assert_eq!(x.to_le_bytes(), x.0);
+}
+
+#[test]
+fn const_fn() {
+    const X: Byte = Byte(0b1111_1111).with_foo(false).with_baz(0b010);
+    const FOO: bool = X.foo();
+    const BAR: u8 = X.bar();
+    assert_eq!(X.bits(), 0b1101_0110);
+    assert!(!FOO);
+    assert_eq!(BAR, 0b11);
+}
+
+#[test]
+fn builder() {
+    let x = Map::builder().flag(1, true).pair(1, 0b10).flag(3, true).build();
+    assert_eq!(x.bits(), 0b1000_1010);
+    let x = x.into_builder().flag(1, false).build();
+    assert_eq!(x.bits(), 0b1000_1000);
+}
+
+#[test]
+fn masked_eq() {
+    fn hash(x: &Descriptor) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        x.hash(&mut hasher);
+        hasher.finish()
+    }
+    let x = Descriptor([0xDA, 0xBC, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+    let y = Descriptor([0xDA, 0xBC, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF]);
+    let z = Descriptor([0xDA, 0xBD, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+    assert!(x == y);
+    assert!(x != z);
+    assert_eq!(hash(&x), hash(&y));
+}
+
+#[test]
+fn masked_eq_long() {
+    let mut x = Long([0; 20]);
+    let mut y = Long([0; 20]);
+    y.0[17] = 0xFF;
+    assert!(x != y);
+    x.write_tail(0xF);
+    assert!(x == y);
+    y.0[16] = 0xFF;
+    assert!(x == y);
+}
+
+#[test]
+fn atomic() {
+    let x = AtomicU8::new(0);
+    assert!(!Map::fetch_set_flag(&x, 1, Ordering::SeqCst).flag(1));
+    assert!(Map::fetch_toggle_flag(&x, 1, Ordering::SeqCst).flag(1));
+    Map::fetch_set_flag(&x, 3, Ordering::Release);
+    assert_eq!(Map::fetch_write_pair(&x, 1, 0b10, Ordering::AcqRel).0, 0b0000_1000);
+    assert_eq!(x.load(Ordering::SeqCst), 0b1000_1000);
+    Map::fetch_clear_flag(&x, 3, Ordering::Relaxed);
+    let prev = Map::fetch_modify(&x, Ordering::SeqCst, |x| {
+        x.write_pair(0, 0b11);
+    });
+    assert_eq!(prev.0, 0b1000_0000);
+    assert_eq!(x.load(Ordering::SeqCst), 0b1011_0000);
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use super::Speed;
+    use drone_core::bitfield::Bitfield;
+    use serde_test::{assert_de_tokens, assert_tokens, Token};
+
+    #[derive(Bitfield, Copy, Clone, Debug, PartialEq)]
+    #[bitfield(serde, foo(rw, 0))]
+    struct Raw(u16);
+
+    #[derive(Bitfield, Copy, Clone, Debug, PartialEq)]
+    #[bitfield(
+        serde_struct,
+        flag(rw, 0),
+        level(rw, 1, 3),
+        offset(rw, 4, 4, i8),
+        speed(rw, 8, 2, Speed),
+        pair[2](rw, 10, 2),
+        hidden(w, 14)
+    )]
+    struct Named(u16);
+
+    #[test]
+    fn raw() {
+        assert_tokens(&Raw(0x1234), &[Token::U16(0x1234)]);
+    }
+
+    #[test]
+    fn named() {
+        assert_tokens(&Named(0b0000_1110_1101_0101), &[
+            Token::Struct { name: "Named", len: 5 },
+            Token::Str("flag"),
+            Token::Bool(true),
+            Token::Str("level"),
+            Token::U16(0b010),
+            Token::Str("offset"),
+            Token::I8(-3),
+            Token::Str("speed"),
+            Token::U8(2),
+            Token::Str("pair"),
+            Token::Tuple { len: 2 },
+            Token::U16(0b11),
+            Token::U16(0b00),
+            Token::TupleEnd,
+            Token::StructEnd,
+        ]);
+        assert_de_tokens(&Named(0b0000_0000_0000_0110), &[
+            Token::Struct { name: "Named", len: 2 },
+            Token::Str("level"),
+            Token::U16(0b011),
+            Token::Str("unknown"),
+            Token::Bool(true),
+            Token::StructEnd,
+        ]);
+        assert_de_tokens(&Named(0b0000_1110_1101_0101), &[
+            Token::Seq { len: Some(5) },
+            Token::Bool(true),
+            Token::U16(0b010),
+            Token::I8(-3),
+            Token::U8(2),
+            Token::Tuple { len: 2 },
+            Token::U16(0b11),
+            Token::U16(0b00),
+            Token::TupleEnd,
+            Token::SeqEnd,
+        ]);
+    }
+}
@@ -0,0 +1,99 @@
+#![feature(allocator_api)]
+#![feature(const_fn)]
+
+use drone_core::{heap::Allocator, thr::Thread};
+
+drone_core::config_override! { "
+[memory]
+flash = { size = \"128K\", origin = 0x08000000 }
+ram = { size = \"20K\", origin = 0x20000000 }
+
+[heap]
+size = \"10K\"
+pools = [
+    { block = \"4\", capacity = 896 },
+    { block = \"32\", capacity = 80 },
+    { block = \"256\", capacity = 16 },
+]
+
+[probe]
+gdb-client-command = \"gdb-multiarch\"
+
+[log.dso]
+baud-rate = 115200
+serial-endpoint = \"/dev/ttyACM0\"
+" }
+
+drone_core::config! {
+    pub mod consts;
+
+    heap {
+        pub struct Heap;
+
+        region CCM {
+            origin = 0x10000000;
+            layout = "tests/heap_layout.toml";
+        }
+    }
+
+    log {
+        TELEMETRY = 5;
+        PROFILE = 6;
+    }
+
+    thr {
+        threads = [SYS_TICK, UART];
+
+        pub struct Thr {
+            pub priority: u8 = 3;
+        }
+
+        pub struct ThrLocal {}
+    }
+}
+
+#[test]
+fn memory() {
+    assert_eq!(consts::FLASH_ORIGIN, 0x0800_0000);
+    assert_eq!(consts::FLASH_SIZE, 128 * 1024);
+    assert_eq!(consts::RAM_ORIGIN, 0x2000_0000);
+    assert_eq!(consts::RAM_SIZE, 20 * 1024);
+    assert_eq!(consts::HEAP_SIZE, 10 * 1024);
+    assert_eq!(consts::HEAP_ORIGIN + consts::HEAP_SIZE, consts::RAM_ORIGIN + consts::RAM_SIZE);
+    assert_eq!(consts::LOG_BAUD_RATE, 115_200);
+    assert_eq!(consts::CCM_ORIGIN, 0x1000_0000);
+    assert_eq!(consts::CCM_SIZE, 10 * 1024);
+}
+
+#[test]
+fn log_ports() {
+    assert_eq!(consts::TELEMETRY_PORT, 5);
+    assert_eq!(consts::PROFILE_PORT, 6);
+}
+
+#[test]
+fn heap() {
+    let heap = Heap::new();
+    let mut pools = Vec::new();
+    let mut size = 0;
+    for i in 0..Heap::POOL_COUNT {
+        let pool = unsafe { heap.get_pool_unchecked(i) };
+        pools.push((pool.size(), pool.capacity()));
+        size += pool.size() * pool.capacity();
+    }
+    assert_eq!(pools[..3], [(4, 896), (32, 80), (256, 16)]);
+    assert_eq!(size, consts::HEAP_SIZE + consts::CCM_SIZE);
+    assert_eq!(Heap::CCM, 1);
+}
+
+#[test]
+fn threads() {
+    assert_eq!(consts::THR_COUNT, 2);
+    assert_eq!(consts::SYS_TICK_THR_NUM, 0);
+    assert_eq!(consts::UART_THR_NUM, 1);
+    unsafe {
+        assert_eq!(THREADS.len(), consts::THR_COUNT);
+        assert_eq!(THREADS[consts::UART_THR_NUM].priority, 3);
+        assert!(THREADS[consts::UART_THR_NUM].fib_chain().is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use drone_core::critical;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::*},
+        Arc,
+    },
+    thread,
+};
+
+#[test]
+fn nested() {
+    let result = critical::with(|_| critical::with(|_| 1) + 1);
+    assert_eq!(result, 2);
+}
+
+#[test]
+fn exclusive() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let threads = (0..4)
+        .map(|_| {
+            let counter = Arc::clone(&counter);
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    critical::with(|_| {
+                        let value = counter.load(Relaxed);
+                        counter.store(value + 1, Relaxed);
+                    });
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(counter.load(Relaxed), 4000);
+}
+
+#[test]
+fn unwind() {
+    let result = thread::spawn(|| critical::with(|_| panic!("inside critical section"))).join();
+    assert!(result.is_err());
+    let result = thread::spawn(|| critical::with(|_| 1)).join();
+    assert_eq!(result.unwrap(), 1);
+    assert_eq!(critical::with(|_| 2), 2);
+}
@@ -9,10 +9,18 @@ use drone_core::prelude::*;
 
 use core::{
     pin::Pin,
-    sync::atomic::{AtomicUsize, Ordering::*},
+    sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering::*},
     task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    time::Duration,
+};
+use drone_core::{
+    future::{
+        self as drone_future, block_on_with, CancelGuard, Deadline, PollStats, TaskArena, Tick,
+        TimedOut,
+    },
+    sync::spsc::{oneshot, ring},
+    thr,
 };
-use drone_core::{sync::spsc::oneshot, thr};
 use futures::prelude::*;
 
 static mut THREADS: [Thr; 1] = [Thr::new(0)];
@@ -25,6 +33,28 @@ thr! {
 
 struct Counter(AtomicUsize);
 
+/// Defines a tick source with its own clock, so that tests running in
+/// parallel don't advance each other's time.
+macro_rules! test_tick {
+    ($tick:ident, $now:ident) => {
+        static $now: AtomicU64 = AtomicU64::new(0);
+
+        struct $tick;
+
+        impl Tick for $tick {
+            const FREQ: u64 = 1_000;
+
+            fn now() -> u64 {
+                $now.load(Relaxed)
+            }
+
+            fn schedule_wakeup(_at: u64, waker: &Waker) {
+                waker.wake_by_ref();
+            }
+        }
+    };
+}
+
 impl Counter {
     fn to_waker(&'static self) -> Waker {
         unsafe fn clone(counter: *const ()) -> RawWaker {
@@ -76,3 +106,216 @@ fn test_nested() {
     assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Ok(2)));
     assert_eq!(COUNTER.0.load(Relaxed), 1);
 }
+
+#[test]
+fn test_block_on() {
+    let (tx, rx) = oneshot::channel::<usize>();
+    let handle = std::thread::spawn(move || tx.send(1));
+    let number = block_on_with(rx, std::thread::yield_now);
+    assert_eq!(number, Ok(1));
+    assert_eq!(handle.join().unwrap(), Ok(()));
+}
+
+#[test]
+fn test_tick() {
+    test_tick!(TestTick, NOW);
+    static COUNTER: Counter = Counter(AtomicUsize::new(0));
+    let waker = COUNTER.to_waker();
+    let mut cx = Context::from_waker(&waker);
+    assert_eq!(TestTick::ticks(Duration::from_micros(2_500)), 2);
+    let mut sleep = drone_future::sleep::<TestTick>(Duration::from_millis(10));
+    assert_eq!(sleep.tick(), 10);
+    assert_eq!(sleep.next(Duration::from_millis(10)).tick(), 20);
+    let mut timeout =
+        drone_future::timeout::<TestTick, _>(future::pending::<()>(), Duration::from_millis(5));
+    let mut ready =
+        drone_future::timeout::<TestTick, _>(future::ready(1), Duration::from_millis(5));
+    assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+    assert_eq!(Pin::new(&mut timeout).poll(&mut cx), Poll::Pending);
+    assert_eq!(Pin::new(&mut ready).poll(&mut cx), Poll::Ready(Ok(1)));
+    NOW.store(5, Relaxed);
+    assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+    assert_eq!(Pin::new(&mut timeout).poll(&mut cx), Poll::Ready(Err(TimedOut)));
+    NOW.store(10, Relaxed);
+    assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Ready(()));
+    assert!(Deadline::<TestTick>::at(10).is_elapsed());
+    let mut interval = drone_future::interval::<TestTick>(Duration::from_millis(3));
+    assert_eq!(Pin::new(&mut interval).poll_next(&mut cx), Poll::Pending);
+    NOW.store(17, Relaxed);
+    assert_eq!(Pin::new(&mut interval).poll_next(&mut cx), Poll::Ready(Some(())));
+    assert_eq!(Pin::new(&mut interval).poll_next(&mut cx), Poll::Ready(Some(())));
+    assert_eq!(Pin::new(&mut interval).poll_next(&mut cx), Poll::Pending);
+    assert_eq!(interval.deadline().tick(), 19);
+}
+
+#[test]
+fn test_join_select() {
+    static COUNTER: Counter = Counter(AtomicUsize::new(0));
+    let waker = COUNTER.to_waker();
+    let mut cx = Context::from_waker(&waker);
+    let (tx1, rx1) = oneshot::channel::<usize>();
+    let (tx2, rx2) = oneshot::channel::<usize>();
+    let mut join = drone_future::join3(rx1, rx2, future::ready(3));
+    assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+    assert_eq!(tx2.send(2), Ok(()));
+    assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+    assert_eq!(tx1.send(1), Ok(()));
+    assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Ready((Ok(1), Ok(2), 3)));
+    let (tx1, rx1) = oneshot::channel::<usize>();
+    let (tx2, rx2) = oneshot::channel::<usize>();
+    let mut select = drone_future::select(rx1, rx2);
+    assert_eq!(Pin::new(&mut select).poll(&mut cx), Poll::Pending);
+    assert_eq!(tx2.send(2), Ok(()));
+    assert_eq!(Pin::new(&mut select).poll(&mut cx), Poll::Ready(future::Either::Right(Ok(2))));
+    drop(tx1);
+}
+
+#[test]
+fn test_yield_now() {
+    static COUNTER: Counter = Counter(AtomicUsize::new(0));
+    let waker = COUNTER.to_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = drone_future::yield_now();
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    assert_eq!(COUNTER.0.load(Relaxed), 1);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(()));
+    assert_eq!(COUNTER.0.load(Relaxed), 1);
+}
+
+#[test]
+fn test_task_arena() {
+    static COUNTER: Counter = Counter(AtomicUsize::new(0));
+    let waker = COUNTER.to_waker();
+    let mut cx = Context::from_waker(&waker);
+    let arena = TaskArena::<2, 2>::new();
+    futures::pin_mut!(arena);
+    let (tx, rx) = oneshot::channel::<usize>();
+    assert!(arena.as_mut().spawn(future::ready([0_u64; 3]).map(drop)).is_err());
+    assert!(arena.as_mut().spawn(rx.map(drop)).is_ok());
+    assert!(arena.as_mut().spawn(drone_future::yield_now()).is_ok());
+    assert!(arena.as_mut().spawn(drone_future::yield_now()).is_err());
+    assert_eq!(arena.len(), 2);
+    assert_eq!(arena.as_mut().poll_all(&mut cx), Poll::Pending);
+    assert_eq!(arena.as_mut().poll_all(&mut cx), Poll::Pending);
+    assert_eq!(arena.len(), 1);
+    assert_eq!(tx.send(1), Ok(()));
+    assert_eq!(arena.as_mut().poll_all(&mut cx), Poll::Ready(()));
+    assert!(arena.is_empty());
+}
+
+#[test]
+fn test_instrument() {
+    static CYCLES: AtomicU32 = AtomicU32::new(0);
+    static STATS: PollStats = PollStats::new();
+    fn cycles() -> u32 {
+        CYCLES.fetch_add(1, Relaxed)
+    }
+    let (tx, rx) = oneshot::channel::<usize>();
+    let waker = futures::task::noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = drone_future::instrument(rx, &STATS, cycles);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    assert_eq!(STATS.completion_cycles(), None);
+    assert_eq!(tx.send(1), Ok(()));
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Ok(1)));
+    assert_eq!(STATS.polls(), 2);
+    assert_eq!(STATS.busy_cycles(), 2);
+    assert_eq!(STATS.max_poll_cycles(), 1);
+    assert_eq!(STATS.completion_cycles(), Some(3));
+}
+
+#[test]
+fn test_budget() {
+    static COUNTER: Counter = Counter(AtomicUsize::new(0));
+    let waker = COUNTER.to_waker();
+    let mut cx = Context::from_waker(&waker);
+    let (tx, rx) = oneshot::channel::<usize>();
+    let mut fut = drone_future::budget(rx, 2);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.used(), 0);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.used(), 0);
+    assert_eq!(COUNTER.0.load(Relaxed), 0);
+    assert_eq!(tx.send(1), Ok(()));
+    assert_eq!(COUNTER.0.load(Relaxed), 1);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(Ok(1)));
+}
+
+#[test]
+fn test_budget_busy() {
+    static COUNTER: Counter = Counter(AtomicUsize::new(0));
+    let waker = COUNTER.to_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(drone_future::budget(
+        async {
+            for _ in 0..3_u8 {
+                drone_future::yield_now().await;
+            }
+        },
+        2,
+    ));
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.used(), 2);
+    assert_eq!(COUNTER.0.load(Relaxed), 2);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.used(), 0);
+    assert_eq!(COUNTER.0.load(Relaxed), 3);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(()));
+    assert_eq!(COUNTER.0.load(Relaxed), 4);
+}
+
+#[test]
+fn test_on_cancel() {
+    static CLEANUPS: AtomicUsize = AtomicUsize::new(0);
+    let cleanup = || {
+        CLEANUPS.fetch_add(1, Relaxed);
+    };
+    let waker = futures::task::noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = drone_future::on_cancel(drone_future::yield_now(), cleanup);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(()));
+    drop(fut);
+    assert_eq!(CLEANUPS.load(Relaxed), 0);
+    let mut fut = drone_future::on_cancel(drone_future::yield_now(), cleanup);
+    assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
+    drop(fut);
+    assert_eq!(CLEANUPS.load(Relaxed), 1);
+    CancelGuard::new(cleanup).defuse();
+    assert_eq!(CLEANUPS.load(Relaxed), 1);
+    drop(CancelGuard::new(cleanup));
+    assert_eq!(CLEANUPS.load(Relaxed), 2);
+}
+
+#[test]
+fn test_latest_debounce() {
+    test_tick!(TestTick, NOW);
+    let waker = futures::task::noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let (mut tx, rx) = ring::channel::<usize, !>(8);
+    let mut latest = drone_future::latest(rx);
+    assert_eq!(Pin::new(&mut latest).poll_next(&mut cx), Poll::Pending);
+    assert_eq!(tx.send(1), Ok(()));
+    assert_eq!(tx.send(2), Ok(()));
+    assert_eq!(Pin::new(&mut latest).poll_next(&mut cx), Poll::Ready(Some(Ok(2))));
+    assert_eq!(Pin::new(&mut latest).poll_next(&mut cx), Poll::Pending);
+    let rx = latest.into_inner();
+    assert_eq!(tx.send(3), Ok(()));
+    NOW.store(100, Relaxed);
+    let mut debounce = drone_future::debounce::<TestTick, _>(rx, Duration::from_millis(5));
+    assert_eq!(Pin::new(&mut debounce).poll_next(&mut cx), Poll::Pending);
+    assert_eq!(debounce.deadline().map(Deadline::tick), Some(105));
+    NOW.store(104, Relaxed);
+    assert_eq!(tx.send(4), Ok(()));
+    assert_eq!(Pin::new(&mut debounce).poll_next(&mut cx), Poll::Pending);
+    assert_eq!(debounce.deadline().map(Deadline::tick), Some(109));
+    NOW.store(109, Relaxed);
+    assert_eq!(Pin::new(&mut debounce).poll_next(&mut cx), Poll::Ready(Some(Ok(4))));
+    assert_eq!(tx.send(5), Ok(()));
+    drop(tx);
+    assert_eq!(Pin::new(&mut debounce).poll_next(&mut cx), Poll::Ready(Some(Ok(5))));
+    assert_eq!(Pin::new(&mut debounce).poll_next(&mut cx), Poll::Ready(None));
+}
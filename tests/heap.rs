@@ -4,7 +4,7 @@
 use crate::core::mem::size_of;
 use std as core;
 
-use drone_core::heap;
+use drone_core::heap::{self, Allocator};
 
 drone_core::config_override! { "
 [memory.flash]
@@ -30,7 +30,32 @@ heap! {
     pub struct Heap;
 }
 
+heap! {
+    pub struct TomlHeap;
+    layout = "tests/heap_layout.toml";
+}
+
+heap! {
+    pub struct JsonHeap;
+    layout = "tests/heap_layout.json";
+}
+
 #[test]
 fn size() {
     assert_eq!(size_of::<Heap>(), size_of::<heap::Pool>() * 3);
 }
+
+#[test]
+fn layout_file() {
+    fn pools<A: Allocator>(heap: &A) -> Vec<(usize, usize)> {
+        (0..A::POOL_COUNT)
+            .map(|i| unsafe { heap.get_pool_unchecked(i) })
+            .map(|pool| (pool.size(), pool.capacity()))
+            .collect()
+    }
+    assert_eq!(size_of::<TomlHeap>(), size_of::<heap::Pool>() * 4);
+    assert_eq!(size_of::<JsonHeap>(), size_of::<heap::Pool>() * 4);
+    let expected = [(4, 384), (16, 224), (64, 40), (256, 10)];
+    assert_eq!(pools(&TomlHeap::new()), expected);
+    assert_eq!(pools(&JsonHeap::new()), expected);
+}
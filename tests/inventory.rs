@@ -0,0 +1,27 @@
+use drone_core::inventory::{self, LazyInventory};
+use std::{
+    panic,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+struct Uart;
+
+impl inventory::Item for Uart {
+    fn teardown(&mut self, _token: &mut inventory::GuardToken<Self>) {}
+}
+
+#[test]
+fn lazy_init_panic() {
+    static FAIL: AtomicBool = AtomicBool::new(true);
+    static UART: LazyInventory<Uart> = LazyInventory::new(|| {
+        if FAIL.swap(false, Ordering::Relaxed) {
+            panic!("init failed");
+        }
+        Uart
+    });
+    assert!(panic::catch_unwind(|| UART.acquire()).is_err());
+    assert!(!UART.is_acquired());
+    assert!(UART.acquire().is_some());
+    assert!(UART.is_acquired());
+    assert!(UART.acquire().is_none());
+}
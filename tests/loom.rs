@@ -0,0 +1,57 @@
+#![cfg(loom)]
+
+use drone_core::sync::spsc::{oneshot, pulse, ring};
+use futures::prelude::*;
+use loom::{future::block_on, thread};
+
+#[test]
+fn oneshot_send_recv() {
+    loom::model(|| {
+        let (tx, rx) = oneshot::channel::<usize>();
+        let sender = thread::spawn(move || tx.send(1).unwrap());
+        assert_eq!(block_on(rx), Ok(1));
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn oneshot_cancel() {
+    loom::model(|| {
+        let (tx, rx) = oneshot::channel::<usize>();
+        let receiver = thread::spawn(move || drop(rx));
+        let _ = tx.send(1);
+        receiver.join().unwrap();
+    });
+}
+
+#[test]
+fn pulse_send_recv() {
+    loom::model(|| {
+        let (mut tx, mut rx) = pulse::channel::<()>();
+        let sender = thread::spawn(move || {
+            tx.send(1).unwrap();
+            tx.send(2).unwrap();
+        });
+        let mut total = 0;
+        while let Some(pulses) = block_on(rx.next()) {
+            total += pulses.unwrap().get();
+        }
+        assert_eq!(total, 3);
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn ring_send_recv() {
+    loom::model(|| {
+        let (mut tx, mut rx) = ring::channel::<usize, ()>(2);
+        let sender = thread::spawn(move || {
+            tx.send(1).unwrap();
+            tx.send(2).unwrap();
+        });
+        assert_eq!(block_on(rx.next()), Some(Ok(1)));
+        assert_eq!(block_on(rx.next()), Some(Ok(2)));
+        assert_eq!(block_on(rx.next()), None);
+        sender.join().unwrap();
+    });
+}
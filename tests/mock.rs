@@ -0,0 +1,64 @@
+#![cfg(all(feature = "mock", not(feature = "sim")))]
+#![feature(proc_macro_hygiene)]
+
+use drone_core::{
+    reg,
+    reg::{
+        mem::{region, MmioRegion},
+        mock,
+        prelude::*,
+    },
+    token::Token,
+};
+use std::thread;
+
+reg! {
+    pub mod TIM2 CNT;
+    0x4000_0024 0x10 0x0000
+    RReg WReg;
+    CNT { 0 16 RRRegField WWRegField }
+}
+
+reg! {
+    pub mod TIM2 ARR;
+    0x4000_002C 0x10 0xFFFF
+    RReg WReg;
+    ARR { 0 16 RRRegField WWRegField }
+}
+
+region! {
+    pub struct Keys: [u128; 2] = 0x2000_0000;
+}
+
+#[test]
+fn mock_memory() {
+    let cnt = unsafe { tim2_cnt::Reg::<Urt>::take() };
+    let mut arr = unsafe { tim2_arr::Reg::<Urt>::take() };
+    mock::poke::<u16>(0x4000_0024, 0x1234);
+    assert_eq!(cnt.load().cnt(), 0x1234);
+    arr.reset();
+    assert_eq!(mock::peek::<u16>(0x4000_002C), 0xFFFF);
+    arr.store(|r| r.write_arr(0x0100));
+    assert_eq!(mock::peek::<u64>(0x4000_0028) >> 32, 0x0100);
+    assert_eq!(mock::peek::<u16>(0x4000_0024), 0x1234);
+}
+
+#[test]
+fn mock_region() {
+    let mut keys = unsafe { Keys::take() }.into_slice();
+    keys.write(1, 0x0011_2233_4455_6677_8899_AABB_CCDD_EEFF);
+    assert_eq!(keys.read(1), 0x0011_2233_4455_6677_8899_AABB_CCDD_EEFF);
+    assert_eq!(mock::peek::<u64>(0x2000_0010), 0x8899_AABB_CCDD_EEFF);
+    assert_eq!(mock::peek::<u64>(0x2000_0018), 0x0011_2233_4455_6677);
+    assert_eq!(keys.read(0), 0);
+}
+
+#[test]
+fn mock_thread_local() {
+    let cnt = unsafe { tim2_cnt::Reg::<Urt>::take() };
+    mock::poke::<u16>(0x4000_0024, 0x1234);
+    thread::spawn(|| assert_eq!(mock::peek::<u16>(0x4000_0024), 0)).join().unwrap();
+    assert_eq!(cnt.load().cnt(), 0x1234);
+    mock::reset();
+    assert_eq!(cnt.load().cnt(), 0);
+}
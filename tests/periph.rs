@@ -1,6 +1,8 @@
 #![feature(proc_macro_hygiene)]
 
 use drone_core::{
+    inventory::Inventory,
+    periph::{self, PeriphClock, PeriphDyn},
     reg,
     reg::{marker::*, prelude::*},
     token::Token,
@@ -8,7 +10,7 @@ use drone_core::{
 
 reg! {
     pub mod RCC AHB2ENR;
-    0 0x20 0 RReg WReg;
+    0x4002_104C 0x20 0 RReg WReg;
     GPIOAEN { 0 1 RRRegField WWRegField }
     GPIOBEN { 1 1 RRRegField WWRegField }
     GPIOCEN { 2 1 RRRegField WWRegField }
@@ -18,47 +20,47 @@ reg! {
 
 reg! {
     pub mod GPIOA ODR;
-    0 0x20 0 RReg WReg;
+    0x4800_0014 0x20 0 RReg WReg;
     ODR0 { 0 1 RRRegField WWRegField }
     ODR1 { 1 1 RRRegField WWRegField }
 }
 
 reg! {
     pub mod GPIOA IDR;
-    0 0x20 0 RReg WReg;
+    0x4800_0010 0x20 0 RReg WReg;
     IDR0 { 0 1 RRRegField WWRegField }
     IDR1 { 1 1 RRRegField WWRegField }
 }
 
 reg! {
     pub mod GPIOB ODR;
-    0 0x20 0 RReg WReg;
+    0x4800_0414 0x20 0 RReg WReg;
     ODR0 { 0 1 RRRegField WWRegField }
     ODR1 { 1 1 RRRegField WWRegField }
 }
 
 reg! {
     pub mod GPIOB IDR;
-    0 0x20 0 RReg WReg;
+    0x4800_0410 0x20 0 RReg WReg;
     IDR0 { 0 1 RRRegField WWRegField }
 }
 
 reg! {
     pub mod GPIOC ODR;
-    0 0x20 0 RReg WReg;
+    0x4800_0814 0x20 0 RReg WReg;
     ODR0 { 0 1 RRRegField WWRegField }
 }
 
 reg! {
     pub mod TIM1 CCMR1_Output;
-    0 32 0 RReg WReg;
+    0x4001_2C18 32 0 RReg WReg;
     OC1CE { 7 1 RRRegField WWRegField }
     OC1M { 4 3 RRRegField WWRegField }
     OC1PE { 3 1 RRRegField WWRegField }
     OC1FE { 2 1 RRRegField WWRegField }
     CC1S { 0 2 RRRegField WWRegField }
     pub mod TIM1 CCMR1_Input;
-    0 32 0 RReg WReg;
+    0x4001_2C18 32 0 RReg WReg;
     IC1F { 4 4 RRRegField WWRegField }
     IC1PSC { 2 2 RRRegField WWRegField }
     CC1S { 0 2 RRRegField WWRegField }
@@ -66,14 +68,14 @@ reg! {
 
 reg! {
     pub mod TIM1 CCMR2_Output;
-    0 32 0 RReg WReg;
+    0x4001_2C1C 32 0 RReg WReg;
     OC1CE { 7 1 RRRegField WWRegField }
     OC1M { 4 3 RRRegField WWRegField }
     OC1PE { 3 1 RRRegField WWRegField }
     OC1FE { 2 1 RRRegField WWRegField }
     CC1S { 0 2 RRRegField WWRegField }
     pub mod TIM1 CCMR2_Input;
-    0 32 0 RReg WReg;
+    0x4001_2C1C 32 0 RReg WReg;
     IC1F { 4 4 RRRegField WWRegField }
     IC1PSC { 2 2 RRRegField WWRegField }
     CC1S { 0 2 RRRegField WWRegField }
@@ -81,14 +83,14 @@ reg! {
 
 reg! {
     pub mod TIM2 CCMR1_Output;
-    0 32 0 RReg WReg;
+    0x4000_0018 32 0 RReg WReg;
     OC1CE { 7 1 RRRegField WWRegField }
     OC1M { 4 3 RRRegField WWRegField }
     OC1PE { 3 1 RRRegField WWRegField }
     OC1FE { 2 1 RRRegField WWRegField }
     CC1S { 0 2 RRRegField WWRegField }
     pub mod TIM2 CCMR1_Input;
-    0 32 0 RReg WReg;
+    0x4000_0018 32 0 RReg WReg;
     IC1F { 4 4 RRRegField WWRegField }
     IC1PSC { 2 2 RRRegField WWRegField }
     CC1S { 0 2 RRRegField WWRegField }
@@ -96,10 +98,10 @@ reg! {
 
 reg! {
     pub mod TWIM0_NS TASKS_STARTTX;
-    0 32 0 WReg WoReg;
+    0x5000_8008 32 0 WReg WoReg;
     TASKS_STARTTX { 0 1 WWRegField WoWRegField }
     pub mod UARTE0_NS TASKS_STARTTX;
-    0 32 0 WReg WoReg;
+    0x5000_8008 32 0 WReg WoReg;
     TASKS_STARTTX { 0 1 WWRegField WoWRegField }
 }
 
@@ -122,17 +124,66 @@ reg_tokens! {
     pub struct Regs;
 }
 
+periph::singular! {
+    pub macro periph_tim1_pwm;
+    pub struct Tim1PwmPeriph;
+    crate;
+    crate;
+
+    TIM1 {
+        CCMR1_Output;
+    }
+}
+
+periph::singular! {
+    pub macro periph_tim1_capture;
+    pub struct Tim1CapturePeriph;
+    crate;
+    crate;
+
+    TIM1 {
+        CCMR2_Output;
+    }
+}
+
+periph::singular! {
+    pub macro periph_gpio_tim;
+    pub struct GpioTimPeriph;
+    crate;
+    crate;
+
+    gpio: gpio::GpioPeriph<gpio::GpioB> = periph_gpio_b;
+
+    TIM2 {
+        CCMR1_Output;
+    }
+}
+
+macro_rules! gpio_odr_address {
+    ($name:ident, $reg:ty) => {
+        pub const $name: usize = <$reg as Reg<Srt>>::ADDRESS;
+    };
+}
+
+periph::instances! {
+    gpio_odr_address!;
+    for N in 0..2 {
+        (GPIO_ODR#N, gpioa::odr::Reg<Srt>);
+    }
+    (GPIO_ODR2, gpioc::odr::Reg<Srt>);
+}
+
 pub mod gpio {
     use drone_core::{periph, reg::marker::*};
 
     periph! {
         pub trait GpioMap {}
-        pub struct GpioPeriph;
+        pub struct GpioPeriph: Inventory + RegTable + Dyn;
 
         RCC {
             AHB2ENR {
                 0x20 RwReg Shared;
-                GPIOEN { RwRwRegFieldBit }
+                GPIOEN { RwRwRegFieldBit Clock }
                 GPIORST { RwRwRegFieldBit Option }
             }
         }
@@ -242,8 +293,11 @@ pub mod tim {
     use drone_core::{periph, reg::marker::*};
 
     periph! {
-        pub trait TimMap {}
-        pub struct TimPeriph;
+        pub trait TimMap {
+            const CHANNELS: usize;
+            const ADVANCED: bool = false;
+        }
+        pub struct TimPeriph: Dyn;
 
         TIM {
             CCMR1 {
@@ -276,7 +330,10 @@ pub mod tim {
     periph::map! {
         pub macro periph_tim1;
         pub struct Tim1;
-        impl TimMap for Tim1 {}
+        impl TimMap for Tim1 {
+            const CHANNELS: usize = 4;
+            const ADVANCED: bool = true;
+        }
         super;
         crate::tim;
 
@@ -312,7 +369,9 @@ pub mod tim {
     periph::map! {
         pub macro periph_tim2;
         pub struct Tim2;
-        impl TimMap for Tim2 {}
+        impl TimMap for Tim2 {
+            const CHANNELS: usize = 2;
+        }
         super;
         crate::tim;
 
@@ -338,6 +397,115 @@ pub mod tim {
     }
 }
 
+pub mod adc {
+    use drone_core::{periph, reg::marker::*};
+
+    periph! {
+        pub trait AdcMap {}
+        pub struct AdcPeriph;
+
+        RCC {
+            AHB2ENR {
+                0x20 RwReg Shared;
+                ADCEN { RwRwRegFieldBit }
+                #[cfg(target_os = "none")]
+                ADCRST { RwRwRegFieldBit }
+            }
+        }
+
+        ADC {
+            #[cfg(target_os = "none")]
+            IDR {
+                0x20 RwReg;
+                IDR0 { RwRwRegFieldBit }
+            }
+            ODR {
+                0x20 RwReg;
+                ODR0 { RwRwRegFieldBit }
+            }
+        }
+    }
+
+    periph::map! {
+        pub macro periph_adc;
+        pub struct Adc;
+        impl AdcMap for Adc {}
+        super;
+        crate::adc;
+
+        RCC {
+            AHB2ENR {
+                AHB2ENR Shared;
+                ADCEN { GPIOCEN }
+                #[cfg(target_os = "none")]
+                ADCRST { GPIOBRST }
+            }
+        }
+
+        ADC {
+            GPIOA;
+            #[cfg(target_os = "none")]
+            IDR {
+                IDR;
+                IDR0 { IDR0 }
+            }
+            ODR {
+                ODR;
+                ODR0 { ODR0 }
+            }
+        }
+    }
+}
+
+pub mod reset {
+    use drone_core::{periph, reg::marker::*};
+
+    periph! {
+        pub trait ResetMap {}
+        pub struct ResetPeriph;
+
+        RCC {
+            AHB2ENR {
+                0x20 RwReg Shared;
+                RST { RwRwRegFieldBit }
+            }
+        }
+    }
+
+    periph::map! {
+        pub macro periph_reset_a;
+        pub struct ResetA;
+        impl ResetMap for ResetA {}
+        super;
+        crate::reset;
+
+        RCC {
+            AHB2ENR {
+                AHB2ENR Shared;
+                RST { GPIOARST }
+            }
+        }
+    }
+
+    periph::map! {
+        pub macro periph_reset_b;
+        pub struct ResetB;
+        impl ResetMap for ResetB {}
+        super;
+        crate::reset;
+
+        RCC {
+            AHB2ENR {
+                AHB2ENR Shared;
+                #[cfg(target_os = "none")]
+                RST { GPIOBEN }
+                #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+                RST { GPIOBRST }
+            }
+        }
+    }
+}
+
 pub mod uarte {
     use drone_core::{periph, reg::marker::*};
 
@@ -495,3 +663,260 @@ fn generic_variants_without_holes() {
     let tim1 = periph_tim1!(reg);
     f(tim1);
 }
+
+#[test]
+fn cfg_gated() {
+    use adc::*;
+    fn f<T: AdcMap>(adc: AdcPeriph<T>) {
+        let AdcPeriph { rcc_ahb2enr_adcen, adc_odr } = adc;
+        if false {
+            rcc_ahb2enr_adcen.read_bit();
+            adc_odr.load();
+        }
+    }
+    let reg = unsafe { Regs::take() };
+    let adc = periph_adc!(reg);
+    f(adc);
+}
+
+#[test]
+fn generic_driver() {
+    use gpio::*;
+    struct GpioDrv<T: GpioMap> {
+        periph: GpioPeriph<T>,
+    }
+    impl<T: GpioMap> GpioDrv<T> {
+        fn init(periph: GpioPeriph<T>) -> Self {
+            Self { periph }
+        }
+
+        fn enabled(&self) -> bool {
+            self.periph.rcc_ahb2enr_gpioen.read_bit()
+        }
+
+        fn set_odr0(&self) {
+            let mut val = self.periph.gpio_odr.load().val();
+            self.periph.gpio_odr.odr0().set(&mut val);
+            self.periph.gpio_odr.store_val(val);
+        }
+
+        fn free(self) -> GpioPeriph<T> {
+            self.periph
+        }
+    }
+    let reg = unsafe { Regs::take() };
+    let gpio_a = GpioDrv::init(periph_gpio_a!(reg));
+    let gpio_c = GpioDrv::init(periph_gpio_c!(reg));
+    if false {
+        gpio_a.enabled();
+        gpio_a.set_odr0();
+        gpio_c.enabled();
+        gpio_c.set_odr0();
+    }
+    let _gpio_a: GpioPeriph<gpio::GpioA> = gpio_a.free();
+    let _gpio_c: GpioPeriph<gpio::GpioC> = gpio_c.free();
+}
+
+#[test]
+fn partial() {
+    let reg = unsafe { Regs::take() };
+    let pwm = periph_tim1_pwm!(reg);
+    let capture = periph_tim1_capture!(reg);
+    let gpio_a = periph_gpio_a!(reg);
+    let Tim1PwmPeriph { tim1_ccmr1_output } = pwm;
+    let Tim1CapturePeriph { tim1_ccmr2_output } = capture;
+    let _tim1_ccmr1_input = tim1_ccmr1_output.into_tim1_ccmr1_input();
+    let _tim1_ccmr2_input = tim1_ccmr2_output.into_tim1_ccmr2_input();
+    let _gpio_b_odr = reg.gpiob_odr;
+    let _tim2_ccmr1_output = reg.tim2_ccmr1_output;
+    drop(gpio_a);
+}
+
+#[test]
+fn composed() {
+    let reg = unsafe { Regs::take() };
+    let GpioTimPeriph { gpio, tim2_ccmr1_output } = periph_gpio_tim!(reg);
+    let gpio::GpioPeriph { rcc_ahb2enr_gpioen, gpio_odr, .. } = gpio;
+    let _tim2_ccmr1_input = tim2_ccmr1_output.into_tim2_ccmr1_input();
+    if false {
+        rcc_ahb2enr_gpioen.read_bit();
+        gpio_odr.load();
+    }
+}
+
+#[test]
+fn release() {
+    let mut reg = unsafe { Regs::take() };
+    let gpio_a = periph_gpio_a!(reg);
+    periph_gpio_a!(release gpio_a => reg);
+    let tim1 = periph_tim1!(reg);
+    periph_tim1!(release tim1 => reg);
+    let uarte0_ns = periph_uarte0_ns!(reg);
+    periph_uarte0_ns!(release uarte0_ns => reg);
+    let composed = periph_gpio_tim!(reg);
+    periph_gpio_tim!(release composed => reg);
+    let _reg: Regs = reg;
+}
+
+#[test]
+fn dump() {
+    let reg = unsafe { Regs::take() };
+    let gpio_a = periph_gpio_a!(reg);
+    let tim1 = periph_tim1!(reg);
+    if false {
+        gpio_a.dump(|name, value| println!("{} = {:#x}", name, value));
+        tim1.dump(|name, value| println!("{} = {:#x}", name, value));
+    }
+}
+
+#[test]
+fn instances() {
+    assert_eq!(GPIO_ODR0, 0x4800_0014);
+    assert_eq!(GPIO_ODR1, 0x4800_0014);
+    assert_eq!(GPIO_ODR2, 0x4800_0814);
+}
+
+#[test]
+fn clock() {
+    use gpio::*;
+    fn f<T: GpioMap>(gpio: &GpioPeriph<T>) {
+        let _clock = gpio.clock_guard();
+        gpio.is_clock_enabled();
+    }
+    let reg = unsafe { Regs::take() };
+    let gpio_a = periph_gpio_a!(reg);
+    if false {
+        f(&gpio_a);
+        gpio_a.enable_clock();
+        gpio_a.disable_clock();
+    }
+}
+
+#[test]
+fn clock_guard_restores() {
+    use core::cell::Cell;
+    struct Gate(Cell<bool>);
+    impl PeriphClock for Gate {
+        fn enable_clock(&self) {
+            self.0.set(true);
+        }
+
+        fn disable_clock(&self) {
+            self.0.set(false);
+        }
+
+        fn is_clock_enabled(&self) -> bool {
+            self.0.get()
+        }
+    }
+    let gate = Gate(Cell::new(false));
+    {
+        let _clock = gate.clock_guard();
+        assert!(gate.is_clock_enabled());
+        {
+            let _clock = gate.clock_guard();
+        }
+        assert!(gate.is_clock_enabled());
+    }
+    assert!(!gate.is_clock_enabled());
+    gate.enable_clock();
+    drop((&gate as &dyn PeriphClock).clock_guard());
+    assert!(gate.is_clock_enabled());
+}
+
+#[test]
+fn object_safe() {
+    use gpio::{GpioPeriphDyn, GpioPeriphRef};
+    let reg = unsafe { Regs::take() };
+    let mut gpio_a = periph_gpio_a!(reg);
+    let mut gpio_c = periph_gpio_c!(reg);
+    let tim1 = periph_tim1!(reg);
+    let periphs: [&dyn PeriphDyn; 3] = [&gpio_a, &gpio_c, &tim1];
+    assert!(periphs[0].variant_name().ends_with("GpioA"));
+    assert!(periphs[1].variant_name().ends_with("GpioC"));
+    assert!(periphs[2].variant_name().ends_with("Tim1"));
+    let clocks: [&dyn PeriphClock; 2] = [&gpio_a, &gpio_c];
+    if false {
+        for periph in &periphs {
+            periph.dump_dyn(&mut |_, _| {});
+        }
+        for clock in &clocks {
+            let _clock = clock.clock_guard();
+        }
+    }
+    let gpios = [GpioPeriphRef::from(&mut gpio_a), GpioPeriphRef::from(&mut gpio_c)];
+    assert!(gpios[0].variant_name().ends_with("GpioA"));
+    assert!(gpios[1].variant_name().ends_with("GpioC"));
+    assert_eq!(gpios[1].reg_table().len(), gpio::GpioPeriph::<gpio::GpioC>::REG_TABLE.len());
+    let gpio: &dyn GpioPeriphDyn = &*gpios[0];
+    if false {
+        gpio.enable_clock();
+    }
+}
+
+#[test]
+fn inventory() {
+    use gpio::*;
+    fn display<T: GpioMap>(gpio: &GpioPeriph<T>) {
+        gpio.dump(|_, _| {});
+    }
+    fn flash<T: GpioMap>(gpio: &GpioPeriph<T>) {
+        gpio.is_clock_enabled();
+    }
+    let reg = unsafe { Regs::take() };
+    let mut gpio_a = Inventory::new(periph_gpio_a!(reg));
+    if false {
+        {
+            let guard = Inventory::guard(&mut gpio_a);
+            display(&guard);
+        }
+        {
+            let guard = Inventory::guard(&mut gpio_a);
+            flash(&guard);
+        }
+    }
+    let _gpio_a: GpioPeriph<GpioA> = Inventory::free(gpio_a);
+}
+
+#[test]
+fn field_alias() {
+    use reset::*;
+    fn f<T: ResetMap>(reset: &ResetPeriph<T>) {
+        reset.rcc_ahb2enr_rst.read_bit();
+    }
+    let reg = unsafe { Regs::take() };
+    let reset_a = periph_reset_a!(reg);
+    let reset_b = periph_reset_b!(reg);
+    let _: &rcc::ahb2enr::Gpioarst<Srt> = &reset_a.rcc_ahb2enr_rst;
+    #[cfg(target_os = "linux")]
+    let _: &rcc::ahb2enr::Gpiobrst<Srt> = &reset_b.rcc_ahb2enr_rst;
+    if false {
+        f(&reset_a);
+        f(&reset_b);
+    }
+}
+
+#[test]
+fn capabilities() {
+    use tim::*;
+    fn channels<T: TimMap>(_tim: &TimPeriph<T>) -> usize {
+        if T::ADVANCED { T::CHANNELS + 1 } else { T::CHANNELS }
+    }
+    let reg = unsafe { Regs::take() };
+    let tim1 = periph_tim1!(reg);
+    let tim2 = periph_tim2!(reg);
+    assert_eq!(channels(&tim1), 5);
+    assert_eq!(channels(&tim2), 2);
+}
+
+#[test]
+fn reg_table() {
+    use gpio::*;
+    let table = GpioPeriph::<GpioA>::REG_TABLE;
+    assert_eq!(table.len(), 2);
+    assert_eq!((table[0].name, table[0].address, table[0].size), ("RCC_AHB2ENR", 0x4002_104C, 32));
+    assert_eq!((table[1].name, table[1].address, table[1].size), ("GPIOA_ODR", 0x4800_0014, 32));
+    assert_eq!(table[1], gpioa::odr::Reg::<Srt>::LAYOUT);
+    let table = GpioPeriph::<GpioB>::REG_TABLE;
+    assert_eq!((table[1].name, table[1].address, table[1].size), ("GPIOB_ODR", 0x4800_0414, 32));
+}
@@ -1,6 +1,15 @@
 #![feature(proc_macro_hygiene)]
 
-use drone_core::{bitfield::Bitfield, reg::prelude::*, token::Token};
+use drone_core::{
+    bitfield::Bitfield,
+    reg::{
+        field::{RRRegFieldBit, RRRegFieldBits, WWRegFieldBit, WWRegFieldBits},
+        marker::{PrivilegedReg, UnprivilegedReg},
+        prelude::*,
+        FieldChange,
+    },
+    token::Token,
+};
 use std::mem::{size_of, size_of_val};
 
 use drone_core::reg;
@@ -22,6 +31,16 @@ reg! {
     REVISION { 0 4 RRRegField RoRRegField }
 }
 
+reg! {
+    /// MPU control register.
+    #[privileged]
+    pub mod MPU CTRL;
+    0xE000_ED94 0x20 0x0000_0000
+    RReg WReg;
+    /// Enables the MPU.
+    ENABLE { 0 1 RRRegField WWRegField }
+}
+
 reg! {
     /// Capture/Compare mode register 1. (input mode)
     pub mod TIM1 CCMR1_Input;
@@ -50,6 +69,55 @@ reg! {
     CC1S { 8 2 RRRegField WWRegField }
 }
 
+reg! {
+    /// Cycle count register.
+    pub mod DWT CYCCNT64;
+    0xE000_1100 0x40 0x0000_0001_0000_0000
+    RReg WReg;
+    /// Upper bits of the cycle counter.
+    HIGH { 32 31 RRRegField WWRegField }
+    /// Lower word of the cycle counter.
+    LOW { 0 32 RRRegField WWRegField }
+    /// Overflow flag.
+    OVF { 63 1 RRRegField WWRegField }
+}
+
+reg! {
+    /// Port output speed register.
+    pub mod GPIOB OSPEEDR;
+    0x4800_0408 0x20 0x0000_00C0
+    RReg WReg;
+    /// Port 3 output speed.
+    OSPEEDR3 {
+        6 2 RRRegField WWRegField {
+            /// Low speed.
+            Low = 0,
+            /// High speed.
+            High = 3,
+        }
+    }
+    /// Port 0 output speed.
+    OSPEEDR0 {
+        0 3 RRRegField WWRegField {
+            /// Low speed.
+            Low = 0,
+            /// Medium speed.
+            Medium = 0b001,
+            /// Very high speed.
+            VeryHigh = 0b111,
+        }
+    }
+}
+
+reg! {
+    /// Comparator control and status register.
+    pub mod COMP CSR;
+    0x4001_001C 0x20 0x0000_0000
+    RReg WReg;
+    /// Differential mode.
+    DIFF { 0 1 RRRegField WWRegField }
+}
+
 reg::tokens! {
     /// Intermediate register tokens macro.
     pub macro reg_tokens_intermediate;
@@ -79,6 +147,16 @@ reg::tokens! {
 reg_tokens! {
     /// Register tokens.
     pub struct Regs;
+    split {
+        /// System control block register tokens.
+        pub struct ScbRegs {
+            scb_cpuid;
+        }
+        /// Advanced-timer register tokens.
+        pub struct TimRegs {
+            tim1_ccmr1_input;
+        }
+    }
 }
 
 #[test]
@@ -93,6 +171,92 @@ fn sizes() {
     assert_eq!(size_of::<scb::Cpuid<Srt>>(), 0);
     assert_eq!(size_of::<scb::Cpuid<Crt>>(), 0);
     assert_eq!(size_of::<scb::cpuid::Val>(), 4);
+    assert_eq!(size_of::<dwt_cyccnt64::Val>(), 8);
+}
+
+#[test]
+fn wide_fields() {
+    let reg = unsafe { dwt_cyccnt64::Reg::<Urt>::take() };
+    let mut hold = reg.default();
+    assert_eq!(hold.high(), 1);
+    assert_eq!(hold.low(), 0);
+    hold.write_low(0xFFFF_FFFF).set_ovf();
+    assert_eq!(hold.val().bits(), 0x8000_0001_FFFF_FFFF);
+    assert_eq!(reg.high.read(&hold.val()), 1);
+}
+
+#[test]
+fn masked_fields() {
+    let reg = unsafe { tim1_ccmr1_output::Reg::<Urt>::take() };
+    let mut val = reg.default_val();
+    reg.oc1m.write(&mut val, 0b101);
+    reg.oc1m.toggle(&mut val, 0b011);
+    assert_eq!(reg.oc1m.read(&val), 0b110);
+    reg.oc1m.update_masked(&mut val, 0b110, 0b011);
+    assert_eq!(reg.oc1m.read(&val), 0b010);
+    reg.oc1m.update_masked(&mut val, 0b1000, 0b1111);
+    assert_eq!(val.bits(), 0b010 << 12);
+}
+
+#[test]
+fn enumerated_values() {
+    use gpiob_ospeedr::{Ospeedr0Enum, Ospeedr3Enum};
+    let reg = unsafe { gpiob_ospeedr::Reg::<Urt>::take() };
+    let mut hold = reg.default();
+    assert_eq!(hold.ospeedr3_enum(), Some(Ospeedr3Enum::High));
+    assert_eq!(hold.ospeedr0_enum(), Some(Ospeedr0Enum::Low));
+    hold.write_ospeedr3_enum(Ospeedr3Enum::Low).write_ospeedr0_enum(Ospeedr0Enum::VeryHigh);
+    assert_eq!(hold.val().bits(), 0b111);
+    assert_eq!(reg.ospeedr0.read_enum(&hold.val()), Some(Ospeedr0Enum::VeryHigh));
+    hold.write_ospeedr0_enum(Ospeedr0Enum::Medium);
+    assert_eq!(hold.ospeedr0(), 0b001);
+    for bits in &[0b010, 0b011, 0b100, 0b101, 0b110] {
+        hold.write_ospeedr0(*bits);
+        assert_eq!(hold.ospeedr0_enum(), None);
+    }
+    hold.write_ospeedr3(0b10);
+    assert_eq!(reg.ospeedr3.read_enum(&hold.val()), None);
+    assert_eq!(hold.ospeedr0(), 0b110);
+}
+
+#[test]
+fn hold_diff() {
+    let reg = unsafe { tim1_ccmr1_output::Reg::<Urt>::take() };
+    let old = reg.default();
+    let mut new = reg.default();
+    new.write_oc1m(0b110).set_oc1pe();
+    let mut changes = old.diff_fields(&new);
+    let oc1m = changes.next().unwrap();
+    assert_eq!(oc1m, FieldChange { name: "OC1M", old: 0, new: 0b110 });
+    assert_eq!(oc1m.to_string(), "OC1M: 0x0 -> 0x6");
+    assert_eq!(changes.next(), Some(FieldChange { name: "OC1PE", old: 0, new: 1 }));
+    assert_eq!(changes.next(), None);
+    assert_eq!(new.diff_fields(&new).count(), 0);
+    let reg = unsafe { comp_csr::Reg::<Urt>::take() };
+    let mut hold = reg.default();
+    hold.set_diff();
+    assert!(hold.diff());
+    assert_eq!(hold.diff_fields(&reg.default()).count(), 1);
+}
+
+#[test]
+fn field_tokens() {
+    fn bits<F: RRRegFieldBits<Urt> + WWRegFieldBits<Urt>>(_field: &F) -> (usize, usize) {
+        (F::OFFSET, F::WIDTH)
+    }
+    fn bit<F: RRRegFieldBit<Urt> + WWRegFieldBit<Urt>>(_field: &F) -> (usize, usize) {
+        (F::OFFSET, F::WIDTH)
+    }
+    fn read_only<F: RoRRegField<Srt>>(_field: &F) -> usize {
+        F::OFFSET
+    }
+    let reg = unsafe { tim1_ccmr1_output::Reg::<Urt>::take() };
+    assert_eq!(bits(&reg.oc1m), (12, 3));
+    assert_eq!(bit(&reg.oc1pe), (11, 1));
+    let cpuid = unsafe { scb_cpuid::Reg::<Srt>::take() };
+    assert_eq!(read_only(&cpuid.partno), 4);
+    let oc1ce = reg.oc1ce.into_copy();
+    assert_eq!(size_of_val(&oc1ce), 0);
 }
 
 #[test]
@@ -102,9 +266,63 @@ fn tokens() {
     assert_eq!(size_of_val(&reg.tim1_ccmr1_input), 0);
 }
 
+#[test]
+fn block_map() {
+    assert_eq!(scb_cpuid::Reg::<Srt>::LAYOUT.size, 32);
+    assert_eq!(dwt_cyccnt64::Reg::<Srt>::LAYOUT.size, 64);
+    assert_eq!(scb::MAP.len(), 1);
+    assert_eq!(scb::MAP[0], scb_cpuid::Reg::<Srt>::LAYOUT);
+    assert_eq!(scb::MAP[0].name, "SCB_CPUID");
+    assert_eq!(scb::MAP[0].address, 0xE000_ED00);
+    assert_eq!(scb::MAP[0].size, 32);
+    assert_eq!(scb::MAP[0].reset, 0x410F_C241);
+    assert_eq!(scb::MAP[0].fields.len(), 5);
+    assert_eq!(tim1::MAP.len(), 2);
+}
+
+#[test]
+fn split() {
+    let (scb, tim) = unsafe { Regs::take() }.split();
+    assert_eq!(size_of::<ScbRegs>(), 0);
+    assert_eq!(size_of_val(&scb.scb_cpuid), 0);
+    assert_eq!(size_of_val(&tim.tim1_ccmr1_input), 0);
+}
+
 #[test]
 fn variants() {
     let input: tim1::Ccmr1Input<Srt> = unsafe { Token::take() };
     let output: tim1::Ccmr1Output<Srt> = input.into_tim1_ccmr1_output();
     let _input: tim1::Ccmr1Input<Srt> = output.into_tim1_ccmr1_input();
 }
+
+#[test]
+fn privilege_levels() {
+    fn privileged<R: PrivilegedReg<Srt>>(_reg: R) {}
+    fn unprivileged<R: UnprivilegedReg<Srt>>(_reg: R) {}
+    privileged(unsafe { mpu_ctrl::Reg::<Srt>::take() });
+    unprivileged(unsafe { scb_cpuid::Reg::<Srt>::take() });
+}
+
+#[test]
+fn tag_conversions() {
+    let cpuid = unsafe { scb_cpuid::Reg::<Urt>::take() };
+    let cpuid = cpuid.into_sync().into_copy();
+    let cpuid = unsafe { scb_cpuid::Reg::<Srt>::from_copy(cpuid) };
+    let val = cpuid.default_val();
+    let partno = cpuid.partno.into_copy();
+    let partno = unsafe { scb_cpuid::Partno::<Srt>::from_copy(partno) };
+    assert_eq!(partno.read(&val), 0xC24);
+}
+
+#[cfg(feature = "regfmt")]
+#[test]
+fn hold_fmt() {
+    let reg = unsafe { tim1_ccmr1_output::Reg::<Urt>::take() };
+    let mut hold = reg.default();
+    hold.write_oc1m(0b110).set_oc1pe();
+    assert_eq!(
+        format!("{}", hold),
+        "CCMR1_Output { OC1CE: 0, OC1M: 6, OC1PE: 1, OC1FE: 0, CC1S: 0 }"
+    );
+    assert_eq!(format!("{:?}", hold), format!("{}", hold));
+}
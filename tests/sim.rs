@@ -0,0 +1,650 @@
+#![cfg(feature = "sim")]
+#![feature(const_fn)]
+#![feature(generators)]
+#![feature(proc_macro_hygiene)]
+
+use drone_core::{
+    log::Port,
+    periph::{PeriphClock, PeriphDyn},
+    reg,
+    reg::{mem::MmioRegion, prelude::*, DynReg, DynRegError, RegDyn, RegSummary, WRegShadow},
+    sim,
+    sim::{Expect, MemWrite},
+    thr,
+    thr::{prelude::*, Thread},
+    token::Token,
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering::*},
+        Arc,
+    },
+};
+
+static mut THREADS: [Thr; 2] = [Thr::new(0), Thr::new(1)];
+
+thr! {
+    use THREADS;
+    pub struct Thr {}
+    pub struct ThrLocal {}
+}
+
+#[derive(Clone, Copy)]
+struct Thr0;
+
+unsafe impl Token for Thr0 {
+    unsafe fn take() -> Self {
+        Self
+    }
+}
+
+unsafe impl ThrToken for Thr0 {
+    type Thr = Thr;
+
+    const THR_NUM: usize = 0;
+}
+
+reg! {
+    pub mod RCC CR;
+    0x4002_1000 0x20 0x0000_0000
+    RReg WReg;
+    HSION { 0 1 RRRegField WWRegField }
+    HSITRIM { 3 5 RRRegField WWRegField }
+}
+
+reg! {
+    pub mod USART SR;
+    0x4001_3800 0x20 0x0000_0000
+    RReg WReg;
+    ORE { 3 1 RcRegField }
+    RXNE { 5 1 RRRegField W1cRegField }
+    TC { 6 1 RRRegField W1cRegField }
+    LBDIE { 8 1 RRRegField WWRegField }
+}
+
+reg! {
+    pub mod RTC CRL;
+    0x4000_2804 0x20 0x0000_0020
+    RReg WReg;
+    SECF { 0 1 RRRegField W1cRegField }
+    ALRF { 1 1 RRRegField W1cRegField }
+    CNF { 4 1 RRRegField WWRegField }
+}
+
+reg! {
+    pub mod ADC1 CR2;
+    0x4001_2408 0x20 0x0000_0000
+    RReg WReg;
+    ADON { 0 1 RRRegField WWRegField }
+    CONT { 1 1 RRRegField WWRegField }
+    SWSTART { 22 1 RRRegField WWRegField WsRegField }
+}
+
+reg! {
+    pub mod DMA1 CCR[3; 0x14];
+    0x4002_0008 0x20 0x0000_0000
+    RReg WReg;
+    EN { 0 1 RRRegField WWRegField }
+    PL { 12 2 RRRegField WWRegField }
+}
+
+reg! {
+    pub mod USART DR;
+    0x4001_3804 0x10 0x0000
+    RReg WReg RegFifo;
+}
+
+reg! {
+    #[access(0x10, 0x08)]
+    pub mod TIM2 CNT;
+    0x4000_0024 0x20 0x0000_0000
+    RReg WReg;
+    CNT { 0 32 RRRegField WWRegField }
+}
+
+reg! {
+    pub mod GPIOC ODR;
+    0x4001_100C 0x20 0x0000_0000
+    RReg WReg RegBitBand;
+    ODR13 { 13 1 RRRegField WWRegField }
+}
+
+reg! {
+    pub mod USART BRR;
+    0x4001_3808 0x20 0x0000_0000
+    WReg WoReg;
+}
+
+reg! {
+    pub mod TIM2 EGR;
+    0x4000_0014 0x20 0x0000_0000
+    WReg WoReg;
+    UG { 0 1 WWRegField WoWRegField }
+    CC1G { 1 1 WWRegField WoWRegField }
+    CC2G { 2 1 WWRegField WoWRegField }
+}
+
+reg! {
+    pub mod LATCH OUT;
+    0x6000_0000 0x20 0x0000_0000
+    WReg WoReg;
+    LED0 { 0 1 WWRegField WoWRegField }
+    LED1 { 1 1 WWRegField WoWRegField }
+    LED2 { 2 1 WWRegField WoWRegField }
+}
+
+reg! {
+    pub mod UART CR;
+    0x0C 0x20 0x0000_0000
+    RReg WReg;
+    UE { 13 1 RRRegField WWRegField }
+    M { 12 1 RRRegField WWRegField }
+}
+
+reg::mem::region! {
+    pub struct Mailbox: [u32; 4] = 0x2000_F000;
+}
+
+reg::tokens! {
+    macro reg_tokens;
+    crate;
+    crate;
+
+    pub mod RCC {
+        CR;
+    }
+    pub mod USART {
+        SR;
+        DR;
+        BRR;
+    }
+}
+
+reg_tokens! {
+    #[summary]
+    #[dyn_reg]
+    pub struct Regs;
+}
+
+pub mod usart {
+    use drone_core::{periph, reg::marker::*};
+
+    periph! {
+        pub trait UsartMap {}
+        pub struct UsartPeriph: Dyn;
+
+        RCC {
+            CR {
+                0x20 RwReg Shared;
+                USARTEN { RwRwRegFieldBit Clock }
+            }
+        }
+
+        USART {
+            SR {
+                0x20 RwReg;
+                LBDIE { RwRwRegFieldBit }
+            }
+        }
+    }
+
+    periph::map! {
+        pub macro periph_usart1;
+        pub struct Usart1;
+        impl UsartMap for Usart1 {}
+        super;
+        crate::usart;
+
+        RCC {
+            CR {
+                CR Shared;
+                USARTEN { HSION }
+            }
+        }
+
+        USART {
+            USART;
+            SR {
+                SR;
+                LBDIE { LBDIE }
+            }
+        }
+    }
+}
+
+#[test]
+fn registers() {
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    sim::poke::<u32>(0x4002_1000, 0x80);
+    assert_eq!(cr.load().hsitrim(), 0x10);
+    cr.modify(|r| r.set_hsion());
+    assert_eq!(sim::peek::<u32>(0x4002_1000), 0x81);
+    assert_eq!(sim::peek::<u8>(0x4002_1000), 0x81);
+    assert_eq!(sim::peek::<u32>(0x4002_1004), 0);
+}
+
+#[test]
+fn reset_value() {
+    sim::reset();
+    let mut ccr = unsafe { dma1_ccr0::Reg::<Urt>::take() };
+    ccr.assert_reset();
+    ccr.store(|r| r.set_en());
+    assert!(!ccr.is_reset());
+    ccr.reset();
+    ccr.assert_reset();
+}
+
+#[test]
+#[should_panic(expected = "RCC_CR = 0x1, expected the reset value 0x0")]
+fn reset_value_mismatch() {
+    sim::reset();
+    sim::poke::<u32>(0x4002_1000, 1);
+    unsafe { rcc_cr::Reg::<Urt>::take() }.assert_reset();
+}
+
+#[test]
+fn transaction() {
+    sim::reset();
+    let mut sr = unsafe { usart_sr::Reg::<Urt>::take() };
+    sim::poke::<u32>(0x4001_3800, 0x40);
+    sim::record();
+    let mut tx = sr.begin();
+    tx.set_lbdie();
+    assert!(tx.tc());
+    drop(tx);
+    let mut tx = sr.begin();
+    tx.set_lbdie();
+    tx.commit();
+    sim::assert_writes(&[Expect::write(0x4001_3800, 0x100_u32)]);
+}
+
+#[test]
+fn read_to_clear() {
+    sim::reset();
+    sim::clear_on_read::<u32>(0x4001_3800, 0x08);
+    sim::poke::<u32>(0x4001_3800, 0x48);
+    let sr = unsafe { usart_sr::Reg::<Srt>::take() };
+    assert_eq!(sim::peek::<u32>(0x4001_3800), 0x48);
+    assert!(sr.ore.read_clear_bit().into_inner());
+    assert!(!sr.ore.read_clear_bit().into_inner());
+    assert_eq!(sim::peek::<u32>(0x4001_3800), 0x40);
+    sim::poke::<u32>(0x4001_3800, 0x48);
+    let hold = sr.load();
+    assert!(hold.ore().into_inner());
+    assert!(hold.tc());
+    assert!(!sr.load().ore().into_inner());
+}
+
+#[test]
+fn modify_atomic() {
+    sim::reset();
+    let cr = unsafe { rcc_cr::Reg::<Urt>::take() }.into_copy();
+    sim::poke::<u32>(0x4002_1000, 0x80);
+    let copy = cr;
+    cr.modify_atomic(|r| r.set_hsion());
+    copy.modify_atomic(|r| r.write_hsitrim(0x1F));
+    assert_eq!(sim::peek::<u32>(0x4002_1000), 0xF9);
+}
+
+#[cfg(feature = "regtrace")]
+#[test]
+fn register_trace() {
+    use drone_core::log::REGTRACE_PORT;
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    sim::poke::<u32>(0x4002_1000, 0x80);
+    sim::capture_log(REGTRACE_PORT);
+    cr.modify(|r| r.set_hsion());
+    let words = sim::take_log(REGTRACE_PORT)
+        .chunks(4)
+        .map(|word| u32::from_be_bytes([word[0], word[1], word[2], word[3]]))
+        .collect::<Vec<_>>();
+    assert_eq!(words, [0xE100_0020, 0x4002_1000, 0x80, 0xE200_0020, 0x4002_1000, 0x81]);
+}
+
+#[test]
+fn write_one_to_clear() {
+    sim::reset();
+    let mut sr = unsafe { usart_sr::Reg::<Urt>::take() };
+    sim::poke::<u32>(0x4001_3800, 0x60);
+    sim::record();
+    sr.modify(|r| r.set_lbdie());
+    sr.rxne.clear_bit();
+    sim::assert_writes(&[
+        Expect::write(0x4001_3800, 0x100_u32),
+        Expect::write(0x4001_3800, 0x20_u32),
+    ]);
+}
+
+#[test]
+fn write_one_to_clear_flag() {
+    sim::reset();
+    let mut crl = unsafe { rtc_crl::Reg::<Urt>::take() };
+    sim::poke::<u32>(0x4000_2804, 0x13);
+    sim::record();
+    crl.split_fields().secf.clear_flag();
+    sim::assert_writes(&[Expect::write(0x4000_2804, 0x11_u32)]);
+}
+
+#[test]
+fn write_side_effects() {
+    sim::reset();
+    let mut cr2 = unsafe { adc1_cr2::Reg::<Urt>::take() };
+    sim::poke::<u32>(0x4001_2408, 0x40_0001);
+    sim::record();
+    cr2.modify(|r| r.set_cont());
+    cr2.modify(|r| r.set_swstart());
+    sim::assert_writes(&[
+        Expect::write(0x4001_2408, 0x03_u32),
+        Expect::write(0x4001_2408, 0x40_0003_u32),
+    ]);
+}
+
+#[test]
+fn register_array() {
+    sim::reset();
+    let mut ccr = unsafe {
+        dma1_ccr::Array::<Urt> { ccr0: Token::take(), ccr1: Token::take(), ccr2: Token::take() }
+    };
+    sim::record();
+    ccr.ccr1.store(|r| r.set_en());
+    for index in 0..dma1_ccr::COUNT {
+        ccr.get(index).unwrap().modify(|r| r.write_pl(2));
+    }
+    assert_eq!(ccr.get(1).unwrap().load().pl(), 2);
+    sim::assert_writes(&[
+        Expect::write(0x4002_001C, 0x0001_u32),
+        Expect::write(0x4002_0008, 0x2000_u32),
+        Expect::write(0x4002_001C, 0x2001_u32),
+        Expect::write(0x4002_0030, 0x2000_u32),
+    ]);
+}
+
+#[test]
+fn split_fields() {
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    let fields = cr.split_fields();
+    let (hsion, hsitrim) = (fields.hsion, fields.hsitrim);
+    hsion.set_bit();
+    hsitrim.write_bits(0x10);
+    assert!(hsion.read_bit());
+    assert_eq!(sim::peek::<u32>(0x4002_1000), 0x81);
+    hsion.toggle_bit();
+    assert_eq!(hsitrim.read_bits(), 0x10);
+    hsitrim.toggle_bits(0x11);
+    assert_eq!(hsitrim.read_bits(), 0x01);
+    hsitrim.write_bits_masked(0x03, 0x1E);
+    assert_eq!(sim::peek::<u32>(0x4002_1000), 0x10);
+    assert!(!cr.load().hsion());
+}
+
+#[test]
+fn bit_band() {
+    sim::reset();
+    let odr = unsafe { gpioc_odr::Reg::<Urt>::take() };
+    let odr13 = odr.odr13_bb();
+    assert_eq!(odr13.address(), 0x4222_01B4);
+    odr13.set();
+    assert!(odr13.read());
+    assert_eq!(sim::peek::<u32>(0x4222_01B4), 1);
+    odr13.clear();
+    assert_eq!(sim::peek::<u32>(0x4222_01B4), 0);
+}
+
+#[test]
+fn partial_access() {
+    sim::reset();
+    sim::poke::<u32>(0x4000_0024, 0x1234_5678);
+    let cnt = unsafe { tim2_cnt::Reg::<Srt>::take() };
+    assert_eq!(cnt.load_u16(1), 0x1234);
+    assert_eq!(cnt.load_u8(0), 0x78);
+    sim::record();
+    cnt.store_u16(0, 0xABCD);
+    cnt.store_u8(3, 0xEF);
+    assert_eq!(cnt.load_bits(), 0xEF34_ABCD);
+    sim::assert_writes(&[
+        Expect::write(0x4000_0024, 0xABCD_u16),
+        Expect::write(0x4000_0027, 0xEF_u8),
+    ]);
+}
+
+#[test]
+fn shadow() {
+    sim::reset();
+    let mut out = WRegShadow::new(unsafe { latch_out::Reg::<Urt>::take() });
+    sim::record();
+    out.store(|r| r.set_led0().set_led2());
+    out.modify(|r| r.set_led1().clear_led0());
+    assert_eq!(out.bits(), 0b110);
+    out.store_bits(0);
+    sim::assert_writes(&[
+        Expect::write(0x6000_0000, 0b101_u32),
+        Expect::write(0x6000_0000, 0b110_u32),
+        Expect::write(0x6000_0000, 0_u32),
+    ]);
+}
+
+#[test]
+fn run_time_base() {
+    sim::reset();
+    sim::poke::<u32>(0x4000_480C, 0x1000);
+    let mut cr = unsafe { RegDyn::new(uart_cr::Reg::<Urt>::take(), 0x4000_4800) };
+    assert_eq!(cr.address(), 0x4000_480C);
+    assert!(cr.load().m());
+    sim::record();
+    cr.modify(|r| r.set_ue());
+    cr.store(|r| r.set_m());
+    sim::assert_writes(&[
+        Expect::write(0x4000_480C, 0x3000_u32),
+        Expect::write(0x4000_480C, 0x1000_u32),
+    ]);
+}
+
+#[test]
+fn memory_region() {
+    sim::reset();
+    let mailbox = unsafe { Mailbox::take() }.into_slice();
+    let (mut head, mut tail) = mailbox.split_at(1);
+    assert_eq!((head.len(), tail.len()), (1, 3));
+    sim::record();
+    head.write(0, 0xCAFE);
+    tail.write_from(1, &[1, 2]);
+    tail.fence();
+    let mut buf = [0; 3];
+    tail.read_into(0, &mut buf);
+    assert_eq!(buf, [0, 1, 2]);
+    assert_eq!(head.read(0), 0xCAFE);
+    sim::assert_writes(&[
+        Expect::write(0x2000_F000, 0xCAFE_u32),
+        Expect::write(0x2000_F008, 1_u32),
+        Expect::write(0x2000_F00C, 2_u32),
+    ]);
+}
+
+#[test]
+fn summary() {
+    sim::reset();
+    sim::poke::<u32>(0x4002_1000, 0x81);
+    let view = rcc_cr::Reg::<Srt>::summary_view().unwrap();
+    assert_eq!(view.load().to_string(), "RCC_CR = 0x00000081 HSION=0x1 HSITRIM=0x10");
+    assert!(usart_brr::Reg::<Srt>::summary_view().is_none());
+    assert!(usart_dr::Reg::<Srt>::summary_view().is_none());
+    assert!(usart_sr::Reg::<Srt>::summary_view().is_none());
+    sim::record();
+    unsafe { Regs::summary(Port::new(2)) };
+    sim::assert_writes(&[]);
+}
+
+#[test]
+fn dyn_reg() {
+    sim::reset();
+    let regs = unsafe { Regs::take() };
+    let cr = regs.dyn_reg("RCC_CR").unwrap();
+    assert_eq!((cr.layout().address, cr.layout().size), (0x4002_1000, 32));
+    cr.store_raw(0x81).unwrap();
+    assert_eq!(cr.load_raw(), Ok(0x81));
+    assert_eq!(sim::peek::<u32>(0x4002_1000), 0x81);
+    assert!(regs.dyn_reg("RCC_CFGR").is_none());
+    let mut names = Vec::new();
+    regs.for_each_dyn_reg(|reg| names.push(reg.layout().name));
+    assert_eq!(names, ["RCC_CR", "USART_BRR", "USART_DR", "USART_SR"]);
+    let egr = unsafe { tim2_egr::Reg::<Srt>::take() };
+    assert_eq!(DynReg::load_raw(&egr), Err(DynRegError::NotReadable));
+}
+
+#[test]
+fn periph_dyn() {
+    sim::reset();
+    let regs = unsafe { Regs::take() };
+    let usart1 = periph_usart1!(regs);
+    sim::poke::<u32>(0x4001_3800, 0x100);
+    let periph: &dyn PeriphDyn = &usart1;
+    assert!(periph.variant_name().ends_with("Usart1"));
+    let mut dump = Vec::new();
+    periph.dump_dyn(&mut |name, value| dump.push((name, value)));
+    assert_eq!(dump, [("USART_SR", 0x100)]);
+}
+
+#[test]
+fn periph_clock() {
+    sim::reset();
+    let regs = unsafe { Regs::take() };
+    let usart1 = periph_usart1!(regs);
+    sim::poke::<u32>(0x4002_1000, 0x80);
+    {
+        let _clock = usart1.clock_guard();
+        assert!(usart1.is_clock_enabled());
+        assert_eq!(sim::peek::<u32>(0x4002_1000), 0x81);
+    }
+    assert!(!usart1.is_clock_enabled());
+    assert_eq!(sim::peek::<u32>(0x4002_1000), 0x80);
+    usart1.enable_clock();
+    drop(usart1.clock_guard());
+    assert_eq!(sim::peek::<u32>(0x4002_1000), 0x81);
+    let clock: &dyn PeriphClock = &usart1;
+    clock.disable_clock();
+    assert!(!clock.is_clock_enabled());
+}
+
+#[test]
+fn threads() {
+    sim::reset();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let thr = unsafe { Thr0::take() };
+    let inner = Arc::clone(&counter);
+    thr.add(move || {
+        for _ in 0..3 {
+            inner.fetch_add(1, Relaxed);
+            yield;
+        }
+    });
+    assert_eq!(sim::run(), 0);
+    sim::trigger(thr);
+    sim::trigger(thr);
+    assert_eq!(counter.load(Relaxed), 0);
+    assert_eq!(sim::run(), 2);
+    assert_eq!(counter.load(Relaxed), 2);
+    assert!(!thr.to_thr().fib_chain().is_empty());
+}
+
+#[test]
+fn lend() {
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    let thr = unsafe { Thr0::take() };
+    // The handle is dropped by the fiber before the closure returns.
+    unsafe {
+        reg::lend(&mut cr, |mut lent| {
+            thr.add_once(move || lent.modify(|r| r.set_hsion()));
+            sim::trigger(thr);
+            assert_eq!(sim::run(), 1);
+        });
+    }
+    assert!(cr.load().hsion());
+}
+
+#[test]
+fn lend_panic() {
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+        reg::lend(&mut cr, |mut lent| {
+            lent.modify(|r| r.set_hsion());
+            panic!("borrower failed");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(cr.load().hsion());
+}
+
+#[test]
+fn critical() {
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    let mut sr = unsafe { usart_sr::Reg::<Urt>::take() };
+    sim::record();
+    let hsion = reg::critical((&mut cr, &mut sr), |(cr, sr), _cs| {
+        cr.store(|r| r.set_hsion());
+        sr.store(|r| r.set_lbdie());
+        cr.load().hsion()
+    });
+    assert!(hsion);
+    sim::assert_writes(&[Expect::write(0x4002_1000, 1_u32), Expect::write(0x4001_3800, 0x100_u32)]);
+}
+
+#[test]
+fn write_sequence() {
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    cr.store(|r| r.set_hsion());
+    sim::record();
+    cr.store(|r| r.write_hsitrim(0x1F));
+    cr.modify(|r| r.set_hsion());
+    sim::poke::<u8>(0x2000_0000, 0xAA);
+    assert_eq!(sim::take_writes(), [
+        MemWrite { address: 0x4002_1000, value: 0xF8, width: 32 },
+        MemWrite { address: 0x4002_1000, value: 0xF9, width: 32 },
+        MemWrite { address: 0x2000_0000, value: 0xAA, width: 8 },
+    ]);
+    cr.reset();
+    cr.modify(|r| r.set_hsion().write_hsitrim(3));
+    sim::poke::<u8>(0x2000_0000, 0xBB);
+    sim::assert_writes(&[
+        Expect::write(0x4002_1000, 0_u32),
+        Expect::masked(0x4002_1000, 1_u32, 1),
+        Expect::skip(),
+    ]);
+    cr.reset();
+    sim::assert_writes(&[Expect::skip(), Expect::any()]);
+}
+
+#[test]
+#[should_panic(expected = "memory writes don't match")]
+fn write_sequence_mismatch() {
+    sim::reset();
+    let mut cr = unsafe { rcc_cr::Reg::<Urt>::take() };
+    sim::record();
+    cr.store(|r| r.set_hsion());
+    sim::assert_writes(&[Expect::write(0x4002_1000, 1_u8)]);
+}
+
+#[test]
+fn fifo() {
+    sim::reset();
+    let mut dr = unsafe { usart_dr::Reg::<Urt>::take() };
+    sim::poke::<u16>(0x4001_3804, 0x55);
+    assert_eq!(dr.read_iter(3).collect::<Vec<_>>(), [0x55, 0x55, 0x55]);
+    let mut buf = [0; 2];
+    dr.read_into(&mut buf);
+    assert_eq!(buf, [0x55, 0x55]);
+    sim::record();
+    dr.write_from(&[1, 2, 3]);
+    sim::assert_writes(&[
+        Expect::write(0x4001_3804, 1_u16),
+        Expect::write(0x4001_3804, 2_u16),
+        Expect::write(0x4001_3804, 3_u16),
+    ]);
+}
@@ -9,7 +9,7 @@ use drone_core::{
 };
 use std::sync::{
     atomic::{AtomicI8, Ordering::*},
-    Arc,
+    Arc, Mutex,
 };
 
 static mut THREADS: [Thr; 3] = [Thr::new(0), Thr::new(1), Thr::new(2)];
@@ -123,3 +123,50 @@ fn fiber_once() {
         assert_eq!(counter.load(Relaxed), -2);
     }
 }
+
+#[test]
+fn round_robin() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let chain = fib::Chain::new();
+    chain.set_round_robin(true);
+    for id in 0..3 {
+        let log = Arc::clone(&log);
+        let mut count = 0;
+        chain.add(fib::new_fn(move || {
+            log.lock().unwrap().push(id);
+            count += 1;
+            if count < 3 { fib::Yielded(()) } else { fib::Complete(()) }
+        }));
+    }
+    unsafe {
+        chain.drain();
+        assert_eq!(*log.lock().unwrap(), [2, 1, 0]);
+        log.lock().unwrap().clear();
+        chain.drain();
+        assert_eq!(*log.lock().unwrap(), [1, 0, 2]);
+        log.lock().unwrap().clear();
+        chain.drain();
+        assert_eq!(*log.lock().unwrap(), [0, 2, 1]);
+    }
+    assert!(chain.is_empty());
+}
+
+#[test]
+fn add_while_draining() {
+    static CHAIN: fib::Chain = fib::Chain::new();
+    static COUNTER: AtomicI8 = AtomicI8::new(0);
+    CHAIN.add(fib::new_fn(|| {
+        CHAIN.add(fib::new_fn(|| {
+            COUNTER.fetch_add(1, Relaxed);
+            fib::Complete(())
+        }));
+        fib::Complete(())
+    }));
+    unsafe {
+        CHAIN.drain();
+        assert!(!CHAIN.is_empty());
+        CHAIN.drain();
+    }
+    assert_eq!(COUNTER.load(Relaxed), 1);
+    assert!(CHAIN.is_empty());
+}
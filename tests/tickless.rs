@@ -0,0 +1,20 @@
+use drone_core::future::{next_wakeup, Wakeup, WAKEUP_CAPACITY};
+
+#[test]
+fn overflow() {
+    let mut wakeups =
+        (0..WAKEUP_CAPACITY as u64).map(|at| Wakeup::register(at + 100)).collect::<Vec<_>>();
+    assert_eq!(next_wakeup(), Some(100));
+    let late = Wakeup::register(300);
+    let early = Wakeup::register(50);
+    assert_eq!(next_wakeup(), Some(50));
+    wakeups.clear();
+    assert_eq!(next_wakeup(), Some(50));
+    drop(early);
+    assert_eq!(next_wakeup(), Some(50));
+    drop(late);
+    assert_eq!(next_wakeup(), None);
+    let wakeup = Wakeup::register(200);
+    assert_eq!(next_wakeup(), Some(200));
+    drop(wakeup);
+}
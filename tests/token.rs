@@ -0,0 +1,55 @@
+use core::mem::size_of;
+use drone_core::token::{find_duplicate, simple_token, Token, TokenLend, TokenRecord};
+
+simple_token! {
+    /// Test token A.
+    pub struct TokenA;
+}
+
+simple_token! {
+    /// Test token B.
+    pub struct TokenB;
+}
+
+simple_token! {
+    /// Test token C.
+    pub struct TokenC;
+}
+
+fn take_b(token: TokenB) -> TokenB {
+    token
+}
+
+#[test]
+fn tuple_tokens() {
+    assert_eq!(size_of::<(TokenA,)>(), 0);
+    assert_eq!(size_of::<(TokenA, TokenB, TokenC)>(), 0);
+    let (a, b, c) = unsafe { <(TokenA, TokenB, TokenC)>::take() };
+    let (b, c) = (b, c).lend(|(b, c)| (take_b(b), c));
+    let group = (a, (b, c));
+    let (_a, (_b, _c)) = group.lend(|group| group);
+}
+
+#[test]
+fn token_records() {
+    let records = [
+        TokenRecord { name: "app::uart::Token", address: 0 },
+        TokenRecord { name: "app::spi::Token", address: 0 },
+        TokenRecord { name: "app::gpioa_odr::Reg", address: 0x4002_0014 },
+        TokenRecord { name: "app::gpiob_odr::Reg", address: 0x4002_0414 },
+    ];
+    assert!(!records[0].conflicts_with(&records[1]));
+    assert!(!records[2].conflicts_with(&records[3]));
+    assert!(find_duplicate(&records).is_none());
+    let alias = TokenRecord { name: "drv::gpioa::Odr", address: 0x4002_0014 };
+    assert!(records[2].conflicts_with(&alias));
+    let twin = TokenRecord { name: "app::spi::Token", address: 0 };
+    assert!(records[1].conflicts_with(&twin));
+    let records = [
+        TokenRecord { name: "app::uart::Token", address: 0 },
+        TokenRecord { name: "app::spi::Token", address: 0 },
+        twin,
+    ];
+    let (a, b) = find_duplicate(&records).unwrap();
+    assert_eq!((a.name, b.name), ("app::spi::Token", "app::spi::Token"));
+}